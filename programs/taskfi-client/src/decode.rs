@@ -0,0 +1,281 @@
+//! Typed decoding of the program's events and accounts from their raw bytes.
+//!
+//! Both begin with the 8-byte discriminator Anchor derives from the type's name, which picks
+//! the variant; events then carry the schema version they were written under, so an indexer
+//! running an older client reports events it cannot read instead of misreading them.
+
+use std::fmt;
+
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use taskfi_escrow::EVENT_SCHEMA_VERSION;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Too short to hold a discriminator
+    TooShort,
+    /// The discriminator names no event or account of the program
+    UnknownDiscriminator([u8; 8]),
+    /// The event was written under a schema version whose layout this client does not read:
+    /// a newer one, or one before the event's layout last changed
+    UnsupportedVersion { name: &'static str, version: u8 },
+    /// The bytes do not hold the named type's layout, such as an account not yet migrated
+    InvalidData { name: &'static str },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooShort => write!(f, "data is shorter than a discriminator"),
+            DecodeError::UnknownDiscriminator(discriminator) => {
+                write!(f, "unknown discriminator {discriminator:?}")
+            }
+            DecodeError::UnsupportedVersion { name, version } => write!(
+                f,
+                "{name} has schema version {version}, which this client cannot read"
+            ),
+            DecodeError::InvalidData { name } => write!(f, "data is not a valid {name}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn discriminator(data: &[u8]) -> Result<[u8; 8], DecodeError> {
+    data.get(..8)
+        .map(|bytes| bytes.try_into().unwrap())
+        .ok_or(DecodeError::TooShort)
+}
+
+/// Schema version that introduced an event's current layout. Events written before it have
+/// the old layout, which this client no longer carries.
+fn layout_version(name: &str) -> u8 {
+    match name {
+        // Version 2 replaced the job id string with its hash
+        "EscrowCreated" | "JobApplied" | "BidBondRefunded" => 2,
+        // Version 3 added the reason code
+        "EmergencyRefundIssued" => 3,
+        // Version 4 added dispute state to each milestone
+        "MilestonesSet" | "MilestonesAmended" => 4,
+        _ => 1,
+    }
+}
+
+macro_rules! program_events {
+    ($($name:ident),* $(,)?) => {
+        /// Every event the program emits
+        pub enum ProgramEvent {
+            $($name(taskfi_escrow::$name),)*
+        }
+
+        impl ProgramEvent {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(ProgramEvent::$name(_) => stringify!($name),)*
+                }
+            }
+
+            /// Schema version the event was written under
+            pub fn version(&self) -> u8 {
+                match self {
+                    $(ProgramEvent::$name(event) => event.version,)*
+                }
+            }
+        }
+
+        /// Decode an event from its discriminator and borsh body, as logged by `emit!`
+        pub fn decode_event(data: &[u8]) -> Result<ProgramEvent, DecodeError> {
+            let discriminator = discriminator(data)?;
+            $(
+                if discriminator == taskfi_escrow::$name::DISCRIMINATOR {
+                    let name = stringify!($name);
+                    // Every event leads with its schema version
+                    let version = *data.get(8).ok_or(DecodeError::InvalidData { name })?;
+                    if version > EVENT_SCHEMA_VERSION || version < layout_version(name) {
+                        return Err(DecodeError::UnsupportedVersion { name, version });
+                    }
+                    return taskfi_escrow::$name::try_from_slice(&data[8..])
+                        .map(ProgramEvent::$name)
+                        .map_err(|_| DecodeError::InvalidData { name });
+                }
+            )*
+            Err(DecodeError::UnknownDiscriminator(discriminator))
+        }
+    };
+}
+
+macro_rules! program_accounts {
+    ($($name:ident),* $(,)?) => {
+        /// Every account type the program owns
+        pub enum ProgramAccount {
+            $($name(Box<taskfi_escrow::$name>),)*
+        }
+
+        impl ProgramAccount {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(ProgramAccount::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+
+        /// Decode the data of an account the program owns
+        pub fn decode_account(data: &[u8]) -> Result<ProgramAccount, DecodeError> {
+            let discriminator = discriminator(data)?;
+            $(
+                if discriminator == taskfi_escrow::$name::DISCRIMINATOR {
+                    return taskfi_escrow::$name::try_deserialize(&mut &data[..])
+                        .map(|account| ProgramAccount::$name(Box::new(account)))
+                        .map_err(|_| DecodeError::InvalidData {
+                            name: stringify!($name),
+                        });
+                }
+            )*
+            Err(DecodeError::UnknownDiscriminator(discriminator))
+        }
+    };
+}
+
+program_events! {
+    EscrowCreated,
+    PaymentReleased,
+    DisputeInitiated,
+    DisputeResolved,
+    EmergencyRefundIssued,
+    JurorRegistered,
+    JurorDeregistered,
+    JurorsDrawn,
+    JurorAssignmentAccepted,
+    JurorSlashed,
+    VoteCommitted,
+    VoteRevealed,
+    PanelVotesTallied,
+    ConfigUpdated,
+    DisputeEscalated,
+    EscalationBondsSettled,
+    RefundRequested,
+    RefundApproved,
+    ReleaseRequested,
+    ReleaseProposed,
+    ReleaseProposalCancelled,
+    EscrowFrozen,
+    EscrowUnfrozen,
+    HeldPaymentWithdrawn,
+    PaymentClawedBack,
+    RoleTransferred,
+    BeneficiaryChanged,
+    SessionCreated,
+    SessionRevoked,
+    CompressedEscrowCreated,
+    CompressedEscrowSettled,
+    NonceAdvanced,
+    AffiliateRegistered,
+    HirerReferred,
+    PlatformFeeCollected,
+    AffiliateRewardsClaimed,
+    CouponCreated,
+    CouponRedeemed,
+    GrantCreated,
+    GrantClaimed,
+    GrantRevoked,
+    GrantCancelled,
+    RetainerCreated,
+    RetainerAutoRenewEnabled,
+    RetainerCancelled,
+    RetainerPeriodPaid,
+    InvoiceIssued,
+    PriceFeedUpdated,
+    PriceSnapshotRecorded,
+    StakePosted,
+    StakesSettled,
+    FreelancerDefaulted,
+    AdminProposed,
+    AdminProposalCancelled,
+    AdminTransferred,
+    ResolutionApproved,
+    KycAttested,
+    EscrowFunded,
+    UnfundedEscrowClosed,
+    SettledEscrowClosed,
+    ContributionReceived,
+    ContributionWithdrawn,
+    ChangeOrderProposed,
+    ChangeOrderAccepted,
+    ChangeOrderCancelled,
+    SubmissionRejected,
+    SlaTermsSet,
+    BusinessCalendarSet,
+    MilestonesSet,
+    MilestonesAmended,
+    MilestoneSubmitted,
+    MilestoneReleased,
+    MilestoneRefunded,
+    MilestoneDisputed,
+    MilestoneDisputeResolved,
+    MilestoneResolutionApproved,
+    PayoutDestinationSet,
+    RefundDestinationSet,
+    MintConfigSet,
+    VaultMigrated,
+    EscrowMetadataSet,
+    TaxonomyEntrySet,
+    EscrowSkillsSet,
+    JurorSpecialtySet,
+    BadgeMinted,
+    DisputeAutoResolved,
+    MediatorAssigned,
+    MediationRecommended,
+    SettlementProposed,
+    SettlementAccepted,
+    SettlementWithdrawn,
+    SettlementExecuted,
+    ArbitratorPaid,
+    CharityRoutingSet,
+    JobApplied,
+    BidBondRefunded,
+    ContestCreated,
+    ContestEntrySubmitted,
+    ContestWinnersSelected,
+    PoolPayoutMade,
+    PoolDistributed,
+    ContestCancelled,
+    BountyCreated,
+    BountyClaimSubmitted,
+    BountyAwarded,
+    BountyClaimRejected,
+    BountyCancelled,
+}
+
+program_accounts! {
+    Escrow,
+    PriceFeed,
+    Invoice,
+    EscrowIndex,
+    EscrowPage,
+    CategoryIndex,
+    FreelancerEarnings,
+    Leaderboard,
+    DailyStats,
+    Affiliate,
+    JobApplication,
+    Contest,
+    ContestEntry,
+    Bounty,
+    BountyClaim,
+    Reputation,
+    AdminRefundUsage,
+    AuditLog,
+    KycAttestation,
+    Contribution,
+    TaxonomyEntry,
+    SettlementProposal,
+    EscrowTombstone,
+    JobRegistration,
+    MintConfig,
+    Coupon,
+    Config,
+    JurorPool,
+    JurorPanel,
+    JurorAssignment,
+    SessionKey,
+    CompressedTree,
+}
@@ -0,0 +1,20 @@
+//! Transaction builders for off-chain clients of the TaskFi escrow program.
+//!
+//! Instructions that take an account per juror, contributor or recipient outgrow a legacy
+//! transaction long before they reach the program's own limits. `VersionedTransactionBuilder`
+//! compiles them into v0 transactions that load those accounts from address lookup tables,
+//! `lookup_table` creates and fills the tables, and `panel` lays out the remaining accounts
+//! for a juror panel's tally. `EventStream` follows the program's events for backends, and
+//! `decode_event` and `decode_account` turn raw bytes into the program's own types.
+
+pub mod decode;
+pub mod lookup_table;
+pub mod panel;
+pub mod stream;
+pub mod transaction;
+
+pub use decode::{decode_account, decode_event, DecodeError, ProgramAccount, ProgramEvent};
+pub use lookup_table::{lookup_table_addresses, LookupTable};
+pub use panel::tally_remaining_accounts;
+pub use stream::{events_in, EventStream, LogSource, RawEvent, TransactionLogs};
+pub use transaction::{ClientError, VersionedTransactionBuilder};
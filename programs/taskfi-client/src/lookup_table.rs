@@ -0,0 +1,123 @@
+//! Address lookup tables holding the accounts of the escrow's account-heavy instructions.
+
+use std::collections::HashSet;
+
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+
+/// Address lookup table program
+pub const PROGRAM_ID: Pubkey = solana_sdk::pubkey!("AddressLookupTab1e1111111111111111111111111");
+
+/// Most addresses a lookup table holds
+pub const MAX_ADDRESSES: usize = 256;
+
+/// Most addresses one extend instruction carries while its transaction stays within the
+/// packet size alongside the payer's and authority's signatures
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 30;
+
+// Instruction tags of the lookup table program, encoded like its bincode enum variants
+const CREATE_LOOKUP_TABLE: u32 = 0;
+const EXTEND_LOOKUP_TABLE: u32 = 2;
+
+/// A lookup table under `authority`, at the address derived from the slot it is created in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupTable {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+    pub recent_slot: u64,
+    pub bump: u8,
+}
+
+impl LookupTable {
+    pub fn new(authority: Pubkey, recent_slot: u64) -> Self {
+        let (address, bump) = Pubkey::find_program_address(
+            &[authority.as_ref(), &recent_slot.to_le_bytes()],
+            &PROGRAM_ID,
+        );
+        Self {
+            address,
+            authority,
+            recent_slot,
+            bump,
+        }
+    }
+
+    /// Create the table, paid for by `payer`. `recent_slot` must still be in the slot hashes
+    /// sysvar when the transaction lands.
+    pub fn create_ix(&self, payer: &Pubkey) -> Instruction {
+        let mut data = CREATE_LOOKUP_TABLE.to_le_bytes().to_vec();
+        data.extend_from_slice(&self.recent_slot.to_le_bytes());
+        data.push(self.bump);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.address, false),
+                AccountMeta::new_readonly(self.authority, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data,
+        }
+    }
+
+    /// Append `addresses` to the table, one instruction per `MAX_ADDRESSES_PER_EXTEND` of them,
+    /// each to be sent in its own transaction signed by the authority and `payer`. New entries
+    /// can be looked up from the slot after they are added.
+    pub fn extend_ixs(&self, payer: &Pubkey, addresses: &[Pubkey]) -> Vec<Instruction> {
+        addresses
+            .chunks(MAX_ADDRESSES_PER_EXTEND)
+            .map(|chunk| {
+                let mut data = EXTEND_LOOKUP_TABLE.to_le_bytes().to_vec();
+                data.extend_from_slice(&(chunk.len() as u64).to_le_bytes());
+                for address in chunk {
+                    data.extend_from_slice(address.as_ref());
+                }
+                Instruction {
+                    program_id: PROGRAM_ID,
+                    accounts: vec![
+                        AccountMeta::new(self.address, false),
+                        AccountMeta::new_readonly(self.authority, true),
+                        AccountMeta::new(*payer, true),
+                        AccountMeta::new_readonly(system_program::ID, false),
+                    ],
+                    data,
+                }
+            })
+            .collect()
+    }
+
+    /// The table as a v0 message compiles against it, once it holds `addresses`
+    pub fn account(&self, addresses: Vec<Pubkey>) -> AddressLookupTableAccount {
+        AddressLookupTableAccount {
+            key: self.address,
+            addresses,
+        }
+    }
+}
+
+/// Accounts of `instructions` that can be loaded from a lookup table, in the order they first
+/// appear: every key that signs none of them and is not an invoked program, since signers and
+/// program ids must stay among the message's static keys
+pub fn lookup_table_addresses(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let static_keys: HashSet<Pubkey> = instructions
+        .iter()
+        .map(|ix| ix.program_id)
+        .chain(
+            instructions
+                .iter()
+                .flat_map(|ix| &ix.accounts)
+                .filter(|meta| meta.is_signer)
+                .map(|meta| meta.pubkey),
+        )
+        .collect();
+
+    let mut seen = HashSet::new();
+    instructions
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .map(|meta| meta.pubkey)
+        .filter(|key| !static_keys.contains(key) && seen.insert(*key))
+        .collect()
+}
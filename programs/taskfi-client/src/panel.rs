@@ -0,0 +1,41 @@
+//! Remaining accounts for a juror panel's tally, the program's most account-heavy ruling.
+
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+/// Remaining accounts `tally_panel_votes` takes for the panel on `escrow`: the assignment of
+/// each of its `jurors`, then each juror's token account when the platform charges a juror
+/// fee (pass none otherwise), then a `(contribution, token account)` pair per contributor
+/// when the escrow is crowdfunded
+pub fn tally_remaining_accounts(
+    escrow: &Pubkey,
+    jurors: u8,
+    juror_token_accounts: &[Pubkey],
+    refund_accounts: &[(Pubkey, Pubkey)],
+) -> Vec<AccountMeta> {
+    let (panel, _) = Pubkey::find_program_address(&[b"panel", escrow.as_ref()], &taskfi_escrow::ID);
+    (0..jurors)
+        .map(|index| {
+            let (assignment, _) = Pubkey::find_program_address(
+                &[b"assignment", panel.as_ref(), &[index]],
+                &taskfi_escrow::ID,
+            );
+            AccountMeta::new_readonly(assignment, false)
+        })
+        .chain(
+            juror_token_accounts
+                .iter()
+                .map(|token_account| AccountMeta::new(*token_account, false)),
+        )
+        .chain(
+            refund_accounts
+                .iter()
+                .flat_map(|(contribution, token_account)| {
+                    [
+                        AccountMeta::new_readonly(*contribution, false),
+                        AccountMeta::new(*token_account, false),
+                    ]
+                }),
+        )
+        .collect()
+}
@@ -0,0 +1,334 @@
+//! The program's events streamed from transaction logs, across reconnects.
+//!
+//! A `LogSource` is the transport: a `logsSubscribe` WebSocket, a Geyser plugin feed or
+//! anything else that yields the logs of confirmed transactions mentioning the program and
+//! can fetch the ones missed while it was down. `EventStream` drives it, reconnecting and
+//! backfilling after a dropped connection, and yields each event the program emitted once
+//! and in order.
+//!
+//! The crate has no network client of its own, so it ships no source. The JSON-RPC helpers
+//! build and parse the messages a source exchanges with a standard RPC node: the
+//! `logsSubscribe` session for the live feed, and `getSignaturesForAddress` then
+//! `getTransaction` for the backfill, over whichever WebSocket and HTTP client the backend
+//! already uses. Account subscriptions are not covered.
+
+use std::collections::{HashSet, VecDeque};
+use std::io;
+
+use anchor_lang::Event;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::decode::{decode_event, DecodeError, ProgramEvent};
+
+/// Log prefix of data emitted with `sol_log_data`, which `emit!` uses for events
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// Most signatures `getSignaturesForAddress` returns in one page
+pub const SIGNATURES_PAGE: usize = 1_000;
+
+/// How many recent signatures the stream remembers to drop transactions a backfill repeats
+const SEEN_SIGNATURES: usize = 1_024;
+
+/// Logs of one confirmed transaction that mentions the program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionLogs {
+    pub signature: String,
+    pub slot: u64,
+    /// Whether the transaction failed, in which case none of its events took effect
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+/// An event the program emitted, still encoded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawEvent {
+    pub signature: String,
+    pub slot: u64,
+    /// Position among the program's events in the transaction
+    pub index: usize,
+    /// Discriminator followed by the borsh-encoded event
+    pub data: Vec<u8>,
+}
+
+impl RawEvent {
+    /// Decode the event as `E`, or `None` when it is another event
+    pub fn decode<E: Event>(&self) -> Option<E> {
+        if self.data.len() < 8 || self.data[..8] != E::DISCRIMINATOR {
+            return None;
+        }
+        E::try_from_slice(&self.data[8..]).ok()
+    }
+
+    /// Decode the event as whichever of the program's events it is
+    pub fn event(&self) -> Result<ProgramEvent, DecodeError> {
+        decode_event(&self.data)
+    }
+}
+
+/// Events the escrow program emitted in `transaction`, in order. Data logged by other
+/// programs it calls, or that call it, is skipped, as is everything in a failed transaction.
+pub fn events_in(transaction: &TransactionLogs) -> Vec<RawEvent> {
+    if transaction.failed {
+        return Vec::new();
+    }
+    let program = taskfi_escrow::ID.to_string();
+    let mut invoked: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in &transaction.logs {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if invoked.last() == Some(&program.as_str()) {
+                if let Ok(data) = STANDARD.decode(data) {
+                    events.push(RawEvent {
+                        signature: transaction.signature.clone(),
+                        slot: transaction.slot,
+                        index: events.len(),
+                        data,
+                    });
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(id), Some("invoke")) => invoked.push(id),
+                (Some(id), Some("success" | "failed:")) if invoked.last() == Some(&id) => {
+                    invoked.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// Transport the stream reads transaction logs from
+pub trait LogSource {
+    /// Open, or reopen after an error, a subscription to the logs of transactions that
+    /// mention the escrow program. Sources that back off between attempts wait here.
+    fn subscribe(&mut self) -> io::Result<()>;
+
+    /// Next transaction from the subscription, waiting for one. An error drops the
+    /// connection, and the stream subscribes again.
+    fn next_logs(&mut self) -> io::Result<TransactionLogs>;
+
+    /// Transactions mentioning the program that landed after `signature`, oldest first
+    fn backfill(&mut self, signature: &str) -> io::Result<Vec<TransactionLogs>>;
+}
+
+/// Iterator over the program's events from a `LogSource`, resubscribing and backfilling
+/// whatever landed in between when the connection drops. Yields an error once
+/// `max_reconnects` attempts in a row have failed, and tries again if polled after it.
+pub struct EventStream<S> {
+    source: S,
+    max_reconnects: u32,
+    failed_reconnects: u32,
+    subscribed: bool,
+    last_signature: Option<String>,
+    seen: VecDeque<String>,
+    seen_set: HashSet<String>,
+    pending: VecDeque<RawEvent>,
+}
+
+impl<S: LogSource> EventStream<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            max_reconnects: 5,
+            failed_reconnects: 0,
+            subscribed: false,
+            last_signature: None,
+            seen: VecDeque::new(),
+            seen_set: HashSet::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Start after `signature`, backfilling from it on the first subscription, to resume
+    /// where a consumer that persisted its position left off
+    pub fn resume_after(mut self, signature: impl Into<String>) -> Self {
+        let signature = signature.into();
+        self.remember(&signature);
+        self.last_signature = Some(signature);
+        self
+    }
+
+    pub fn max_reconnects(mut self, attempts: u32) -> Self {
+        self.max_reconnects = attempts;
+        self
+    }
+
+    /// Signature of the last transaction read, to persist for `resume_after`
+    pub fn last_signature(&self) -> Option<&str> {
+        self.last_signature.as_deref()
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    fn remember(&mut self, signature: &str) -> bool {
+        if !self.seen_set.insert(signature.to_string()) {
+            return false;
+        }
+        self.seen.push_back(signature.to_string());
+        if self.seen.len() > SEEN_SIGNATURES {
+            let oldest = self.seen.pop_front().unwrap();
+            self.seen_set.remove(&oldest);
+        }
+        true
+    }
+
+    fn accept(&mut self, transaction: TransactionLogs) {
+        if !self.remember(&transaction.signature) {
+            return;
+        }
+        self.pending.extend(events_in(&transaction));
+        self.last_signature = Some(transaction.signature);
+    }
+
+    fn reconnect(&mut self) -> io::Result<()> {
+        self.source.subscribe()?;
+        // Subscribe first so nothing lands between the backfill and the live feed; the
+        // signatures seen drop what the two have in common
+        if let Some(signature) = self.last_signature.clone() {
+            for transaction in self.source.backfill(&signature)? {
+                self.accept(transaction);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: LogSource> Iterator for EventStream<S> {
+    type Item = io::Result<RawEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if !self.subscribed {
+                match self.reconnect() {
+                    Ok(()) => {
+                        self.subscribed = true;
+                        self.failed_reconnects = 0;
+                    }
+                    Err(err) => {
+                        self.failed_reconnects += 1;
+                        if self.failed_reconnects > self.max_reconnects {
+                            self.failed_reconnects = 0;
+                            return Some(Err(err));
+                        }
+                    }
+                }
+                continue;
+            }
+            match self.source.next_logs() {
+                Ok(transaction) => self.accept(transaction),
+                Err(_) => self.subscribed = false,
+            }
+        }
+    }
+}
+
+/// `logsSubscribe` request for transactions that mention the escrow program
+pub fn logs_subscribe_request(id: u64, commitment: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "logsSubscribe",
+        "params": [
+            { "mentions": [taskfi_escrow::ID.to_string()] },
+            { "commitment": commitment },
+        ],
+    })
+    .to_string()
+}
+
+/// The transaction in a `logsNotification`, or `None` for any other message such as the
+/// subscription's confirmation
+pub fn parse_logs_notification(message: &str) -> Option<TransactionLogs> {
+    let message: Value = serde_json::from_str(message).ok()?;
+    if message["method"] != "logsNotification" {
+        return None;
+    }
+    let result = &message["params"]["result"];
+    let value = &result["value"];
+    Some(TransactionLogs {
+        signature: value["signature"].as_str()?.to_string(),
+        slot: result["context"]["slot"].as_u64()?,
+        failed: !value["err"].is_null(),
+        logs: value["logs"]
+            .as_array()?
+            .iter()
+            .filter_map(|line| line.as_str().map(str::to_string))
+            .collect(),
+    })
+}
+
+/// `getSignaturesForAddress` request for a page of the escrow program's transactions that
+/// landed after `until`, newest first, starting before `before` when paging further back
+pub fn signatures_request(id: u64, until: &str, before: Option<&str>, commitment: &str) -> String {
+    let mut options = json!({
+        "until": until,
+        "limit": SIGNATURES_PAGE,
+        "commitment": commitment,
+    });
+    if let Some(before) = before {
+        options["before"] = json!(before);
+    }
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "getSignaturesForAddress",
+        "params": [taskfi_escrow::ID.to_string(), options],
+    })
+    .to_string()
+}
+
+/// Signatures in a `getSignaturesForAddress` response, newest first as the node returns
+/// them. A page shorter than `SIGNATURES_PAGE` is the last one.
+pub fn parse_signatures(response: &str) -> Option<Vec<String>> {
+    let response: Value = serde_json::from_str(response).ok()?;
+    response["result"]
+        .as_array()?
+        .iter()
+        .map(|entry| entry["signature"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// `getTransaction` request for the logs of a backfilled `signature`
+pub fn transaction_request(id: u64, signature: &str, commitment: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "getTransaction",
+        "params": [
+            signature,
+            {
+                "encoding": "json",
+                "commitment": commitment,
+                "maxSupportedTransactionVersion": 0,
+            },
+        ],
+    })
+    .to_string()
+}
+
+/// The transaction in a `getTransaction` response, or `None` when the node no longer has it
+pub fn parse_transaction(signature: &str, response: &str) -> Option<TransactionLogs> {
+    let response: Value = serde_json::from_str(response).ok()?;
+    let result = &response["result"];
+    let meta = &result["meta"];
+    Some(TransactionLogs {
+        signature: signature.to_string(),
+        slot: result["slot"].as_u64()?,
+        failed: !meta["err"].is_null(),
+        logs: meta["logMessages"]
+            .as_array()?
+            .iter()
+            .filter_map(|line| line.as_str().map(str::to_string))
+            .collect(),
+    })
+}
@@ -0,0 +1,154 @@
+//! v0 transactions that load the accounts of large instructions from lookup tables.
+
+use std::fmt;
+
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::{v0, CompileError, Message, VersionedMessage};
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::SignerError;
+use solana_sdk::signers::Signers;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClientError {
+    /// The instructions could not be compiled into a v0 message against the lookup tables
+    Compile(CompileError),
+    /// The signers passed do not match the message's required signers
+    Sign(SignerError),
+    /// The serialized transaction is larger than the cluster accepts; the accounts it names
+    /// need a lookup table, or the instructions a transaction of their own
+    TooLarge { size: usize },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Compile(err) => write!(f, "failed to compile v0 message: {err}"),
+            ClientError::Sign(err) => write!(f, "failed to sign transaction: {err}"),
+            ClientError::TooLarge { size } => write!(
+                f,
+                "transaction is {size} bytes, over the {PACKET_DATA_SIZE} byte limit"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<CompileError> for ClientError {
+    fn from(err: CompileError) -> Self {
+        ClientError::Compile(err)
+    }
+}
+
+impl From<SignerError> for ClientError {
+    fn from(err: SignerError) -> Self {
+        ClientError::Sign(err)
+    }
+}
+
+/// Builds a v0 transaction from instructions and the lookup tables their accounts are in.
+/// Accounts found in a table cost one byte instead of 32, so a panel tally or a contest payout
+/// that overflows a legacy transaction fits in one.
+#[derive(Clone, Debug)]
+pub struct VersionedTransactionBuilder {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+    compute_unit_limit: Option<u32>,
+    compute_unit_price: Option<u64>,
+}
+
+impl VersionedTransactionBuilder {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: Vec::new(),
+            lookup_tables: Vec::new(),
+            compute_unit_limit: None,
+            compute_unit_price: None,
+        }
+    }
+
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn instructions(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    /// Load any account of the instructions found in `table` from it
+    pub fn lookup_table(mut self, table: AddressLookupTableAccount) -> Self {
+        self.lookup_tables.push(table);
+        self
+    }
+
+    /// Request `units` of compute instead of the default, for tallies over a full panel
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    /// Bid a priority fee of `micro_lamports` per compute unit
+    pub fn compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    /// The instructions as sent, after any compute budget instructions
+    fn all_instructions(&self) -> Vec<Instruction> {
+        self.compute_unit_limit
+            .map(ComputeBudgetInstruction::set_compute_unit_limit)
+            .into_iter()
+            .chain(
+                self.compute_unit_price
+                    .map(ComputeBudgetInstruction::set_compute_unit_price),
+            )
+            .chain(self.instructions.iter().cloned())
+            .collect()
+    }
+
+    /// Compile the v0 message without signing it, for wallets that sign it themselves
+    pub fn message(&self, recent_blockhash: Hash) -> Result<v0::Message, ClientError> {
+        Ok(v0::Message::try_compile(
+            &self.payer,
+            &self.all_instructions(),
+            &self.lookup_tables,
+            recent_blockhash,
+        )?)
+    }
+
+    /// Compile, sign and size-check the transaction
+    pub fn build<T: Signers + ?Sized>(
+        &self,
+        recent_blockhash: Hash,
+        signers: &T,
+    ) -> Result<VersionedTransaction, ClientError> {
+        let message = VersionedMessage::V0(self.message(recent_blockhash)?);
+        let transaction = VersionedTransaction::try_new(message, signers)?;
+        let size = transaction_size(&transaction);
+        if size > PACKET_DATA_SIZE {
+            return Err(ClientError::TooLarge { size });
+        }
+        Ok(transaction)
+    }
+
+    /// Size of the same instructions sent as a legacy transaction, to tell whether they need
+    /// a lookup table at all
+    pub fn legacy_size(&self) -> usize {
+        let message = Message::new(&self.all_instructions(), Some(&self.payer));
+        bincode::serialized_size(&Transaction::new_unsigned(message)).unwrap() as usize
+    }
+}
+
+/// Serialized size of `transaction` as it goes over the wire
+pub fn transaction_size(transaction: &VersionedTransaction) -> usize {
+    bincode::serialized_size(transaction).unwrap() as usize
+}
@@ -0,0 +1,149 @@
+//! Size budgets of the program's main instructions, as the fixtures send them: transaction
+//! size, instruction data and account locks, each checked with room to spare.
+//!
+//! Run with `--nocapture` to print the table. These are not compute-unit budgets. The native
+//! runtime these tests run on does not meter compute units, and no metered SBF runtime is
+//! part of the workspace yet, so per-instruction compute budgets remain an open follow-up
+//! rather than something this file covers.
+
+use solana_sdk::hash::Hash;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use taskfi_client::*;
+use taskfi_test_utils::*;
+
+/// Accounts a transaction may lock on mainnet
+const MAX_ACCOUNT_LOCKS: usize = 64;
+
+/// Room each instruction keeps under the limits, for the accounts and arguments later
+/// features add
+const SIZE_HEADROOM: usize = 200;
+const ACCOUNT_HEADROOM: usize = 16;
+
+struct Budget {
+    name: &'static str,
+    accounts: usize,
+    data: usize,
+    legacy_size: usize,
+}
+
+/// Measure the last transaction `env` processed, paid for by its first signer
+fn measure(name: &'static str, env: &TestEnv) -> Budget {
+    let instructions = env.last_instructions();
+    let payer = instructions
+        .iter()
+        .flat_map(|instruction| &instruction.accounts)
+        .find(|meta| meta.is_signer)
+        .map_or_else(Pubkey::new_unique, |meta| meta.pubkey);
+    let builder = VersionedTransactionBuilder::new(payer).instructions(instructions.to_vec());
+    Budget {
+        name,
+        accounts: builder.message(Hash::default()).unwrap().account_keys.len(),
+        data: instructions
+            .iter()
+            .map(|instruction| instruction.data.len())
+            .sum(),
+        legacy_size: builder.legacy_size(),
+    }
+}
+
+#[test]
+fn instructions_stay_well_within_size_and_lock_limits() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.bid_bond_lamports = 1_000;
+    });
+    let mut budgets = Vec::new();
+
+    let hirer = env.create_wallet();
+    let applicant = env.create_wallet();
+    env.process(
+        platform.apply_to_job_ix(&hirer, "job-budget-disputed", &applicant),
+        &[applicant],
+    )
+    .unwrap();
+    budgets.push(measure("apply_to_job", &env));
+    let disputed = EscrowBuilder::new("job-budget-disputed")
+        .hirer(hirer)
+        .create(&mut env);
+    budgets.push(measure("initialize_escrow", &env));
+    env.process(
+        platform.refund_bid_bond_ix(&hirer, "job-budget-disputed", &applicant),
+        &[],
+    )
+    .unwrap();
+    budgets.push(measure("refund_bid_bond", &env));
+
+    env.process(
+        disputed.initiate_dispute_ix(&env, &platform, &disputed.freelancer),
+        &[disputed.freelancer],
+    )
+    .unwrap();
+    budgets.push(measure("initiate_dispute", &env));
+    env.process(
+        disputed.escalate_ix(&platform, &disputed.hirer),
+        &[disputed.hirer],
+    )
+    .unwrap();
+    budgets.push(measure("escalate_dispute", &env));
+    env.next_slot();
+    env.process(
+        disputed.resolve_ix(&platform, &platform.moderator, 400_000, 600_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+    budgets.push(measure("resolve_dispute", &env));
+
+    let released = EscrowBuilder::new("job-budget-released").create(&mut env);
+    env.process(
+        released.release_ix(&platform, &released.hirer),
+        &[released.hirer],
+    )
+    .unwrap();
+    budgets.push(measure("release_payment", &env));
+
+    let settled = EscrowBuilder::new("job-budget-settled").create(&mut env);
+    env.process(
+        settled.initiate_dispute_ix(&env, &platform, &settled.hirer),
+        &[settled.hirer],
+    )
+    .unwrap();
+    env.next_slot();
+    env.process(
+        settled.propose_settlement_ix(&settled.hirer, 300_000, 700_000),
+        &[settled.hirer],
+    )
+    .unwrap();
+    budgets.push(measure("propose_settlement", &env));
+    env.process(
+        settled.accept_settlement_ix(&env, &platform, &settled.freelancer),
+        &[settled.freelancer],
+    )
+    .unwrap();
+    budgets.push(measure("accept_settlement", &env));
+
+    println!(
+        "{:<20} {:>8} {:>6} {:>12}",
+        "instruction", "accounts", "data", "legacy bytes"
+    );
+    for budget in &budgets {
+        println!(
+            "{:<20} {:>8} {:>6} {:>12}",
+            budget.name, budget.accounts, budget.data, budget.legacy_size
+        );
+    }
+    for budget in &budgets {
+        assert!(
+            budget.legacy_size + SIZE_HEADROOM <= PACKET_DATA_SIZE,
+            "{} takes {} of {PACKET_DATA_SIZE} bytes",
+            budget.name,
+            budget.legacy_size
+        );
+        assert!(
+            budget.accounts + ACCOUNT_HEADROOM <= MAX_ACCOUNT_LOCKS,
+            "{} locks {} of {MAX_ACCOUNT_LOCKS} accounts",
+            budget.name,
+            budget.accounts
+        );
+    }
+}
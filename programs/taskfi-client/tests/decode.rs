@@ -0,0 +1,92 @@
+use anchor_lang::{Discriminator, Event};
+use taskfi_client::*;
+use taskfi_escrow::{job_id_hash, EscrowCreated, EVENT_SCHEMA_VERSION};
+use taskfi_test_utils::*;
+
+#[test]
+fn events_decode_into_their_variant() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let escrow = EscrowBuilder::new("job-decode-event").create(&mut env);
+    let created = env.events::<EscrowCreated>().remove(0);
+
+    let event = decode_event(&created.data()).unwrap();
+    assert_eq!(event.name(), "EscrowCreated");
+    assert_eq!(event.version(), EVENT_SCHEMA_VERSION);
+    let ProgramEvent::EscrowCreated(decoded) = event else {
+        panic!("decoded as {}", event.name());
+    };
+    assert_eq!(decoded.escrow, escrow.escrow);
+    assert_eq!(decoded.job_id_hash, job_id_hash("job-decode-event"));
+    assert_eq!(decoded.amount, created.amount);
+}
+
+#[test]
+fn events_from_a_newer_schema_are_reported_not_misread() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    EscrowBuilder::new("job-decode-version").create(&mut env);
+    let mut data = env.events::<EscrowCreated>()[0].data();
+    data[8] = EVENT_SCHEMA_VERSION + 1;
+    assert!(matches!(
+        decode_event(&data),
+        Err(DecodeError::UnsupportedVersion {
+            name: "EscrowCreated",
+            version
+        }) if version == EVENT_SCHEMA_VERSION + 1
+    ));
+
+    // Version 1 carried the job id string, which this layout cannot read
+    data[8] = 1;
+    assert!(matches!(
+        decode_event(&data),
+        Err(DecodeError::UnsupportedVersion {
+            name: "EscrowCreated",
+            version: 1
+        })
+    ));
+
+    data[8] = EVENT_SCHEMA_VERSION;
+    data.truncate(data.len() - 1);
+    assert!(matches!(
+        decode_event(&data),
+        Err(DecodeError::InvalidData {
+            name: "EscrowCreated"
+        })
+    ));
+    assert!(matches!(
+        decode_event(&[0; 8]),
+        Err(DecodeError::UnknownDiscriminator(d)) if d == [0; 8]
+    ));
+    assert!(matches!(decode_event(&[1, 2]), Err(DecodeError::TooShort)));
+}
+
+#[test]
+fn program_accounts_decode_into_their_variant() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let escrow = EscrowBuilder::new("job-decode-account").create(&mut env);
+
+    let data = &env.account(&escrow.escrow).unwrap().data;
+    let account = decode_account(data).unwrap();
+    assert_eq!(account.name(), "Escrow");
+    let ProgramAccount::Escrow(decoded) = account else {
+        panic!("decoded as {}", account.name());
+    };
+    assert_eq!(decoded.hirer, escrow.hirer);
+    assert_eq!(decoded.freelancer, escrow.freelancer);
+
+    let config = decode_account(&env.account(&platform.config).unwrap().data).unwrap();
+    assert!(matches!(config, ProgramAccount::Config(_)));
+
+    // A layout cut short, like an account from before a migration, is not misread
+    let truncated = &data[..taskfi_escrow::Escrow::DISCRIMINATOR.len() + 40];
+    assert!(matches!(
+        decode_account(truncated),
+        Err(DecodeError::InvalidData { name: "Escrow" })
+    ));
+    assert!(matches!(
+        decode_account(&env.account(&escrow.vault).unwrap().data),
+        Err(DecodeError::UnknownDiscriminator(_))
+    ));
+}
@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+use std::io;
+
+use anchor_lang::Event;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_sdk::pubkey::Pubkey;
+use taskfi_client::stream::{
+    logs_subscribe_request, parse_logs_notification, parse_signatures, parse_transaction,
+    signatures_request, transaction_request, SIGNATURES_PAGE,
+};
+use taskfi_client::*;
+use taskfi_escrow::{RefundDestinationSet, EVENT_SCHEMA_VERSION};
+
+fn event(event_seq: u64) -> RefundDestinationSet {
+    RefundDestinationSet {
+        version: EVENT_SCHEMA_VERSION,
+        event_seq,
+        escrow: Pubkey::new_unique(),
+        destination: Pubkey::new_unique(),
+    }
+}
+
+fn program_data(event: &impl Event) -> String {
+    format!("Program data: {}", STANDARD.encode(event.data()))
+}
+
+/// A transaction calling the escrow program once, which emits `events`
+fn transaction(signature: &str, slot: u64, events: &[RefundDestinationSet]) -> TransactionLogs {
+    let program = taskfi_escrow::ID;
+    let mut logs = vec![format!("Program {program} invoke [1]")];
+    logs.extend(events.iter().map(program_data));
+    logs.push(format!(
+        "Program {program} consumed 21000 of 200000 compute units"
+    ));
+    logs.push(format!("Program {program} success"));
+    TransactionLogs {
+        signature: signature.to_string(),
+        slot,
+        failed: false,
+        logs,
+    }
+}
+
+enum Step {
+    Logs(TransactionLogs),
+    Drop,
+    FailSubscribe,
+}
+
+/// Plays back `script` as the live feed; `history` is every transaction the cluster has,
+/// which backfills are served from
+struct ScriptedSource {
+    script: VecDeque<Step>,
+    history: Vec<TransactionLogs>,
+    subscriptions: u32,
+    backfills: Vec<String>,
+}
+
+impl ScriptedSource {
+    fn new(script: Vec<Step>, history: Vec<TransactionLogs>) -> Self {
+        Self {
+            script: script.into(),
+            history,
+            subscriptions: 0,
+            backfills: Vec::new(),
+        }
+    }
+}
+
+impl LogSource for ScriptedSource {
+    fn subscribe(&mut self) -> io::Result<()> {
+        if matches!(self.script.front(), Some(Step::FailSubscribe) | None) {
+            self.script.pop_front();
+            return Err(io::ErrorKind::ConnectionRefused.into());
+        }
+        self.subscriptions += 1;
+        Ok(())
+    }
+
+    fn next_logs(&mut self) -> io::Result<TransactionLogs> {
+        match self.script.pop_front() {
+            Some(Step::Logs(logs)) => Ok(logs),
+            _ => Err(io::ErrorKind::ConnectionReset.into()),
+        }
+    }
+
+    fn backfill(&mut self, signature: &str) -> io::Result<Vec<TransactionLogs>> {
+        self.backfills.push(signature.to_string());
+        let after = self
+            .history
+            .iter()
+            .position(|logs| logs.signature == signature)
+            .map_or(0, |index| index + 1);
+        Ok(self.history[after..].to_vec())
+    }
+}
+
+#[test]
+fn events_missed_while_disconnected_are_backfilled_once_and_in_order() {
+    let events: Vec<_> = (0..5).map(event).collect();
+    let history = vec![
+        transaction("sig-1", 10, &events[..1]),
+        transaction("sig-2", 11, &events[1..3]),
+        transaction("sig-3", 12, &events[3..4]),
+        transaction("sig-4", 13, &events[4..]),
+    ];
+    // sig-2 lands while the connection is down; sig-3 is both backfilled and delivered live
+    let script = vec![
+        Step::Logs(history[0].clone()),
+        Step::Drop,
+        Step::FailSubscribe,
+        Step::Logs(history[2].clone()),
+        Step::Logs(history[3].clone()),
+    ];
+    let mut stream = EventStream::new(ScriptedSource::new(script, history));
+
+    let streamed: Vec<RawEvent> = stream.by_ref().take(5).map(Result::unwrap).collect();
+    let decoded: Vec<u64> = streamed
+        .iter()
+        .map(|raw| raw.decode::<RefundDestinationSet>().unwrap().event_seq)
+        .collect();
+    assert_eq!(decoded, vec![0, 1, 2, 3, 4]);
+    assert_eq!(
+        streamed
+            .iter()
+            .map(|raw| (raw.signature.as_str(), raw.index))
+            .collect::<Vec<_>>(),
+        vec![
+            ("sig-1", 0),
+            ("sig-2", 0),
+            ("sig-2", 1),
+            ("sig-3", 0),
+            ("sig-4", 0)
+        ]
+    );
+    assert_eq!(stream.source().subscriptions, 2);
+    assert_eq!(stream.source().backfills, vec!["sig-1".to_string()]);
+    assert_eq!(stream.last_signature(), Some("sig-4"));
+    assert_eq!(
+        streamed[0].decode::<RefundDestinationSet>().unwrap().escrow,
+        events[0].escrow
+    );
+
+    // With nothing left to connect to, the stream gives up after the configured attempts
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn resumed_streams_backfill_from_the_persisted_signature() {
+    let events: Vec<_> = (0..2).map(event).collect();
+    let history = vec![
+        transaction("sig-1", 10, &events[..1]),
+        transaction("sig-2", 11, &events[1..]),
+    ];
+    let script = vec![Step::Logs(history[0].clone())];
+    let mut stream = EventStream::new(ScriptedSource::new(script, history))
+        .resume_after("sig-1")
+        .max_reconnects(0);
+
+    let raw = stream.next().unwrap().unwrap();
+    assert_eq!(raw.signature, "sig-2");
+    // The live copy of sig-1 was already consumed before the restart
+    assert!(stream.next().unwrap().is_err());
+    assert_eq!(stream.source().backfills, vec!["sig-1".to_string()]);
+}
+
+#[test]
+fn only_events_of_the_escrow_program_in_successful_transactions_are_read() {
+    let program = taskfi_escrow::ID;
+    let other = Pubkey::new_unique();
+    let ours = event(7);
+    let mut logs = transaction("sig", 1, &[]);
+    logs.logs = vec![
+        format!("Program {other} invoke [1]"),
+        program_data(&event(1)),
+        format!("Program {program} invoke [2]"),
+        "Program log: Instruction: SetRefundDestination".to_string(),
+        program_data(&ours),
+        format!("Program {program} success"),
+        program_data(&event(2)),
+        format!("Program {other} success"),
+    ];
+    let events = events_in(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0]
+            .decode::<RefundDestinationSet>()
+            .unwrap()
+            .event_seq,
+        7
+    );
+    assert_eq!(events[0].event().unwrap().name(), "RefundDestinationSet");
+    // Decoding as another event type finds nothing
+    assert!(events[0]
+        .decode::<taskfi_escrow::BeneficiaryChanged>()
+        .is_none());
+
+    logs.failed = true;
+    assert!(events_in(&logs).is_empty());
+}
+
+#[test]
+fn websocket_notifications_are_parsed() {
+    let request: serde_json::Value =
+        serde_json::from_str(&logs_subscribe_request(3, "confirmed")).unwrap();
+    assert_eq!(request["method"], "logsSubscribe");
+    assert_eq!(
+        request["params"][0]["mentions"][0],
+        taskfi_escrow::ID.to_string()
+    );
+
+    let notification = r#"{"jsonrpc":"2.0","method":"logsNotification","params":{
+        "result":{"context":{"slot":5208469},"value":{"signature":"5h6x","err":null,
+        "logs":["Program log: hello"]}},"subscription":24040}}"#;
+    assert_eq!(
+        parse_logs_notification(notification),
+        Some(TransactionLogs {
+            signature: "5h6x".to_string(),
+            slot: 5_208_469,
+            failed: false,
+            logs: vec!["Program log: hello".to_string()],
+        })
+    );
+    let failed = notification.replace(
+        r#""err":null"#,
+        r#""err":{"InstructionError":[0,"Custom"]}"#,
+    );
+    assert!(parse_logs_notification(&failed).unwrap().failed);
+    assert_eq!(
+        parse_logs_notification(r#"{"jsonrpc":"2.0","result":24040,"id":3}"#),
+        None
+    );
+}
+
+#[test]
+fn backfill_requests_and_responses_are_parsed() {
+    let request: serde_json::Value =
+        serde_json::from_str(&signatures_request(4, "5h6x", None, "confirmed")).unwrap();
+    assert_eq!(request["method"], "getSignaturesForAddress");
+    assert_eq!(request["params"][0], taskfi_escrow::ID.to_string());
+    assert_eq!(request["params"][1]["until"], "5h6x");
+    assert_eq!(request["params"][1]["limit"], SIGNATURES_PAGE);
+    assert!(request["params"][1].get("before").is_none());
+    let request: serde_json::Value =
+        serde_json::from_str(&signatures_request(5, "5h6x", Some("9k2a"), "confirmed")).unwrap();
+    assert_eq!(request["params"][1]["before"], "9k2a");
+
+    let page = r#"{"jsonrpc":"2.0","result":[
+        {"signature":"9k2a","slot":5208471,"err":null,"memo":null,"blockTime":null},
+        {"signature":"7c1b","slot":5208470,"err":null,"memo":null,"blockTime":null}],"id":4}"#;
+    assert_eq!(
+        parse_signatures(page),
+        Some(vec!["9k2a".to_string(), "7c1b".to_string()])
+    );
+    assert_eq!(
+        parse_signatures(r#"{"jsonrpc":"2.0","error":{"code":-32602},"id":4}"#),
+        None
+    );
+
+    let request: serde_json::Value =
+        serde_json::from_str(&transaction_request(6, "7c1b", "confirmed")).unwrap();
+    assert_eq!(request["method"], "getTransaction");
+    assert_eq!(request["params"][0], "7c1b");
+    assert_eq!(request["params"][1]["maxSupportedTransactionVersion"], 0);
+
+    let response = r#"{"jsonrpc":"2.0","result":{"slot":5208470,"blockTime":null,
+        "meta":{"err":null,"logMessages":["Program log: hello"]},"transaction":{}},"id":6}"#;
+    assert_eq!(
+        parse_transaction("7c1b", response),
+        Some(TransactionLogs {
+            signature: "7c1b".to_string(),
+            slot: 5_208_470,
+            failed: false,
+            logs: vec!["Program log: hello".to_string()],
+        })
+    );
+    assert_eq!(
+        parse_transaction("7c1b", r#"{"jsonrpc":"2.0","result":null,"id":6}"#),
+        None
+    );
+}
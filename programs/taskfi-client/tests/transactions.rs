@@ -0,0 +1,181 @@
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::{Signer, SignerError};
+use taskfi_client::lookup_table::{self, MAX_ADDRESSES_PER_EXTEND};
+use taskfi_client::transaction::transaction_size;
+use taskfi_client::*;
+use taskfi_escrow::JurorPanel;
+use taskfi_test_utils::*;
+
+/// A full panel drawn on a disputed escrow, on a platform that pays its jurors a fee
+fn setup_full_panel() -> (TestEnv, Platform, EscrowFixture, JurorPanel) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.juror_fee_bps = 300;
+        params.jurors_per_panel = JurorPanel::MAX_JURORS as u8;
+    });
+    platform.register_jurors(&mut env, 2 * JurorPanel::MAX_JURORS, 1_000);
+    let escrow = EscrowBuilder::new("job-client-panel").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    for party in [escrow.hirer, escrow.freelancer] {
+        env.process(escrow.escalate_ix(&platform, &party), &[party])
+            .unwrap();
+    }
+    env.process(
+        escrow.draw_jurors_ix(&platform, &platform.moderator, JurorPanel::MAX_JURORS as u8),
+        &[platform.moderator],
+    )
+    .unwrap();
+    let panel = env.get(&escrow.panel());
+    (env, platform, escrow, panel)
+}
+
+#[test]
+fn full_panel_tallies_fit_a_v0_transaction_through_a_lookup_table() {
+    let (_env, _platform, escrow, panel) = setup_full_panel();
+    let tally = escrow.tally_paying_ix(&panel);
+
+    // The client lays out the same remaining accounts the fixtures pass
+    let juror_token_accounts: Vec<Pubkey> = panel
+        .jurors
+        .iter()
+        .map(|juror| pda::ata(juror, &escrow.mint))
+        .collect();
+    let remaining = tally_remaining_accounts(
+        &escrow.escrow,
+        panel.jurors.len() as u8,
+        &juror_token_accounts,
+        &[],
+    );
+    assert_eq!(
+        tally.accounts[tally.accounts.len() - remaining.len()..],
+        remaining[..]
+    );
+
+    let payer = Keypair::new();
+    let builder = VersionedTransactionBuilder::new(payer.pubkey())
+        .compute_unit_limit(1_400_000)
+        .compute_unit_price(1_000)
+        .instruction(tally.clone());
+    assert!(builder.legacy_size() > PACKET_DATA_SIZE);
+    assert!(matches!(
+        builder.build(Hash::default(), &[&payer]),
+        Err(ClientError::TooLarge { .. })
+    ));
+
+    let table = LookupTable::new(payer.pubkey(), 42);
+    let addresses = lookup_table_addresses(std::slice::from_ref(&tally));
+    assert!(addresses.len() <= lookup_table::MAX_ADDRESSES);
+    assert!(!addresses.contains(&taskfi_escrow::ID));
+    let transaction = builder
+        .lookup_table(table.account(addresses.clone()))
+        .build(Hash::default(), &[&payer])
+        .unwrap();
+    assert!(transaction_size(&transaction) <= PACKET_DATA_SIZE);
+
+    let lookups = transaction.message.address_table_lookups().unwrap();
+    assert_eq!(lookups.len(), 1);
+    assert_eq!(lookups[0].account_key, table.address);
+    assert_eq!(
+        lookups[0].writable_indexes.len() + lookups[0].readonly_indexes.len(),
+        addresses.len()
+    );
+    // Only the payer and the invoked programs stay in the static keys
+    let static_keys = transaction.message.static_account_keys();
+    assert_eq!(static_keys.len(), 3);
+    assert_eq!(static_keys[0], payer.pubkey());
+    assert!(static_keys.contains(&solana_sdk::compute_budget::ID));
+    assert!(static_keys.contains(&taskfi_escrow::ID));
+}
+
+#[test]
+fn lookup_table_instructions_encode_like_the_program_expects() {
+    let authority = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let table = LookupTable::new(authority, 1_234);
+    let (address, bump) = Pubkey::find_program_address(
+        &[authority.as_ref(), &1_234u64.to_le_bytes()],
+        &lookup_table::PROGRAM_ID,
+    );
+    assert_eq!((table.address, table.bump), (address, bump));
+
+    let create = table.create_ix(&payer);
+    assert_eq!(create.program_id, lookup_table::PROGRAM_ID);
+    let mut expected = vec![0, 0, 0, 0];
+    expected.extend_from_slice(&1_234u64.to_le_bytes());
+    expected.push(bump);
+    assert_eq!(create.data, expected);
+    assert_eq!(
+        create.accounts[..3],
+        [
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new(payer, true),
+        ]
+    );
+
+    // Addresses are split across extends small enough to send one per transaction
+    let addresses: Vec<Pubkey> = (0..2 * MAX_ADDRESSES_PER_EXTEND + 5)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    let extends = table.extend_ixs(&payer, &addresses);
+    assert_eq!(extends.len(), 3);
+    let last = &extends[2];
+    assert_eq!(last.data[..4], [2, 0, 0, 0]);
+    assert_eq!(last.data[4..12], 5u64.to_le_bytes());
+    assert_eq!(last.data[12..44], addresses[60].to_bytes());
+    assert_eq!(last.data.len(), 12 + 5 * 32);
+    assert!(last.accounts[1].is_signer && last.accounts[2].is_signer);
+}
+
+#[test]
+fn signers_and_programs_stay_out_of_lookup_tables() {
+    let program = Pubkey::new_unique();
+    let signer = Pubkey::new_unique();
+    let shared = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    let instructions = [
+        Instruction::new_with_bytes(
+            program,
+            &[],
+            vec![
+                AccountMeta::new(shared, false),
+                AccountMeta::new_readonly(signer, false),
+            ],
+        ),
+        Instruction::new_with_bytes(
+            program,
+            &[],
+            vec![
+                AccountMeta::new_readonly(signer, true),
+                AccountMeta::new_readonly(other, false),
+                AccountMeta::new(shared, false),
+                AccountMeta::new_readonly(program, false),
+            ],
+        ),
+    ];
+    assert_eq!(lookup_table_addresses(&instructions), vec![shared, other]);
+}
+
+#[test]
+fn transactions_need_every_required_signer() {
+    let payer = Keypair::new();
+    let party = Pubkey::new_unique();
+    let builder =
+        VersionedTransactionBuilder::new(payer.pubkey()).instruction(Instruction::new_with_bytes(
+            taskfi_escrow::ID,
+            &[],
+            vec![AccountMeta::new_readonly(party, true)],
+        ));
+    assert_eq!(
+        builder.build(Hash::default(), &[&payer]).unwrap_err(),
+        ClientError::Sign(SignerError::NotEnoughSigners)
+    );
+}
@@ -1,6 +1,14 @@
+#![allow(clippy::result_large_err)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{
+    self,
+    spl_token::{error::TokenError, instruction::AuthorityType},
+    Approve, Burn, CloseAccount, FreezeAccount, Mint, MintTo, SetAuthority, Token, TokenAccount,
+    TransferChecked,
+};
+use anchor_spl::associated_token::{self, get_associated_token_address, AssociatedToken};
 
 declare_id!("EscrowTaskFi1111111111111111111111111111111");
 
@@ -10,418 +18,13902 @@ pub mod taskfi_escrow {
 
     /// Initialize an escrow for a job payment
     pub fn initialize_escrow(
-        ctx: Context<InitializeEscrow>,
+        mut ctx: Context<InitializeEscrow>,
+        job_id: String,
+        amount: u64,
+        deadline: i64,
+        clawback_window: i64,
+    ) -> Result<()> {
+        open_escrow(&mut ctx, job_id, amount, deadline, clawback_window, None)
+    }
+
+    /// Create an escrow's accounts and terms without moving funds, so the terms exist before
+    /// funding. The hirer funds it in one or more fund_escrow tranches; work may start once
+    /// `funding_threshold_bps` of the amount is in the vault, and payout needs all of it.
+    /// Until fully funded within `unfunded_escrow_ttl`, the escrow may be closed.
+    ///
+    /// A `funder` other than the hirer (a sponsor or employer of record) may be named to pay
+    /// the tranches and receive refunds, while the hirer keeps approval authority.
+    pub fn create_escrow(
+        mut ctx: Context<InitializeEscrow>,
         job_id: String,
         amount: u64,
         deadline: i64,
+        clawback_window: i64,
+        funding_threshold_bps: u16,
+        funder: Option<Pubkey>,
     ) -> Result<()> {
+        require!(
+            funding_threshold_bps > 0 && funding_threshold_bps <= BPS_DENOMINATOR,
+            EscrowError::InvalidFundingThreshold
+        );
+        open_escrow(
+            &mut ctx,
+            job_id,
+            amount,
+            deadline,
+            clawback_window,
+            Some(funding_threshold_bps),
+        )?;
+        if let Some(funder) = funder {
+            ctx.accounts.escrow.funder = funder;
+        }
+        Ok(())
+    }
+
+    /// Move a tranche of the agreed amount into a created escrow's vault
+    /// (called by the funder, before the TTL)
+    pub fn fund_escrow(ctx: Context<FundEscrow>, amount: u64) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
-        
-        escrow.hirer = ctx.accounts.hirer.key();
-        escrow.freelancer = ctx.accounts.freelancer.key();
-        escrow.job_id = job_id;
-        escrow.amount = amount;
-        escrow.deadline = deadline;
-        escrow.is_released = false;
-        escrow.is_disputed = false;
-        escrow.created_at = Clock::get()?.unix_timestamp;
-        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        require!(!escrow.is_crowdfunded, EscrowError::CrowdfundedEscrow);
+        escrow.check_tranche(amount, Clock::get()?.unix_timestamp)?;
+        escrow.record_tranche(amount);
+        escrow.record_action(EscrowAction::Funded, ctx.accounts.funder.key())?;
 
-        // Transfer tokens from hirer to escrow account
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.hirer_token_account.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.escrow_token_account.to_account_info(),
-                authority: ctx.accounts.hirer.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
             },
         );
-        token::transfer(cpi_ctx, amount)?;
+        transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
 
-        emit!(EscrowCreated {
+        emit!(EscrowFunded {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
             escrow: escrow.key(),
-            hirer: escrow.hirer,
-            freelancer: escrow.freelancer,
-            job_id: escrow.job_id.clone(),
-            amount: escrow.amount,
-            deadline: escrow.deadline,
+            funder: escrow.funder,
+            amount,
+            funded_amount: escrow.funded_amount,
+            total: escrow.amount,
         });
 
         Ok(())
     }
 
-    /// Release payment to freelancer (called by hirer or admin)
-    pub fn release_payment(ctx: Context<ReleasePayment>) -> Result<()> {
+    /// Close an escrow that was never fully funded, returning any tranches to the funder and
+    /// its rent to the hirer (called by hirer before work may start, or by anyone once the
+    /// funding TTL has passed)
+    pub fn close_unfunded_escrow(ctx: Context<CloseUnfundedEscrow>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
         let escrow = &mut ctx.accounts.escrow;
-        
-        require!(!escrow.is_released, EscrowError::AlreadyReleased);
-        require!(!escrow.is_disputed, EscrowError::InDispute);
-
-        // Only hirer or platform admin can release
-        let signer = ctx.accounts.signer.key();
+        require!(!escrow.is_fully_funded(), EscrowError::EscrowAlreadyFunded);
+        let fund_by = escrow.fund_by.ok_or(EscrowError::EscrowAlreadyFunded)?;
         require!(
-            signer == escrow.hirer || signer == ctx.accounts.platform_admin.key(),
-            EscrowError::UnauthorizedRelease
+            (ctx.accounts.signer.key() == escrow.hirer && !escrow.is_funded)
+                || Clock::get()?.unix_timestamp > fund_by,
+            EscrowError::UnfundedEscrowNotExpired
+        );
+        require!(
+            escrow.contributor_count == 0,
+            EscrowError::ContributionsOutstanding
         );
 
-        // Transfer tokens from escrow to freelancer
+        // Stakes posted on the unfunded escrow go back to each party before the rent is returned
+        let (hirer_stake, freelancer_stake) = (escrow.hirer_stake, escrow.freelancer_stake);
+        escrow.hirer_stake = 0;
+        escrow.freelancer_stake = 0;
+        **escrow_info.try_borrow_mut_lamports()? -= hirer_stake + freelancer_stake;
+        **ctx.accounts.hirer.try_borrow_mut_lamports()? += hirer_stake;
+        **ctx.accounts.freelancer.try_borrow_mut_lamports()? += freelancer_stake;
+
+        let escrow_key = escrow.key();
+        ctx.accounts
+            .escrow_page
+            .escrows
+            .retain(|listed| *listed != escrow_key);
+        unlist_from_category(escrow, ctx.accounts.category_page.as_mut())?;
+        ctx.accounts.escrow_index.record_settlement();
+        retire_escrow(
+            escrow,
+            &mut ctx.accounts.tombstone,
+            &ctx.accounts.signer,
+            *ctx.bumps.get("tombstone").unwrap(),
+        )?;
+
         let escrow_seeds = &[
             b"escrow",
-            escrow.job_id.as_bytes(),
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
             &[escrow.bump],
         ];
         let signer_seeds = &[&escrow_seeds[..]];
-
+        if escrow.funded_amount > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.funder_token_account.to_account_info(),
+                    authority: escrow_info.clone(),
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, escrow.funded_amount, ctx.accounts.mint.decimals)?;
+        }
         let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.freelancer_token_account.to_account_info(),
-                authority: ctx.accounts.escrow.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.rent_payer.to_account_info(),
+                authority: escrow_info.clone(),
             },
             signer_seeds,
         );
-        token::transfer(cpi_ctx, escrow.amount)?;
+        token::close_account(cpi_ctx).map_err(token_error)?;
+
+        emit!(UnfundedEscrowClosed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow_key,
+            closed_by: ctx.accounts.signer.key(),
+            refunded: escrow.funded_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Close a released escrow once nothing is left on it: no held payment, bond or stake, an
+    /// emptied vault and every juror assignment closed. Its rent goes back to the rent payer
+    /// and a tombstone keeps the address from being opened again (callable by anyone).
+    /// `remaining_accounts` holds the panel's juror assignments, in order, when one was drawn.
+    pub fn close_settled_escrow(ctx: Context<CloseSettledEscrow>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(escrow.is_released, EscrowError::NotReleased);
+        require!(
+            escrow.held_until.is_none()
+                && escrow.hirer_bond == 0
+                && escrow.freelancer_bond == 0
+                && escrow.hirer_stake == 0
+                && escrow.freelancer_stake == 0
+                && ctx.accounts.escrow_token_account.data_is_empty(),
+            EscrowError::EscrowNotSettled
+        );
+
+        // Assignments still read the escrow, so each must be closed before it goes
+        let panel_info = &ctx.accounts.panel;
+        if panel_info.owner == ctx.program_id {
+            let panel = Account::<JurorPanel>::try_from(panel_info)?;
+            require!(
+                ctx.remaining_accounts.len() == panel.jurors.len(),
+                EscrowError::InvalidAssignmentAccount
+            );
+            for (index, assignment_info) in ctx.remaining_accounts.iter().enumerate() {
+                let (expected, _) = Pubkey::find_program_address(
+                    &[b"assignment", panel.key().as_ref(), &[index as u8]],
+                    ctx.program_id,
+                );
+                require_keys_eq!(
+                    assignment_info.key(),
+                    expected,
+                    EscrowError::InvalidAssignmentAccount
+                );
+                require!(
+                    assignment_info.data_is_empty(),
+                    EscrowError::AssignmentStillOpen
+                );
+            }
+        }
+
+        let escrow_key = escrow.key();
+        ctx.accounts
+            .escrow_page
+            .escrows
+            .retain(|listed| *listed != escrow_key);
+        unlist_from_category(escrow, ctx.accounts.category_page.as_mut())?;
+        retire_escrow(
+            escrow,
+            &mut ctx.accounts.tombstone,
+            &ctx.accounts.signer,
+            *ctx.bumps.get("tombstone").unwrap(),
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        emit!(SettledEscrowClosed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow_key,
+            closed_by: ctx.accounts.signer.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Initialize a grant: an escrow with no deliverable whose funds vest linearly to the
+    /// recipient between `vesting_start` and `vesting_end`. The grantor may revoke the unvested
+    /// part for `revocation_window` seconds after creation.
+    pub fn initialize_grant(
+        mut ctx: Context<InitializeEscrow>,
+        job_id: String,
+        amount: u64,
+        vesting_start: i64,
+        vesting_end: i64,
+        revocation_window: i64,
+    ) -> Result<()> {
+        require!(
+            vesting_start < vesting_end && revocation_window >= 0,
+            EscrowError::InvalidGrantSchedule
+        );
+        open_escrow(&mut ctx, job_id, amount, vesting_end, 0, None)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        let grant = GrantTerms {
+            total: amount,
+            claimed: 0,
+            vesting_start,
+            vesting_end,
+            revocable_until: escrow
+                .created_at
+                .checked_add(revocation_window)
+                .ok_or(EscrowError::MathOverflow)?,
+        };
+
+        emit!(GrantCreated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            grantor: escrow.hirer,
+            recipient: escrow.freelancer,
+            amount,
+            vesting_start,
+            vesting_end,
+            revocable_until: grant.revocable_until,
+        });
+        escrow.grant = Some(grant);
+
+        Ok(())
+    }
+
+    /// Initialize a retainer: an escrow funding one period of the freelancer's time, paid out
+    /// when the period ends. With auto-renewal enabled, each payout also pulls the next period
+    /// from the hirer. Either party may cancel, effective `notice_period` seconds later.
+    pub fn initialize_retainer(
+        mut ctx: Context<InitializeEscrow>,
+        job_id: String,
+        period_amount: u64,
+        period_length: i64,
+        notice_period: i64,
+    ) -> Result<()> {
+        require!(
+            period_length > 0 && notice_period >= 0,
+            EscrowError::InvalidRetainerTerms
+        );
+        let period_end = Clock::get()?
+            .unix_timestamp
+            .checked_add(period_length)
+            .ok_or(EscrowError::MathOverflow)?;
+        open_escrow(&mut ctx, job_id, period_amount, period_end, 0, None)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        emit!(RetainerCreated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            period_amount,
+            period_length,
+            period_end,
+            notice_period,
+        });
+        escrow.retainer = Some(RetainerTerms {
+            period_amount,
+            period_length,
+            period_end,
+            notice_period,
+            auto_renew: false,
+            ends_at: None,
+            periods_paid: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Release payment to freelancer, less any SLA penalty (called by hirer or admin)
+    pub fn release_payment<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleasePayment<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            !escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowFrozen
+        );
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            !escrow.has_disputed_milestone(),
+            EscrowError::MilestoneDisputed
+        );
+
+        // Only hirer or platform admin can release
+        let signer = session_authority(
+            &ctx.accounts.signer,
+            &ctx.accounts.session,
+            SessionKey::RELEASE_PAYMENT,
+            escrow.amount,
+        )?;
+        require!(
+            signer == escrow.hirer || signer == ctx.accounts.platform_admin.key(),
+            EscrowError::UnauthorizedRelease
+        );
+
+        // Large releases act as the confirmation of an earlier, matured proposal
+        let config = &ctx.accounts.config;
+        if config.requires_two_step_release(escrow.amount) || escrow.is_large {
+            let proposed_at = escrow
+                .release_proposed_at
+                .ok_or(EscrowError::ReleaseNotProposed)?;
+            let confirmable_at = proposed_at
+                .checked_add(config.release_confirm_delay)
+                .ok_or(EscrowError::MathOverflow)?;
+            require!(
+                Clock::get()?.unix_timestamp >= confirmable_at,
+                EscrowError::ReleaseConfirmDelayActive
+            );
+        }
 
+        // Book the release before any tokens move. SLA breaches return part of the payout to
+        // the funder, so the freelancer is paid what is left.
+        let breakdown = escrow.sla_breakdown();
+        let amount = escrow.amount - breakdown.penalty;
+        let now = Clock::get()?.unix_timestamp;
         escrow.is_released = true;
-        escrow.released_at = Some(Clock::get()?.unix_timestamp);
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(now);
+        escrow.record_action(EscrowAction::Released, signer)?;
+
+        let pays_now = escrow.clawback_window == 0;
+        if pays_now {
+            let fee = escrow.platform_fee(amount);
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                amount - fee,
+                fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+        } else {
+            // Clawback mode: funds stay in the vault until withdraw_held_payment
+            escrow.held_until = Some(
+                now.checked_add(escrow.clawback_window)
+                    .ok_or(EscrowError::MathOverflow)?,
+            );
+        }
+
+        apply_sla_penalty(
+            escrow,
+            &escrow_info,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_token_account,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            breakdown.penalty,
+        )?;
+
+        // Transfer tokens from escrow to freelancer
+        if pays_now {
+            let freelancer_token_account = load_payout_account(
+                escrow,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.freelancer,
+                &ctx.accounts.mint,
+                &ctx.accounts.signer,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.token_program,
+                &ctx.accounts.system_program,
+            )?;
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &freelancer_token_account,
+                &ctx.accounts.token_program,
+                amount,
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
 
         emit!(PaymentReleased {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
             escrow: escrow.key(),
             freelancer: escrow.freelancer,
             amount: escrow.amount,
             released_by: signer,
+            delivery_breaches: breakdown.delivery_breaches,
+            response_breaches: breakdown.response_breaches,
+            sla_penalty: breakdown.penalty,
         });
 
         Ok(())
     }
 
     /// Initiate dispute (called by hirer or freelancer)
-    pub fn initiate_dispute(ctx: Context<InitiateDispute>, reason: String) -> Result<()> {
+    pub fn initiate_dispute(
+        ctx: Context<InitiateDispute>,
+        reason_code: DisputeReasonCode,
+        reason: DisputeStatement,
+    ) -> Result<()> {
+        reason.validate()?;
         let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        require!(escrow.milestones.is_empty(), EscrowError::MilestoneEscrow);
         
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
         require!(!escrow.is_released, EscrowError::AlreadyReleased);
         require!(!escrow.is_disputed, EscrowError::AlreadyDisputed);
 
-        let signer = ctx.accounts.signer.key();
+        let signer = session_authority(
+            &ctx.accounts.signer,
+            &ctx.accounts.session,
+            SessionKey::INITIATE_DISPUTE,
+            escrow.amount,
+        )?;
         require!(
             signer == escrow.hirer || signer == escrow.freelancer,
             EscrowError::UnauthorizedDispute
         );
 
+        let now = Clock::get()?.unix_timestamp;
+        let (role, reputation) = if signer == escrow.hirer {
+            (EscrowRole::Hirer, &mut ctx.accounts.hirer_reputation)
+        } else {
+            (
+                EscrowRole::Freelancer,
+                &mut ctx.accounts.freelancer_reputation,
+            )
+        };
+        open_dispute(
+            &ctx.accounts.config,
+            reputation,
+            &mut ctx.accounts.daily_stats,
+            *ctx.bumps.get("daily_stats").unwrap(),
+            reason_code,
+            escrow.release_requested_at.is_some() || now > escrow.deadline,
+            now,
+        )?;
+        escrow.disputed_by = Some(role);
+
         escrow.is_disputed = true;
         escrow.dispute_reason = Some(reason.clone());
-        escrow.disputed_at = Some(Clock::get()?.unix_timestamp);
+        escrow.dispute_reason_code = Some(reason_code);
+        escrow.disputed_at = Some(now);
+        escrow.dispute_tier = DisputeTier::Mediation;
+        escrow.mediator = None;
+        escrow.mediation = None;
+        escrow.tier_deadline = now
+            .checked_add(ctx.accounts.config.tier_windows[DisputeTier::Mediation as usize])
+            .ok_or(EscrowError::MathOverflow)?;
 
+        escrow.record_action(EscrowAction::DisputeOpened, signer)?;
         emit!(DisputeInitiated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
             escrow: escrow.key(),
             initiated_by: signer,
+            reason_code,
             reason,
         });
 
         Ok(())
     }
 
-    /// Resolve dispute (called by platform admin only)
-    pub fn resolve_dispute(
-        ctx: Context<ResolveDispute>,
-        hirer_amount: u64,
-        freelancer_amount: u64,
+    /// Resolve dispute with explicit amounts or a canonical template (called by platform
+    /// admin only)
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveDispute<'info>>,
+        ruling: DisputeRuling,
     ) -> Result<()> {
+        ctx.accounts
+            .config
+            .check_rate_limit(AdminAction::ResolveDispute, Clock::get()?.unix_timestamp)?;
+        let escrow_info = ctx.accounts.escrow.to_account_info();
         let escrow = &mut ctx.accounts.escrow;
         
         require!(escrow.is_disputed, EscrowError::NotInDispute);
         require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            !escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowFrozen
+        );
+
+        // Moderators rule within their tier window; only the admin rules at the final tier.
+        // Above the dual-approval threshold any other moderator or the admin co-signs the ruling.
+        let resolver = ctx.accounts.admin.key();
+        let config = &ctx.accounts.config;
+        let requires_dual_approval = config.requires_dual_approval(escrow.amount);
+        let co_signing = requires_dual_approval
+            && matches!(&escrow.pending_resolution, Some(approval) if approval.approver != resolver);
+        let co_signer = co_signing && config.is_moderator(&resolver);
+        match escrow.dispute_tier {
+            DisputeTier::Moderator => {
+                require!(
+                    config.moderators.contains(&resolver) || co_signer,
+                    EscrowError::UnauthorizedResolution
+                );
+                require!(
+                    Clock::get()?.unix_timestamp <= escrow.tier_deadline,
+                    EscrowError::TierWindowElapsed
+                );
+            }
+            DisputeTier::Final => {
+                require!(
+                    resolver == config.admin || co_signer,
+                    EscrowError::UnauthorizedResolution
+                );
+            }
+            DisputeTier::Mediation | DisputeTier::Panel => {
+                return err!(EscrowError::WrongDisputeTier);
+            }
+        }
+        let (hirer_amount, freelancer_amount) = ruling.amounts(escrow.amount);
         require!(
             hirer_amount + freelancer_amount == escrow.amount,
             EscrowError::InvalidSplitAmount
         );
+        escrow.guard_same_slot(EscrowAction::DisputeResolved)?;
+
+        // A moderator ruling pays the moderator who made it; the admin rules for free
+        let mut arbitrator = resolver;
+        if requires_dual_approval {
+            if !co_signing {
+                // First approval (or a revision by the same key): record it and wait
+                escrow.pending_resolution = Some(ResolutionApproval {
+                    approver: resolver,
+                    hirer_amount,
+                    freelancer_amount,
+                });
+                ctx.accounts.audit_log.record(
+                    resolver,
+                    AdminAction::ResolveDispute,
+                    escrow.key(),
+                )?;
+
+                emit!(ResolutionApproved {
+                    version: EVENT_SCHEMA_VERSION,
+                    event_seq: next_event_seq(&mut escrow.event_seq),
+                    escrow: escrow.key(),
+                    approver: resolver,
+                    hirer_amount,
+                    freelancer_amount,
+                });
+                return Ok(());
+            }
+            let approval = escrow.pending_resolution.take().unwrap();
+            require!(
+                approval.hirer_amount == hirer_amount
+                    && approval.freelancer_amount == freelancer_amount,
+                EscrowError::ResolutionSplitMismatch
+            );
+            arbitrator = approval.approver;
+        }
+
+        let arbitration_fee = if escrow.dispute_tier == DisputeTier::Moderator {
+            (escrow.amount as u128 * config.moderator_fee_bps as u128 / BPS_DENOMINATOR as u128)
+                as u64
+        } else {
+            0
+        };
+        let (hirer_fee, freelancer_fee) =
+            arbitration_fee_shares(arbitration_fee, escrow.amount, freelancer_amount);
+        let ruled_freelancer_amount = freelancer_amount;
+        let hirer_amount = hirer_amount - hirer_fee;
+        let freelancer_amount = freelancer_amount - freelancer_fee;
+
+        // Book the ruling before any tokens move
+        escrow.is_released = true;
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(Clock::get()?.unix_timestamp);
+        escrow.ruling_freelancer_amount = Some(ruled_freelancer_amount);
+        record_dispute_outcome(
+            &escrow.dispute_outcome(),
+            &ctx.accounts.config,
+            &mut ctx.accounts.hirer_reputation,
+            &mut ctx.accounts.freelancer_reputation,
+        )?;
+        ctx.accounts
+            .audit_log
+            .record(resolver, AdminAction::ResolveDispute, escrow.key())?;
+        escrow.record_action(EscrowAction::DisputeResolved, resolver)?;
+        if freelancer_amount > 0 {
+            let fee = escrow.platform_fee(freelancer_amount);
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                freelancer_amount - fee,
+                fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+        }
+
+        // Transfer freelancer's portion
+        if freelancer_amount > 0 {
+            let freelancer_token_account = load_payout_account(
+                escrow,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.freelancer,
+                &ctx.accounts.mint,
+                &ctx.accounts.admin,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.token_program,
+                &ctx.accounts.system_program,
+            )?;
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &freelancer_token_account,
+                &ctx.accounts.token_program,
+                freelancer_amount,
+            )?;
+        }
 
         let escrow_seeds = &[
             b"escrow",
-            escrow.job_id.as_bytes(),
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
             &[escrow.bump],
         ];
         let signer_seeds = &[&escrow_seeds[..]];
 
-        // Transfer freelancer's portion
-        if freelancer_amount > 0 {
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.escrow_token_account.to_account_info(),
-                    to: ctx.accounts.freelancer_token_account.to_account_info(),
-                    authority: ctx.accounts.escrow.to_account_info(),
-                },
+        if arbitration_fee > 0 {
+            let destination = ctx
+                .accounts
+                .arbitrator_token_account
+                .as_ref()
+                .ok_or(EscrowError::InvalidArbitratorAccount)?;
+            pay_arbitrator(
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                destination,
+                &arbitrator,
+                &ctx.accounts.token_program,
+                arbitration_fee,
                 signer_seeds,
-            );
-            token::transfer(cpi_ctx, freelancer_amount)?;
+            )?;
         }
 
         // Transfer hirer's portion (refund)
         if hirer_amount > 0 {
+            refund_funders(
+                escrow,
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                hirer_amount,
+                signer_seeds,
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        if arbitration_fee > 0 {
+            emit!(ArbitratorPaid {
+                version: EVENT_SCHEMA_VERSION,
+                event_seq: next_event_seq(&mut escrow.event_seq),
+                escrow: escrow.key(),
+                arbitrator,
+                tier: escrow.dispute_tier,
+                amount: arbitration_fee,
+            });
+        }
+
+        emit!(DisputeResolved {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            hirer_amount: escrow.amount - ruled_freelancer_amount,
+            freelancer_amount: ruled_freelancer_amount,
+            template: ruling.template(),
+            resolved_by: resolver,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a dispute on an escrow of at most `auto_resolve_max_amount` by the configured
+    /// policy instead of arbitration, once `auto_resolve_cooling_off` has passed since it was
+    /// opened: the freelancer gets `auto_resolve_freelancer_bps` of the amount and the funder
+    /// the rest, less the fixed `auto_resolve_fee` kept by the treasury (called by either party)
+    pub fn auto_resolve_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, AutoResolveDispute<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let config = &ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == escrow.hirer || signer == escrow.freelancer,
+            EscrowError::UnauthorizedDispute
+        );
+        require!(
+            escrow.amount <= config.auto_resolve_max_amount,
+            EscrowError::AutoResolutionUnavailable
+        );
+        require!(
+            !matches!(escrow.dispute_reason_code, Some(code) if !code.auto_resolvable()),
+            EscrowError::AutoResolutionUnavailable
+        );
+        // A drawn panel is already ruling on the dispute
+        require!(
+            escrow.dispute_tier != DisputeTier::Panel,
+            EscrowError::WrongDisputeTier
+        );
+        let cooled_off_at = escrow
+            .disputed_at
+            .unwrap_or_default()
+            .checked_add(config.auto_resolve_cooling_off)
+            .ok_or(EscrowError::MathOverflow)?;
+        require!(now >= cooled_off_at, EscrowError::CoolingOffActive);
+        escrow.guard_same_slot(EscrowAction::DisputeResolved)?;
+
+        let freelancer_amount = (escrow.amount as u128 * config.auto_resolve_freelancer_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        let hirer_share = escrow.amount - freelancer_amount;
+        let fee = config.auto_resolve_fee.min(hirer_share);
+        let hirer_amount = hirer_share - fee;
+
+        // Book the ruling before any tokens move
+        escrow.is_released = true;
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(now);
+        escrow.ruling_freelancer_amount = Some(freelancer_amount);
+        record_dispute_outcome(
+            &escrow.dispute_outcome(),
+            &ctx.accounts.config,
+            &mut ctx.accounts.hirer_reputation,
+            &mut ctx.accounts.freelancer_reputation,
+        )?;
+        escrow.record_action(EscrowAction::DisputeResolved, signer)?;
+        if freelancer_amount > 0 {
+            let platform_fee = escrow.platform_fee(freelancer_amount);
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                freelancer_amount - platform_fee,
+                platform_fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.token_program,
+                freelancer_amount,
+            )?;
+        }
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+
+        if fee > 0 {
+            let treasury = &ctx.accounts.fees.treasury_token_account;
+            require!(
+                treasury.owner == ctx.accounts.config.treasury
+                    && treasury.mint == ctx.accounts.escrow_token_account.mint,
+                EscrowError::InvalidTreasuryAccount
+            );
             let cpi_ctx = CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.escrow_token_account.to_account_info(),
-                    to: ctx.accounts.hirer_token_account.to_account_info(),
-                    authority: ctx.accounts.escrow.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: escrow_info.clone(),
                 },
                 signer_seeds,
             );
-            token::transfer(cpi_ctx, hirer_amount)?;
+            transfer_tokens(cpi_ctx, fee, ctx.accounts.mint.decimals)?;
         }
 
-        escrow.is_released = true;
-        escrow.released_at = Some(Clock::get()?.unix_timestamp);
+        if hirer_amount > 0 {
+            refund_funders(
+                escrow,
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                hirer_amount,
+                signer_seeds,
+            )?;
+        }
 
-        emit!(DisputeResolved {
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(DisputeAutoResolved {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
             escrow: escrow.key(),
             hirer_amount,
             freelancer_amount,
-            resolved_by: ctx.accounts.admin.key(),
+            fee,
+            triggered_by: signer,
         });
 
         Ok(())
     }
 
-    /// Emergency refund (called by platform admin only, for emergencies)
-    pub fn emergency_refund(ctx: Context<EmergencyRefund>) -> Result<()> {
+    /// Emergency refund of an escrow that is disputed, frozen or past its deadline, giving the
+    /// reason it was needed (called by platform admin only, for emergencies)
+    pub fn emergency_refund<'info>(
+        ctx: Context<'_, '_, '_, 'info, EmergencyRefund<'info>>,
+        reason: EmergencyRefundReason,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .check_rate_limit(AdminAction::EmergencyRefund, Clock::get()?.unix_timestamp)?;
+        let escrow_info = ctx.accounts.escrow.to_account_info();
         let escrow = &mut ctx.accounts.escrow;
+        escrow.guard_same_slot(EscrowAction::Refunded)?;
         
         require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        // An escrow still running normally settles through its own flows
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            escrow.is_disputed || escrow.is_frozen(now) || now > escrow.deadline,
+            EscrowError::EmergencyRefundNotAllowed
+        );
+        // Only what is in the vault goes back: a crowdfunded escrow may still be short of its
+        // amount, and a grant's claims come off its amount but not what it was funded with
+        let refunded = escrow.amount.min(escrow.funded_amount);
+        ctx.accounts.refund_usage.record(
+            &ctx.accounts.config,
+            ctx.accounts.admin.key(),
+            *ctx.bumps.get("refund_usage").unwrap(),
+            refunded,
+        )?;
+
+        if escrow.is_disputed {
+            close_dispute(
+                escrow.disputed_by,
+                &mut ctx.accounts.hirer_reputation,
+                &mut ctx.accounts.freelancer_reputation,
+            );
+        }
+        for milestone in &escrow.milestones {
+            if milestone.status == MilestoneStatus::Disputed {
+                close_dispute(
+                    milestone.disputed_by,
+                    &mut ctx.accounts.hirer_reputation,
+                    &mut ctx.accounts.freelancer_reputation,
+                );
+            }
+        }
+        escrow.is_released = true;
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(now);
+
+        ctx.accounts.audit_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::EmergencyRefund,
+            escrow.key(),
+        )?;
+
+        escrow.record_action(EscrowAction::Refunded, ctx.accounts.admin.key())?;
 
         let escrow_seeds = &[
             b"escrow",
-            escrow.job_id.as_bytes(),
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
             &[escrow.bump],
         ];
         let signer_seeds = &[&escrow_seeds[..]];
 
-        // Refund full amount to hirer
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.hirer_token_account.to_account_info(),
-                authority: ctx.accounts.escrow.to_account_info(),
-            },
+        refund_funders(
+            escrow,
+            &escrow_info,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_token_account,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            refunded,
             signer_seeds,
-        );
-        token::transfer(cpi_ctx, escrow.amount)?;
+        )?;
 
-        escrow.is_released = true;
-        escrow.released_at = Some(Clock::get()?.unix_timestamp);
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
 
         emit!(EmergencyRefundIssued {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
             escrow: escrow.key(),
-            amount: escrow.amount,
+            amount: refunded,
             refunded_by: ctx.accounts.admin.key(),
+            reason,
         });
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(job_id: String)]
-pub struct InitializeEscrow<'info> {
-    #[account(
-        init,
-        payer = hirer,
-        space = Escrow::SIZE,
-        seeds = [b"escrow", job_id.as_bytes()],
-        bump
-    )]
-    pub escrow: Account<'info, Escrow>,
-    
-    #[account(mut)]
-    pub hirer: Signer<'info>,
-    
-    /// CHECK: Freelancer public key, validated in business logic
-    pub freelancer: UncheckedAccount<'info>,
-    
-    #[account(mut)]
-    pub hirer_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        init,
-        payer = hirer,
-        associated_token::mint = hirer_token_account.mint,
-        associated_token::authority = escrow,
-    )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
-    
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    /// Initialize the platform config and the juror staking pool
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.stake_mint = ctx.accounts.stake_mint.key();
+        config.apply(params)?;
+        config.event_seq = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
 
-#[derive(Accounts)]
-pub struct ReleasePayment<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.job_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
-    
+        let juror_pool = &mut ctx.accounts.juror_pool;
+        juror_pool.total_stake = 0;
+        juror_pool.jurors = Vec::new();
+        juror_pool.event_seq = 0;
+        juror_pool.bump = *ctx.bumps.get("juror_pool").unwrap();
+
+        let audit_log = &mut ctx.accounts.audit_log;
+        audit_log.head = 0;
+        audit_log.total = 0;
+        audit_log.entries = Vec::new();
+        audit_log.bump = *ctx.bumps.get("audit_log").unwrap();
+
+        Ok(())
+    }
+
+    /// Stake into the juror pool, registering the signer for arbitration duty
+    pub fn register_juror(ctx: Context<RegisterJuror>, amount: u64) -> Result<()> {
+        let juror_pool = &mut ctx.accounts.juror_pool;
+        let juror = ctx.accounts.juror.key();
+
+        let stake = match juror_pool.jurors.iter_mut().find(|j| j.juror == juror) {
+            Some(entry) => {
+                entry.stake = entry
+                    .stake
+                    .checked_add(amount)
+                    .ok_or(EscrowError::MathOverflow)?;
+                entry.stake
+            }
+            None => {
+                require!(
+                    juror_pool.jurors.len() < JurorPool::MAX_JURORS,
+                    EscrowError::JurorPoolFull
+                );
+                juror_pool.jurors.push(PooledJuror {
+                    juror,
+                    stake: amount,
+                    open_assignments: 0,
+                    specialty: None,
+                });
+                amount
+            }
+        };
+        require!(
+            stake >= ctx.accounts.config.min_juror_stake,
+            EscrowError::StakeBelowMinimum
+        );
+        juror_pool.total_stake = juror_pool
+            .total_stake
+            .checked_add(amount)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.juror_token_account.to_account_info(),
+                mint: ctx.accounts.stake_mint.to_account_info(),
+                to: ctx.accounts.pool_vault.to_account_info(),
+                authority: ctx.accounts.juror.to_account_info(),
+            },
+        );
+        transfer_tokens(cpi_ctx, amount, ctx.accounts.stake_mint.decimals)?;
+
+        emit!(JurorRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut juror_pool.event_seq),
+            juror,
+            stake,
+            total_stake: juror_pool.total_stake,
+        });
+
+        Ok(())
+    }
+
+    /// Leave the juror pool and withdraw the full stake
+    pub fn deregister_juror(ctx: Context<DeregisterJuror>) -> Result<()> {
+        let pool_info = ctx.accounts.juror_pool.to_account_info();
+        let juror_pool = &mut ctx.accounts.juror_pool;
+        let juror = ctx.accounts.juror.key();
+
+        let index = juror_pool
+            .jurors
+            .iter()
+            .position(|j| j.juror == juror)
+            .ok_or(EscrowError::JurorNotRegistered)?;
+        require!(
+            juror_pool.jurors[index].open_assignments == 0,
+            EscrowError::JurorHasOpenAssignments
+        );
+
+        let entry = juror_pool.jurors.remove(index);
+        juror_pool.total_stake -= entry.stake;
+
+        let pool_seeds = &[b"juror_pool".as_ref(), &[juror_pool.bump]];
+        let signer_seeds = &[&pool_seeds[..]];
+
+        if entry.stake > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_vault.to_account_info(),
+                    mint: ctx.accounts.stake_mint.to_account_info(),
+                    to: ctx.accounts.juror_token_account.to_account_info(),
+                    authority: pool_info,
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, entry.stake, ctx.accounts.stake_mint.decimals)?;
+        }
+
+        emit!(JurorDeregistered {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut juror_pool.event_seq),
+            juror,
+            stake: entry.stake,
+        });
+
+        Ok(())
+    }
+
+    /// Draw a stake-weighted juror panel for a disputed escrow. Only moderators may draw, so
+    /// neither party can pick the slot whose hash seeds the panel.
+    pub fn draw_jurors<'info>(ctx: Context<'_, '_, '_, 'info, DrawJurors<'info>>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let config = &ctx.accounts.config;
+
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            escrow.dispute_tier == DisputeTier::Panel,
+            EscrowError::WrongDisputeTier
+        );
+
+        let signer = ctx.accounts.signer.key();
+        require!(
+            config.is_moderator(&signer),
+            EscrowError::UnauthorizedJurorDraw
+        );
+
+        let k = config.jurors_per_panel as usize;
+        require!(
+            ctx.remaining_accounts.len() == k,
+            EscrowError::InvalidAssignmentAccount
+        );
+
+        let seed = panel_seed(&ctx.accounts.recent_slothashes, &escrow.key())?;
+        let picks = draw_weighted(
+            &ctx.accounts.juror_pool.jurors,
+            k,
+            &seed,
+            &[escrow.hirer, escrow.freelancer],
+            escrow.taxonomy_category,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let respond_by = now
+            .checked_add(config.juror_response_window)
+            .ok_or(EscrowError::MathOverflow)?;
+        let commit_deadline = respond_by
+            .checked_add(config.vote_commit_window)
+            .ok_or(EscrowError::MathOverflow)?;
+        let reveal_deadline = commit_deadline
+            .checked_add(config.vote_reveal_window)
+            .ok_or(EscrowError::MathOverflow)?;
+        let escrow_key = escrow.key();
+        let panel_key = ctx.accounts.panel.key();
+        let rent = Rent::get()?;
+
+        let mut jurors = Vec::with_capacity(k);
+        for (index, (pick, assignment_info)) in
+            picks.iter().zip(ctx.remaining_accounts.iter()).enumerate()
+        {
+            let juror = ctx.accounts.juror_pool.jurors[*pick].juror;
+            let stake = ctx.accounts.juror_pool.jurors[*pick].stake;
+            let index = index as u8;
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"assignment", panel_key.as_ref(), &[index]],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                assignment_info.key(),
+                expected,
+                EscrowError::InvalidAssignmentAccount
+            );
+
+            let assignment_seeds = &[b"assignment", panel_key.as_ref(), &[index], &[bump]];
+            anchor_lang::system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::CreateAccount {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: assignment_info.clone(),
+                    },
+                    &[&assignment_seeds[..]],
+                ),
+                rent.minimum_balance(JurorAssignment::SIZE),
+                JurorAssignment::SIZE as u64,
+                ctx.program_id,
+            )?;
+
+            let assignment = JurorAssignment {
+                panel: panel_key,
+                escrow: escrow_key,
+                juror,
+                stake,
+                index,
+                respond_by,
+                responded: false,
+                commitment: None,
+                revealed_bps: None,
+                slashed: false,
+                bump,
+            };
+            assignment.try_serialize(&mut &mut assignment_info.try_borrow_mut_data()?[..])?;
+
+            ctx.accounts.juror_pool.jurors[*pick].open_assignments += 1;
+            jurors.push(juror);
+        }
+
+        let panel = &mut ctx.accounts.panel;
+        panel.escrow = escrow_key;
+        panel.jurors = jurors.clone();
+        panel.payer = ctx.accounts.payer.key();
+        panel.drawn_at = now;
+        panel.respond_by = respond_by;
+        panel.commit_deadline = commit_deadline;
+        panel.reveal_deadline = reveal_deadline;
+        panel.ruling_bps = None;
+        panel.bump = *ctx.bumps.get("panel").unwrap();
+
+        emit!(JurorsDrawn {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.escrow.event_seq),
+            escrow: escrow_key,
+            panel: panel_key,
+            jurors,
+            respond_by,
+            commit_deadline,
+            reveal_deadline,
+        });
+
+        Ok(())
+    }
+
+    /// Acknowledge a juror assignment before the response deadline
+    pub fn accept_assignment(ctx: Context<AcceptAssignment>) -> Result<()> {
+        let assignment = &mut ctx.accounts.assignment;
+
+        require!(
+            !assignment.responded,
+            EscrowError::AssignmentAlreadyAnswered
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= assignment.respond_by,
+            EscrowError::ResponseWindowClosed
+        );
+
+        assignment.responded = true;
+
+        emit!(JurorAssignmentAccepted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.escrow.event_seq),
+            escrow: assignment.escrow,
+            juror: assignment.juror,
+        });
+
+        Ok(())
+    }
+
+    /// Slash a juror who missed the response deadline or never revealed a committed vote
+    /// (permissionless crank)
+    pub fn slash_juror(ctx: Context<SlashJuror>) -> Result<()> {
+        let pool_info = ctx.accounts.juror_pool.to_account_info();
+        let juror_pool = &mut ctx.accounts.juror_pool;
+        let assignment = &mut ctx.accounts.assignment;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(!assignment.slashed, EscrowError::AssignmentAlreadyAnswered);
+        if assignment.responded {
+            require!(
+                !assignment.is_excused(&ctx.accounts.escrow, &ctx.accounts.panel),
+                EscrowError::AlreadyReleased
+            );
+            require!(
+                assignment.revealed_bps.is_none(),
+                EscrowError::AssignmentAlreadyAnswered
+            );
+            require!(
+                now > ctx.accounts.panel.reveal_deadline,
+                EscrowError::RevealWindowOpen
+            );
+        } else {
+            require!(now > assignment.respond_by, EscrowError::ResponseWindowOpen);
+        }
+
+        let entry = juror_pool
+            .jurors
+            .iter_mut()
+            .find(|j| j.juror == assignment.juror)
+            .ok_or(EscrowError::JurorNotRegistered)?;
+        let amount = ctx.accounts.config.juror_slash_amount.min(entry.stake);
+        entry.stake -= amount;
+        entry.open_assignments -= 1;
+        juror_pool.total_stake -= amount;
+        assignment.slashed = true;
+
+        if amount > 0 {
+            let pool_seeds = &[b"juror_pool".as_ref(), &[juror_pool.bump]];
+            let signer_seeds = &[&pool_seeds[..]];
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_vault.to_account_info(),
+                    mint: ctx.accounts.stake_mint.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: pool_info,
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, amount, ctx.accounts.stake_mint.decimals)?;
+        }
+
+        emit!(JurorSlashed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.escrow.event_seq),
+            escrow: assignment.escrow,
+            juror: assignment.juror,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Close a finished juror assignment, freeing the juror's stake for withdrawal
+    pub fn close_juror_assignment(ctx: Context<CloseJurorAssignment>) -> Result<()> {
+        let assignment = &ctx.accounts.assignment;
+
+        require!(
+            assignment.slashed
+                || assignment.revealed_bps.is_some()
+                || assignment.is_excused(&ctx.accounts.escrow, &ctx.accounts.panel),
+            EscrowError::AssignmentStillOpen
+        );
+
+        if !assignment.slashed {
+            if let Some(entry) = ctx
+                .accounts
+                .juror_pool
+                .jurors
+                .iter_mut()
+                .find(|j| j.juror == assignment.juror)
+            {
+                entry.open_assignments -= 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Commit a hidden panel vote as hash(freelancer_bps, salt, juror)
+    pub fn commit_vote(ctx: Context<CastVote>, commitment: [u8; 32]) -> Result<()> {
+        let assignment = &mut ctx.accounts.assignment;
+
+        require!(assignment.responded, EscrowError::AssignmentNotAccepted);
+        require!(
+            Clock::get()?.unix_timestamp <= ctx.accounts.panel.commit_deadline,
+            EscrowError::CommitWindowClosed
+        );
+
+        // Re-committing before the deadline simply replaces the previous commitment
+        assignment.commitment = Some(commitment);
+
+        emit!(VoteCommitted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.escrow.event_seq),
+            escrow: assignment.escrow,
+            juror: assignment.juror,
+        });
+
+        Ok(())
+    }
+
+    /// Reveal a committed panel vote once the commit window has closed
+    pub fn reveal_vote(ctx: Context<CastVote>, freelancer_bps: u16, salt: [u8; 32]) -> Result<()> {
+        let panel = &ctx.accounts.panel;
+        let assignment = &mut ctx.accounts.assignment;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(now > panel.commit_deadline, EscrowError::CommitWindowOpen);
+        require!(
+            now <= panel.reveal_deadline,
+            EscrowError::RevealWindowClosed
+        );
+        require!(
+            assignment.revealed_bps.is_none(),
+            EscrowError::VoteAlreadyRevealed
+        );
+        require!(
+            freelancer_bps <= BPS_DENOMINATOR,
+            EscrowError::InvalidRuling
+        );
+
+        let commitment = assignment.commitment.ok_or(EscrowError::VoteNotCommitted)?;
+        require!(
+            vote_commitment(freelancer_bps, &salt, &assignment.juror) == commitment,
+            EscrowError::CommitmentMismatch
+        );
+
+        assignment.revealed_bps = Some(freelancer_bps);
+
+        emit!(VoteRevealed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.escrow.event_seq),
+            escrow: assignment.escrow,
+            juror: assignment.juror,
+            freelancer_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Tally revealed panel votes by stake and pay out the winning split (permissionless crank)
+    pub fn tally_panel_votes<'info>(
+        ctx: Context<'_, '_, '_, 'info, TallyPanelVotes<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let panel = &mut ctx.accounts.panel;
+
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            !escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowFrozen
+        );
+        require!(
+            escrow.dispute_tier == DisputeTier::Panel,
+            EscrowError::WrongDisputeTier
+        );
+        // With a juror fee, each juror's token account follows the assignments
+        let jurors = panel.jurors.len();
+        let paid_accounts = if ctx.accounts.config.juror_fee_bps > 0 {
+            jurors
+        } else {
+            0
+        };
+        require!(
+            ctx.remaining_accounts.len() == jurors + paid_accounts + escrow.refund_account_count(),
+            EscrowError::InvalidAssignmentAccount
+        );
+
+        let mut votes = Vec::with_capacity(jurors);
+        let mut revealed = Vec::with_capacity(jurors);
+        for (index, assignment_info) in ctx.remaining_accounts.iter().take(jurors).enumerate() {
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"assignment", panel.key().as_ref(), &[index as u8]],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                assignment_info.key(),
+                expected,
+                EscrowError::InvalidAssignmentAccount
+            );
+            let assignment = Account::<JurorAssignment>::try_from(assignment_info)?;
+            if let Some(bps) = assignment.revealed_bps {
+                votes.push((bps, assignment.stake));
+                revealed.push((index, assignment.juror));
+            }
+        }
+
+        let all_revealed = votes.len() == jurors;
+        require!(
+            all_revealed || Clock::get()?.unix_timestamp > panel.reveal_deadline,
+            EscrowError::RevealWindowOpen
+        );
+        escrow.guard_same_slot(EscrowAction::DisputeResolved)?;
+
+        let freelancer_bps = weighted_median(&mut votes).ok_or(EscrowError::NoRevealedVotes)?;
+        let freelancer_amount =
+            (escrow.amount as u128 * freelancer_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let hirer_amount = escrow.amount - freelancer_amount;
+
+        // Jurors who revealed split the panel fee evenly, any dust stays with the parties
+        let panel_fee = (escrow.amount as u128 * ctx.accounts.config.juror_fee_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        let juror_fee = panel_fee / revealed.len() as u64;
+        let arbitration_fee = juror_fee * revealed.len() as u64;
+        let (hirer_fee, freelancer_fee) =
+            arbitration_fee_shares(arbitration_fee, escrow.amount, freelancer_amount);
+        let ruled_freelancer_amount = freelancer_amount;
+        let hirer_amount = hirer_amount - hirer_fee;
+        let freelancer_amount = freelancer_amount - freelancer_fee;
+
+        // Book the ruling before any tokens move
+        escrow.is_released = true;
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(Clock::get()?.unix_timestamp);
+        escrow.ruling_freelancer_amount = Some(ruled_freelancer_amount);
+        record_dispute_outcome(
+            &escrow.dispute_outcome(),
+            &ctx.accounts.config,
+            &mut ctx.accounts.hirer_reputation,
+            &mut ctx.accounts.freelancer_reputation,
+        )?;
+        panel.ruling_bps = Some(freelancer_bps);
+        escrow.record_action(EscrowAction::DisputeResolved, panel.key())?;
+        if freelancer_amount > 0 {
+            let fee = escrow.platform_fee(freelancer_amount);
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                freelancer_amount - fee,
+                fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.token_program,
+                freelancer_amount,
+            )?;
+        }
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+
+        if juror_fee > 0 {
+            for &(index, juror) in &revealed {
+                let destination =
+                    Account::<TokenAccount>::try_from(&ctx.remaining_accounts[jurors + index])?;
+                pay_arbitrator(
+                    &escrow_info,
+                    &ctx.accounts.escrow_token_account,
+                    &ctx.accounts.mint,
+                    &destination,
+                    &juror,
+                    &ctx.accounts.token_program,
+                    juror_fee,
+                    signer_seeds,
+                )?;
+            }
+        }
+
+        if hirer_amount > 0 {
+            refund_funders(
+                escrow,
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                &ctx.remaining_accounts[jurors + paid_accounts..],
+                hirer_amount,
+                signer_seeds,
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        if juror_fee > 0 {
+            for &(_, juror) in &revealed {
+                emit!(ArbitratorPaid {
+                    version: EVENT_SCHEMA_VERSION,
+                    event_seq: next_event_seq(&mut escrow.event_seq),
+                    escrow: escrow.key(),
+                    arbitrator: juror,
+                    tier: DisputeTier::Panel,
+                    amount: juror_fee,
+                });
+            }
+        }
+
+        emit!(PanelVotesTallied {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            panel: panel.key(),
+            freelancer_bps,
+            votes_counted: votes.len() as u8,
+        });
+
+        emit!(DisputeResolved {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            hirer_amount: escrow.amount - ruled_freelancer_amount,
+            freelancer_amount: ruled_freelancer_amount,
+            template: None,
+            resolved_by: panel.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Update the platform config (admin only)
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        ctx.accounts
+            .config
+            .check_rate_limit(AdminAction::UpdateConfig, Clock::get()?.unix_timestamp)?;
+        ctx.accounts.config.apply(params)?;
+
+        ctx.accounts.audit_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::UpdateConfig,
+            ctx.accounts.config.key(),
+        )?;
+
+        emit!(ConfigUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.config.event_seq),
+            admin: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Attach a registered mediator to a dispute still in its mediation stage (moderators only)
+    pub fn assign_mediator(ctx: Context<AssignMediator>, mediator: Pubkey) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(
+            config.is_moderator(&ctx.accounts.moderator.key()),
+            EscrowError::UnauthorizedModeration
+        );
+        require!(
+            config.mediators.contains(&mediator),
+            EscrowError::NotAMediator
+        );
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(
+            escrow.dispute_tier == DisputeTier::Mediation,
+            EscrowError::WrongDisputeTier
+        );
+
+        escrow.mediator = Some(mediator);
+        // A recommendation belongs to the mediator who made it
+        escrow.mediation = None;
+
+        emit!(MediatorAssigned {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            mediator,
+            assigned_by: ctx.accounts.moderator.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Record the assigned mediator's non-binding split; it may be revised until the dispute escalates
+    pub fn recommend_resolution(
+        ctx: Context<RecommendResolution>,
+        hirer_amount: u64,
+        freelancer_amount: u64,
+        note_hash: [u8; 32],
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            escrow.mediator == Some(ctx.accounts.mediator.key()),
+            EscrowError::NotAMediator
+        );
+        require!(
+            escrow.dispute_tier == DisputeTier::Mediation,
+            EscrowError::WrongDisputeTier
+        );
+        require!(
+            hirer_amount.checked_add(freelancer_amount) == Some(escrow.amount),
+            EscrowError::InvalidSplitAmount
+        );
+
+        let recommendation = MediationRecommendation {
+            hirer_amount,
+            freelancer_amount,
+            note_hash,
+            recorded_at: Clock::get()?.unix_timestamp,
+        };
+        escrow.mediation = Some(recommendation);
+
+        emit!(MediationRecommended {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            mediator: ctx.accounts.mediator.key(),
+            recommendation,
+        });
+
+        Ok(())
+    }
+
+    /// Post a split of the disputed escrow for both parties to accept. The mediator or either
+    /// party may propose; a new proposal replaces the last one, and a proposing party accepts
+    /// their own offer
+    pub fn propose_settlement(
+        ctx: Context<ProposeSettlement>,
+        hirer_amount: u64,
+        freelancer_amount: u64,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        let proposer = ctx.accounts.proposer.key();
+        require!(
+            proposer == escrow.hirer
+                || proposer == escrow.freelancer
+                || escrow.mediator == Some(proposer),
+            EscrowError::UnauthorizedSettlement
+        );
+        // A drawn panel is already ruling on the dispute
+        require!(
+            escrow.dispute_tier != DisputeTier::Panel,
+            EscrowError::WrongDisputeTier
+        );
+        require!(
+            hirer_amount.checked_add(freelancer_amount) == Some(escrow.amount),
+            EscrowError::InvalidSplitAmount
+        );
+
+        let settlement = &mut ctx.accounts.settlement;
+        if settlement.rent_payer == Pubkey::default() {
+            settlement.escrow = escrow.key();
+            settlement.rent_payer = proposer;
+            settlement.bump = *ctx.bumps.get("settlement").unwrap();
+        }
+        // Every offer stays on record for the arbiters should negotiation fail
+        require!(
+            settlement.history.len() < SettlementProposal::MAX_OFFERS,
+            EscrowError::TooManySettlementOffers
+        );
+        let now = Clock::get()?.unix_timestamp;
+        settlement.history.push(SettlementOffer {
+            proposer,
+            hirer_amount,
+            freelancer_amount,
+            proposed_at: now,
+            withdrawn: false,
+        });
+        settlement.proposer = proposer;
+        settlement.hirer_amount = hirer_amount;
+        settlement.freelancer_amount = freelancer_amount;
+        settlement.hirer_accepted = proposer == escrow.hirer;
+        settlement.freelancer_accepted = proposer == escrow.freelancer;
+        settlement.proposed_at = now;
+        settlement.is_open = true;
+
+        emit!(SettlementProposed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            proposer,
+            hirer_amount,
+            freelancer_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Accept the pending settlement proposal. Once both parties have accepted, the split is
+    /// paid out and the dispute closes without a ruling
+    pub fn accept_settlement<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptSettlement<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        require!(
+            escrow.dispute_tier != DisputeTier::Panel,
+            EscrowError::WrongDisputeTier
+        );
+
+        let signer = ctx.accounts.signer.key();
+        let settlement = &mut ctx.accounts.settlement;
+        require!(settlement.is_open, EscrowError::NoOpenSettlement);
+        if signer == escrow.hirer {
+            settlement.hirer_accepted = true;
+        } else if signer == escrow.freelancer {
+            settlement.freelancer_accepted = true;
+        } else {
+            return err!(EscrowError::UnauthorizedSettlement);
+        }
+
+        emit!(SettlementAccepted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            accepted_by: signer,
+        });
+
+        if !(settlement.hirer_accepted && settlement.freelancer_accepted) {
+            return Ok(());
+        }
+        escrow.guard_same_slot(EscrowAction::Settled)?;
+
+        let hirer_amount = settlement.hirer_amount;
+        let freelancer_amount = settlement.freelancer_amount;
+        require!(
+            hirer_amount.checked_add(freelancer_amount) == Some(escrow.amount),
+            EscrowError::InvalidSplitAmount
+        );
+
+        // Book the settlement before any tokens move
+        escrow.is_released = true;
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(now);
+        escrow.ruling_freelancer_amount = Some(freelancer_amount);
+        // An agreed settlement has no loser, so it starts no cooldown
+        record_dispute_split(
+            &escrow.dispute_outcome(),
+            &mut ctx.accounts.hirer_reputation,
+            &mut ctx.accounts.freelancer_reputation,
+        )?;
+        escrow.record_action(EscrowAction::Settled, signer)?;
+        if freelancer_amount > 0 {
+            let platform_fee = escrow.platform_fee(freelancer_amount);
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                freelancer_amount - platform_fee,
+                platform_fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.token_program,
+                freelancer_amount,
+            )?;
+        }
+
+        if hirer_amount > 0 {
+            let escrow_seeds = &[
+                b"escrow",
+                escrow.opened_by.as_ref(),
+                escrow.job_id_hash.as_ref(),
+                &[escrow.bump],
+            ];
+            refund_funders(
+                escrow,
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                hirer_amount,
+                &[&escrow_seeds[..]],
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.escrow_rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(SettlementExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            proposer: ctx.accounts.settlement.proposer,
+            hirer_amount,
+            freelancer_amount,
+        });
+
+        ctx.accounts
+            .settlement
+            .close(ctx.accounts.rent_payer.to_account_info())
+    }
+
+    /// Take back the open settlement offer before the counterparty accepts it (its proposer only).
+    /// The offer stays in the proposal's history, marked withdrawn
+    pub fn withdraw_settlement(ctx: Context<WithdrawSettlement>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let settlement = &mut ctx.accounts.settlement;
+
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(settlement.is_open, EscrowError::NoOpenSettlement);
+        require!(
+            settlement.proposer == ctx.accounts.proposer.key(),
+            EscrowError::UnauthorizedSettlement
+        );
+
+        settlement.is_open = false;
+        settlement.hirer_accepted = false;
+        settlement.freelancer_accepted = false;
+        if let Some(offer) = settlement.history.last_mut() {
+            offer.withdrawn = true;
+        }
+
+        emit!(SettlementWithdrawn {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            proposer: settlement.proposer,
+            offers: settlement.history.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Escalate a dispute to the next resolution tier by posting that tier's bond
+    /// (called by hirer or freelancer)
+    pub fn escalate_dispute(ctx: Context<EscalateDispute>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let config = &ctx.accounts.config;
+
+        require!(escrow.is_disputed, EscrowError::NotInDispute);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == escrow.hirer || signer == escrow.freelancer,
+            EscrowError::UnauthorizedDispute
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        // With mediation required, the parties hear the mediator out unless the window lapsed
+        if escrow.dispute_tier == DisputeTier::Mediation && config.mediation_required {
+            require!(
+                escrow.mediation.is_some() || now > escrow.tier_deadline,
+                EscrowError::MediationIncomplete
+            );
+        }
+
+        let next = escrow
+            .dispute_tier
+            .next()
+            .ok_or(EscrowError::FinalTierReached)?;
+        let bond = config.escalation_bonds[next as usize - 1];
+        if signer == escrow.hirer {
+            escrow.hirer_bond = escrow
+                .hirer_bond
+                .checked_add(bond)
+                .ok_or(EscrowError::MathOverflow)?;
+        } else {
+            escrow.freelancer_bond = escrow
+                .freelancer_bond
+                .checked_add(bond)
+                .ok_or(EscrowError::MathOverflow)?;
+        }
+
+        escrow.dispute_tier = next;
+        // An approval given at the old tier does not carry over to the new one
+        escrow.pending_resolution = None;
+        escrow.tier_deadline = now
+            .checked_add(config.tier_windows[next as usize])
+            .ok_or(EscrowError::MathOverflow)?;
+
+        if bond > 0 {
+            // Bonds are held as lamports on the escrow account until settle_escalation_bonds
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.signer.to_account_info(),
+                    to: escrow.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_ctx, bond)?;
+        }
+
+        escrow.record_action(EscrowAction::DisputeEscalated, signer)?;
+        emit!(DisputeEscalated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            escalated_by: signer,
+            tier: next,
+            bond,
+            tier_deadline: escrow.tier_deadline,
+        });
+
+        Ok(())
+    }
+
+    /// Return or forfeit escalation bonds once a dispute has settled (permissionless crank).
+    /// The party that received the smaller share forfeits its bonds to the other party;
+    /// an even split or a settlement without a ruling refunds both.
+    pub fn settle_escalation_bonds(ctx: Context<SettleEscalationBonds>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.is_released, EscrowError::NotReleased);
+        let total = escrow.hirer_bond + escrow.freelancer_bond;
+        require!(total > 0, EscrowError::NoEscalationBonds);
+
+        let (to_hirer, to_freelancer) =
+            escrow.forfeit_to_winner(escrow.hirer_bond, escrow.freelancer_bond);
+        escrow.hirer_bond = 0;
+        escrow.freelancer_bond = 0;
+
+        **escrow_info.try_borrow_mut_lamports()? -= total;
+        **ctx.accounts.hirer.try_borrow_mut_lamports()? += to_hirer;
+        **ctx.accounts.freelancer.try_borrow_mut_lamports()? += to_freelancer;
+
+        emit!(EscalationBondsSettled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            to_hirer,
+            to_freelancer,
+        });
+
+        Ok(())
+    }
+
+    /// Ask the freelancer to agree to a full refund without opening a dispute (called by hirer)
+    pub fn request_refund(ctx: Context<RequestRefund>, reason: String) -> Result<()> {
+        require!(
+            reason.len() <= Escrow::MAX_REASON_LEN,
+            EscrowError::ReasonTooLong
+        );
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            escrow.refund_requested_at.is_none(),
+            EscrowError::RefundAlreadyRequested
+        );
+
+        escrow.refund_requested_at = Some(Clock::get()?.unix_timestamp);
+
+        escrow.record_action(EscrowAction::RefundRequested, ctx.accounts.hirer.key())?;
+        emit!(RefundRequested {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            hirer: escrow.hirer,
+            reason,
+        });
+
+        Ok(())
+    }
+
+    /// Agree to a pending refund request, returning the funds and closing the escrow
+    /// (called by freelancer)
+    pub fn approve_refund<'info>(
+        ctx: Context<'_, '_, '_, 'info, ApproveRefund<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            !escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowFrozen
+        );
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            !escrow.has_disputed_milestone(),
+            EscrowError::MilestoneDisputed
+        );
+        require!(
+            escrow.refund_requested_at.is_some(),
+            EscrowError::RefundNotRequested
+        );
+
+        // Both stakes go back to their parties before the rent is returned to its payer
+        let (hirer_stake, freelancer_stake) = (escrow.hirer_stake, escrow.freelancer_stake);
+        escrow.hirer_stake = 0;
+        escrow.freelancer_stake = 0;
+        **escrow.to_account_info().try_borrow_mut_lamports()? -= hirer_stake + freelancer_stake;
+        **ctx.accounts.hirer.try_borrow_mut_lamports()? += hirer_stake;
+        **ctx.accounts.freelancer.try_borrow_mut_lamports()? += freelancer_stake;
+
+        let escrow_key = escrow.key();
+        ctx.accounts
+            .escrow_page
+            .escrows
+            .retain(|listed| *listed != escrow_key);
+        unlist_from_category(escrow, ctx.accounts.category_page.as_mut())?;
+        ctx.accounts.escrow_index.record_settlement();
+        retire_escrow(
+            escrow,
+            &mut ctx.accounts.tombstone,
+            &ctx.accounts.freelancer,
+            *ctx.bumps.get("tombstone").unwrap(),
+        )?;
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+
+        // Return the whole vault balance so the vault can be closed below
+        let amount = ctx.accounts.escrow_token_account.amount;
+        refund_funders(
+            escrow,
+            &escrow_info,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_token_account,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            amount,
+            signer_seeds,
+        )?;
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.rent_payer.to_account_info(),
+                authority: escrow_info,
+            },
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx).map_err(token_error)?;
+
+        emit!(RefundApproved {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            hirer: escrow.hirer,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Mark the work as delivered and start the hirer's review clock (called by freelancer)
+    pub fn request_release(ctx: Context<RequestRelease>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        require!(escrow.milestones.is_empty(), EscrowError::MilestoneEscrow);
+
+        let signer = session_authority(
+            &ctx.accounts.signer,
+            &ctx.accounts.session,
+            SessionKey::REQUEST_RELEASE,
+            escrow.amount,
+        )?;
+        require_keys_eq!(signer, escrow.freelancer, EscrowError::UnauthorizedRelease);
+        require!(escrow.is_funded, EscrowError::EscrowNotFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            escrow.release_requested_at.is_none(),
+            EscrowError::ReleaseAlreadyRequested
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        escrow.release_requested_at = Some(now);
+        escrow.first_submitted_at.get_or_insert(now);
+        if let (Some(sla), Some(rejected_at)) = (escrow.sla, escrow.last_rejected_at) {
+            if escrow.elapsed(rejected_at, now) > sla.response_time {
+                escrow.slow_responses += 1;
+            }
+        }
+
+        escrow.record_action(EscrowAction::ReleaseRequested, signer)?;
+        emit!(ReleaseRequested {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            freelancer: escrow.freelancer,
+            review_ends_at: escrow
+                .after(now, ctx.accounts.config.review_window_for(escrow))
+                .ok_or(EscrowError::MathOverflow)?,
+        });
+
+        Ok(())
+    }
+
+    /// Send delivered work back for revision, with the hash of the off-chain reason. The
+    /// escrow returns to active work and the review window restarts at the next
+    /// request_release. Once `max_revisions` submissions were rejected, later ones can no
+    /// longer be sent back and auto-release unless the hirer disputes them. (called by hirer)
+    pub fn reject_submission(ctx: Context<RejectSubmission>, reason_hash: [u8; 32]) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            !escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowFrozen
+        );
+        require!(
+            escrow.release_requested_at.is_some(),
+            EscrowError::ReleaseNotRequested
+        );
+        let max_revisions = ctx.accounts.config.max_revisions;
+        require!(
+            max_revisions == 0 || escrow.rejection_count < max_revisions,
+            EscrowError::RevisionLimitReached
+        );
+
+        escrow.release_requested_at = None;
+        escrow.last_rejected_at = Some(Clock::get()?.unix_timestamp);
+        escrow.rejection_count += 1;
+        escrow.last_rejection_hash = Some(reason_hash);
+
+        escrow.record_action(EscrowAction::SubmissionRejected, ctx.accounts.hirer.key())?;
+        emit!(SubmissionRejected {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            reason_hash,
+            rejection_count: escrow.rejection_count,
+        });
+
+        Ok(())
+    }
+
+    /// Claim payment once the review window has elapsed without approval or dispute, less any
+    /// SLA penalty (called by freelancer)
+    pub fn claim_auto_release<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAutoRelease<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+
+        let signer = session_authority(
+            &ctx.accounts.signer,
+            &ctx.accounts.session,
+            SessionKey::CLAIM_AUTO_RELEASE,
+            escrow.amount,
+        )?;
+        require_keys_eq!(signer, escrow.freelancer, EscrowError::UnauthorizedRelease);
+
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            !escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowFrozen
+        );
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+
+        let requested_at = escrow
+            .release_requested_at
+            .ok_or(EscrowError::ReleaseNotRequested)?;
+        let review_ends_at = escrow
+            .after(requested_at, ctx.accounts.config.review_window_for(escrow))
+            .ok_or(EscrowError::MathOverflow)?;
+        require!(
+            Clock::get()?.unix_timestamp >= review_ends_at,
+            EscrowError::ReviewWindowOpen
+        );
+
+        // Book the release before any tokens move. SLA breaches return part of the payout to
+        // the funder, so the freelancer is paid what is left.
+        let breakdown = escrow.sla_breakdown();
+        let amount = escrow.amount - breakdown.penalty;
+        let now = Clock::get()?.unix_timestamp;
+        escrow.is_released = true;
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(now);
+        escrow.record_action(EscrowAction::Released, signer)?;
+
+        let pays_now = escrow.clawback_window == 0;
+        if pays_now {
+            let fee = escrow.platform_fee(amount);
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                amount - fee,
+                fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+        } else {
+            // Clawback mode: funds stay in the vault until withdraw_held_payment
+            escrow.held_until = Some(
+                now.checked_add(escrow.clawback_window)
+                    .ok_or(EscrowError::MathOverflow)?,
+            );
+        }
+
+        apply_sla_penalty(
+            escrow,
+            &escrow_info,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_token_account,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            breakdown.penalty,
+        )?;
+
+        // Transfer tokens from escrow to freelancer
+        if pays_now {
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.token_program,
+                amount,
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(PaymentReleased {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            freelancer: escrow.freelancer,
+            amount: escrow.amount,
+            released_by: escrow.freelancer,
+            delivery_breaches: breakdown.delivery_breaches,
+            response_breaches: breakdown.response_breaches,
+            sla_penalty: breakdown.penalty,
+        });
+
+        Ok(())
+    }
+
+    /// Propose releasing a large escrow; release_payment confirms it after the delay
+    /// (called by hirer or admin)
+    pub fn propose_release(ctx: Context<ProposeRelease>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        let config = &ctx.accounts.config;
+
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            escrow.release_proposed_at.is_none(),
+            EscrowError::ReleaseAlreadyProposed
+        );
+
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == escrow.hirer || signer == config.admin,
+            EscrowError::UnauthorizedRelease
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        escrow.release_proposed_at = Some(now);
+
+        escrow.record_action(EscrowAction::ReleaseProposed, signer)?;
+        emit!(ReleaseProposed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            proposed_by: signer,
+            confirmable_at: now
+                .checked_add(config.release_confirm_delay)
+                .ok_or(EscrowError::MathOverflow)?,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a pending release proposal (called by hirer or admin)
+    pub fn cancel_release_proposal(ctx: Context<ProposeRelease>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(
+            escrow.release_proposed_at.is_some(),
+            EscrowError::ReleaseNotProposed
+        );
+
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == escrow.hirer || signer == ctx.accounts.config.admin,
+            EscrowError::UnauthorizedRelease
+        );
+
+        escrow.release_proposed_at = None;
+
+        emit!(ReleaseProposalCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            cancelled_by: signer,
+        });
+
+        Ok(())
+    }
+
+    /// Block release and refund paths while an escrow is investigated (moderation role only).
+    /// The total freeze, counted from the first freeze, never exceeds the configured maximum.
+    pub fn freeze_escrow(ctx: Context<ModerateEscrow>, duration: i64) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let config = &ctx.accounts.config;
+
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        let moderator = ctx.accounts.moderator.key();
+        require!(
+            config.is_moderator(&moderator),
+            EscrowError::UnauthorizedModeration
+        );
+        require!(duration > 0, EscrowError::InvalidFreezeDuration);
+
+        let now = Clock::get()?.unix_timestamp;
+        let first_frozen_at = escrow.first_frozen_at.unwrap_or(now);
+        let frozen_until = now.checked_add(duration).ok_or(EscrowError::MathOverflow)?;
+        let limit = first_frozen_at
+            .checked_add(config.max_freeze_duration)
+            .ok_or(EscrowError::MathOverflow)?;
+        require!(frozen_until <= limit, EscrowError::FreezeLimitExceeded);
+
+        escrow.first_frozen_at = Some(first_frozen_at);
+        escrow.frozen_until = Some(frozen_until);
+
+        ctx.accounts
+            .audit_log
+            .record(moderator, AdminAction::FreezeEscrow, escrow.key())?;
+
+        escrow.record_action(EscrowAction::Frozen, moderator)?;
+        emit!(EscrowFrozen {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            frozen_by: moderator,
+            frozen_until,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a moderation freeze early (moderation role only)
+    pub fn unfreeze_escrow(ctx: Context<ModerateEscrow>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        let moderator = ctx.accounts.moderator.key();
+        require!(
+            ctx.accounts.config.is_moderator(&moderator),
+            EscrowError::UnauthorizedModeration
+        );
+        require!(
+            escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowNotFrozen
+        );
+
+        escrow.frozen_until = None;
+
+        ctx.accounts
+            .audit_log
+            .record(moderator, AdminAction::UnfreezeEscrow, escrow.key())?;
+
+        escrow.record_action(EscrowAction::Unfrozen, moderator)?;
+        emit!(EscrowUnfrozen {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            unfrozen_by: moderator,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a released payment once its clawback window has passed (called by freelancer)
+    pub fn withdraw_held_payment(ctx: Context<WithdrawHeldPayment>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        let held_until = escrow.held_until.ok_or(EscrowError::NoHeldPayment)?;
+        require!(
+            Clock::get()?.unix_timestamp >= held_until,
+            EscrowError::ClawbackWindowOpen
+        );
+
+        escrow.held_until = None;
+        escrow.record_action(
+            EscrowAction::HeldPaymentWithdrawn,
+            ctx.accounts.freelancer.key(),
+        )?;
+        let amount = escrow.amount;
+        let fee = escrow.platform_fee(amount);
+        ctx.accounts.freelancer_earnings.record_payout(
+            ctx.accounts.escrow_token_account.mint,
+            amount - fee,
+            fee,
+        )?;
+        rank_freelancer(
+            &mut ctx.accounts.fees,
+            &ctx.accounts.freelancer_earnings,
+            ctx.accounts.escrow_token_account.mint,
+        )?;
+
+        pay_freelancer(
+            &mut ctx.accounts.fees,
+            escrow,
+            &ctx.accounts.config,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.freelancer_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(HeldPaymentWithdrawn {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            freelancer: escrow.freelancer,
+            amount: escrow.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Reverse a held release on proven fraud, refunding the funder (called by platform admin only)
+    pub fn clawback_payment<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClawbackPayment<'info>>,
+        reason: String,
+    ) -> Result<()> {
+        require!(
+            reason.len() <= Escrow::MAX_REASON_LEN,
+            EscrowError::ReasonTooLong
+        );
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+
+        let held_until = escrow.held_until.ok_or(EscrowError::NoHeldPayment)?;
+        require!(
+            Clock::get()?.unix_timestamp < held_until,
+            EscrowError::ClawbackWindowClosed
+        );
+
+        escrow.held_until = None;
+
+        ctx.accounts.audit_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::ClawbackPayment,
+            escrow.key(),
+        )?;
+
+        escrow.record_action(EscrowAction::ClawedBack, ctx.accounts.admin.key())?;
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+
+        refund_funders(
+            escrow,
+            &escrow_info,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_token_account,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            escrow.amount,
+            signer_seeds,
+        )?;
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(PaymentClawedBack {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            hirer: escrow.hirer,
+            amount: escrow.amount,
+            clawed_back_by: ctx.accounts.admin.key(),
+            reason,
+        });
+
+        Ok(())
+    }
+
+    /// Hand the signer's role (hirer or freelancer) on an escrow to a new wallet.
+    /// The counterparty must co-sign when the config requires consent.
+    pub fn transfer_role(ctx: Context<TransferRole>, new_key: Pubkey) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        let signer = ctx.accounts.signer.key();
+        let (role, counterparty) = if signer == escrow.hirer {
+            (EscrowRole::Hirer, escrow.freelancer)
+        } else if signer == escrow.freelancer {
+            (EscrowRole::Freelancer, escrow.hirer)
+        } else {
+            return err!(EscrowError::UnauthorizedRoleTransfer);
+        };
+        require!(
+            new_key != signer && new_key != counterparty,
+            EscrowError::InvalidRoleTransfer
+        );
+
+        if ctx.accounts.config.role_transfer_requires_consent {
+            let consent = ctx
+                .accounts
+                .counterparty
+                .as_ref()
+                .ok_or(EscrowError::CounterpartyConsentRequired)?;
+            require_keys_eq!(
+                consent.key(),
+                counterparty,
+                EscrowError::CounterpartyConsentRequired
+            );
+        }
+
+        match role {
+            EscrowRole::Hirer => escrow.hirer = new_key,
+            EscrowRole::Freelancer => escrow.freelancer = new_key,
+        }
+
+        let earnings = &mut ctx.accounts.new_key_earnings;
+        earnings.owner = new_key;
+        earnings.bump = *ctx.bumps.get("new_key_earnings").unwrap();
+        ctx.accounts
+            .new_key_reputation
+            .open(new_key, *ctx.bumps.get("new_key_reputation").unwrap());
+
+        // Nothing prepared against the nonce under the old key carries over to the new one
+        let nonce = escrow.nonce;
+        escrow.consume_nonce(nonce)?;
+
+        escrow.record_action(EscrowAction::RoleTransferred, signer)?;
+        emit!(RoleTransferred {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            role,
+            old_key: signer,
+            new_key,
+        });
+
+        Ok(())
+    }
+
+    /// Hand `role` on the escrow to a new wallet with both current parties signing, for an
+    /// acquisition, a substituted contractor or a compromised wallet. The role's payout routing
+    /// moves with it: the freelancer's payouts, or the hirer's refunds when the hirer funds the
+    /// escrow, go to `destination` when passed and otherwise to the new wallet's own accounts.
+    /// (signed by hirer and freelancer, before settlement)
+    pub fn change_beneficiary(
+        ctx: Context<ChangeBeneficiary>,
+        role: EscrowRole,
+        new_key: Pubkey,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            new_key != escrow.hirer && new_key != escrow.freelancer,
+            EscrowError::InvalidRoleTransfer
+        );
+        let destination = ctx.accounts.destination.as_ref();
+        if let Some(destination) = destination {
+            require!(!destination.is_frozen(), EscrowError::PayoutAccountFrozen);
+        }
+        let destination = destination.map(|destination| destination.key());
+
+        let old_key = match role {
+            EscrowRole::Hirer => {
+                let old_key = escrow.hirer;
+                escrow.hirer = new_key;
+                // A sponsor keeps its refunds; otherwise they follow the hirer role
+                if escrow.funder == old_key {
+                    escrow.funder = new_key;
+                    escrow.refund_destination = destination;
+                } else {
+                    require!(destination.is_none(), EscrowError::InvalidRefundDestination);
+                }
+                old_key
+            }
+            EscrowRole::Freelancer => {
+                let old_key = escrow.freelancer;
+                escrow.freelancer = new_key;
+                escrow.payout_destination = destination;
+                old_key
+            }
+        };
+
+        let earnings = &mut ctx.accounts.new_key_earnings;
+        earnings.owner = new_key;
+        earnings.bump = *ctx.bumps.get("new_key_earnings").unwrap();
+        ctx.accounts
+            .new_key_reputation
+            .open(new_key, *ctx.bumps.get("new_key_reputation").unwrap());
+
+        // Nothing prepared against the nonce under the old key carries over to the new one
+        let nonce = escrow.nonce;
+        escrow.consume_nonce(nonce)?;
+
+        escrow.record_action(EscrowAction::RoleTransferred, old_key)?;
+        emit!(BeneficiaryChanged {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            role,
+            old_key,
+            new_key,
+            destination,
+        });
+
+        Ok(())
+    }
+
+    /// Register a short-lived session key that may sign scoped actions on the owner's behalf
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_at: i64,
+        permissions: u32,
+        max_amount: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            expires_at > now && expires_at - now <= SessionKey::MAX_DURATION,
+            EscrowError::InvalidSessionExpiry
+        );
+        require!(
+            permissions != 0 && permissions & !SessionKey::ALL_PERMISSIONS == 0,
+            EscrowError::InvalidSessionPermissions
+        );
+
+        let session = &mut ctx.accounts.session;
+        session.owner = ctx.accounts.owner.key();
+        session.session_key = session_key;
+        session.expires_at = expires_at;
+        session.permissions = permissions;
+        session.max_amount = max_amount;
+        session.payer = ctx.accounts.payer.key();
+        session.event_seq = 0;
+        session.bump = *ctx.bumps.get("session").unwrap();
+
+        emit!(SessionCreated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut session.event_seq),
+            owner: session.owner,
+            session_key,
+            expires_at,
+            permissions,
+            max_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a session key and reclaim its rent (called by the owner)
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        emit!(SessionRevoked {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.session.event_seq),
+            owner: ctx.accounts.owner.key(),
+            session_key: ctx.accounts.session.session_key,
+        });
+
+        Ok(())
+    }
+
+    /// Create the freelancer leaderboard of a mint, which payouts passing it keep ranked by
+    /// the volume each freelancer has settled in the mint (admin only)
+    pub fn initialize_leaderboard(ctx: Context<InitializeLeaderboard>) -> Result<()> {
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.mint = ctx.accounts.mint.key();
+        leaderboard.entries = Vec::new();
+        leaderboard.bump = *ctx.bumps.get("leaderboard").unwrap();
+
+        Ok(())
+    }
+
+    /// Create the compressed-escrow tree and shared vault for a mint (admin only)
+    pub fn initialize_compressed_tree(ctx: Context<InitializeCompressedTree>) -> Result<()> {
+        let tree = &mut ctx.accounts.tree;
+        tree.mint = ctx.accounts.mint.key();
+        tree.next_index = 0;
+        tree.filled_subtrees = [[0u8; 32]; CompressedTree::DEPTH];
+        tree.root = merkle_zero_hashes()[CompressedTree::DEPTH];
+        tree.event_seq = 0;
+        tree.bump = *ctx.bumps.get("tree").unwrap();
+
+        Ok(())
+    }
+
+    /// Open a micro-escrow as a leaf in the compressed tree instead of its own PDA and vault
+    pub fn create_compressed_escrow(
+        ctx: Context<CreateCompressedEscrow>,
+        freelancer: Pubkey,
+        amount: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidCompressedAmount);
+        require!(
+            amount <= ctx.accounts.config.max_compressed_escrow_amount,
+            EscrowError::InvalidCompressedAmount
+        );
+
+        let tree_key = ctx.accounts.tree.key();
+        let tree = &mut ctx.accounts.tree;
+        let leaf = CompressedEscrowLeaf {
+            index: tree.next_index,
+            hirer: ctx.accounts.hirer.key(),
+            freelancer,
+            amount,
+            deadline,
+        };
+        tree.append(leaf.hash(&tree_key))?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.hirer_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.tree_vault.to_account_info(),
+                authority: ctx.accounts.hirer.to_account_info(),
+            },
+        );
+        transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        // Indexers rebuild the tree from these events to serve proofs
+        emit!(CompressedEscrowCreated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut tree.event_seq),
+            tree: tree_key,
+            leaf,
+            root: tree.root,
+        });
+
+        Ok(())
+    }
+
+    /// Release a compressed micro-escrow to its freelancer, proving the leaf (called by hirer)
+    pub fn release_compressed_escrow(
+        ctx: Context<SettleCompressedEscrow>,
+        leaf: CompressedEscrowLeaf,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.hirer.key(),
+            leaf.hirer,
+            EscrowError::UnauthorizedRelease
+        );
+        require_keys_eq!(
+            ctx.accounts.recipient_token_account.owner,
+            leaf.freelancer,
+            EscrowError::InvalidCompressedRecipient
+        );
+
+        settle_compressed_leaf(ctx, &leaf, &proof)?;
+
+        Ok(())
+    }
+
+    /// Refund a compressed micro-escrow to its hirer once the deadline has passed (called by hirer)
+    pub fn refund_compressed_escrow(
+        ctx: Context<SettleCompressedEscrow>,
+        leaf: CompressedEscrowLeaf,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.hirer.key(),
+            leaf.hirer,
+            EscrowError::UnauthorizedRelease
+        );
+        require_keys_eq!(
+            ctx.accounts.recipient_token_account.owner,
+            leaf.hirer,
+            EscrowError::InvalidCompressedRecipient
+        );
+        require!(
+            Clock::get()?.unix_timestamp > leaf.deadline,
+            EscrowError::DeadlineNotReached
+        );
+
+        settle_compressed_leaf(ctx, &leaf, &proof)?;
+
+        Ok(())
+    }
+
+    /// Burn the current nonce, so anything a party prepared against it goes stale
+    /// (called by hirer or freelancer)
+    pub fn advance_nonce(ctx: Context<AdvanceNonce>, nonce: u64) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == escrow.hirer || signer == escrow.freelancer,
+            EscrowError::UnauthorizedParty
+        );
+
+        escrow.consume_nonce(nonce)?;
+
+        emit!(NonceAdvanced {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            advanced_by: signer,
+            nonce: escrow.nonce,
+        });
+
+        Ok(())
+    }
+
+    /// Fail the transaction unless this deployment satisfies the client's expected version.
+    /// Clients prepend it so transactions built for another program version cannot execute.
+    pub fn assert_min_version(_ctx: Context<AssertVersion>, min_version: u16) -> Result<()> {
+        require!(
+            min_version <= PROGRAM_VERSION,
+            EscrowError::ProgramVersionTooOld
+        );
+        require!(
+            min_version >= MIN_COMPATIBLE_VERSION,
+            EscrowError::ClientVersionIncompatible
+        );
+
+        Ok(())
+    }
+
+    /// Recheck that an escrow's vault and lamports back what its state says it owes
+    /// (permissionless, for monitoring)
+    pub fn assert_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+
+        // A held release stays in the vault until it is withdrawn or clawed back. Only what
+        // has been funded is owed: an escrow filling up in tranches or from contributors holds
+        // less than its amount, and a grant's claims come off its amount but not its funding.
+        let outstanding = if !escrow.is_released || escrow.held_until.is_some() {
+            escrow.amount.min(escrow.funded_amount)
+        } else {
+            0
+        };
+        // Settlement closes the vault once it empties it
+        let vault = &ctx.accounts.escrow_token_account;
+        let vault_balance = if vault.data_is_empty() {
+            0
+        } else {
+            TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?.amount
+        };
+        require!(
+            vault_balance >= outstanding,
+            EscrowError::VaultUnderfunded
+        );
+
+        let escrow_info = escrow.to_account_info();
+        let bonds = escrow
+            .hirer_bond
+            .checked_add(escrow.freelancer_bond)
+            .ok_or(EscrowError::MathOverflow)?;
+        let required = Rent::get()?
+            .minimum_balance(escrow_info.data_len())
+            .checked_add(bonds)
+            .ok_or(EscrowError::MathOverflow)?;
+        require!(
+            escrow_info.lamports() >= required,
+            EscrowError::BondsUnderfunded
+        );
+
+        require!(
+            escrow.is_released == escrow.released_at.is_some()
+                && escrow.is_disputed == escrow.disputed_at.is_some()
+                && (escrow.held_until.is_none() || escrow.is_released)
+                && escrow.ruling_freelancer_amount.unwrap_or(0) <= escrow.amount,
+            EscrowError::InconsistentEscrowState
+        );
+
+        Ok(())
+    }
+    /// Register the signer as an affiliate that can onboard hirers
+    pub fn register_affiliate(ctx: Context<RegisterAffiliate>) -> Result<()> {
+        let affiliate = &mut ctx.accounts.affiliate;
+        affiliate.owner = ctx.accounts.owner.key();
+        affiliate.referred_hirers = 0;
+        affiliate.total_accrued = 0;
+        affiliate.total_claimed = 0;
+        affiliate.event_seq = 0;
+        affiliate.bump = *ctx.bumps.get("affiliate").unwrap();
+
+        emit!(AffiliateRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut affiliate.event_seq),
+            affiliate: affiliate.key(),
+            owner: affiliate.owner,
+        });
+
+        Ok(())
+    }
+
+    /// Record the affiliate that onboarded a hirer (called by the hirer, before their first escrow)
+    pub fn join_affiliate(ctx: Context<JoinAffiliate>) -> Result<()> {
+        let hirer = ctx.accounts.hirer.key();
+        let affiliate = &mut ctx.accounts.affiliate;
+        let index = &mut ctx.accounts.escrow_index;
+
+        require!(index.escrow_count == 0, EscrowError::ReferralWindowClosed);
+        require!(index.referred_by.is_none(), EscrowError::AlreadyReferred);
+        require_keys_neq!(affiliate.owner, hirer, EscrowError::SelfReferral);
+
+        index.owner = hirer;
+        index.bump = *ctx.bumps.get("escrow_index").unwrap();
+        index.referred_by = Some(affiliate.key());
+        affiliate.referred_hirers = affiliate
+            .referred_hirers
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        emit!(HirerReferred {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut affiliate.event_seq),
+            affiliate: affiliate.key(),
+            hirer,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw an affiliate's accrued fee share for one mint (called by the affiliate)
+    pub fn claim_affiliate_rewards(ctx: Context<ClaimAffiliateRewards>) -> Result<()> {
+        let affiliate_info = ctx.accounts.affiliate.to_account_info();
+        let affiliate = &mut ctx.accounts.affiliate;
+
+        let amount = ctx.accounts.affiliate_vault.amount;
+        require!(amount > 0, EscrowError::NoAffiliateRewards);
+        affiliate.total_claimed = affiliate
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        let affiliate_seeds = &[
+            b"affiliate".as_ref(),
+            affiliate.owner.as_ref(),
+            &[affiliate.bump],
+        ];
+        let signer_seeds = &[&affiliate_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.affiliate_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: affiliate_info,
+            },
+            signer_seeds,
+        );
+        transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit!(AffiliateRewardsClaimed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut affiliate.event_seq),
+            affiliate: affiliate.key(),
+            mint: ctx.accounts.affiliate_vault.mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Create a fee discount coupon redeemable by `code_hash` (admin only)
+    pub fn create_coupon(
+        ctx: Context<CreateCoupon>,
+        code_hash: [u8; 32],
+        discount_bps: u16,
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            discount_bps > 0 && discount_bps <= BPS_DENOMINATOR && max_uses > 0,
+            EscrowError::InvalidCoupon
+        );
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            EscrowError::InvalidCoupon
+        );
+
+        let coupon = &mut ctx.accounts.coupon;
+        coupon.code_hash = code_hash;
+        coupon.discount_bps = discount_bps;
+        coupon.max_uses = max_uses;
+        coupon.remaining_uses = max_uses;
+        coupon.expires_at = expires_at;
+        coupon.bump = *ctx.bumps.get("coupon").unwrap();
+
+        ctx.accounts.audit_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::CreateCoupon,
+            coupon.key(),
+        )?;
+
+        emit!(CouponCreated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.config.event_seq),
+            coupon: coupon.key(),
+            discount_bps,
+            max_uses,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw the vested, unclaimed part of a grant (called by the recipient)
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        let mut grant = escrow.grant.clone().ok_or(EscrowError::NotAGrant)?;
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+
+        let claimable = grant.vested(now) - grant.claimed;
+        require!(claimable > 0, EscrowError::NothingVested);
+
+        grant.claimed += claimable;
+        let total_claimed = grant.claimed;
+        escrow.grant = Some(grant);
+        escrow.amount -= claimable;
+        if escrow.amount == 0 {
+            escrow.is_released = true;
+            ctx.accounts.escrow_index.record_settlement();
+            escrow.released_at = Some(now);
+        }
+        escrow.record_action(EscrowAction::GrantClaimed, ctx.accounts.freelancer.key())?;
+        let fee = escrow.platform_fee(claimable);
+        ctx.accounts.freelancer_earnings.record_payout(
+            ctx.accounts.escrow_token_account.mint,
+            claimable - fee,
+            fee,
+        )?;
+        rank_freelancer(
+            &mut ctx.accounts.fees,
+            &ctx.accounts.freelancer_earnings,
+            ctx.accounts.escrow_token_account.mint,
+        )?;
+
+        pay_freelancer(
+            &mut ctx.accounts.fees,
+            escrow,
+            &ctx.accounts.config,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.freelancer_token_account,
+            &ctx.accounts.token_program,
+            claimable,
+        )?;
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(GrantClaimed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            recipient: escrow.freelancer,
+            amount: claimable,
+            fee,
+            total_claimed,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke the unvested part of a grant during its revocation window, refunding it to the
+    /// grantor. What has vested so far stays claimable by the recipient. (called by the grantor)
+    pub fn revoke_grant(ctx: Context<RevokeGrant>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        let mut grant = escrow.grant.clone().ok_or(EscrowError::NotAGrant)?;
+        require!(
+            now < grant.revocable_until,
+            EscrowError::RevocationWindowElapsed
+        );
+
+        let vested = grant.vested(now);
+        let refund = grant.total - vested;
+
+        // Cut the schedule off here, so everything left in the vault is already vested
+        grant.total = vested;
+        grant.vesting_start = grant.vesting_start.min(now);
+        grant.vesting_end = now;
+        grant.revocable_until = now;
+        escrow.grant = Some(grant);
+        escrow.amount -= refund;
+        if escrow.amount == 0 {
+            escrow.is_released = true;
+            ctx.accounts.escrow_index.record_settlement();
+            escrow.released_at = Some(now);
+        }
+
+        escrow.record_action(EscrowAction::GrantRevoked, ctx.accounts.hirer.key())?;
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+        if refund > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.hirer_token_account.to_account_info(),
+                    authority: escrow_info,
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, refund, ctx.accounts.mint.decimals)?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(GrantRevoked {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            refunded: refund,
+            vested,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a grant and settle it in one go: the recipient is paid everything vested and
+    /// unclaimed, and the grantor is refunded the unvested remainder. The recipient may walk
+    /// away at any time; the grantor only while the grant is still revocable.
+    /// (called by the grantor or the recipient)
+    pub fn cancel_grant(ctx: Context<CancelGrant>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let signer = ctx.accounts.signer.key();
+        let now = Clock::get()?.unix_timestamp;
+        let mut grant = escrow.grant.clone().ok_or(EscrowError::NotAGrant)?;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        if signer == escrow.hirer {
+            require!(
+                now < grant.revocable_until,
+                EscrowError::RevocationWindowElapsed
+            );
+        } else {
+            require!(signer == escrow.freelancer, EscrowError::UnauthorizedParty);
+        }
+
+        let vested = grant.vested(now);
+        let payout = vested - grant.claimed;
+        let refund = grant.total - vested;
+
+        grant.total = vested;
+        grant.claimed = vested;
+        grant.vesting_start = grant.vesting_start.min(now);
+        grant.vesting_end = now;
+        grant.revocable_until = now;
+        escrow.grant = Some(grant);
+        escrow.amount = 0;
+        escrow.is_released = true;
+        escrow.released_at = Some(now);
+        ctx.accounts.escrow_index.record_settlement();
+
+        escrow.record_action(EscrowAction::Cancelled, signer)?;
+        let fee = escrow.platform_fee(payout);
+        if payout > 0 {
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                payout - fee,
+                fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.token_program,
+                payout,
+            )?;
+        }
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+        if refund > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.hirer_token_account.to_account_info(),
+                    authority: escrow_info,
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, refund, ctx.accounts.mint.decimals)?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(GrantCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            cancelled_by: signer,
+            paid: payout,
+            fee,
+            refunded: refund,
+        });
+
+        Ok(())
+    }
+
+    /// Opt in or out of donating part of the escrow's platform fee to the configured charity
+    /// (called by hirer, before settlement)
+    pub fn set_charity_routing(ctx: Context<SetCharityRouting>, enabled: bool) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+
+        escrow.route_fee_to_charity = enabled;
+
+        emit!(CharityRoutingSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Apply to an open job, locking the configured bid bond in the application account
+    /// (called by the applicant)
+    pub fn apply_to_job(ctx: Context<ApplyToJob>, job_id: String, hirer: Pubkey) -> Result<()> {
+        require!(
+            job_id.len() <= Escrow::MAX_JOB_ID_LEN,
+            EscrowError::JobIdTooLong
+        );
+        // The job is awarded once its escrow exists
+        require!(
+            ctx.accounts.escrow.data_is_empty(),
+            EscrowError::JobAlreadyAwarded
+        );
+
+        let bond = ctx.accounts.config.bid_bond_lamports;
+        let application = &mut ctx.accounts.application;
+        application.job_id = job_id;
+        application.hirer = hirer;
+        application.applicant = ctx.accounts.applicant.key();
+        application.bond = bond;
+        application.applied_at = Clock::get()?.unix_timestamp;
+        application.event_seq = 0;
+        application.bump = *ctx.bumps.get("application").unwrap();
+
+        if bond > 0 {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.applicant.to_account_info(),
+                    to: application.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_ctx, bond)?;
+        }
+
+        emit!(JobApplied {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut application.event_seq),
+            application: application.key(),
+            job_id_hash: job_id_hash(&application.job_id),
+            applicant: application.applicant,
+            bond,
+        });
+
+        Ok(())
+    }
+
+    /// Close an application and return its bond to the applicant (permissionless crank).
+    /// Applicants who lost the job are refunded as soon as it is awarded, the awarded
+    /// applicant once the escrow settles, and everyone after the lock period if the job
+    /// is never awarded.
+    pub fn refund_bid_bond(ctx: Context<RefundBidBond>) -> Result<()> {
+        let application = &mut ctx.accounts.application;
+        let now = Clock::get()?.unix_timestamp;
+
+        if ctx.accounts.escrow.data_is_empty() {
+            let unlocks_at = application
+                .applied_at
+                .checked_add(ctx.accounts.config.bid_bond_lock_period)
+                .ok_or(EscrowError::MathOverflow)?;
+            require!(now >= unlocks_at, EscrowError::BidBondLocked);
+        } else {
+            let escrow = Account::<Escrow>::try_from(&ctx.accounts.escrow)?;
+            require!(
+                escrow.freelancer != application.applicant || escrow.is_released,
+                EscrowError::BidBondLocked
+            );
+        }
+
+        emit!(BidBondRefunded {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut application.event_seq),
+            application: application.key(),
+            job_id_hash: job_id_hash(&application.job_id),
+            applicant: application.applicant,
+            bond: application.bond,
+        });
+
+        Ok(())
+    }
+
+    /// Open a contest: fund a prize that is split across placements chosen after the
+    /// submission deadline. The judge defaults to the hirer and may be any key, such as a
+    /// judging panel's multisig. (called by hirer)
+    pub fn create_contest(
+        ctx: Context<CreateContest>,
+        contest_id: String,
+        prize: u64,
+        placements: Vec<u16>,
+        submission_deadline: i64,
+        judge: Option<Pubkey>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(prize > 0, EscrowError::InvalidContestPrize);
+        require!(
+            submission_deadline > now,
+            EscrowError::InvalidContestDeadline
+        );
+        let placement_bps: u32 = placements.iter().map(|bps| *bps as u32).sum();
+        require!(
+            !placements.is_empty()
+                && placements.len() <= Contest::MAX_PLACEMENTS
+                && placement_bps == BPS_DENOMINATOR as u32,
+            EscrowError::InvalidPlacements
+        );
+
+        let contest = &mut ctx.accounts.contest;
+        contest.contest_id = contest_id;
+        contest.hirer = ctx.accounts.hirer.key();
+        contest.judge = judge.unwrap_or(contest.hirer);
+        contest.mint = ctx.accounts.mint.key();
+        contest.prize = prize;
+        contest.placements = placements;
+        contest.submission_deadline = submission_deadline;
+        contest.entry_count = 0;
+        contest.is_settled = false;
+        contest.event_seq = 0;
+        contest.bump = *ctx.bumps.get("contest").unwrap();
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.hirer_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.contest_vault.to_account_info(),
+                authority: ctx.accounts.hirer.to_account_info(),
+            },
+        );
+        transfer_tokens(cpi_ctx, prize, ctx.accounts.mint.decimals)?;
+
+        emit!(ContestCreated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut contest.event_seq),
+            contest: contest.key(),
+            hirer: contest.hirer,
+            judge: contest.judge,
+            prize,
+            placements: contest.placements.clone(),
+            submission_deadline,
+        });
+
+        Ok(())
+    }
+
+    /// Enter a contest with the hash of a submission (called by the entrant, before the deadline)
+    pub fn submit_contest_entry(
+        ctx: Context<SubmitContestEntry>,
+        submission_hash: [u8; 32],
+    ) -> Result<()> {
+        let contest = &mut ctx.accounts.contest;
+        let entrant = ctx.accounts.entrant.key();
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now < contest.submission_deadline,
+            EscrowError::SubmissionsClosed
+        );
+        require!(
+            entrant != contest.hirer && entrant != contest.judge,
+            EscrowError::IneligibleEntrant
+        );
+
+        let entry = &mut ctx.accounts.entry;
+        entry.contest = contest.key();
+        entry.entrant = entrant;
+        entry.submission_hash = submission_hash;
+        entry.submitted_at = now;
+        entry.bump = *ctx.bumps.get("entry").unwrap();
+        contest.entry_count = contest
+            .entry_count
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        emit!(ContestEntrySubmitted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut contest.event_seq),
+            contest: contest.key(),
+            entry: entry.key(),
+            entrant,
+            submission_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Pick the winning entries after the deadline and pay out the prize by placement.
+    /// `remaining_accounts` holds an `(entry, winner token account)` pair per winner, first
+    /// place first. Placements left unawarded are refunded to the hirer. (called by the judge)
+    pub fn select_contest_winners<'info>(
+        ctx: Context<'_, '_, '_, 'info, SelectContestWinners<'info>>,
+    ) -> Result<()> {
+        let contest_info = ctx.accounts.contest.to_account_info();
+        let contest = &mut ctx.accounts.contest;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!contest.is_settled, EscrowError::ContestSettled);
+        require!(
+            now >= contest.submission_deadline,
+            EscrowError::SubmissionsStillOpen
+        );
+
+        let pairs = ctx.remaining_accounts;
+        let winner_count = pairs.len() / 2;
+        require!(
+            winner_count * 2 == pairs.len()
+                && winner_count > 0
+                && winner_count <= contest.placements.len(),
+            EscrowError::InvalidWinnerAccounts
+        );
+        contest.is_settled = true;
+
+        let contest_seeds = &[
+            b"contest",
+            contest.contest_id.as_bytes(),
+            &[contest.bump],
+        ];
+        let signer_seeds = &[&contest_seeds[..]];
+        let mut winners = Vec::with_capacity(winner_count);
+        let mut prizes = Vec::with_capacity(winner_count);
+        for (place, pair) in pairs.chunks(2).enumerate() {
+            let entry = Account::<ContestEntry>::try_from(&pair[0])?;
+            require_keys_eq!(
+                entry.contest,
+                contest.key(),
+                EscrowError::InvalidWinnerAccounts
+            );
+            require!(
+                !winners.contains(&entry.entrant),
+                EscrowError::DuplicateWinner
+            );
+            let winner_token_account = Account::<TokenAccount>::try_from(&pair[1])?;
+            require!(
+                winner_token_account.owner == entry.entrant
+                    && winner_token_account.mint == contest.mint,
+                EscrowError::InvalidWinnerAccounts
+            );
+
+            let prize = (contest.prize as u128 * contest.placements[place] as u128
+                / BPS_DENOMINATOR as u128) as u64;
+            if prize > 0 {
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.contest_vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: pair[1].clone(),
+                        authority: contest_info.clone(),
+                    },
+                    signer_seeds,
+                );
+                transfer_tokens(cpi_ctx, prize, ctx.accounts.mint.decimals)?;
+            }
+            winners.push(entry.entrant);
+            prizes.push(prize);
+        }
+
+        // Unawarded placements and rounding dust go back to the hirer
+        let refunded = contest.prize - prizes.iter().sum::<u64>();
+        if refunded > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.contest_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.hirer_token_account.to_account_info(),
+                    authority: contest_info,
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, refunded, ctx.accounts.mint.decimals)?;
+        }
+
+        emit!(ContestWinnersSelected {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut contest.event_seq),
+            contest: contest.key(),
+            winners,
+            prizes,
+            refunded,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out a contest's whole prize pool to an arbitrary list of recipients instead of by
+    /// placement, e.g. for hackathon tracks. The amounts must add up to the prize exactly;
+    /// `remaining_accounts` holds each recipient's token account in the same order.
+    /// (called by the judge, after the submission deadline)
+    pub fn distribute_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributePool<'info>>,
+        payouts: Vec<PoolPayout>,
+    ) -> Result<()> {
+        let contest_info = ctx.accounts.contest.to_account_info();
+        let contest = &mut ctx.accounts.contest;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!contest.is_settled, EscrowError::ContestSettled);
+        require!(
+            now >= contest.submission_deadline,
+            EscrowError::SubmissionsStillOpen
+        );
+        require!(
+            !payouts.is_empty() && payouts.len() == ctx.remaining_accounts.len(),
+            EscrowError::InvalidPoolPayouts
+        );
+        let total = payouts.iter().try_fold(0u64, |total, payout| {
+            total
+                .checked_add(payout.amount)
+                .ok_or(EscrowError::MathOverflow)
+        })?;
+        require!(total == contest.prize, EscrowError::PoolNotBalanced);
+        contest.is_settled = true;
+
+        // The seeds borrow copies so the per-recipient events can advance the sequence
+        let contest_id = contest.contest_id.clone();
+        let contest_seeds = &[
+            b"contest",
+            contest_id.as_bytes(),
+            &[contest.bump],
+        ];
+        let signer_seeds = &[&contest_seeds[..]];
+        for (payout, token_account_info) in payouts.iter().zip(ctx.remaining_accounts.iter()) {
+            let token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+            require!(
+                token_account.owner == payout.recipient && token_account.mint == contest.mint,
+                EscrowError::InvalidPoolPayouts
+            );
+            if payout.amount > 0 {
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.contest_vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: token_account_info.clone(),
+                        authority: contest_info.clone(),
+                    },
+                    signer_seeds,
+                );
+                transfer_tokens(cpi_ctx, payout.amount, ctx.accounts.mint.decimals)?;
+            }
+
+            emit!(PoolPayoutMade {
+                version: EVENT_SCHEMA_VERSION,
+                event_seq: next_event_seq(&mut contest.event_seq),
+                contest: contest.key(),
+                recipient: payout.recipient,
+                amount: payout.amount,
+            });
+        }
+
+        emit!(PoolDistributed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut contest.event_seq),
+            contest: contest.key(),
+            recipients: payouts.len() as u32,
+            total,
+        });
+
+        Ok(())
+    }
+
+    /// Reclaim the prize of a contest that closed without entries (called by hirer)
+    pub fn cancel_contest(ctx: Context<CancelContest>) -> Result<()> {
+        let contest_info = ctx.accounts.contest.to_account_info();
+        let contest = &mut ctx.accounts.contest;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!contest.is_settled, EscrowError::ContestSettled);
+        require!(
+            now >= contest.submission_deadline,
+            EscrowError::SubmissionsStillOpen
+        );
+        require!(contest.entry_count == 0, EscrowError::ContestHasEntries);
+        contest.is_settled = true;
+
+        let contest_seeds = &[
+            b"contest",
+            contest.contest_id.as_bytes(),
+            &[contest.bump],
+        ];
+        let signer_seeds = &[&contest_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.contest_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.hirer_token_account.to_account_info(),
+                authority: contest_info,
+            },
+            signer_seeds,
+        );
+        transfer_tokens(cpi_ctx, contest.prize, ctx.accounts.mint.decimals)?;
+
+        emit!(ContestCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut contest.event_seq),
+            contest: contest.key(),
+            refunded: contest.prize,
+        });
+
+        Ok(())
+    }
+
+    /// Post a bounty that anyone may claim until `claim_deadline` (called by hirer)
+    pub fn create_bounty(
+        ctx: Context<CreateBounty>,
+        bounty_id: String,
+        reward: u64,
+        claim_deadline: i64,
+    ) -> Result<()> {
+        require!(reward > 0, EscrowError::InvalidBountyReward);
+        require!(
+            claim_deadline > Clock::get()?.unix_timestamp,
+            EscrowError::InvalidBountyDeadline
+        );
+
+        let bounty = &mut ctx.accounts.bounty;
+        bounty.bounty_id = bounty_id;
+        bounty.hirer = ctx.accounts.hirer.key();
+        bounty.mint = ctx.accounts.mint.key();
+        bounty.reward = reward;
+        bounty.claim_deadline = claim_deadline;
+        bounty.open_claims = 0;
+        bounty.awarded_to = None;
+        bounty.is_settled = false;
+        bounty.event_seq = 0;
+        bounty.bump = *ctx.bumps.get("bounty").unwrap();
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.hirer_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.bounty_vault.to_account_info(),
+                authority: ctx.accounts.hirer.to_account_info(),
+            },
+        );
+        transfer_tokens(cpi_ctx, reward, ctx.accounts.mint.decimals)?;
+
+        emit!(BountyCreated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut bounty.event_seq),
+            bounty: bounty.key(),
+            hirer: bounty.hirer,
+            reward,
+            claim_deadline,
+        });
+
+        Ok(())
+    }
+
+    /// Claim a bounty with the hash of the proof of work. Any number of claims may be open at
+    /// once, one per claimant; the hirer approves at most one of them.
+    /// (called by the claimant, before the claim deadline)
+    pub fn submit_bounty_claim(
+        ctx: Context<SubmitBountyClaim>,
+        proof_hash: [u8; 32],
+    ) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        let claimant = ctx.accounts.claimant.key();
+        let now = Clock::get()?.unix_timestamp;
+        require!(!bounty.is_settled, EscrowError::BountySettled);
+        require!(now < bounty.claim_deadline, EscrowError::ClaimWindowClosed);
+        require!(claimant != bounty.hirer, EscrowError::IneligibleClaimant);
+
+        let claim = &mut ctx.accounts.claim;
+        claim.bounty = bounty.key();
+        claim.claimant = claimant;
+        claim.proof_hash = proof_hash;
+        claim.submitted_at = now;
+        claim.bump = *ctx.bumps.get("claim").unwrap();
+        bounty.open_claims = bounty
+            .open_claims
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        emit!(BountyClaimSubmitted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut bounty.event_seq),
+            bounty: bounty.key(),
+            claim: claim.key(),
+            claimant,
+            proof_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Approve one claim and pay its claimant the reward. The first approval settles the
+    /// bounty, so every other open claim can only be rejected afterwards. (called by hirer)
+    pub fn approve_bounty_claim(ctx: Context<ApproveBountyClaim>) -> Result<()> {
+        let bounty_info = ctx.accounts.bounty.to_account_info();
+        let bounty = &mut ctx.accounts.bounty;
+        let claimant = ctx.accounts.claim.claimant;
+        require!(!bounty.is_settled, EscrowError::BountySettled);
+        bounty.open_claims -= 1;
+        bounty.awarded_to = Some(claimant);
+        bounty.is_settled = true;
+
+        let bounty_seeds = &[
+            b"bounty",
+            bounty.bounty_id.as_bytes(),
+            &[bounty.bump],
+        ];
+        let signer_seeds = &[&bounty_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.bounty_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.claimant_token_account.to_account_info(),
+                authority: bounty_info,
+            },
+            signer_seeds,
+        );
+        transfer_tokens(cpi_ctx, bounty.reward, ctx.accounts.mint.decimals)?;
+
+        emit!(BountyAwarded {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut bounty.event_seq),
+            bounty: bounty.key(),
+            claim: ctx.accounts.claim.key(),
+            claimant,
+            reward: bounty.reward,
+        });
+
+        Ok(())
+    }
+
+    /// Reject a claim, closing it and returning its rent to the claimant (called by hirer)
+    pub fn reject_bounty_claim(ctx: Context<RejectBountyClaim>) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        bounty.open_claims -= 1;
+
+        emit!(BountyClaimRejected {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut bounty.event_seq),
+            bounty: bounty.key(),
+            claim: ctx.accounts.claim.key(),
+            claimant: ctx.accounts.claim.claimant,
+        });
+
+        Ok(())
+    }
+
+    /// Reclaim the reward of a bounty whose claim window closed with no open claims
+    /// (called by hirer)
+    pub fn cancel_bounty(ctx: Context<CancelBounty>) -> Result<()> {
+        let bounty_info = ctx.accounts.bounty.to_account_info();
+        let bounty = &mut ctx.accounts.bounty;
+        require!(!bounty.is_settled, EscrowError::BountySettled);
+        require!(
+            Clock::get()?.unix_timestamp >= bounty.claim_deadline,
+            EscrowError::ClaimWindowOpen
+        );
+        require!(bounty.open_claims == 0, EscrowError::BountyHasOpenClaims);
+        bounty.is_settled = true;
+
+        let bounty_seeds = &[
+            b"bounty",
+            bounty.bounty_id.as_bytes(),
+            &[bounty.bump],
+        ];
+        let signer_seeds = &[&bounty_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.bounty_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.hirer_token_account.to_account_info(),
+                authority: bounty_info,
+            },
+            signer_seeds,
+        );
+        transfer_tokens(cpi_ctx, bounty.reward, ctx.accounts.mint.decimals)?;
+
+        emit!(BountyCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut bounty.event_seq),
+            bounty: bounty.key(),
+            refunded: bounty.reward,
+        });
+
+        Ok(())
+    }
+
+    /// Opt a retainer into auto-renewal by approving the escrow as delegate over the hirer's
+    /// token account for up to `max_periods` further periods. Calling it again resets the
+    /// allowance. (called by hirer)
+    pub fn enable_retainer_auto_renew(
+        ctx: Context<EnableRetainerAutoRenew>,
+        max_periods: u32,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let mut retainer = escrow.retainer.clone().ok_or(EscrowError::NotARetainer)?;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(max_periods > 0, EscrowError::InvalidRetainerTerms);
+
+        let allowance = retainer
+            .period_amount
+            .checked_mul(max_periods as u64)
+            .ok_or(EscrowError::MathOverflow)?;
+        retainer.auto_renew = true;
+        escrow.retainer = Some(retainer);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.hirer_token_account.to_account_info(),
+                delegate: escrow.to_account_info(),
+                authority: ctx.accounts.hirer.to_account_info(),
+            },
+        );
+        token::approve(cpi_ctx, allowance).map_err(token_error)?;
+
+        emit!(RetainerAutoRenewEnabled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            allowance,
+        });
+
+        Ok(())
+    }
+
+    /// Give notice to end a retainer. Periods keep renewing until the notice runs out, so
+    /// neither party can walk away from the committed notice period. (called by hirer or
+    /// freelancer)
+    pub fn cancel_retainer(ctx: Context<CancelRetainer>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let signer = ctx.accounts.signer.key();
+        let mut retainer = escrow.retainer.clone().ok_or(EscrowError::NotARetainer)?;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            signer == escrow.hirer || signer == escrow.freelancer,
+            EscrowError::UnauthorizedParty
+        );
+        require!(
+            retainer.ends_at.is_none(),
+            EscrowError::RetainerAlreadyCancelled
+        );
+
+        let ends_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(retainer.notice_period)
+            .ok_or(EscrowError::MathOverflow)?;
+        retainer.ends_at = Some(ends_at);
+
+        escrow.record_action(EscrowAction::Cancelled, signer)?;
+        emit!(RetainerCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            cancelled_by: signer,
+            ends_at,
+        });
+        escrow.retainer = Some(retainer);
+
+        Ok(())
+    }
+
+    /// Pay out a retainer period once it has ended (permissionless crank). An auto-renewing
+    /// retainer then pulls the next period from the hirer's delegated allowance, unless the
+    /// notice has run out by now or the allowance or balance falls short, in which case the
+    /// retainer settles.
+    pub fn renew_retainer(ctx: Context<RenewRetainer>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        let mut retainer = escrow.retainer.clone().ok_or(EscrowError::NotARetainer)?;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        require!(
+            now >= retainer.period_end,
+            EscrowError::RetainerPeriodActive
+        );
+
+        let paid = escrow.amount;
+        retainer.periods_paid = retainer
+            .periods_paid
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        // A revoked or exhausted allowance ends the retainer rather than failing the payout
+        let hirer_account = &ctx.accounts.hirer_token_account;
+        let funded = hirer_account.delegate == COption::Some(escrow.key())
+            && hirer_account.delegated_amount >= retainer.period_amount
+            && hirer_account.amount >= retainer.period_amount;
+        let notice_lapsed = matches!(retainer.ends_at, Some(ends_at) if now >= ends_at);
+        let renews = retainer.auto_renew && funded && !notice_lapsed;
+        if renews {
+            retainer.period_end = now
+                .checked_add(retainer.period_length)
+                .ok_or(EscrowError::MathOverflow)?;
+            escrow.amount = retainer.period_amount;
+            escrow.deadline = retainer.period_end;
+        } else {
+            escrow.amount = 0;
+            escrow.is_released = true;
+            escrow.released_at = Some(now);
+            ctx.accounts.escrow_index.record_settlement();
+        }
+        let period = retainer.periods_paid;
+        let period_amount = retainer.period_amount;
+        let next_period_end = renews.then_some(retainer.period_end);
+        escrow.retainer = Some(retainer);
+        escrow.record_action(EscrowAction::RetainerRenewed, crate::ID)?;
+
+        let fee = escrow.platform_fee(paid);
+        ctx.accounts.freelancer_earnings.record_payout(
+            ctx.accounts.escrow_token_account.mint,
+            paid - fee,
+            fee,
+        )?;
+        rank_freelancer(
+            &mut ctx.accounts.fees,
+            &ctx.accounts.freelancer_earnings,
+            ctx.accounts.escrow_token_account.mint,
+        )?;
+        pay_freelancer(
+            &mut ctx.accounts.fees,
+            escrow,
+            &ctx.accounts.config,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.freelancer_token_account,
+            &ctx.accounts.token_program,
+            paid,
+        )?;
+
+        if renews {
+            let escrow_seeds = &[
+                b"escrow",
+                escrow.opened_by.as_ref(),
+                escrow.job_id_hash.as_ref(),
+                &[escrow.bump],
+            ];
+            let signer_seeds = &[&escrow_seeds[..]];
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.hirer_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: escrow_info,
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, period_amount, ctx.accounts.mint.decimals)?;
+        }
+
+        if !renews {
+            close_vault(
+                escrow,
+                &mut ctx.accounts.escrow_token_account,
+                &ctx.accounts.rent_payer,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        emit!(RetainerPeriodPaid {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            period,
+            paid,
+            fee,
+            renewed: renews,
+            next_period_end,
+        });
+
+        Ok(())
+    }
+
+    /// Issue an invoice for everything paid out since the previous one. The amounts and dates
+    /// come from the escrow's payout records, so the invoice can be verified from chain state;
+    /// `line_items_hash` commits to the off-chain line items. (called by freelancer)
+    pub fn issue_invoice(ctx: Context<IssueInvoice>, line_items_hash: [u8; 32]) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let payouts = escrow.uninvoiced.clone();
+        require!(payouts.gross > 0, EscrowError::NothingToInvoice);
+
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.escrow = escrow.key();
+        invoice.invoice_no = escrow.invoice_count;
+        invoice.issuer = escrow.freelancer;
+        invoice.billed_to = escrow.hirer;
+        invoice.mint = escrow.mint;
+        invoice.line_items_hash = line_items_hash;
+        invoice.gross = payouts.gross;
+        invoice.fee = payouts.fee;
+        invoice.net = payouts.gross - payouts.fee;
+        invoice.first_paid_at = payouts.first_paid_at;
+        invoice.last_paid_at = payouts.last_paid_at;
+        invoice.issued_at = Clock::get()?.unix_timestamp;
+        invoice.bump = *ctx.bumps.get("invoice").unwrap();
+
+        escrow.uninvoiced = UninvoicedPayouts::default();
+        escrow.invoice_count = escrow
+            .invoice_count
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        emit!(InvoiceIssued {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            invoice: invoice.key(),
+            invoice_no: invoice.invoice_no,
+            gross: invoice.gross,
+            fee: invoice.fee,
+            net: invoice.net,
+        });
+
+        Ok(())
+    }
+
+    /// Publish the USD price of a mint (called by the configured price oracle)
+    pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, price: i64, expo: i32) -> Result<()> {
+        require!(price > 0, EscrowError::InvalidPrice);
+
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.mint = ctx.accounts.mint.key();
+        price_feed.price = price;
+        price_feed.expo = expo;
+        price_feed.published_at = Clock::get()?.unix_timestamp;
+        price_feed.bump = *ctx.bumps.get("price_feed").unwrap();
+
+        emit!(PriceFeedUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.config.event_seq),
+            mint: price_feed.mint,
+            price,
+            expo,
+            published_at: price_feed.published_at,
+        });
+
+        Ok(())
+    }
+
+    /// Turn an escrow into a dual-deposit escrow: the hirer stakes `stake` lamports and the
+    /// freelancer is expected to match it. A lost dispute forfeits the loser's stake to the
+    /// other party. (called by hirer, before settlement)
+    pub fn enable_dual_deposit(ctx: Context<EnableDualDeposit>, stake: u64) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(stake > 0, EscrowError::InvalidStake);
+        require!(
+            escrow.stake_amount == 0,
+            EscrowError::DualDepositAlreadyEnabled
+        );
+
+        escrow.stake_amount = stake;
+        escrow.hirer_stake = stake;
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.hirer.to_account_info(),
+                to: escrow.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, stake)?;
+
+        emit!(StakePosted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            party: escrow.hirer,
+            stake,
+        });
+
+        Ok(())
+    }
+
+    /// Match the hirer's stake on a dual-deposit escrow (called by freelancer, before settlement)
+    pub fn post_freelancer_stake(ctx: Context<PostFreelancerStake>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(escrow.stake_amount > 0, EscrowError::NotDualDeposit);
+        require!(
+            escrow.freelancer_stake == 0,
+            EscrowError::StakeAlreadyPosted
+        );
+
+        let stake = escrow.stake_amount;
+        escrow.freelancer_stake = stake;
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.freelancer.to_account_info(),
+                to: escrow.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, stake)?;
+
+        emit!(StakePosted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            party: escrow.freelancer,
+            stake,
+        });
+
+        Ok(())
+    }
+
+    /// Return or forfeit dual-deposit stakes once the escrow has settled, by the same rule as
+    /// escalation bonds (permissionless crank)
+    pub fn settle_stakes(ctx: Context<SettleEscalationBonds>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.is_released, EscrowError::NotReleased);
+        let total = escrow.hirer_stake + escrow.freelancer_stake;
+        require!(total > 0, EscrowError::NoStakes);
+
+        let (to_hirer, to_freelancer) =
+            escrow.forfeit_to_winner(escrow.hirer_stake, escrow.freelancer_stake);
+        escrow.hirer_stake = 0;
+        escrow.freelancer_stake = 0;
+
+        **escrow_info.try_borrow_mut_lamports()? -= total;
+        **ctx.accounts.hirer.try_borrow_mut_lamports()? += to_hirer;
+        **ctx.accounts.freelancer.try_borrow_mut_lamports()? += to_freelancer;
+
+        emit!(StakesSettled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            to_hirer,
+            to_freelancer,
+        });
+
+        Ok(())
+    }
+
+    /// Declare the freelancer in default once the deadline passes with nothing delivered,
+    /// refunding the escrow and slashing `default_slash_bps` of the freelancer's stake to the
+    /// hirer. The rest of both stakes is returned by settle_stakes. (called by hirer)
+    pub fn trigger_default<'info>(
+        ctx: Context<'_, '_, '_, 'info, TriggerDefault<'info>>,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        require!(escrow.milestones.is_empty(), EscrowError::MilestoneEscrow);
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(now > escrow.deadline, EscrowError::DeadlineNotReached);
+        require!(
+            escrow.release_requested_at.is_none(),
+            EscrowError::WorkDelivered
+        );
+
+        let slashed = (escrow.freelancer_stake as u128
+            * ctx.accounts.config.default_slash_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        escrow.freelancer_stake -= slashed;
+        **escrow_info.try_borrow_mut_lamports()? -= slashed;
+        **ctx.accounts.hirer.try_borrow_mut_lamports()? += slashed;
+
+        escrow.is_released = true;
+        ctx.accounts.escrow_index.record_settlement();
+        escrow.released_at = Some(now);
+
+        escrow.record_action(EscrowAction::Defaulted, ctx.accounts.hirer.key())?;
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+
+        refund_funders(
+            escrow,
+            &escrow_info,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_token_account,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            escrow.amount,
+            signer_seeds,
+        )?;
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(FreelancerDefaulted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            freelancer: escrow.freelancer,
+            refunded: escrow.amount,
+            slashed,
+        });
+
+        Ok(())
+    }
+
+    /// Propose handing the admin role to `new_admin`, who may accept it once
+    /// `admin_transfer_delay` has passed (called by platform admin only)
+    pub fn propose_new_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.pending_admin.is_none(),
+            EscrowError::AdminProposalPending
+        );
+        require!(new_admin != config.admin, EscrowError::InvalidNewAdmin);
+
+        let activates_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(config.admin_transfer_delay)
+            .ok_or(EscrowError::MathOverflow)?;
+        config.pending_admin = Some(new_admin);
+        config.pending_admin_activates_at = activates_at;
+        ctx.accounts
+            .audit_log
+            .record(config.admin, AdminAction::ProposeAdmin, new_admin)?;
+
+        emit!(AdminProposed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut config.event_seq),
+            admin: config.admin,
+            new_admin,
+            activates_at,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a pending admin handover (called by platform admin only)
+    pub fn cancel_admin_proposal(ctx: Context<ProposeAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let new_admin = config.pending_admin.ok_or(EscrowError::NoPendingAdmin)?;
+
+        config.pending_admin = None;
+        config.pending_admin_activates_at = 0;
+        ctx.accounts
+            .audit_log
+            .record(config.admin, AdminAction::CancelAdminProposal, new_admin)?;
+
+        emit!(AdminProposalCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut config.event_seq),
+            admin: config.admin,
+            new_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Take over the admin role once the handover delay has passed (called by the proposed admin)
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let new_admin = config.pending_admin.ok_or(EscrowError::NoPendingAdmin)?;
+        require_keys_eq!(
+            ctx.accounts.new_admin.key(),
+            new_admin,
+            EscrowError::UnauthorizedAdminAcceptance
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= config.pending_admin_activates_at,
+            EscrowError::AdminTransferDelayActive
+        );
+
+        let old_admin = config.admin;
+        config.admin = new_admin;
+        config.pending_admin = None;
+        config.pending_admin_activates_at = 0;
+        ctx.accounts
+            .audit_log
+            .record(new_admin, AdminAction::AcceptAdmin, new_admin)?;
+
+        emit!(AdminTransferred {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut config.event_seq),
+            old_admin,
+            new_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Attest that `subject` passed KYC until `expires_at`; attesting again replaces the
+    /// record, so an expiry in the past revokes it (called by the configured KYC attestor)
+    pub fn attest_kyc(ctx: Context<AttestKyc>, subject: Pubkey, expires_at: i64) -> Result<()> {
+        let attestation = &mut ctx.accounts.kyc_attestation;
+        attestation.subject = subject;
+        attestation.attestor = ctx.accounts.attestor.key();
+        attestation.attested_at = Clock::get()?.unix_timestamp;
+        attestation.expires_at = expires_at;
+        attestation.bump = *ctx.bumps.get("kyc_attestation").unwrap();
+
+        emit!(KycAttested {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.config.event_seq),
+            subject,
+            attestor: attestation.attestor,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Open a created escrow to contributions from any wallet, each recorded in its own
+    /// receipt, in place of fund_escrow (called by hirer, before any funding)
+    pub fn enable_crowdfunding(ctx: Context<EnableCrowdfunding>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.fund_by.is_some(), EscrowError::EscrowAlreadyFunded);
+        require!(escrow.funded_amount == 0, EscrowError::EscrowAlreadyFunded);
+        require!(!escrow.is_crowdfunded, EscrowError::CrowdfundedEscrow);
+
+        escrow.is_crowdfunded = true;
+
+        Ok(())
+    }
+
+    /// Add to a crowdfunded escrow (called by any contributor, before the funding TTL)
+    pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.is_crowdfunded, EscrowError::NotCrowdfunded);
+        escrow.check_tranche(amount, Clock::get()?.unix_timestamp)?;
+        escrow.record_tranche(amount);
+
+        let contribution = &mut ctx.accounts.contribution;
+        if contribution.amount == 0 {
+            contribution.escrow = escrow.key();
+            contribution.contributor = ctx.accounts.contributor.key();
+            contribution.bump = *ctx.bumps.get("contribution").unwrap();
+            escrow.contributor_count += 1;
+        }
+        contribution.amount += amount;
+        escrow.record_action(EscrowAction::Contributed, ctx.accounts.contributor.key())?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.contributor_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.contributor.to_account_info(),
+            },
+        );
+        transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit!(ContributionReceived {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            contributor: contribution.contributor,
+            amount,
+            contributed: contribution.amount,
+            funded_amount: escrow.funded_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Take a contribution back out while the crowdfunded escrow is short of its threshold,
+    /// or once its funding TTL lapsed short of the total (called by the contributor)
+    pub fn withdraw_contribution(ctx: Context<WithdrawContribution>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_fully_funded(), EscrowError::EscrowAlreadyFunded);
+        let fund_by = escrow.fund_by.ok_or(EscrowError::EscrowAlreadyFunded)?;
+        require!(
+            !escrow.is_funded || Clock::get()?.unix_timestamp > fund_by,
+            EscrowError::UnfundedEscrowNotExpired
+        );
+
+        let amount = ctx.accounts.contribution.amount;
+        escrow.funded_amount -= amount;
+        escrow.contributor_count -= 1;
+        escrow.is_funded = escrow.funded_bps() >= escrow.funding_threshold_bps;
+        escrow.record_action(
+            EscrowAction::ContributionWithdrawn,
+            ctx.accounts.contributor.key(),
+        )?;
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.contributor_token_account.to_account_info(),
+                authority: escrow_info,
+            },
+            signer_seeds,
+        );
+        transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit!(ContributionWithdrawn {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            contributor: ctx.accounts.contributor.key(),
+            amount,
+            funded_amount: escrow.funded_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Split the escrow into `milestones`, adding up to its amount, that are submitted,
+    /// released and disputed one at a time. A dispute on one milestone leaves the others to
+    /// release as usual, and each milestone's own deadline prices its late delivery and
+    /// refunds it once missed, in place of the escrow-wide deadline. (signed by hirer and
+    /// freelancer, before delivery)
+    pub fn set_milestones(
+        ctx: Context<SetMilestones>,
+        milestones: Vec<MilestoneTerms>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        require!(escrow.retainer.is_none(), EscrowError::RetainerEscrow);
+        require!(
+            escrow.clawback_window == 0,
+            EscrowError::MilestonesUnsupported
+        );
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            escrow.pending_change.is_none(),
+            EscrowError::ChangeOrderPending
+        );
+        require!(
+            escrow.milestones.is_empty(),
+            EscrowError::MilestonesAlreadySet
+        );
+        require!(
+            escrow.first_submitted_at.is_none(),
+            EscrowError::WorkDelivered
+        );
+        require!(
+            (1..=Escrow::MAX_MILESTONES).contains(&milestones.len())
+                && MilestoneTerms::total(&milestones, 0) == Some(escrow.amount),
+            EscrowError::InvalidMilestones
+        );
+        MilestoneTerms::check(&milestones, Clock::get()?.unix_timestamp)?;
+
+        escrow.milestones = milestones.iter().map(MilestoneTerms::milestone).collect();
+
+        emit!(MilestonesSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            milestones: escrow.milestones.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Replace the milestones not started yet with `milestones`, re-splitting them, moving
+    /// their deadlines or changing how many there are. The amended milestones, with those
+    /// submitted or in dispute, must still add up to the funded amount; started milestones
+    /// keep their place ahead of the amended ones. (signed by hirer and freelancer)
+    pub fn amend_milestones(
+        ctx: Context<SetMilestones>,
+        milestones: Vec<MilestoneTerms>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!escrow.milestones.is_empty(), EscrowError::NoMilestones);
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let amended = milestones;
+
+        let mut milestones: Vec<Milestone> = escrow
+            .milestones
+            .iter()
+            .filter(|milestone| milestone.status != MilestoneStatus::Pending)
+            .copied()
+            .collect();
+        let started = milestones
+            .iter()
+            .filter(|milestone| milestone.status != MilestoneStatus::Settled)
+            .map(|milestone| milestone.amount)
+            .sum::<u64>();
+        require!(
+            milestones.len() + amended.len() <= Escrow::MAX_MILESTONES
+                && MilestoneTerms::total(&amended, started) == Some(escrow.funded_amount),
+            EscrowError::InvalidMilestones
+        );
+        MilestoneTerms::check(&amended, now)?;
+        milestones.extend(amended.iter().map(MilestoneTerms::milestone));
+        escrow.milestones = milestones;
+
+        emit!(MilestonesAmended {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            milestones: escrow.milestones.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Mark milestone `index` as delivered, fixing whether it came in after its deadline and
+    /// starting the review window after which the freelancer may claim it (called by
+    /// freelancer)
+    pub fn submit_milestone(ctx: Context<RequestRelease>, index: u8) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let signer = session_authority(
+            &ctx.accounts.signer,
+            &ctx.accounts.session,
+            SessionKey::REQUEST_RELEASE,
+            escrow.amount,
+        )?;
+        require_keys_eq!(signer, escrow.freelancer, EscrowError::UnauthorizedRelease);
+        let now = Clock::get()?.unix_timestamp;
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let milestone = escrow
+            .milestones
+            .get_mut(index as usize)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        require!(
+            milestone.status == MilestoneStatus::Pending,
+            EscrowError::MilestoneNotOpen
+        );
+
+        milestone.status = MilestoneStatus::Submitted;
+        milestone.submitted_at = Some(now);
+        escrow.record_action(EscrowAction::ReleaseRequested, signer)?;
+
+        emit!(MilestoneSubmitted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            index,
+            review_ends_at: escrow
+                .after(now, ctx.accounts.config.review_window_for(escrow))
+                .ok_or(EscrowError::MathOverflow)?,
+        });
+
+        Ok(())
+    }
+
+    /// Release milestone `index` to the freelancer, less its late penalty if it was
+    /// delivered after its deadline. Releasing the last open milestone settles the escrow.
+    /// (called by hirer or admin)
+    pub fn release_milestone<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleasePayment<'info>>,
+        index: u8,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let milestone = *escrow.open_milestone(index)?;
+        let amount = milestone.amount;
+
+        let signer = session_authority(
+            &ctx.accounts.signer,
+            &ctx.accounts.session,
+            SessionKey::RELEASE_PAYMENT,
+            amount,
+        )?;
+        require!(
+            signer == escrow.hirer || signer == ctx.accounts.platform_admin.key(),
+            EscrowError::UnauthorizedRelease
+        );
+
+        // Large releases act as the confirmation of an earlier, matured proposal
+        let config = &ctx.accounts.config;
+        if config.requires_two_step_release(amount) || escrow.is_large {
+            let proposed_at = escrow
+                .release_proposed_at
+                .ok_or(EscrowError::ReleaseNotProposed)?;
+            let confirmable_at = proposed_at
+                .checked_add(config.release_confirm_delay)
+                .ok_or(EscrowError::MathOverflow)?;
+            require!(
+                now >= confirmable_at,
+                EscrowError::ReleaseConfirmDelayActive
+            );
+        }
+
+        // Book the release before any tokens move. A late delivery returns part of the
+        // milestone to the funder, so the freelancer is paid what is left.
+        let late_penalty = escrow.milestone_penalty(&milestone);
+        let payout = amount - late_penalty;
+        if escrow.settle_milestone(index, now) {
+            ctx.accounts.escrow_index.record_settlement();
+        }
+        escrow.record_action(EscrowAction::Released, signer)?;
+        let fee = escrow.platform_fee(payout);
+        ctx.accounts.freelancer_earnings.record_payout(
+            ctx.accounts.escrow_token_account.mint,
+            payout - fee,
+            fee,
+        )?;
+        rank_freelancer(
+            &mut ctx.accounts.fees,
+            &ctx.accounts.freelancer_earnings,
+            ctx.accounts.escrow_token_account.mint,
+        )?;
+
+        let freelancer_token_account = load_payout_account(
+            escrow,
+            &ctx.accounts.freelancer_token_account,
+            &ctx.accounts.freelancer,
+            &ctx.accounts.mint,
+            &ctx.accounts.signer,
+            &ctx.accounts.associated_token_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+        )?;
+        pay_freelancer(
+            &mut ctx.accounts.fees,
+            escrow,
+            &ctx.accounts.config,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &freelancer_token_account,
+            &ctx.accounts.token_program,
+            payout,
+        )?;
+        if late_penalty > 0 {
+            let escrow_seeds = &[
+                b"escrow",
+                escrow.opened_by.as_ref(),
+                escrow.job_id_hash.as_ref(),
+                &[escrow.bump],
+            ];
+            refund_funders(
+                escrow,
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                late_penalty,
+                &[&escrow_seeds[..]],
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(MilestoneReleased {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            index,
+            amount,
+            released_by: signer,
+            late_penalty,
+        });
+
+        Ok(())
+    }
+
+    /// Claim submitted milestone `index` once the hirer let its review window pass without
+    /// releasing or disputing it, less its late penalty if it was delivered after its
+    /// deadline. Claiming the last open milestone settles the escrow. (called by freelancer)
+    pub fn claim_milestone<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAutoRelease<'info>>,
+        index: u8,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let milestone = *escrow
+            .milestones
+            .get(index as usize)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        let signer = session_authority(
+            &ctx.accounts.signer,
+            &ctx.accounts.session,
+            SessionKey::CLAIM_AUTO_RELEASE,
+            milestone.amount,
+        )?;
+        require_keys_eq!(signer, escrow.freelancer, EscrowError::UnauthorizedRelease);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let submitted_at = match (milestone.status, milestone.submitted_at) {
+            (MilestoneStatus::Submitted, Some(submitted_at)) => submitted_at,
+            (MilestoneStatus::Pending, _) => return err!(EscrowError::ReleaseNotRequested),
+            _ => return err!(EscrowError::MilestoneNotOpen),
+        };
+        let review_ends_at = escrow
+            .after(submitted_at, ctx.accounts.config.review_window_for(escrow))
+            .ok_or(EscrowError::MathOverflow)?;
+        require!(now >= review_ends_at, EscrowError::ReviewWindowOpen);
+
+        // Book the release before any tokens move. A late delivery returns part of the
+        // milestone to the funder, so the freelancer is paid what is left.
+        let late_penalty = escrow.milestone_penalty(&milestone);
+        let payout = milestone.amount - late_penalty;
+        if escrow.settle_milestone(index, now) {
+            ctx.accounts.escrow_index.record_settlement();
+        }
+        escrow.record_action(EscrowAction::Released, signer)?;
+        let fee = escrow.platform_fee(payout);
+        ctx.accounts.freelancer_earnings.record_payout(
+            ctx.accounts.escrow_token_account.mint,
+            payout - fee,
+            fee,
+        )?;
+        rank_freelancer(
+            &mut ctx.accounts.fees,
+            &ctx.accounts.freelancer_earnings,
+            ctx.accounts.escrow_token_account.mint,
+        )?;
+
+        pay_freelancer(
+            &mut ctx.accounts.fees,
+            escrow,
+            &ctx.accounts.config,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.freelancer_token_account,
+            &ctx.accounts.token_program,
+            payout,
+        )?;
+        if late_penalty > 0 {
+            let escrow_seeds = &[
+                b"escrow",
+                escrow.opened_by.as_ref(),
+                escrow.job_id_hash.as_ref(),
+                &[escrow.bump],
+            ];
+            refund_funders(
+                escrow,
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                late_penalty,
+                &[&escrow_seeds[..]],
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(MilestoneReleased {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            index,
+            amount: milestone.amount,
+            released_by: signer,
+            late_penalty,
+        });
+
+        Ok(())
+    }
+
+    /// Dispute milestone `index` under `reason_code`. Only that milestone waits for a ruling;
+    /// the others can still be released. (called by hirer or freelancer)
+    pub fn dispute_milestone(
+        ctx: Context<DisputeMilestone>,
+        index: u8,
+        reason_code: DisputeReasonCode,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let signer = ctx.accounts.signer.key();
+        let now = Clock::get()?.unix_timestamp;
+        let disputed_by = escrow
+            .role_of(&signer)
+            .ok_or(EscrowError::UnauthorizedDispute)?;
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let milestone = escrow.open_milestone(index)?;
+
+        // The same rules as initiate_dispute, with the milestone's submission and deadline
+        // standing in for the escrow's
+        let reputation = match disputed_by {
+            EscrowRole::Hirer => &mut ctx.accounts.hirer_reputation,
+            EscrowRole::Freelancer => &mut ctx.accounts.freelancer_reputation,
+        };
+        open_dispute(
+            &ctx.accounts.config,
+            reputation,
+            &mut ctx.accounts.daily_stats,
+            *ctx.bumps.get("daily_stats").unwrap(),
+            reason_code,
+            milestone.submitted_at.is_some() || now > milestone.deadline,
+            now,
+        )?;
+
+        let milestone = &mut escrow.milestones[index as usize];
+        milestone.status = MilestoneStatus::Disputed;
+        milestone.disputed_by = Some(disputed_by);
+        milestone.pending_resolution = None;
+        escrow.record_action(EscrowAction::DisputeOpened, signer)?;
+
+        emit!(MilestoneDisputed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            index,
+            disputed_by,
+            reason_code,
+        });
+
+        Ok(())
+    }
+
+    /// Rule on the dispute over milestone `index`: the freelancer gets `freelancer_amount` of
+    /// it and the funder the rest. Milestones at or above the dual-approval threshold need the
+    /// same split approved by two moderators or the admin, as in `resolve_dispute`, and a
+    /// moderator's ruling pays the moderator fee. (called by a moderator or the platform admin)
+    pub fn resolve_milestone_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveMilestoneDispute<'info>>,
+        index: u8,
+        freelancer_amount: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts
+            .config
+            .check_rate_limit(AdminAction::ResolveDispute, now)?;
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let milestone = escrow
+            .milestones
+            .get(index as usize)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        require!(
+            milestone.status == MilestoneStatus::Disputed,
+            EscrowError::MilestoneNotDisputed
+        );
+        let amount = milestone.amount;
+        let disputed_by = milestone.disputed_by;
+        let pending_resolution = milestone.pending_resolution;
+
+        let resolver = ctx.accounts.admin.key();
+        let config = &ctx.accounts.config;
+        require!(
+            config.is_moderator(&resolver),
+            EscrowError::UnauthorizedResolution
+        );
+        require!(freelancer_amount <= amount, EscrowError::InvalidSplitAmount);
+        escrow.guard_same_slot(EscrowAction::DisputeResolved)?;
+        let hirer_amount = amount - freelancer_amount;
+
+        // A moderator ruling pays the moderator who made it; the admin rules for free
+        let mut arbitrator = resolver;
+        if config.requires_dual_approval(amount) {
+            match pending_resolution {
+                Some(approval) if approval.approver != resolver => {
+                    require!(
+                        approval.hirer_amount == hirer_amount
+                            && approval.freelancer_amount == freelancer_amount,
+                        EscrowError::ResolutionSplitMismatch
+                    );
+                    arbitrator = approval.approver;
+                }
+                _ => {
+                    // First approval (or a revision by the same key): record it and wait
+                    escrow.milestones[index as usize].pending_resolution =
+                        Some(ResolutionApproval {
+                            approver: resolver,
+                            hirer_amount,
+                            freelancer_amount,
+                        });
+                    ctx.accounts.audit_log.record(
+                        resolver,
+                        AdminAction::ResolveDispute,
+                        escrow.key(),
+                    )?;
+
+                    emit!(MilestoneResolutionApproved {
+                        version: EVENT_SCHEMA_VERSION,
+                        event_seq: next_event_seq(&mut escrow.event_seq),
+                        escrow: escrow.key(),
+                        index,
+                        approver: resolver,
+                        hirer_amount,
+                        freelancer_amount,
+                    });
+                    return Ok(());
+                }
+            }
+        }
+
+        let arbitration_fee = if arbitrator != config.admin {
+            (amount as u128 * config.moderator_fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+        } else {
+            0
+        };
+        let (hirer_fee, freelancer_fee) =
+            arbitration_fee_shares(arbitration_fee, amount, freelancer_amount);
+        let ruled_freelancer_amount = freelancer_amount;
+        let hirer_amount = hirer_amount - hirer_fee;
+        let freelancer_amount = freelancer_amount - freelancer_fee;
+
+        // Book the ruling before any tokens move
+        if escrow.settle_milestone(index, now) {
+            ctx.accounts.escrow_index.record_settlement();
+        }
+        escrow.milestones[index as usize].pending_resolution = None;
+        record_dispute_outcome(
+            &DisputeOutcome {
+                disputed_by,
+                amount,
+                freelancer_amount: Some(ruled_freelancer_amount),
+            },
+            &ctx.accounts.config,
+            &mut ctx.accounts.hirer_reputation,
+            &mut ctx.accounts.freelancer_reputation,
+        )?;
+        ctx.accounts
+            .audit_log
+            .record(resolver, AdminAction::ResolveDispute, escrow.key())?;
+        escrow.record_action(EscrowAction::DisputeResolved, resolver)?;
+
+        if freelancer_amount > 0 {
+            let fee = escrow.platform_fee(freelancer_amount);
+            ctx.accounts.freelancer_earnings.record_payout(
+                ctx.accounts.escrow_token_account.mint,
+                freelancer_amount - fee,
+                fee,
+            )?;
+            rank_freelancer(
+                &mut ctx.accounts.fees,
+                &ctx.accounts.freelancer_earnings,
+                ctx.accounts.escrow_token_account.mint,
+            )?;
+            let freelancer_token_account = load_payout_account(
+                escrow,
+                &ctx.accounts.freelancer_token_account,
+                &ctx.accounts.freelancer,
+                &ctx.accounts.mint,
+                &ctx.accounts.admin,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.token_program,
+                &ctx.accounts.system_program,
+            )?;
+            pay_freelancer(
+                &mut ctx.accounts.fees,
+                escrow,
+                &ctx.accounts.config,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &freelancer_token_account,
+                &ctx.accounts.token_program,
+                freelancer_amount,
+            )?;
+        }
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+
+        if arbitration_fee > 0 {
+            let destination = ctx
+                .accounts
+                .arbitrator_token_account
+                .as_ref()
+                .ok_or(EscrowError::InvalidArbitratorAccount)?;
+            pay_arbitrator(
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                destination,
+                &arbitrator,
+                &ctx.accounts.token_program,
+                arbitration_fee,
+                signer_seeds,
+            )?;
+        }
+
+        if hirer_amount > 0 {
+            refund_funders(
+                escrow,
+                &escrow_info,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.funder_token_account,
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                hirer_amount,
+                signer_seeds,
+            )?;
+        }
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        if arbitration_fee > 0 {
+            emit!(ArbitratorPaid {
+                version: EVENT_SCHEMA_VERSION,
+                event_seq: next_event_seq(&mut escrow.event_seq),
+                escrow: escrow.key(),
+                arbitrator,
+                tier: DisputeTier::Moderator,
+                amount: arbitration_fee,
+            });
+        }
+
+        emit!(MilestoneDisputeResolved {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            index,
+            hirer_amount: amount - ruled_freelancer_amount,
+            freelancer_amount: ruled_freelancer_amount,
+            resolved_by: resolver,
+        });
+
+        Ok(())
+    }
+
+    /// Refund milestone `index` to the funder once its deadline has passed with nothing
+    /// submitted for it, in business time under a business calendar (permissionless crank)
+    pub fn refund_overdue_milestone<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundOverdueMilestone<'info>>,
+        index: u8,
+    ) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        let milestone = *escrow
+            .milestones
+            .get(index as usize)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        require!(
+            milestone.status == MilestoneStatus::Pending
+                && escrow.elapsed(milestone.deadline, now) > 0,
+            EscrowError::MilestoneNotOverdue
+        );
+        escrow.guard_same_slot(EscrowAction::Refunded)?;
+
+        if escrow.settle_milestone(index, now) {
+            ctx.accounts.escrow_index.record_settlement();
+        }
+        escrow.record_action(EscrowAction::Refunded, crate::ID)?;
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        refund_funders(
+            escrow,
+            &escrow_info,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_token_account,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            milestone.amount,
+            &[&escrow_seeds[..]],
+        )?;
+
+        close_vault(
+            escrow,
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.rent_payer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(MilestoneRefunded {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            index,
+            amount: milestone.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a scope change: a new deadline and a signed change to the amount. A hirer
+    /// proposing an increase deposits it now, so the freelancer's acceptance can take effect
+    /// on its own. (called by either party)
+    pub fn propose_change_order(
+        ctx: Context<ManageChangeOrder>,
+        amount_delta: i64,
+        new_deadline: i64,
+        scope_hash: [u8; 32],
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        let proposed_by = escrow
+            .role_of(&ctx.accounts.signer.key())
+            .ok_or(EscrowError::UnauthorizedParty)?;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        require!(escrow.retainer.is_none(), EscrowError::RetainerEscrow);
+        require!(escrow.milestones.is_empty(), EscrowError::MilestoneEscrow);
+        require!(escrow.is_fully_funded(), EscrowError::EscrowNotFullyFunded);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(!escrow.is_frozen(now), EscrowError::EscrowFrozen);
+        require!(
+            escrow.pending_change.is_none(),
+            EscrowError::ChangeOrderPending
+        );
+        require!(new_deadline > now, EscrowError::InvalidDeadline);
+        require!(
+            amount_delta == 0 || !escrow.is_crowdfunded,
+            EscrowError::CrowdfundedEscrow
+        );
+        let new_amount = (escrow.amount as i128 + amount_delta as i128)
+            .try_into()
+            .ok()
+            .filter(|amount: &u64| *amount > 0)
+            .ok_or(EscrowError::InvalidChangeOrder)?;
+        require!(
+            escrow.is_large || !ctx.accounts.config.is_large_escrow(new_amount),
+            EscrowError::KycAttestationRequired
+        );
+
+        let deposited = if proposed_by == EscrowRole::Hirer && amount_delta > 0 {
+            amount_delta as u64
+        } else {
+            0
+        };
+        escrow.pending_change = Some(ChangeOrder {
+            proposed_by,
+            amount_delta,
+            new_deadline,
+            scope_hash,
+            deposited,
+        });
+        if deposited > 0 {
+            deposit_change(&ctx, deposited)?;
+        }
+        let escrow = &mut ctx.accounts.escrow;
+
+        emit!(ChangeOrderProposed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            proposed_by,
+            amount_delta,
+            new_deadline,
+            scope_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Accept the other party's change order, moving the increase in or refunding the decrease
+    /// to the funder and updating the terms in one step (called by the party that did not
+    /// propose it)
+    pub fn accept_change_order(ctx: Context<ManageChangeOrder>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let change = escrow.pending_change.ok_or(EscrowError::NoChangeOrder)?;
+        let accepted_by = escrow
+            .role_of(&ctx.accounts.signer.key())
+            .ok_or(EscrowError::UnauthorizedParty)?;
+        require!(
+            accepted_by != change.proposed_by,
+            EscrowError::UnauthorizedParty
+        );
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::InDispute);
+        require!(
+            !escrow.is_frozen(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowFrozen
+        );
+
+        let escrow = &mut ctx.accounts.escrow;
+        let amount = (escrow.amount as i128 + change.amount_delta as i128) as u64;
+        escrow.funded_amount = (escrow.funded_amount as i128 + change.amount_delta as i128) as u64;
+        escrow.amount = amount;
+        escrow.deadline = change.new_deadline;
+        escrow.pending_change = None;
+
+        escrow.record_action(EscrowAction::ChangeOrderAccepted, ctx.accounts.signer.key())?;
+
+        if change.amount_delta > 0 && change.deposited == 0 {
+            deposit_change(&ctx, change.amount_delta as u64)?;
+        } else if change.amount_delta < 0 {
+            let escrow = &ctx.accounts.escrow;
+            let escrow_seeds = &[
+                b"escrow",
+                escrow.opened_by.as_ref(),
+                escrow.job_id_hash.as_ref(),
+                &[escrow.bump],
+            ];
+            let signer_seeds = &[&escrow_seeds[..]];
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.funder_token_account.to_account_info(),
+                    authority: escrow.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_tokens(
+                cpi_ctx,
+                change.amount_delta.unsigned_abs(),
+                ctx.accounts.mint.decimals,
+            )?;
+        }
+
+        let escrow = &mut ctx.accounts.escrow;
+        emit!(ChangeOrderAccepted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            accepted_by,
+            amount,
+            deadline: change.new_deadline,
+            scope_hash: change.scope_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw or decline a pending change order, returning any deposit to the hirer
+    /// (called by either party)
+    pub fn cancel_change_order(ctx: Context<ManageChangeOrder>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let change = escrow.pending_change.ok_or(EscrowError::NoChangeOrder)?;
+        let cancelled_by = escrow
+            .role_of(&ctx.accounts.signer.key())
+            .ok_or(EscrowError::UnauthorizedParty)?;
+
+        ctx.accounts.escrow.pending_change = None;
+
+        if change.deposited > 0 {
+            let escrow = &ctx.accounts.escrow;
+            let escrow_seeds = &[
+                b"escrow",
+                escrow.opened_by.as_ref(),
+                escrow.job_id_hash.as_ref(),
+                &[escrow.bump],
+            ];
+            let signer_seeds = &[&escrow_seeds[..]];
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.hirer_token_account.to_account_info(),
+                    authority: escrow.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, change.deposited, ctx.accounts.mint.decimals)?;
+        }
+
+        let escrow = &mut ctx.accounts.escrow;
+        emit!(ChangeOrderCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            cancelled_by,
+        });
+
+        Ok(())
+    }
+
+    /// Agree service levels for the escrow: each started `late_period` the first submission
+    /// comes after the deadline, and each revision taking longer than `response_time` after a
+    /// rejection, is a breach costing `breach_bps` of the payout up to `max_penalty_bps`.
+    /// Penalties are withheld at release. (signed by hirer and freelancer, before delivery)
+    pub fn set_sla_terms(ctx: Context<SetSlaTerms>, terms: SlaTerms) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.grant.is_none(), EscrowError::GrantEscrow);
+        require!(escrow.retainer.is_none(), EscrowError::RetainerEscrow);
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            escrow.first_submitted_at.is_none(),
+            EscrowError::WorkDelivered
+        );
+        require!(
+            terms.response_time > 0
+                && terms.late_period > 0
+                && terms.breach_bps <= terms.max_penalty_bps
+                && terms.max_penalty_bps <= BPS_DENOMINATOR,
+            EscrowError::InvalidSlaTerms
+        );
+
+        escrow.sla = Some(terms);
+
+        emit!(SlaTermsSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            terms,
+        });
+
+        Ok(())
+    }
+
+    /// Count this escrow's review windows and lateness in business days, skipping the weekdays
+    /// in `weekend_mask` and `holidays` given as day offsets from today
+    /// (signed by hirer and freelancer, before delivery)
+    pub fn set_business_calendar(
+        ctx: Context<SetBusinessCalendar>,
+        weekend_mask: u8,
+        holidays: Vec<u16>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            escrow.first_submitted_at.is_none(),
+            EscrowError::WorkDelivered
+        );
+        // At least one working day a week, so business time always advances
+        require!(
+            weekend_mask & 0x7f != 0x7f && holidays.len() <= BusinessCalendar::MAX_HOLIDAYS,
+            EscrowError::InvalidBusinessCalendar
+        );
+
+        let calendar = BusinessCalendar {
+            weekend_mask: weekend_mask & 0x7f,
+            first_day: DailyStats::today()?,
+            holidays,
+        };
+        emit!(BusinessCalendarSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            calendar: calendar.clone(),
+        });
+        escrow.calendar = Some(calendar);
+
+        Ok(())
+    }
+
+    /// Route this escrow's payouts to a token account of the escrow mint that the freelancer
+    /// need not own, such as one held for them elsewhere, instead of their own accounts
+    /// (called by freelancer)
+    pub fn set_payout_destination(ctx: Context<SetPayoutDestination>) -> Result<()> {
+        let destination = &ctx.accounts.destination;
+        require!(!destination.is_frozen(), EscrowError::PayoutAccountFrozen);
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+
+        escrow.payout_destination = Some(destination.key());
+
+        emit!(PayoutDestinationSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            destination: destination.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Send this escrow's refunds and the funder's share of rulings to another token account of
+    /// the escrow mint, such as a company treasury instead of the wallet that funded it. Signed
+    /// by the funder as well as the hirer when a sponsor funds the escrow (called by hirer)
+    pub fn set_refund_destination(ctx: Context<SetRefundDestination>) -> Result<()> {
+        let destination = &ctx.accounts.destination;
+        require!(!destination.is_frozen(), EscrowError::RefundAccountFrozen);
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+
+        escrow.refund_destination = Some(destination.key());
+
+        emit!(RefundDestinationSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            destination: destination.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Label the escrow with a category and a short title for explorers, indexes and
+    /// category-specific policies (called by hirer, before settlement)
+    pub fn set_escrow_metadata(
+        ctx: Context<SetEscrowMetadata>,
+        category: EscrowCategory,
+        title: String,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            title.len() <= Escrow::MAX_TITLE_LEN,
+            EscrowError::TitleTooLong
+        );
+
+        escrow.category = Some(category);
+        escrow.title = title;
+
+        emit!(EscrowMetadataSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            category,
+            title: escrow.title.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Tag the escrow with the taxonomy skills it needs, passed as their `TaxonomyEntry`
+    /// accounts in `remaining_accounts` (called by hirer, before settlement)
+    pub fn set_escrow_skills<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetEscrowMetadata<'info>>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.is_released, EscrowError::AlreadyReleased);
+        require!(
+            ctx.remaining_accounts.len() <= Escrow::MAX_SKILLS,
+            EscrowError::TooManySkills
+        );
+
+        let mut skills = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts.iter() {
+            let entry: Account<TaxonomyEntry> = Account::try_from(info)?;
+            require!(
+                entry.kind == TaxonomyKind::Skill && entry.active,
+                EscrowError::TaxonomyEntryUnavailable
+            );
+            if !skills.contains(&entry.index) {
+                skills.push(entry.index);
+            }
+        }
+        escrow.skills = skills;
+
+        emit!(EscrowSkillsSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            skills: escrow.skills.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Declare the taxonomy category a juror specializes in, or clear it by omitting the
+    /// category; panels for escrows in that category are drawn from its specialists
+    /// when there are enough of them (called by a registered juror)
+    pub fn set_juror_specialty(ctx: Context<SetJurorSpecialty>) -> Result<()> {
+        let juror_pool = &mut ctx.accounts.juror_pool;
+        let juror = ctx.accounts.juror.key();
+        let specialty = ctx
+            .accounts
+            .category
+            .as_ref()
+            .map(|category| category.index);
+
+        let entry = juror_pool
+            .jurors
+            .iter_mut()
+            .find(|j| j.juror == juror)
+            .ok_or(EscrowError::JurorNotRegistered)?;
+        entry.specialty = specialty;
+
+        emit!(JurorSpecialtySet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut juror_pool.event_seq),
+            juror,
+            specialty,
+        });
+
+        Ok(())
+    }
+
+    /// Mint the caller a badge for a milestone their earnings and reputation records have
+    /// crossed. Each badge is a one-of-one, zero-decimal mint at `[b"badge", owner, kind]`,
+    /// frozen in the owner's account so it works as a non-transferable credential.
+    pub fn claim_badge(ctx: Context<ClaimBadge>, kind: BadgeKind) -> Result<()> {
+        require!(
+            kind.is_earned(
+                &ctx.accounts.earnings,
+                &ctx.accounts.reputation,
+                &ctx.accounts.config
+            ),
+            EscrowError::BadgeNotEarned
+        );
+
+        let config_info = ctx.accounts.config.to_account_info();
+        let config_seeds = &[b"config".as_ref(), &[ctx.accounts.config.bump]];
+        let signer_seeds = &[&config_seeds[..]];
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                MintTo {
+                    mint: ctx.accounts.badge_mint.to_account_info(),
+                    to: ctx.accounts.badge_account.to_account_info(),
+                    authority: config_info.clone(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        // Dropping the mint authority fixes the supply at one
+        token::set_authority(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                SetAuthority {
+                    account_or_mint: ctx.accounts.badge_mint.to_account_info(),
+                    current_authority: config_info.clone(),
+                },
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+        token::freeze_account(CpiContext::new_with_signer(
+            token_program,
+            FreezeAccount {
+                account: ctx.accounts.badge_account.to_account_info(),
+                mint: ctx.accounts.badge_mint.to_account_info(),
+                authority: config_info,
+            },
+            signer_seeds,
+        ))?;
+
+        emit!(BadgeMinted {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.config.event_seq),
+            owner: ctx.accounts.owner.key(),
+            kind,
+            mint: ctx.accounts.badge_mint.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Move an escrow opened with its associated token account as the vault onto the
+    /// `[b"vault", escrow]` PDA vault, closing the old account (called by hirer)
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.vault = ctx.accounts.vault.key();
+        escrow.mint = ctx.accounts.mint.key();
+        if escrow.rent_payer == Pubkey::default() {
+            escrow.rent_payer = escrow.hirer;
+        }
+
+        let escrow_seeds = &[
+            b"escrow",
+            escrow.opened_by.as_ref(),
+            escrow.job_id_hash.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer_seeds = &[&escrow_seeds[..]];
+
+        let amount = ctx.accounts.legacy_vault.amount;
+        if amount > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.legacy_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: escrow_info.clone(),
+                },
+                signer_seeds,
+            );
+            transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        }
+        // The hirer pays the new vault's rent, so the old one's comes back to them
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.legacy_vault.to_account_info(),
+                destination: ctx.accounts.hirer.to_account_info(),
+                authority: escrow_info,
+            },
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx).map_err(token_error)?;
+
+        emit!(VaultMigrated {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            legacy_vault: ctx.accounts.legacy_vault.key(),
+            vault: escrow.vault,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Set the smallest escrow amount and the fee split dust threshold for a mint, both in
+    /// the mint's base units so each mint's decimals are accounted for (admin only)
+    pub fn set_mint_config(
+        ctx: Context<SetMintConfig>,
+        min_escrow_amount: u64,
+        dust_threshold: u64,
+    ) -> Result<()> {
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.mint = ctx.accounts.mint.key();
+        mint_config.decimals = ctx.accounts.mint.decimals;
+        mint_config.min_escrow_amount = min_escrow_amount;
+        mint_config.dust_threshold = dust_threshold;
+        mint_config.bump = *ctx.bumps.get("mint_config").unwrap();
+
+        ctx.accounts.audit_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::SetMintConfig,
+            mint_config.mint,
+        )?;
+
+        emit!(MintConfigSet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.config.event_seq),
+            mint: mint_config.mint,
+            decimals: mint_config.decimals,
+            min_escrow_amount,
+            dust_threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Create or update a category or skill of the platform taxonomy, which escrows and
+    /// juror profiles reference by index. Categories may carry a platform fee rate that
+    /// replaces the config's for escrows opened in them (admin only)
+    pub fn set_taxonomy_entry(
+        ctx: Context<SetTaxonomyEntry>,
+        kind: TaxonomyKind,
+        index: u16,
+        name: String,
+        fee_bps: Option<u16>,
+        active: bool,
+    ) -> Result<()> {
+        require!(
+            name.len() <= TaxonomyEntry::MAX_NAME_LEN,
+            EscrowError::TaxonomyNameTooLong
+        );
+        if let Some(fee_bps) = fee_bps {
+            require!(
+                kind == TaxonomyKind::Category && fee_bps <= BPS_DENOMINATOR,
+                EscrowError::InvalidFeeConfig
+            );
+        }
+
+        let entry = &mut ctx.accounts.entry;
+        entry.kind = kind;
+        entry.index = index;
+        entry.name = name;
+        entry.fee_bps = fee_bps;
+        entry.active = active;
+        entry.bump = *ctx.bumps.get("entry").unwrap();
+
+        ctx.accounts.audit_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::SetTaxonomyEntry,
+            entry.key(),
+        )?;
+
+        emit!(TaxonomyEntrySet {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut ctx.accounts.config.event_seq),
+            entry: entry.key(),
+            kind,
+            index,
+            name: entry.name.clone(),
+            fee_bps,
+            active,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(job_id: String)]
+pub struct InitializeEscrow<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = rent_payer,
+        space = Escrow::SIZE,
+        seeds = [b"escrow", hirer.key().as_ref(), &job_id_hash(&job_id)],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub hirer: Signer<'info>,
+    
+    /// Pays the rent of the escrow and its vault and gets it back when they close: the hirer,
+    /// or the platform or freelancer covering it for them
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = EscrowIndex::SIZE,
+        seeds = [b"escrow_index", hirer.key().as_ref()],
+        bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = EscrowPage::SIZE,
+        seeds = [
+            b"escrow_page",
+            hirer.key().as_ref(),
+            &escrow_index.open_page.to_le_bytes()
+        ],
+        bump
+    )]
+    pub escrow_page: Account<'info, EscrowPage>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = FreelancerEarnings::SIZE,
+        seeds = [b"earnings", freelancer.key().as_ref()],
+        bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = DailyStats::SIZE,
+        seeds = [b"daily_stats", DailyStats::today()?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub daily_stats: Account<'info, DailyStats>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = Reputation::SIZE,
+        seeds = [b"reputation", hirer.key().as_ref()],
+        bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = Reputation::SIZE,
+        seeds = [b"reputation", freelancer.key().as_ref()],
+        bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    /// CHECK: Freelancer public key, validated in business logic
+    pub freelancer: UncheckedAccount<'info>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, token::mint = mint)]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    /// Where refunds go instead of the funder's own accounts, when passed
+    #[account(token::mint = mint)]
+    pub refund_destination: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = rent_payer,
+        seeds = [b"vault", escrow.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// Required when the hirer was referred by an affiliate
+    #[account(seeds = [b"affiliate", affiliate.owner.as_ref()], bump = affiliate.bump)]
+    pub affiliate: Option<Account<'info, Affiliate>>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        associated_token::mint = mint,
+        associated_token::authority = affiliate,
+    )]
+    pub affiliate_vault: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon.code_hash.as_ref()],
+        bump = coupon.bump
+    )]
+    pub coupon: Option<Account<'info, Coupon>>,
+    
+    /// CHECK: The mint's PriceFeed PDA, snapshotted in `open_escrow` when the oracle prices the
+    /// mint
+    #[account(seeds = [b"price_feed", mint.key().as_ref()], bump)]
+    pub price_feed: UncheckedAccount<'info>,
+    
+    /// Required for escrows in the large escrow tier
+    #[account(
+        seeds = [b"kyc", hirer.key().as_ref()],
+        bump = kyc_attestation.bump
+    )]
+    pub kyc_attestation: Option<Account<'info, KycAttestation>>,
+    
+    /// Claims the job id platform-wide, so no other hirer can open an escrow under it.
+    /// Required when the config enforces globally unique job ids.
+    #[account(
+        init,
+        payer = hirer,
+        space = JobRegistration::SIZE,
+        seeds = [b"job", job_id_hash(&job_id).as_ref()],
+        bump
+    )]
+    pub job_registration: Option<Account<'info, JobRegistration>>,
+    
+    /// CHECK: Tombstone of an earlier escrow at this address, which must not exist
+    #[account(seeds = [b"tombstone", escrow.key().as_ref()], bump)]
+    pub tombstone: UncheckedAccount<'info>,
+    
+    /// CHECK: The mint's MintConfig PDA, read in `open_escrow` when the admin configured one
+    #[account(seeds = [b"mint_config", mint.key().as_ref()], bump)]
+    pub mint_config: UncheckedAccount<'info>,
+    
+    /// Taxonomy category the escrow is opened in; its fee rate, if any, replaces the
+    /// config's platform fee
+    #[account(
+        constraint = category.kind == TaxonomyKind::Category && category.active
+            @ EscrowError::TaxonomyEntryUnavailable
+    )]
+    pub category: Option<Account<'info, TaxonomyEntry>>,
+    
+    /// Required with `category`, to list the escrow under it
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = CategoryIndex::SIZE,
+        seeds = [
+            b"category_index",
+            category
+                .as_ref()
+                .ok_or(EscrowError::CategoryAccountsMissing)?
+                .key()
+                .as_ref()
+        ],
+        bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = EscrowPage::SIZE,
+        seeds = [
+            b"category_page",
+            category
+                .as_ref()
+                .ok_or(EscrowError::CategoryAccountsMissing)?
+                .key()
+                .as_ref(),
+            &category_index
+                .as_ref()
+                .ok_or(EscrowError::CategoryAccountsMissing)?
+                .open_page
+                .to_le_bytes()
+        ],
+        bump
+    )]
+    pub category_page: Option<Account<'info, EscrowPage>>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ReleasePayment<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    
+    pub session: Option<Account<'info, SessionKey>>,
+    
+    /// CHECK: Platform admin key, must match the config admin
+    #[account(address = config.admin)]
+    pub platform_admin: UncheckedAccount<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// CHECK: Any token account the freelancer owns, or the payout destination they chose. Their
+    /// associated token account is opened on the signer's rent if it does not exist yet;
+    /// checked in `load_payout_account`
+    #[account(mut)]
+    pub freelancer_token_account: UncheckedAccount<'info>,
+    
+    /// CHECK: Freelancer of the escrow, the authority of a newly opened token account
+    #[account(address = escrow.freelancer)]
+    pub freelancer: UncheckedAccount<'info>,
+    
+    /// Receives any SLA or milestone late penalty withheld from the freelancer
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateDispute<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    
+    pub session: Option<Account<'info, SessionKey>>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DailyStats::SIZE,
+        seeds = [b"daily_stats", DailyStats::today()?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub daily_stats: Account<'info, DailyStats>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// CHECK: Any token account the freelancer owns, or the payout destination they chose. Their
+    /// associated token account is opened on the signer's rent if it does not exist yet;
+    /// checked in `load_payout_account`
+    #[account(mut)]
+    pub freelancer_token_account: UncheckedAccount<'info>,
+    
+    /// CHECK: Freelancer of the escrow, the authority of a newly opened token account
+    #[account(address = escrow.freelancer)]
+    pub freelancer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    /// Receives the moderator fee, owned by the moderator whose ruling executes
+    #[account(mut)]
+    pub arbitrator_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AutoResolveDispute<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    pub signer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyRefund<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AdminRefundUsage::SIZE,
+        seeds = [b"admin_refunds", admin.key().as_ref()],
+        bump
+    )]
+    pub refund_usage: Account<'info, AdminRefundUsage>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Config::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = JurorPool::SIZE,
+        seeds = [b"juror_pool"],
+        bump
+    )]
+    pub juror_pool: Account<'info, JurorPool>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = AuditLog::SIZE,
+        seeds = [b"audit_log"],
+        bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    pub stake_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = stake_mint,
+        associated_token::authority = juror_pool,
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterJuror<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"juror_pool"], bump = juror_pool.bump)]
+    pub juror_pool: Account<'info, JurorPool>,
+    
+    pub juror: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = config.stake_mint,
+        token::authority = juror,
+    )]
+    pub juror_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = config.stake_mint,
+        associated_token::authority = juror_pool,
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+    
+    #[account(address = config.stake_mint @ EscrowError::InvalidMint)]
+    pub stake_mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterJuror<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"juror_pool"], bump = juror_pool.bump)]
+    pub juror_pool: Account<'info, JurorPool>,
+    
+    pub juror: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = config.stake_mint,
+        token::authority = juror,
+    )]
+    pub juror_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = config.stake_mint,
+        associated_token::authority = juror_pool,
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+    
+    #[account(address = config.stake_mint @ EscrowError::InvalidMint)]
+    pub stake_mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DrawJurors<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"juror_pool"], bump = juror_pool.bump)]
+    pub juror_pool: Account<'info, JurorPool>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = JurorPanel::SIZE,
+        seeds = [b"panel", escrow.key().as_ref()],
+        bump
+    )]
+    pub panel: Account<'info, JurorPanel>,
+    
+    pub signer: Signer<'info>,
+    
+    /// Funds the panel and assignment rent; may be a platform relayer with no authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: SlotHashes sysvar, read manually as the panel randomness source
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAssignment<'info> {
+    #[account(mut, address = assignment.escrow)]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"assignment", assignment.panel.as_ref(), &[assignment.index]],
+        bump = assignment.bump,
+        has_one = juror
+    )]
+    pub assignment: Account<'info, JurorAssignment>,
+    
+    pub juror: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashJuror<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"juror_pool"], bump = juror_pool.bump)]
+    pub juror_pool: Account<'info, JurorPool>,
+    
+    #[account(mut, address = panel.escrow)]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(seeds = [b"panel", panel.escrow.as_ref()], bump = panel.bump)]
+    pub panel: Account<'info, JurorPanel>,
+    
+    #[account(
+        mut,
+        seeds = [b"assignment", panel.key().as_ref(), &[assignment.index]],
+        bump = assignment.bump
+    )]
+    pub assignment: Account<'info, JurorAssignment>,
+    
+    #[account(
+        mut,
+        associated_token::mint = config.stake_mint,
+        associated_token::authority = juror_pool,
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = config.stake_mint,
+        associated_token::authority = config.treasury,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = config.stake_mint @ EscrowError::InvalidMint)]
+    pub stake_mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseJurorAssignment<'info> {
+    #[account(mut, seeds = [b"juror_pool"], bump = juror_pool.bump)]
+    pub juror_pool: Account<'info, JurorPool>,
+    
+    #[account(address = panel.escrow)]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        seeds = [b"panel", panel.escrow.as_ref()],
+        bump = panel.bump,
+        has_one = payer
+    )]
+    pub panel: Account<'info, JurorPanel>,
+    
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"assignment", panel.key().as_ref(), &[assignment.index]],
+        bump = assignment.bump
+    )]
+    pub assignment: Account<'info, JurorAssignment>,
+    
+    /// CHECK: Rent recipient, must match the account that paid for the panel draw
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut, address = panel.escrow)]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(seeds = [b"panel", panel.escrow.as_ref()], bump = panel.bump)]
+    pub panel: Account<'info, JurorPanel>,
+    
+    #[account(
+        mut,
+        seeds = [b"assignment", panel.key().as_ref(), &[assignment.index]],
+        bump = assignment.bump,
+        has_one = juror
+    )]
+    pub assignment: Account<'info, JurorAssignment>,
+    
+    pub juror: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TallyPanelVotes<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(
+        mut,
+        seeds = [b"panel", escrow.key().as_ref()],
+        bump = panel.bump
+    )]
+    pub panel: Account<'info, JurorPanel>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AssignMediator<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub moderator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecommendResolution<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub mediator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeSettlement<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init_if_needed,
+        payer = proposer,
+        space = SettlementProposal::SIZE,
+        seeds = [b"settlement", escrow.key().as_ref()],
+        bump
+    )]
+    pub settlement: Account<'info, SettlementProposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSettlement<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"settlement", escrow.key().as_ref()],
+        bump = settlement.bump
+    )]
+    pub settlement: Account<'info, SettlementProposal>,
+    
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptSettlement<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"settlement", escrow.key().as_ref()],
+        bump = settlement.bump
+    )]
+    pub settlement: Account<'info, SettlementProposal>,
+    
+    /// CHECK: Paid the rent of the settlement proposal, and receives it back once it executes
+    #[account(mut, address = settlement.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    pub signer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub escrow_rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EscalateDispute<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleEscalationBonds<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    /// CHECK: Bond recipient, must be the escrow hirer
+    #[account(mut, address = escrow.hirer)]
+    pub hirer: UncheckedAccount<'info>,
+    
+    /// CHECK: Bond recipient, must be the escrow freelancer
+    #[account(mut, address = escrow.freelancer)]
+    pub freelancer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRefund<'info> {
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(mut)]
+    pub freelancer: Signer<'info>,
+    
+    /// CHECK: Escrow hirer, receives back any stake they posted
+    #[account(mut)]
+    pub hirer: UncheckedAccount<'info>,
+    
+    /// CHECK: Paid the rent of the escrow and its vault, and receives it back
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"escrow_page",
+            escrow_page.owner.as_ref(),
+            &escrow_page.page_no.to_le_bytes()
+        ],
+        bump = escrow_page.bump,
+        constraint = escrow_page.escrows.contains(&escrow.key()) @ EscrowError::EscrowNotListed
+    )]
+    pub escrow_page: Account<'info, EscrowPage>,
+    
+    /// Required when the escrow was opened in a taxonomy category
+    #[account(
+        mut,
+        seeds = [
+            b"category_page",
+            category_page.owner.as_ref(),
+            &category_page.page_no.to_le_bytes()
+        ],
+        bump = category_page.bump,
+        constraint = category_page.escrows.contains(&escrow.key()) @ EscrowError::EscrowNotListed
+    )]
+    pub category_page: Option<Account<'info, EscrowPage>>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    /// Keeps the closed escrow's address from being opened again
+    #[account(
+        init,
+        payer = freelancer,
+        space = EscrowTombstone::SIZE,
+        seeds = [b"tombstone", escrow.key().as_ref()],
+        bump
+    )]
+    pub tombstone: Account<'info, EscrowTombstone>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRelease<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    /// Freelancer, or a session key registered by the freelancer
+    pub signer: Signer<'info>,
+    
+    pub session: Option<Account<'info, SessionKey>>,
+}
+
+#[derive(Accounts)]
+pub struct RejectSubmission<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAutoRelease<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    /// Freelancer, or a session key registered by the freelancer
+    pub signer: Signer<'info>,
+    
+    pub session: Option<Account<'info, SessionKey>>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    /// Receives any SLA or milestone late penalty withheld from the freelancer
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRelease<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ModerateEscrow<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub moderator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawHeldPayment<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    pub freelancer: Signer<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClawbackPayment<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_key: Pubkey)]
+pub struct TransferRole<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    
+    pub counterparty: Option<Signer<'info>>,
+    
+    /// Earnings tracker for `new_key`, so payouts keep working if the freelancer role moves
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = FreelancerEarnings::SIZE,
+        seeds = [b"earnings", new_key.as_ref()],
+        bump
+    )]
+    pub new_key_earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = Reputation::SIZE,
+        seeds = [b"reputation", new_key.as_ref()],
+        bump
+    )]
+    pub new_key_reputation: Account<'info, Reputation>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: EscrowRole, new_key: Pubkey)]
+pub struct ChangeBeneficiary<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub hirer: Signer<'info>,
+    
+    pub freelancer: Signer<'info>,
+    
+    /// Token account the moved role's payouts or refunds go to instead of the new wallet's own
+    #[account(token::mint = escrow.mint)]
+    pub destination: Option<Account<'info, TokenAccount>>,
+    
+    /// Earnings tracker for `new_key`, so payouts keep working if the freelancer role moves
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = FreelancerEarnings::SIZE,
+        seeds = [b"earnings", new_key.as_ref()],
+        bump
+    )]
+    pub new_key_earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = Reputation::SIZE,
+        seeds = [b"reputation", new_key.as_ref()],
+        bump
+    )]
+    pub new_key_reputation: Account<'info, Reputation>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = SessionKey::SIZE,
+        seeds = [b"session", owner.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    
+    pub owner: Signer<'info>,
+    
+    /// Funds the session rent; may be a platform relayer with no authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"session", owner.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump,
+        has_one = owner,
+        has_one = payer
+    )]
+    pub session: Account<'info, SessionKey>,
+    
+    pub owner: Signer<'info>,
+    
+    /// CHECK: Rent recipient, must match the account that funded the session
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLeaderboard<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = Leaderboard::SIZE,
+        seeds = [b"leaderboard", mint.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCompressedTree<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = CompressedTree::SIZE,
+        seeds = [b"compressed_tree", mint.key().as_ref()],
+        bump
+    )]
+    pub tree: Account<'info, CompressedTree>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = tree,
+    )]
+    pub tree_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCompressedEscrow<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"compressed_tree", tree.mint.as_ref()],
+        bump = tree.bump
+    )]
+    pub tree: Account<'info, CompressedTree>,
+    
+    #[account(
+        mut,
+        associated_token::mint = tree.mint,
+        associated_token::authority = tree,
+    )]
+    pub tree_vault: Account<'info, TokenAccount>,
+    
+    pub hirer: Signer<'info>,
+    
+    #[account(mut, token::mint = tree.mint)]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = tree.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleCompressedEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"compressed_tree", tree.mint.as_ref()],
+        bump = tree.bump
+    )]
+    pub tree: Account<'info, CompressedTree>,
+    
+    #[account(
+        mut,
+        associated_token::mint = tree.mint,
+        associated_token::authority = tree,
+    )]
+    pub tree_vault: Account<'info, TokenAccount>,
+    
+    pub hirer: Signer<'info>,
+    
+    #[account(mut, token::mint = tree.mint)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = tree.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceNonce<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AssertVersion {}
+
+#[derive(Accounts)]
+pub struct AssertInvariants<'info> {
+    #[account(seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()], bump = escrow.bump)]
+    pub escrow: Account<'info, Escrow>,
+    
+    /// CHECK: The escrow's vault, closed once settlement empties it
+    #[account(address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterAffiliate<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Affiliate::SIZE,
+        seeds = [b"affiliate", owner.key().as_ref()],
+        bump
+    )]
+    pub affiliate: Account<'info, Affiliate>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinAffiliate<'info> {
+    #[account(
+        mut,
+        seeds = [b"affiliate", affiliate.owner.as_ref()],
+        bump = affiliate.bump
+    )]
+    pub affiliate: Account<'info, Affiliate>,
+    
+    #[account(
+        init_if_needed,
+        payer = hirer,
+        space = EscrowIndex::SIZE,
+        seeds = [b"escrow_index", hirer.key().as_ref()],
+        bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(mut)]
+    pub hirer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAffiliateRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"affiliate", owner.key().as_ref()],
+        bump = affiliate.bump,
+        has_one = owner
+    )]
+    pub affiliate: Account<'info, Affiliate>,
+    
+    pub owner: Signer<'info>,
+    
+    #[account(mut, token::authority = affiliate)]
+    pub affiliate_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut, token::mint = affiliate_vault.mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = affiliate_vault.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// Token accounts receiving the platform fee withheld from a freelancer payout, and the
+/// mint's price feed for the settlement snapshot
+#[derive(Accounts)]
+pub struct FeeAccounts<'info> {
+    /// Treasury token account for the escrow mint, checked against the config in `pay_freelancer`
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    /// Required when the escrow was opened by a referred hirer
+    #[account(mut)]
+    pub affiliate: Option<Account<'info, Affiliate>>,
+    
+    #[account(mut)]
+    pub affiliate_vault: Option<Account<'info, TokenAccount>>,
+    
+    /// Required when the hirer routes part of the fee to charity
+    #[account(mut)]
+    pub charity_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// Required when the fee splits include the insurance pool
+    #[account(mut)]
+    pub insurance_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// Escrow mint, required when the fee splits burn a share
+    #[account(mut)]
+    pub mint: Option<Account<'info, Mint>>,
+    
+    /// Snapshots the mint's USD price at settlement when passed
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+    
+    /// The mint's leaderboard, re-ranked with the freelancer's volume when passed
+    #[account(mut)]
+    pub leaderboard: Option<Account<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+#[instruction(code_hash: [u8; 32])]
+pub struct CreateCoupon<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = Coupon::SIZE,
+        seeds = [b"coupon", code_hash.as_ref()],
+        bump
+    )]
+    pub coupon: Account<'info, Coupon>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    pub freelancer: Signer<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeGrant<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    pub hirer: Signer<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelGrant<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    pub signer: Signer<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EnableRetainerAutoRenew<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+    
+    #[account(address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = escrow_token_account.mint,
+        associated_token::authority = hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRetainer<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RenewRetainer<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    // Renewals pull into this account, so it must be the escrow's own vault
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// Any token account the freelancer owns, or the payout destination they chose
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.pays_to(&freelancer_token_account) @ EscrowError::InvalidPayoutDestination,
+    )]
+    pub freelancer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = escrow_token_account.mint,
+        associated_token::authority = escrow.hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct IssueInvoice<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init,
+        payer = freelancer,
+        space = Invoice::SIZE,
+        seeds = [
+            b"invoice",
+            escrow.key().as_ref(),
+            escrow.invoice_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+    
+    #[account(mut)]
+    pub freelancer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnableDualDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub hirer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PostFreelancerStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub freelancer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TriggerDefault<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(mut)]
+    pub hirer: Signer<'info>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct AttestKyc<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init_if_needed,
+        payer = attestor,
+        space = KycAttestation::SIZE,
+        seeds = [b"kyc", subject.as_ref()],
+        bump
+    )]
+    pub kyc_attestation: Account<'info, KycAttestation>,
+    
+    #[account(
+        mut,
+        address = config.large_escrow.kyc_attestor @ EscrowError::UnauthorizedKycAttestor
+    )]
+    pub attestor: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = funder
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub funder: Signer<'info>,
+    
+    #[account(mut, token::mint = escrow_token_account.mint)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseUnfundedEscrow<'info> {
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"escrow_page",
+            escrow_page.owner.as_ref(),
+            &escrow_page.page_no.to_le_bytes()
+        ],
+        bump = escrow_page.bump,
+        constraint = escrow_page.escrows.contains(&escrow.key()) @ EscrowError::EscrowNotListed
+    )]
+    pub escrow_page: Account<'info, EscrowPage>,
+    
+    /// Required when the escrow was opened in a taxonomy category
+    #[account(
+        mut,
+        seeds = [
+            b"category_page",
+            category_page.owner.as_ref(),
+            &category_page.page_no.to_le_bytes()
+        ],
+        bump = category_page.bump,
+        constraint = category_page.escrows.contains(&escrow.key()) @ EscrowError::EscrowNotListed
+    )]
+    pub category_page: Option<Account<'info, EscrowPage>>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    
+    /// CHECK: Escrow hirer, receives back any stake they posted
+    #[account(mut)]
+    pub hirer: UncheckedAccount<'info>,
+    
+    /// CHECK: Paid the rent of the escrow and its vault, and receives it back
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// CHECK: Escrow freelancer, receives back any stake they posted
+    #[account(mut)]
+    pub freelancer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.funder
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    /// Keeps the closed escrow's address from being opened again
+    #[account(
+        init,
+        payer = signer,
+        space = EscrowTombstone::SIZE,
+        seeds = [b"tombstone", escrow.key().as_ref()],
+        bump
+    )]
+    pub tombstone: Account<'info, EscrowTombstone>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSettledEscrow<'info> {
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"escrow_page",
+            escrow_page.owner.as_ref(),
+            &escrow_page.page_no.to_le_bytes()
+        ],
+        bump = escrow_page.bump,
+        constraint = escrow_page.escrows.contains(&escrow.key()) @ EscrowError::EscrowNotListed
+    )]
+    pub escrow_page: Account<'info, EscrowPage>,
+    
+    /// Required when the escrow was opened in a taxonomy category
+    #[account(
+        mut,
+        seeds = [
+            b"category_page",
+            category_page.owner.as_ref(),
+            &category_page.page_no.to_le_bytes()
+        ],
+        bump = category_page.bump,
+        constraint = category_page.escrows.contains(&escrow.key()) @ EscrowError::EscrowNotListed
+    )]
+    pub category_page: Option<Account<'info, EscrowPage>>,
+    
+    /// CHECK: The escrow's juror panel, which only exists if one was drawn
+    #[account(seeds = [b"panel", escrow.key().as_ref()], bump)]
+    pub panel: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    
+    /// CHECK: Paid the rent of the escrow, and receives it back
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// CHECK: Must already be closed, which settlement does once it empties the vault
+    #[account(address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+    
+    /// Keeps the closed escrow's address from being opened again
+    #[account(
+        init,
+        payer = signer,
+        space = EscrowTombstone::SIZE,
+        seeds = [b"tombstone", escrow.key().as_ref()],
+        bump
+    )]
+    pub tombstone: Account<'info, EscrowTombstone>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnableCrowdfunding<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Contribute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init_if_needed,
+        payer = contributor,
+        space = Contribution::SIZE,
+        seeds = [b"contribution", escrow.key().as_ref(), contributor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+    
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+    
+    #[account(mut, token::mint = escrow_token_account.mint)]
+    pub contributor_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawContribution<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        close = contributor,
+        seeds = [b"contribution", escrow.key().as_ref(), contributor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = contributor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = contributor
+    )]
+    pub contributor_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetMilestones<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+    
+    pub freelancer: Signer<'info>,
+}
+    
+#[derive(Accounts)]
+pub struct DisputeMilestone<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    /// Hirer or freelancer of the escrow
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DailyStats::SIZE,
+        seeds = [b"daily_stats", DailyStats::today()?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub daily_stats: Account<'info, DailyStats>,
+    
+    pub system_program: Program<'info, System>,
+}
+    
+#[derive(Accounts)]
+pub struct ResolveMilestoneDispute<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"earnings", escrow.freelancer.as_ref()],
+        bump = freelancer_earnings.bump
+    )]
+    pub freelancer_earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.hirer.as_ref()],
+        bump = hirer_reputation.bump
+    )]
+    pub hirer_reputation: Account<'info, Reputation>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation", escrow.freelancer.as_ref()],
+        bump = freelancer_reputation.bump
+    )]
+    pub freelancer_reputation: Account<'info, Reputation>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// CHECK: The freelancer's associated token account, opened on the signer's rent if it does
+    /// not exist yet, or the payout destination they chose; checked in `load_payout_account`
+    #[account(mut)]
+    pub freelancer_token_account: UncheckedAccount<'info>,
+    
+    /// CHECK: Freelancer of the escrow, the authority of a newly opened token account
+    #[account(address = escrow.freelancer)]
+    pub freelancer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    /// Receives the moderator fee, owned by the moderator whose ruling executes
+    #[account(mut)]
+    pub arbitrator_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub fees: FeeAccounts<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundOverdueMilestone<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_index", escrow.opened_by.as_ref()],
+        bump = escrow_index.bump
+    )]
+    pub escrow_index: Account<'info, EscrowIndex>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Paid the rent of the vault, and receives it back when settlement empties it
+    #[account(mut, address = escrow.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ManageChangeOrder<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
     pub signer: Signer<'info>,
     
-    /// CHECK: Platform admin key, validated in business logic
-    pub platform_admin: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        token::authority = escrow.hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow_token_account.mint,
+        constraint = escrow.refunds_to(&funder_token_account) @ EscrowError::InvalidRefundDestination,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetSlaTerms<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+    
+    pub freelancer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBusinessCalendar<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+    
+    pub freelancer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRefundDestination<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        has_one = funder
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+    
+    /// The same key as `hirer` unless a sponsor funds the escrow
+    pub funder: Signer<'info>,
+    
+    #[account(token::mint = escrow.mint)]
+    pub destination: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutDestination<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = freelancer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub freelancer: Signer<'info>,
+    
+    #[account(address = escrow.vault @ EscrowError::InvalidVault)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(token::mint = escrow_token_account.mint)]
+    pub destination: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = MintConfig::SIZE,
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer,
+        constraint = escrow.vault == Pubkey::default() @ EscrowError::VaultAlreadyMigrated
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub hirer: Signer<'info>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = escrow,
+    )]
+    pub legacy_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = hirer,
+        seeds = [b"vault", escrow.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetEscrowMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: TaxonomyKind, index: u16)]
+pub struct SetTaxonomyEntry<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut, seeds = [b"audit_log"], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = TaxonomyEntry::SIZE,
+        seeds = [b"taxonomy".as_ref(), &[kind as u8], &index.to_le_bytes()],
+        bump
+    )]
+    pub entry: Account<'info, TaxonomyEntry>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetJurorSpecialty<'info> {
+    #[account(mut, seeds = [b"juror_pool"], bump = juror_pool.bump)]
+    pub juror_pool: Account<'info, JurorPool>,
+    
+    pub juror: Signer<'info>,
+    
+    /// The category to specialize in; omitted to clear the specialty
+    #[account(
+        constraint = category.kind == TaxonomyKind::Category && category.active
+            @ EscrowError::TaxonomyEntryUnavailable
+    )]
+    pub category: Option<Account<'info, TaxonomyEntry>>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: BadgeKind)]
+pub struct ClaimBadge<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(seeds = [b"earnings", owner.key().as_ref()], bump = earnings.bump)]
+    pub earnings: Account<'info, FreelancerEarnings>,
+    
+    #[account(seeds = [b"reputation", owner.key().as_ref()], bump = reputation.bump)]
+    pub reputation: Account<'info, Reputation>,
+    
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"badge".as_ref(), owner.key().as_ref(), &[kind as u8]],
+        bump,
+        mint::decimals = 0,
+        mint::authority = config,
+        mint::freeze_authority = config,
+    )]
+    pub badge_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = badge_mint,
+        associated_token::authority = owner,
+    )]
+    pub badge_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetCharityRouting<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.opened_by.as_ref(), escrow.job_id_hash.as_ref()],
+        bump = escrow.bump,
+        has_one = hirer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub hirer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(job_id: String, hirer: Pubkey)]
+pub struct ApplyToJob<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = applicant,
+        space = JobApplication::SIZE,
+        seeds = [
+            b"application",
+            hirer.as_ref(),
+            &job_id_hash(&job_id),
+            applicant.key().as_ref()
+        ],
+        bump
+    )]
+    pub application: Account<'info, JobApplication>,
+    
+    /// CHECK: Escrow address of the job, only checked for being uninitialized
+    #[account(seeds = [b"escrow", hirer.as_ref(), &job_id_hash(&job_id)], bump)]
+    pub escrow: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub applicant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundBidBond<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"application",
+            application.hirer.as_ref(),
+            &job_id_hash(&application.job_id),
+            application.applicant.as_ref()
+        ],
+        bump = application.bump,
+        close = applicant
+    )]
+    pub application: Account<'info, JobApplication>,
+    
+    /// CHECK: Escrow address of the job, deserialized in business logic once it exists
+    #[account(
+        seeds = [b"escrow", application.hirer.as_ref(), &job_id_hash(&application.job_id)],
+        bump
+    )]
+    pub escrow: UncheckedAccount<'info>,
+    
+    /// CHECK: Bond and rent recipient, must match the applicant
+    #[account(mut, address = application.applicant)]
+    pub applicant: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(contest_id: String)]
+pub struct CreateContest<'info> {
+    #[account(
+        init,
+        payer = hirer,
+        space = Contest::SIZE,
+        seeds = [b"contest", contest_id.as_bytes()],
+        bump
+    )]
+    pub contest: Account<'info, Contest>,
+    
+    #[account(mut)]
+    pub hirer: Signer<'info>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = hirer,
+        associated_token::mint = mint,
+        associated_token::authority = contest,
+    )]
+    pub contest_vault: Account<'info, TokenAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitContestEntry<'info> {
+    #[account(
+        mut,
+        seeds = [b"contest", contest.contest_id.as_bytes()],
+        bump = contest.bump
+    )]
+    pub contest: Account<'info, Contest>,
+    
+    #[account(
+        init,
+        payer = entrant,
+        space = ContestEntry::SIZE,
+        seeds = [b"contest_entry", contest.key().as_ref(), entrant.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, ContestEntry>,
+    
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SelectContestWinners<'info> {
+    #[account(
+        mut,
+        seeds = [b"contest", contest.contest_id.as_bytes()],
+        bump = contest.bump,
+        has_one = judge
+    )]
+    pub contest: Account<'info, Contest>,
+    
+    pub judge: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = contest.mint,
+        associated_token::authority = contest,
+    )]
+    pub contest_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = contest.mint,
+        associated_token::authority = contest.hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = contest.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DistributePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"contest", contest.contest_id.as_bytes()],
+        bump = contest.bump,
+        has_one = judge
+    )]
+    pub contest: Account<'info, Contest>,
+    
+    pub judge: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = contest.mint,
+        associated_token::authority = contest,
+    )]
+    pub contest_vault: Account<'info, TokenAccount>,
+    
+    #[account(address = contest.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelContest<'info> {
+    #[account(
+        mut,
+        seeds = [b"contest", contest.contest_id.as_bytes()],
+        bump = contest.bump,
+        has_one = hirer
+    )]
+    pub contest: Account<'info, Contest>,
+    
+    pub hirer: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = contest.mint,
+        associated_token::authority = contest,
+    )]
+    pub contest_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = contest.mint,
+        token::authority = hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = contest.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(bounty_id: String)]
+pub struct CreateBounty<'info> {
+    #[account(
+        init,
+        payer = hirer,
+        space = Bounty::SIZE,
+        seeds = [b"bounty", bounty_id.as_bytes()],
+        bump
+    )]
+    pub bounty: Account<'info, Bounty>,
     
     #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub hirer: Signer<'info>,
+    
+    pub mint: Account<'info, Mint>,
     
     #[account(
         mut,
-        associated_token::mint = escrow_token_account.mint,
-        associated_token::authority = escrow.freelancer,
+        associated_token::mint = mint,
+        associated_token::authority = hirer,
     )]
-    pub freelancer_token_account: Account<'info, TokenAccount>,
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = hirer,
+        associated_token::mint = mint,
+        associated_token::authority = bounty,
+    )]
+    pub bounty_vault: Account<'info, TokenAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitBountyClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.bounty_id.as_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    
+    #[account(
+        init,
+        payer = claimant,
+        space = BountyClaim::SIZE,
+        seeds = [b"bounty_claim", bounty.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, BountyClaim>,
+    
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveBountyClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.bounty_id.as_bytes()],
+        bump = bounty.bump,
+        has_one = hirer
+    )]
+    pub bounty: Account<'info, Bounty>,
+    
+    #[account(
+        seeds = [b"bounty_claim", bounty.key().as_ref(), claim.claimant.as_ref()],
+        bump = claim.bump
+    )]
+    pub claim: Account<'info, BountyClaim>,
+    
+    pub hirer: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = bounty.mint,
+        associated_token::authority = bounty,
+    )]
+    pub bounty_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = bounty.mint,
+        token::authority = claim.claimant,
+    )]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = bounty.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RejectBountyClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.bounty_id.as_bytes()],
+        bump = bounty.bump,
+        has_one = hirer
+    )]
+    pub bounty: Account<'info, Bounty>,
+    
+    #[account(
+        mut,
+        seeds = [b"bounty_claim", bounty.key().as_ref(), claim.claimant.as_ref()],
+        bump = claim.bump,
+        close = claimant
+    )]
+    pub claim: Account<'info, BountyClaim>,
+    
+    pub hirer: Signer<'info>,
+    
+    /// CHECK: Rent recipient, must match the claimant
+    #[account(mut, address = claim.claimant)]
+    pub claimant: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelBounty<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.bounty_id.as_bytes()],
+        bump = bounty.bump,
+        has_one = hirer
+    )]
+    pub bounty: Account<'info, Bounty>,
+    
+    pub hirer: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = bounty.mint,
+        associated_token::authority = bounty,
+    )]
+    pub bounty_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = bounty.mint,
+        token::authority = hirer,
+    )]
+    pub hirer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = bounty.mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceFeed<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init_if_needed,
+        payer = oracle,
+        space = PriceFeed::SIZE,
+        seeds = [b"price_feed", mint.key().as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut, address = config.price_oracle @ EscrowError::UnauthorizedPriceOracle)]
+    pub oracle: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub hirer: Pubkey,
+    pub freelancer: Pubkey,
+    #[max_len(64)]
+    pub job_id: String,
+    pub amount: u64,
+    pub deadline: i64,
+    pub is_released: bool,
+    pub is_disputed: bool,
+    pub dispute_reason: Option<DisputeStatement>,
+    pub created_at: i64,
+    pub released_at: Option<i64>,
+    pub disputed_at: Option<i64>,
+    pub bump: u8,
+    pub dispute_tier: DisputeTier,
+    pub tier_deadline: i64,
+    pub hirer_bond: u64,
+    pub freelancer_bond: u64,
+    pub ruling_freelancer_amount: Option<u64>,
+    pub refund_requested_at: Option<i64>,
+    pub release_requested_at: Option<i64>,
+    pub release_proposed_at: Option<i64>,
+    pub frozen_until: Option<i64>,
+    pub first_frozen_at: Option<i64>,
+    pub clawback_window: i64,
+    pub held_until: Option<i64>,
+    pub event_seq: u64,
+    pub nonce: u64,
+    pub opened_by: Pubkey,
+    pub hirer_seq: u64,
+    pub fee_bps: u16,
+    pub affiliate: Option<Pubkey>,
+    pub grant: Option<GrantTerms>,
+    pub route_fee_to_charity: bool,
+    pub charity_donated: u64,
+    pub retainer: Option<RetainerTerms>,
+    pub uninvoiced: UninvoicedPayouts,
+    pub invoice_count: u32,
+    pub created_price: Option<PriceSnapshot>,
+    pub settled_price: Option<PriceSnapshot>,
+    pub stake_amount: u64,
+    pub hirer_stake: u64,
+    pub freelancer_stake: u64,
+    pub pending_resolution: Option<ResolutionApproval>,
+    pub is_large: bool,
+    pub disputed_by: Option<EscrowRole>,
+    /// Whether enough has been funded for work to start
+    pub is_funded: bool,
+    /// Set while the escrow awaits fund_escrow; it may be closed after this time
+    pub fund_by: Option<i64>,
+    pub funded_amount: u64,
+    pub funding_threshold_bps: u16,
+    /// Pays the escrow in and receives its refunds; the hirer unless a sponsor was named
+    pub funder: Pubkey,
+    /// Funded through contribute, with one Contribution receipt per wallet
+    pub is_crowdfunded: bool,
+    pub contributor_count: u32,
+    /// Payment schedule splitting the amount, empty unless set_milestones ran. The escrow's
+    /// `amount` then tracks what its unsettled milestones still hold.
+    #[max_len(8)]
+    pub milestones: Vec<Milestone>,
+    pub pending_change: Option<ChangeOrder>,
+    /// Submissions the hirer sent back for revision, kept as context for disputes
+    pub rejection_count: u16,
+    pub last_rejection_hash: Option<[u8; 32]>,
+    pub last_rejected_at: Option<i64>,
+    pub sla: Option<SlaTerms>,
+    pub first_submitted_at: Option<i64>,
+    /// Revisions submitted later than the SLA response time after a rejection
+    pub slow_responses: u16,
+    /// Counts review windows and lateness in business days when set
+    pub calendar: Option<BusinessCalendar>,
+    /// Token account the freelancer's payouts go to in place of the accounts they own
+    pub payout_destination: Option<Pubkey>,
+    /// Fee split shares below this go to the treasury, from the mint's config at creation
+    pub dust_threshold: u64,
+    /// Paid the rent of the escrow and its vault, and gets it back when they close
+    pub rent_payer: Pubkey,
+    /// Token account holding the escrowed funds, the `[b"vault", escrow]` PDA. Unset on
+    /// escrows opened with their associated token account as the vault until migrate_vault.
+    pub vault: Pubkey,
+    /// Hash of the job id, seeding the escrow address together with the hirer that opened it
+    pub job_id_hash: [u8; 32],
+    pub category: Option<EscrowCategory>,
+    #[max_len(48)]
+    pub title: String,
+    /// Index of the taxonomy category the escrow was opened in
+    pub taxonomy_category: Option<u16>,
+    /// Indices of the taxonomy skills the job needs
+    #[max_len(4)]
+    pub skills: Vec<u16>,
+    /// Neutral mediator attached to the current dispute's mediation stage
+    pub mediator: Option<Pubkey>,
+    /// Latest non-binding recommendation of the mediator
+    pub mediation: Option<MediationRecommendation>,
+    /// Category the current dispute was opened under, alongside its statement
+    pub dispute_reason_code: Option<DisputeReasonCode>,
+    /// Latest state transitions, readable after RPCs have pruned the transactions
+    pub history: EscrowHistory,
+    /// Slot of the latest state transition
+    pub last_action_slot: u64,
+    /// Transitions made in `last_action_slot`, one bit per `EscrowAction`
+    pub slot_actions: u32,
+    /// Mint of the vault, passed to every token transfer so it is checked against both sides
+    pub mint: Pubkey,
+    /// Token account refunds and the funder's share of rulings go to in place of the accounts
+    /// the funder owns
+    pub refund_destination: Option<Pubkey>,
+}
+
+impl Escrow {
+    /// Add `action` to the history and to the transitions of the current slot
+    pub fn record_action(&mut self, action: EscrowAction, actor: Pubkey) -> Result<()> {
+        self.history.record(action, actor)?;
+        let slot = Clock::get()?.slot;
+        if slot != self.last_action_slot {
+            self.last_action_slot = slot;
+            self.slot_actions = 0;
+        }
+        self.slot_actions |= action.bit();
+        Ok(())
+    }
+
+    /// Reject `action` if the escrow already went through a transition in this slot that it
+    /// may not directly follow, such as a ruling on a dispute opened moments ago
+    pub fn guard_same_slot(&self, action: EscrowAction) -> Result<()> {
+        if Clock::get()?.slot == self.last_action_slot {
+            require!(
+                self.slot_actions & action.blocked_after() == 0,
+                EscrowError::SameSlotAction
+            );
+        }
+        Ok(())
+    }
+
+    /// Platform fee withheld from a payout of `amount` to the freelancer
+    pub fn platform_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    /// Share of the amount funded so far, in basis points
+    pub fn funded_bps(&self) -> u16 {
+        if self.amount == 0 {
+            return BPS_DENOMINATOR;
+        }
+        (self.funded_amount as u128 * BPS_DENOMINATOR as u128 / self.amount as u128) as u16
+    }
+
+    pub fn is_fully_funded(&self) -> bool {
+        self.funded_amount >= self.amount
+    }
+
+    /// Check a funding tranche of `amount` is still accepted at `now`
+    pub fn check_tranche(&self, amount: u64, now: i64) -> Result<()> {
+        require!(!self.is_fully_funded(), EscrowError::EscrowAlreadyFunded);
+        let fund_by = self.fund_by.ok_or(EscrowError::EscrowAlreadyFunded)?;
+        require!(now <= fund_by, EscrowError::UnfundedEscrowExpired);
+        require!(
+            amount > 0 && amount <= self.amount - self.funded_amount,
+            EscrowError::InvalidFundingTranche
+        );
+        Ok(())
+    }
+
+    /// Remaining accounts refund_funders expects after any the instruction itself takes
+    pub fn refund_account_count(&self) -> usize {
+        if self.is_crowdfunded {
+            2 * self.contributor_count as usize
+        } else {
+            0
+        }
+    }
+
+    /// Count a received tranche, starting work once the threshold is met
+    pub fn record_tranche(&mut self, amount: u64) {
+        self.funded_amount += amount;
+        self.is_funded = self.funded_bps() >= self.funding_threshold_bps;
+        if self.is_fully_funded() {
+            self.fund_by = None;
+        }
+    }
+
+    /// Breaches of the agreed SLA so far and the penalty they add up to
+    pub fn sla_breakdown(&self) -> SlaBreakdown {
+        let Some(sla) = self.sla else {
+            return SlaBreakdown::default();
+        };
+        let late_by = self
+            .first_submitted_at
+            .map_or(0, |submitted_at| self.elapsed(self.deadline, submitted_at));
+        let delivery_breaches = if late_by > 0 {
+            ((late_by + sla.late_period - 1) / sla.late_period).min(u16::MAX as i64) as u16
+        } else {
+            0
+        };
+        let breaches = delivery_breaches as u32 + self.slow_responses as u32;
+        let penalty_bps = (breaches * sla.breach_bps as u32).min(sla.max_penalty_bps as u32);
+        SlaBreakdown {
+            delivery_breaches,
+            response_breaches: self.slow_responses,
+            penalty: (self.amount as u128 * penalty_bps as u128 / BPS_DENOMINATOR as u128) as u64,
+        }
+    }
+
+    /// Time from `from` to `to` as this escrow counts it: business time only when it follows
+    /// a business calendar. Negative when `to` is earlier.
+    pub fn elapsed(&self, from: i64, to: i64) -> i64 {
+        match &self.calendar {
+            Some(calendar) if to > from => calendar.business_time(from, to),
+            Some(calendar) => -calendar.business_time(to, from),
+            None => to - from,
+        }
+    }
+
+    /// When `duration` of this escrow's time has passed after `start`
+    pub fn after(&self, start: i64, duration: i64) -> Option<i64> {
+        match &self.calendar {
+            Some(calendar) => calendar.add_business_time(start, duration),
+            None => start.checked_add(duration),
+        }
+    }
+
+    /// Whether payouts to the freelancer may go to `account`: the chosen payout destination
+    /// when one is set, otherwise any token account the freelancer owns
+    pub fn pays_to(&self, account: &Account<TokenAccount>) -> bool {
+        match self.payout_destination {
+            Some(destination) => account.key() == destination,
+            None => account.owner == self.freelancer,
+        }
+    }
+
+    /// Whether refunds to the funder may go to `account`: the refund destination the hirer
+    /// chose when one is set, otherwise any token account the funder owns
+    pub fn refunds_to(&self, account: &Account<TokenAccount>) -> bool {
+        match self.refund_destination {
+            Some(destination) => account.key() == destination,
+            None => account.owner == self.funder,
+        }
+    }
+
+    /// Which party `key` is on this escrow, if either
+    pub fn role_of(&self, key: &Pubkey) -> Option<EscrowRole> {
+        if *key == self.hirer {
+            Some(EscrowRole::Hirer)
+        } else if *key == self.freelancer {
+            Some(EscrowRole::Freelancer)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a moderation freeze is in effect at `now`
+    pub fn is_frozen(&self, now: i64) -> bool {
+        self.frozen_until.is_some_and(|until| now < until)
+    }
+
+    /// Milestone `index`, as long as it may still be released or disputed
+    pub fn open_milestone(&self, index: u8) -> Result<&Milestone> {
+        let milestone = self
+            .milestones
+            .get(index as usize)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        require!(
+            matches!(
+                milestone.status,
+                MilestoneStatus::Pending | MilestoneStatus::Submitted
+            ),
+            EscrowError::MilestoneNotOpen
+        );
+        Ok(milestone)
+    }
+
+    /// Late penalty withheld from `milestone`: its `late_penalty_bps` of its amount when it
+    /// was submitted after its own deadline, in business time under a business calendar
+    pub fn milestone_penalty(&self, milestone: &Milestone) -> u64 {
+        match milestone.submitted_at {
+            Some(submitted_at) if self.elapsed(milestone.deadline, submitted_at) > 0 => {
+                (milestone.amount as u128 * milestone.late_penalty_bps as u128
+                    / BPS_DENOMINATOR as u128) as u64
+            }
+            _ => 0,
+        }
+    }
+
+    pub fn has_disputed_milestone(&self) -> bool {
+        self.milestones
+            .iter()
+            .any(|milestone| milestone.status == MilestoneStatus::Disputed)
+    }
+
+    /// Settle milestone `index` at `now`, taking its amount off the escrow. Returns whether
+    /// that settled the escrow, with no milestone left open.
+    pub fn settle_milestone(&mut self, index: u8, now: i64) -> bool {
+        let milestone = &mut self.milestones[index as usize];
+        milestone.status = MilestoneStatus::Settled;
+        self.amount -= milestone.amount;
+        self.funded_amount -= milestone.amount;
+        let settled = self
+            .milestones
+            .iter()
+            .all(|milestone| milestone.status == MilestoneStatus::Settled);
+        if settled {
+            self.is_released = true;
+            self.released_at = Some(now);
+        }
+        settled
+    }
+
+    /// Divide what each party put at stake by the dispute ruling: the party that received the
+    /// smaller share forfeits its part to the other, while an even split or a settlement
+    /// without a ruling returns each party its own. Returns `(to_hirer, to_freelancer)`.
+    pub fn forfeit_to_winner(&self, hirer_held: u64, freelancer_held: u64) -> (u64, u64) {
+        let total = hirer_held + freelancer_held;
+        match self.dispute_loser() {
+            Some(EscrowRole::Hirer) => (0, total),
+            Some(EscrowRole::Freelancer) => (total, 0),
+            None => (hirer_held, freelancer_held),
+        }
+    }
+
+    /// The party the dispute ruling gave the smaller share to, if the split was uneven
+    pub fn dispute_loser(&self) -> Option<EscrowRole> {
+        self.dispute_outcome().loser()
+    }
+
+    fn dispute_outcome(&self) -> DisputeOutcome {
+        DisputeOutcome {
+            disputed_by: self.disputed_by,
+            amount: self.amount,
+            freelancer_amount: self.ruling_freelancer_amount,
+        }
+    }
+
+    /// Burn `nonce`, which must be the current one, so anything that referenced it goes stale
+    pub fn consume_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(nonce == self.nonce, EscrowError::StaleNonce);
+        self.nonce = self.nonce.checked_add(1).ok_or(EscrowError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub const SIZE: usize = 8 + Self::INIT_SPACE;
+
+    // The `max_len` attributes of the fields repeat these limits, as they only take literals
+
+    /// Longest job id, in bytes, an escrow may be opened with
+    pub const MAX_JOB_ID_LEN: usize = 64;
+
+    /// Longest title, in bytes, an escrow may carry
+    pub const MAX_TITLE_LEN: usize = 48;
+
+    /// Most milestones an escrow may be split into
+    pub const MAX_MILESTONES: usize = 8;
+
+    /// Longest reason, in bytes, given for a refund request or a clawback
+    pub const MAX_REASON_LEN: usize = 64;
+
+    /// Most taxonomy skills an escrow may be tagged with
+    pub const MAX_SKILLS: usize = 4;
+}
+
+/// Vesting schedule of a grant escrow. The escrow's `amount` tracks what is still in the vault,
+/// while `total` is the amount the schedule vests.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct GrantTerms {
+    pub total: u64,
+    pub claimed: u64,
+    pub vesting_start: i64,
+    pub vesting_end: i64,
+    pub revocable_until: i64,
+}
+
+impl GrantTerms {
+    /// Amount vested by `now`, linear between the start and end of the schedule
+    pub fn vested(&self, now: i64) -> u64 {
+        if now >= self.vesting_end {
+            return self.total;
+        }
+        if now <= self.vesting_start {
+            return 0;
+        }
+        let elapsed = (now - self.vesting_start) as u128;
+        let duration = (self.vesting_end - self.vesting_start) as u128;
+        (self.total as u128 * elapsed / duration) as u64
+    }
+}
+
+/// Billing schedule of a retainer escrow. The escrow's `amount` holds the current period's
+/// funding; `ends_at` is set once either party gives notice.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RetainerTerms {
+    pub period_amount: u64,
+    pub period_length: i64,
+    pub period_end: i64,
+    pub notice_period: i64,
+    pub auto_renew: bool,
+    pub ends_at: Option<i64>,
+    pub periods_paid: u32,
+}
+
+/// One payment of an escrow split into milestones
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct Milestone {
+    pub amount: u64,
+    /// Submissions after this are late, and without one the milestone may be refunded
+    pub deadline: i64,
+    /// Share of the amount a late submission forfeits to the funder
+    pub late_penalty_bps: u16,
+    pub submitted_at: Option<i64>,
+    pub status: MilestoneStatus,
+    /// Party that disputed the milestone, whose open dispute its ruling closes
+    pub disputed_by: Option<EscrowRole>,
+    /// First approval of a ruling on the milestone that needs two approvers
+    pub pending_resolution: Option<ResolutionApproval>,
+}
+
+/// A milestone as the parties agree it in set_milestones and amend_milestones
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MilestoneTerms {
+    pub amount: u64,
+    pub deadline: i64,
+    pub late_penalty_bps: u16,
+}
+
+impl MilestoneTerms {
+    /// `base` plus the amounts of `milestones`, or None if one is zero or the sum overflows
+    pub fn total(milestones: &[MilestoneTerms], base: u64) -> Option<u64> {
+        milestones.iter().try_fold(base, |total, terms| {
+            (terms.amount > 0).then_some(())?;
+            total.checked_add(terms.amount)
+        })
+    }
+
+    /// Check every deadline of `milestones` is still ahead at `now` and every penalty is
+    /// within the milestone's amount
+    pub fn check(milestones: &[MilestoneTerms], now: i64) -> Result<()> {
+        require!(
+            milestones
+                .iter()
+                .all(|terms| terms.late_penalty_bps <= BPS_DENOMINATOR),
+            EscrowError::InvalidMilestones
+        );
+        require!(
+            milestones.iter().all(|terms| terms.deadline > now),
+            EscrowError::InvalidDeadline
+        );
+        Ok(())
+    }
+
+    pub fn milestone(&self) -> Milestone {
+        Milestone {
+            amount: self.amount,
+            deadline: self.deadline,
+            late_penalty_bps: self.late_penalty_bps,
+            submitted_at: None,
+            status: MilestoneStatus::Pending,
+            disputed_by: None,
+            pending_resolution: None,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MilestoneStatus {
+    Pending,
+    /// Delivered and waiting for the hirer's release
+    Submitted,
+    /// Waiting for resolve_milestone_dispute; the escrow's other milestones are unaffected
+    Disputed,
+    /// Paid out to the freelancer, or split between the parties by a ruling
+    Settled,
+}
+
+/// USD price of a mint as `price * 10^expo`, published by the configured price oracle
+#[account]
+pub struct PriceFeed {
+    pub mint: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub published_at: i64,
+    pub bump: u8,
+}
+
+impl PriceFeed {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // mint
+        8 + // price
+        4 + // expo
+        8 + // published_at
+        1; // bump
+
+    /// The current reading, which must have been published at most `max_age` seconds before
+    /// `now`
+    pub fn fresh_snapshot(&self, max_age: i64, now: i64) -> Result<PriceSnapshot> {
+        require!(
+            now - self.published_at <= max_age,
+            EscrowError::StalePrice
+        );
+        Ok(PriceSnapshot {
+            price: self.price,
+            expo: self.expo,
+            published_at: self.published_at,
+        })
+    }
+}
+
+/// A price feed reading kept on an escrow, so its fiat value can be verified later
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub struct PriceSnapshot {
+    pub price: i64,
+    pub expo: i32,
+    pub published_at: i64,
+}
+
+/// Payouts made to the freelancer since their last invoice
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct UninvoicedPayouts {
+    pub gross: u64,
+    pub fee: u64,
+    pub first_paid_at: i64,
+    pub last_paid_at: i64,
+}
+
+impl UninvoicedPayouts {
+    pub fn record(&mut self, gross: u64, fee: u64, now: i64) -> Result<()> {
+        if self.gross == 0 {
+            self.first_paid_at = now;
+        }
+        self.gross = self
+            .gross
+            .checked_add(gross)
+            .ok_or(EscrowError::MathOverflow)?;
+        self.fee = self.fee.checked_add(fee).ok_or(EscrowError::MathOverflow)?;
+        self.last_paid_at = now;
+        Ok(())
+    }
+}
+
+/// A verifiable invoice for one or more payouts of an escrow, numbered per escrow
+#[account]
+pub struct Invoice {
+    pub escrow: Pubkey,
+    pub invoice_no: u32,
+    pub issuer: Pubkey,
+    pub billed_to: Pubkey,
+    pub mint: Pubkey,
+    pub line_items_hash: [u8; 32],
+    pub gross: u64,
+    pub fee: u64,
+    pub net: u64,
+    pub first_paid_at: i64,
+    pub last_paid_at: i64,
+    pub issued_at: i64,
+    pub bump: u8,
+}
+
+impl Invoice {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // escrow
+        4 + // invoice_no
+        32 + // issuer
+        32 + // billed_to
+        32 + // mint
+        32 + // line_items_hash
+        8 + // gross
+        8 + // fee
+        8 + // net
+        8 + // first_paid_at
+        8 + // last_paid_at
+        8 + // issued_at
+        1; // bump
+}
+
+/// Per-hirer escrow counter and directory of escrow pages; `open_page` is the page new
+/// escrows are listed on
+#[account]
+pub struct EscrowIndex {
+    pub owner: Pubkey,
+    pub open_page: u32,
+    pub escrow_count: u64,
+    pub open_escrows: u32,
+    pub settled_escrows: u32,
+    pub referred_by: Option<Pubkey>,
+    pub bump: u8,
+    pub velocity_window_start: i64,
+    pub velocity_window_volume: u64,
+}
+
+impl EscrowIndex {
+    /// Whether the owner has settled enough escrows to be exempt from the open-escrow cap
+    pub fn is_reputed(&self, config: &Config) -> bool {
+        self.settled_escrows >= config.reputation_min_settled
+    }
+
+    /// Add `amount` to the volume locked in the current velocity window, enforcing the limit
+    /// for hirers without a settlement record. Each settled escrow raises the allowance by
+    /// `velocity_limit_step` until the hirer is reputed and the limit lifts entirely.
+    pub fn record_volume(&mut self, config: &Config, amount: u64, now: i64) -> Result<()> {
+        if now
+            >= self
+                .velocity_window_start
+                .saturating_add(config.velocity_window)
+        {
+            self.velocity_window_start = now;
+            self.velocity_window_volume = 0;
+        }
+        self.velocity_window_volume = self
+            .velocity_window_volume
+            .checked_add(amount)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        if config.unreputed_velocity_limit > 0 && !self.is_reputed(config) {
+            let limit = config.unreputed_velocity_limit.saturating_add(
+                config
+                    .velocity_limit_step
+                    .saturating_mul(self.settled_escrows as u64),
+            );
+            require!(
+                self.velocity_window_volume <= limit,
+                EscrowError::VelocityLimitExceeded
+            );
+        }
+        Ok(())
+    }
+
+    pub fn record_settlement(&mut self) {
+        self.open_escrows = self.open_escrows.saturating_sub(1);
+        self.settled_escrows = self.settled_escrows.saturating_add(1);
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // owner
+        4 + // open_page
+        8 + // escrow_count
+        4 + // open_escrows
+        4 + // settled_escrows
+        1 + 32 + // referred_by (Option<Pubkey>)
+        1 + // bump
+        8 + // velocity_window_start
+        8; // velocity_window_volume
+}
+
+/// One fixed-size bucket of a hirer's or a taxonomy category's escrows, in creation order.
+/// Closed escrows leave the page; pages are never reused once full.
+#[account]
+pub struct EscrowPage {
+    pub owner: Pubkey,
+    pub page_no: u32,
+    pub escrows: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl EscrowPage {
+    pub const MAX_ESCROWS: usize = 16;
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // owner
+        4 + // page_no
+        4 + Self::MAX_ESCROWS * 32 + // escrows (vec)
+        1; // bump
+}
+
+/// Escrows listed under a taxonomy category, paged like a hirer's so a board of a
+/// category's escrows can be read straight from chain
+#[account]
+pub struct CategoryIndex {
+    /// The category's `TaxonomyEntry`
+    pub category: Pubkey,
+    pub open_page: u32,
+    pub escrow_count: u64,
+    pub bump: u8,
+}
+
+impl CategoryIndex {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // category
+        4 + // open_page
+        8 + // escrow_count
+        1; // bump
+}
+
+/// Lifetime earnings of a freelancer, for income verification by lending and underwriting
+/// integrations. Held payments count once withdrawn, since a clawback can still reverse them.
+/// `fees_paid` stays at zero until payouts carry a platform fee.
+#[account]
+pub struct FreelancerEarnings {
+    pub owner: Pubkey,
+    pub gross_earned: u64,
+    pub fees_paid: u64,
+    pub payouts: u64,
+    pub mints: Vec<MintEarnings>,
+    pub bump: u8,
+}
+
+impl FreelancerEarnings {
+    pub const MAX_MINTS: usize = 8;
+
+    /// Breakdown entry for a mint, added on first use
+    pub fn mint_entry(&mut self, mint: Pubkey) -> Result<&mut MintEarnings> {
+        let position = match self.mints.iter().position(|entry| entry.mint == mint) {
+            Some(position) => position,
+            None => {
+                require!(
+                    self.mints.len() < Self::MAX_MINTS,
+                    EscrowError::EarningsMintLimitReached
+                );
+                self.mints.push(MintEarnings {
+                    mint,
+                    gross_earned: 0,
+                    fees_paid: 0,
+                });
+                self.mints.len() - 1
+            }
+        };
+        Ok(&mut self.mints[position])
+    }
+
+    /// Record a payout of `net` tokens after `fee` was withheld from it
+    pub fn record_payout(&mut self, mint: Pubkey, net: u64, fee: u64) -> Result<()> {
+        let gross = net.checked_add(fee).ok_or(EscrowError::MathOverflow)?;
+        let entry = self.mint_entry(mint)?;
+        entry.gross_earned = entry
+            .gross_earned
+            .checked_add(gross)
+            .ok_or(EscrowError::MathOverflow)?;
+        entry.fees_paid = entry
+            .fees_paid
+            .checked_add(fee)
+            .ok_or(EscrowError::MathOverflow)?;
+        self.gross_earned = self
+            .gross_earned
+            .checked_add(gross)
+            .ok_or(EscrowError::MathOverflow)?;
+        self.fees_paid = self
+            .fees_paid
+            .checked_add(fee)
+            .ok_or(EscrowError::MathOverflow)?;
+        self.payouts = self
+            .payouts
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // owner
+        8 + // gross_earned
+        8 + // fees_paid
+        8 + // payouts
+        4 + Self::MAX_MINTS * MintEarnings::SIZE + // mints (vec)
+        1; // bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MintEarnings {
+    pub mint: Pubkey,
+    pub gross_earned: u64,
+    pub fees_paid: u64,
+}
+
+impl MintEarnings {
+    pub const SIZE: usize = 32 + 8 + 8;
+}
+
+/// The freelancers with the most volume settled in one mint, highest first
+#[account]
+pub struct Leaderboard {
+    pub mint: Pubkey,
+    pub entries: Vec<LeaderboardEntry>,
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    pub const MAX_ENTRIES: usize = 10;
+
+    /// Place `freelancer` by their new lifetime `volume`, displacing the lowest entry once
+    /// the board is full
+    pub fn rank(&mut self, freelancer: Pubkey, volume: u64) {
+        let entry = LeaderboardEntry { freelancer, volume };
+        match self.entries.iter().position(|e| e.freelancer == freelancer) {
+            Some(position) => self.entries[position] = entry,
+            None if self.entries.len() < Self::MAX_ENTRIES => self.entries.push(entry),
+            None => match self.entries.last_mut() {
+                Some(last) if volume > last.volume => *last = entry,
+                _ => return,
+            },
+        }
+        self.entries.sort_by_key(|e| std::cmp::Reverse(e.volume));
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // mint
+        4 + Self::MAX_ENTRIES * LeaderboardEntry::SIZE + // entries (vec)
+        1; // bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LeaderboardEntry {
+    pub freelancer: Pubkey,
+    /// Gross amount the freelancer has been paid in the mint
+    pub volume: u64,
+}
+
+impl LeaderboardEntry {
+    pub const SIZE: usize = 32 + 8;
+}
+
+/// Activity for one UTC day, so dashboards can chart the platform from chain data alone.
+/// `volume` sums raw token amounts escrowed that day, across all mints.
+#[account]
+pub struct DailyStats {
+    pub day: i64,
+    pub volume: u64,
+    pub escrows_opened: u32,
+    pub disputes_opened: u32,
+    /// Disputes opened, indexed by `DisputeReasonCode`
+    pub disputes_by_reason: [u32; DisputeReasonCode::COUNT],
+    pub bump: u8,
+}
+
+impl DailyStats {
+    pub const SECONDS_PER_DAY: i64 = 86_400;
+
+    /// Day number of `unix_timestamp`, counted from the Unix epoch
+    pub fn day_of(unix_timestamp: i64) -> i64 {
+        unix_timestamp.div_euclid(Self::SECONDS_PER_DAY)
+    }
+
+    pub fn today() -> Result<i64> {
+        Ok(Self::day_of(Clock::get()?.unix_timestamp))
+    }
+
+    /// Stamp the bucket on first write; later writes the same day leave it untouched
+    pub fn open(&mut self, bump: u8) -> Result<()> {
+        self.day = Self::today()?;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        8 + // day
+        8 + // volume
+        4 + // escrows_opened
+        4 + // disputes_opened
+        4 * DisputeReasonCode::COUNT + // disputes_by_reason
+        1; // bump
+}
+
+/// An affiliate that onboards hirers and earns a share of the platform fee on their escrows.
+/// Each mint's share accrues in the affiliate PDA's associated token account until claimed.
+#[account]
+pub struct Affiliate {
+    pub owner: Pubkey,
+    pub referred_hirers: u32,
+    pub total_accrued: u64,
+    pub total_claimed: u64,
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl Affiliate {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // owner
+        4 + // referred_hirers
+        8 + // total_accrued
+        8 + // total_claimed
+        8 + // event_seq
+        1; // bump
+}
+
+/// An application to a job. The bid bond is held as lamports on the account on top of its
+/// rent, and both go back to the applicant when it is closed.
+#[account]
+#[derive(InitSpace)]
+pub struct JobApplication {
+    #[max_len(64)]
+    pub job_id: String,
+    /// Hirer who posted the job; job ids are only unique per hirer
+    pub hirer: Pubkey,
+    pub applicant: Pubkey,
+    pub bond: u64,
+    pub applied_at: i64,
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl JobApplication {
+    pub const SIZE: usize = 8 + Self::INIT_SPACE;
+}
+
+/// A prize funded by a hirer and split across the top entries. `placements` holds each
+/// place's share of the prize in basis points, first place first.
+#[account]
+pub struct Contest {
+    pub contest_id: String,
+    pub hirer: Pubkey,
+    pub judge: Pubkey,
+    pub mint: Pubkey,
+    pub prize: u64,
+    pub placements: Vec<u16>,
+    pub submission_deadline: i64,
+    pub entry_count: u32,
+    pub is_settled: bool,
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl Contest {
+    pub const MAX_PLACEMENTS: usize = 5;
+
+    pub const SIZE: usize = 8 + // discriminator
+        4 + 64 + // contest_id (string)
+        32 + // hirer
+        32 + // judge
+        32 + // mint
+        8 + // prize
+        4 + Self::MAX_PLACEMENTS * 2 + // placements (vec)
+        8 + // submission_deadline
+        4 + // entry_count
+        1 + // is_settled
+        8 + // event_seq
+        1; // bump
+}
+
+/// One recipient's share of a contest prize pool paid by `distribute_pool`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PoolPayout {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// One entrant's submission to a contest, referenced by hash
+#[account]
+pub struct ContestEntry {
+    pub contest: Pubkey,
+    pub entrant: Pubkey,
+    pub submission_hash: [u8; 32],
+    pub submitted_at: i64,
+    pub bump: u8,
+}
+
+impl ContestEntry {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // contest
+        32 + // entrant
+        32 + // submission_hash
+        8 + // submitted_at
+        1; // bump
+}
+
+/// A reward anyone may claim with proof of the work. `open_claims` counts claims that were
+/// neither approved nor rejected yet.
+#[account]
+pub struct Bounty {
+    pub bounty_id: String,
+    pub hirer: Pubkey,
+    pub mint: Pubkey,
+    pub reward: u64,
+    pub claim_deadline: i64,
+    pub open_claims: u32,
+    pub awarded_to: Option<Pubkey>,
+    pub is_settled: bool,
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl Bounty {
+    pub const SIZE: usize = 8 + // discriminator
+        4 + 64 + // bounty_id (string)
+        32 + // hirer
+        32 + // mint
+        8 + // reward
+        8 + // claim_deadline
+        4 + // open_claims
+        1 + 32 + // awarded_to (Option<Pubkey>)
+        1 + // is_settled
+        8 + // event_seq
+        1; // bump
+}
+
+/// One claimant's claim on a bounty, referenced by the hash of its proof
+#[account]
+pub struct BountyClaim {
+    pub bounty: Pubkey,
+    pub claimant: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub submitted_at: i64,
+    pub bump: u8,
+}
+
+impl BountyClaim {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // bounty
+        32 + // claimant
+        32 + // proof_hash
+        8 + // submitted_at
+        1; // bump
+}
+
+/// Dispute track record of one wallet across every escrow it is a party to
+#[account]
+pub struct Reputation {
+    pub owner: Pubkey,
+    pub disputes_lost: u32,
+    /// Disputes this wallet opened that are not resolved yet
+    pub open_disputes: u32,
+    /// The wallet may not open a dispute before this time
+    pub dispute_cooldown_until: i64,
+    pub bump: u8,
+    pub disputes_initiated: u32,
+    /// Rulings that gave this wallet the larger share
+    pub disputes_won: u32,
+    /// Rulings recorded on disputes this wallet was a party to, including even splits
+    pub disputes_resolved: u32,
+    /// Sum of this wallet's share of each ruling, in basis points
+    pub awarded_bps_total: u64,
+}
+
+impl Reputation {
+    /// Stamp the owner on first use; an existing record is left untouched otherwise
+    pub fn open(&mut self, owner: Pubkey, bump: u8) {
+        self.owner = owner;
+        self.bump = bump;
+    }
+
+    /// Count a ruling that awarded this wallet `awarded_bps` of the escrow
+    pub fn record_ruling(&mut self, awarded_bps: u16) -> Result<()> {
+        self.disputes_resolved = self
+            .disputes_resolved
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+        if awarded_bps * 2 > BPS_DENOMINATOR {
+            self.disputes_won = self
+                .disputes_won
+                .checked_add(1)
+                .ok_or(EscrowError::MathOverflow)?;
+        }
+        self.awarded_bps_total = self
+            .awarded_bps_total
+            .checked_add(awarded_bps as u64)
+            .ok_or(EscrowError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// This wallet's average share of the rulings on its disputes, if any were resolved
+    pub fn average_awarded_bps(&self) -> Option<u16> {
+        (self.disputes_resolved > 0)
+            .then(|| (self.awarded_bps_total / self.disputes_resolved as u64) as u16)
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // owner
+        4 + // disputes_lost
+        4 + // open_disputes
+        8 + // dispute_cooldown_until
+        1 + // bump
+        4 + // disputes_initiated
+        4 + // disputes_won
+        4 + // disputes_resolved
+        8; // awarded_bps_total
+}
+
+/// Emergency refunds one admin key has issued in the current epoch, so a compromised key
+/// can only refund up to the configured caps before the epoch rolls over
+#[account]
+pub struct AdminRefundUsage {
+    pub admin: Pubkey,
+    pub epoch: i64,
+    pub refunds: u32,
+    pub value: u64,
+    pub bump: u8,
+}
+
+impl AdminRefundUsage {
+    /// Count a refund of `amount` against the admin's caps, starting a fresh tally when the
+    /// epoch has changed. A cap of zero means no cap.
+    pub fn record(&mut self, config: &Config, admin: Pubkey, bump: u8, amount: u64) -> Result<()> {
+        let epoch = Clock::get()?
+            .unix_timestamp
+            .div_euclid(config.emergency_refund_epoch);
+        if self.admin != admin || self.epoch != epoch {
+            self.admin = admin;
+            self.epoch = epoch;
+            self.refunds = 0;
+            self.value = 0;
+            self.bump = bump;
+        }
+
+        self.refunds = self
+            .refunds
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+        self.value = self
+            .value
+            .checked_add(amount)
+            .ok_or(EscrowError::MathOverflow)?;
+        require!(
+            config.max_emergency_refunds_per_epoch == 0
+                || self.refunds <= config.max_emergency_refunds_per_epoch,
+            EscrowError::EmergencyRefundCountExceeded
+        );
+        require!(
+            config.max_emergency_refund_value_per_epoch == 0
+                || self.value <= config.max_emergency_refund_value_per_epoch,
+            EscrowError::EmergencyRefundValueExceeded
+        );
+        Ok(())
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // admin
+        8 + // epoch
+        4 + // refunds
+        8 + // value
+        1; // bump
+}
+
+/// The most recent privileged actions, newest overwriting oldest once the buffer is full,
+/// so admin and moderator behavior can be audited from a single account
+#[account]
+pub struct AuditLog {
+    /// Slot the next entry is written to once the buffer has wrapped
+    pub head: u16,
+    /// Privileged actions recorded since the platform was initialized
+    pub total: u64,
+    pub entries: Vec<AuditEntry>,
+    pub bump: u8,
+}
+
+impl AuditLog {
+    pub const CAPACITY: usize = 32;
+
+    pub fn record(&mut self, actor: Pubkey, action: AdminAction, target: Pubkey) -> Result<()> {
+        let entry = AuditEntry {
+            actor,
+            action,
+            target,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        if self.entries.len() < Self::CAPACITY {
+            self.entries.push(entry);
+        } else {
+            self.entries[self.head as usize] = entry;
+        }
+        self.head = ((self.head as usize + 1) % Self::CAPACITY) as u16;
+        self.total = self.total.checked_add(1).ok_or(EscrowError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        2 + // head
+        8 + // total
+        4 + Self::CAPACITY * AuditEntry::SIZE + // entries (vec)
+        1; // bump
+}
+
+/// One privileged action; `target` is the escrow acted on, the config or coupon account, or
+/// the incoming admin key for admin handovers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AuditEntry {
+    pub actor: Pubkey,
+    pub action: AdminAction,
+    pub target: Pubkey,
+    pub timestamp: i64,
+}
+
+impl AuditEntry {
+    pub const SIZE: usize = 32 + 1 + 32 + 8;
+}
+
+/// A KYC attestor's statement that `subject` was verified, valid until `expires_at`
+#[account]
+pub struct KycAttestation {
+    pub subject: Pubkey,
+    pub attestor: Pubkey,
+    pub attested_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl KycAttestation {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // subject
+        32 + // attestor
+        8 + // attested_at
+        8 + // expires_at
+        1; // bump
+}
+
+/// One wallet's running contribution to a crowdfunded escrow
+#[account]
+pub struct Contribution {
+    pub escrow: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl Contribution {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // escrow
+        32 + // contributor
+        8 + // amount
+        1; // bump
+}
+
+/// A category or skill of the platform taxonomy at `[b"taxonomy", kind, index]`
+#[account]
+pub struct TaxonomyEntry {
+    pub kind: TaxonomyKind,
+    pub index: u16,
+    pub name: String,
+    /// Platform fee for escrows opened in this category, replacing the config's
+    pub fee_bps: Option<u16>,
+    /// Retired entries stay readable but can no longer be referenced
+    pub active: bool,
+    pub bump: u8,
+}
+
+impl TaxonomyEntry {
+    /// Longest name, in bytes, an entry may carry
+    pub const MAX_NAME_LEN: usize = 32;
+
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // kind
+        2 + // index
+        4 + Self::MAX_NAME_LEN + // name (string)
+        1 + 2 + // fee_bps (Option<u16>)
+        1 + // active
+        1; // bump
+}
+
+/// A split of a disputed escrow at `[b"settlement", escrow]` that pays out once both parties
+/// accept it
+#[account]
+pub struct SettlementProposal {
+    pub escrow: Pubkey,
+    /// Who posted the current terms: the mediator or one of the parties
+    pub proposer: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+    pub hirer_accepted: bool,
+    pub freelancer_accepted: bool,
+    pub proposed_at: i64,
+    /// Whoever posted the first proposal, and receives the rent back
+    pub rent_payer: Pubkey,
+    /// Whether the current terms can still be accepted, cleared when they are withdrawn
+    pub is_open: bool,
+    /// Every offer made on the dispute, oldest first, including the current terms
+    pub history: Vec<SettlementOffer>,
+    pub bump: u8,
+}
+
+impl SettlementProposal {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // escrow
+        32 + // proposer
+        8 + // hirer_amount
+        8 + // freelancer_amount
+        1 + // hirer_accepted
+        1 + // freelancer_accepted
+        8 + // proposed_at
+        32 + // rent_payer
+        1 + // is_open
+        4 + Self::MAX_OFFERS * SettlementOffer::SIZE + // history (vec)
+        1; // bump
+
+    /// Most offers a dispute's negotiation may go through before it has to be ruled on
+    pub const MAX_OFFERS: usize = 8;
+}
+
+/// Left behind when an escrow account closes, so its address, and with it its hirer and job
+/// id, can never be opened again with a fresh history
+#[account]
+pub struct EscrowTombstone {
+    pub escrow: Pubkey,
+    pub hirer: Pubkey,
+    pub job_id_hash: [u8; 32],
+    pub closed_at: i64,
+    pub bump: u8,
+}
+
+impl EscrowTombstone {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // escrow
+        32 + // hirer
+        32 + // job_id_hash
+        8 + // closed_at
+        1; // bump
+}
+
+/// Entry of the job id registry, resolving a job id hash to the escrow opened under it
+#[account]
+pub struct JobRegistration {
+    pub job_id_hash: [u8; 32],
+    pub escrow: Pubkey,
+    pub hirer: Pubkey,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl JobRegistration {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // job_id_hash
+        32 + // escrow
+        32 + // hirer
+        8 + // registered_at
+        1; // bump
+}
+
+/// Per-mint amounts in the mint's base units, so floors can differ between, say, a 6-decimal
+/// stablecoin and a 9-decimal token
+#[account]
+pub struct MintConfig {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// Smallest amount an escrow of this mint may be opened for
+    pub min_escrow_amount: u64,
+    /// Fee split shares below this go to the treasury instead of their destination
+    pub dust_threshold: u64,
+    pub bump: u8,
+}
+
+impl MintConfig {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // mint
+        1 + // decimals
+        8 + // min_escrow_amount
+        8 + // dust_threshold
+        1; // bump
+}
+
+/// A platform fee discount, found by the hash of its code so the code itself stays off-chain.
+/// Redeeming it at `initialize_escrow` takes `discount_bps` off the escrow's fee.
+#[account]
+pub struct Coupon {
+    pub code_hash: [u8; 32],
+    pub discount_bps: u16,
+    pub max_uses: u32,
+    pub remaining_uses: u32,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Coupon {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // code_hash
+        2 + // discount_bps
+        4 + // max_uses
+        4 + // remaining_uses
+        8 + // expires_at
+        1; // bump
+}
+
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub stake_mint: Pubkey,
+    pub jurors_per_panel: u8,
+    pub juror_response_window: i64,
+    pub juror_slash_amount: u64,
+    pub min_juror_stake: u64,
+    pub vote_commit_window: i64,
+    pub vote_reveal_window: i64,
+    pub moderators: Vec<Pubkey>,
+    pub tier_windows: [i64; 4],
+    pub escalation_bonds: [u64; 3],
+    pub review_window: i64,
+    pub two_step_release_threshold: u64,
+    pub release_confirm_delay: i64,
+    pub max_freeze_duration: i64,
+    pub role_transfer_requires_consent: bool,
+    pub max_compressed_escrow_amount: u64,
+    pub unreputed_open_escrow_limit: u32,
+    pub reputation_min_settled: u32,
+    pub platform_fee_bps: u16,
+    pub fee_splits: Vec<FeeSplit>,
+    pub insurance_pool: Pubkey,
+    pub charity: Pubkey,
+    pub charity_share_bps: u16,
+    pub bid_bond_lamports: u64,
+    pub bid_bond_lock_period: i64,
+    pub price_oracle: Pubkey,
+    /// Oldest a price feed reading may be, in seconds, to be snapshotted on an escrow
+    pub max_price_age: i64,
+    pub default_slash_bps: u16,
+    pub emergency_refund_epoch: i64,
+    pub max_emergency_refunds_per_epoch: u32,
+    pub max_emergency_refund_value_per_epoch: u64,
+    pub rate_limits: RateLimits,
+    pub admin_transfer_delay: i64,
+    pub resolution_rate: RateCounter,
+    pub emergency_refund_rate: RateCounter,
+    pub config_update_rate: RateCounter,
+    pub pending_admin: Option<Pubkey>,
+    pub pending_admin_activates_at: i64,
+    pub dual_approval_threshold: u64,
+    pub large_escrow: LargeEscrowTier,
+    pub velocity_window: i64,
+    pub unreputed_velocity_limit: u64,
+    pub velocity_limit_step: u64,
+    pub lost_dispute_cooldown: i64,
+    pub dispute_requires_trigger: bool,
+    pub max_open_disputes: u32,
+    pub unfunded_escrow_ttl: i64,
+    pub max_revisions: u16,
+    pub require_job_registration: bool,
+    pub badge_volume_mint: Pubkey,
+    pub badge_volume_threshold: u64,
+    pub auto_resolve_max_amount: u64,
+    pub auto_resolve_cooling_off: i64,
+    pub auto_resolve_freelancer_bps: u16,
+    pub auto_resolve_fee: u64,
+    pub mediators: Vec<Pubkey>,
+    pub mediation_required: bool,
+    pub moderator_fee_bps: u16,
+    pub juror_fee_bps: u16,
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl Config {
+    pub const MAX_MODERATORS: usize = 8;
+    pub const MAX_MEDIATORS: usize = 8;
+    /// Highest share of a disputed escrow a tier's arbitrators may be paid
+    pub const MAX_ARBITRATION_FEE_BPS: u16 = 1_000;
+    pub const MAX_FEE_SPLITS: usize = 4;
+
+    /// Whether `key` holds the moderation role (moderators and the admin)
+    pub fn is_moderator(&self, key: &Pubkey) -> bool {
+        *key == self.admin || self.moderators.contains(key)
+    }
+
+    /// Count a use of a rate-limited privileged instruction, failing once the current window's
+    /// allowance is spent. Actions without a configured limit are not counted.
+    pub fn check_rate_limit(&mut self, action: AdminAction, now: i64) -> Result<()> {
+        let limits = &self.rate_limits;
+        let (counter, max) = match action {
+            AdminAction::ResolveDispute => (&mut self.resolution_rate, limits.max_resolutions),
+            AdminAction::EmergencyRefund => (
+                &mut self.emergency_refund_rate,
+                limits.max_emergency_refunds,
+            ),
+            AdminAction::UpdateConfig => (&mut self.config_update_rate, limits.max_config_updates),
+            _ => return Ok(()),
+        };
+        if max == 0 {
+            return Ok(());
+        }
+        counter.hit(limits.window, max, now)
+    }
+
+    /// Whether an escrow of `amount` falls in the large escrow tier (a zero threshold disables it)
+    pub fn is_large_escrow(&self, amount: u64) -> bool {
+        self.large_escrow.threshold > 0 && amount >= self.large_escrow.threshold
+    }
+
+    /// How long the hirer has to review delivered work on `escrow` before it auto-releases
+    pub fn review_window_for(&self, escrow: &Escrow) -> i64 {
+        if escrow.is_large {
+            self.large_escrow.review_window
+        } else {
+            self.review_window
+        }
+    }
+
+    /// Whether ruling on an escrow of `amount` needs two distinct approvers (zero disables it)
+    pub fn requires_dual_approval(&self, amount: u64) -> bool {
+        self.dual_approval_threshold > 0 && amount >= self.dual_approval_threshold
+    }
+
+    /// Whether releasing `amount` needs a propose/confirm round trip (a zero threshold disables it)
+    pub fn requires_two_step_release(&self, amount: u64) -> bool {
+        self.two_step_release_threshold > 0 && amount >= self.two_step_release_threshold
+    }
+
+    /// Validate and store the tunable parameters
+    pub fn apply(&mut self, params: ConfigParams) -> Result<()> {
+        require!(
+            params.jurors_per_panel > 0
+                && params.jurors_per_panel as usize <= JurorPanel::MAX_JURORS,
+            EscrowError::InvalidPanelSize
+        );
+        require!(
+            params.juror_response_window > 0,
+            EscrowError::InvalidResponseWindow
+        );
+        require!(
+            params.vote_commit_window > 0 && params.vote_reveal_window > 0,
+            EscrowError::InvalidVoteWindow
+        );
+        require!(
+            params.moderators.len() <= Self::MAX_MODERATORS,
+            EscrowError::TooManyModerators
+        );
+        require!(
+            params.mediators.len() <= Self::MAX_MEDIATORS,
+            EscrowError::TooManyMediators
+        );
+        require!(
+            params.moderator_fee_bps <= Self::MAX_ARBITRATION_FEE_BPS
+                && params.juror_fee_bps <= Self::MAX_ARBITRATION_FEE_BPS,
+            EscrowError::InvalidArbitrationFee
+        );
+        // Each rung of the ladder must allow at least as long as the one below it
+        require!(
+            params.tier_windows[0] > 0 && params.tier_windows.windows(2).all(|w| w[0] <= w[1]),
+            EscrowError::InvalidTierConfig
+        );
+        require!(params.review_window > 0, EscrowError::InvalidReviewWindow);
+        require!(
+            params.release_confirm_delay >= 0,
+            EscrowError::InvalidReleaseDelay
+        );
+        require!(
+            params.max_freeze_duration >= 0,
+            EscrowError::InvalidFreezeDuration
+        );
+        require!(
+            params.platform_fee_bps <= BPS_DENOMINATOR
+                && params.charity_share_bps <= BPS_DENOMINATOR,
+            EscrowError::InvalidFeeConfig
+        );
+        // Splits divide the whole fee; with none configured it all goes to the treasury
+        let split_bps: u32 = params.fee_splits.iter().map(|split| split.bps as u32).sum();
+        require!(
+            params.fee_splits.len() <= Self::MAX_FEE_SPLITS
+                && (params.fee_splits.is_empty() || split_bps == BPS_DENOMINATOR as u32),
+            EscrowError::InvalidFeeSplits
+        );
+        require!(
+            params.bid_bond_lock_period >= 0,
+            EscrowError::InvalidBidBondConfig
+        );
+        require!(params.max_price_age > 0, EscrowError::InvalidMaxPriceAge);
+        require!(
+            params.default_slash_bps <= BPS_DENOMINATOR,
+            EscrowError::InvalidSlashConfig
+        );
+        require!(
+            params.emergency_refund_epoch > 0,
+            EscrowError::InvalidEmergencyRefundEpoch
+        );
+        require!(
+            params.rate_limits.window > 0,
+            EscrowError::InvalidRateLimits
+        );
+        require!(
+            params.auto_resolve_cooling_off >= 0
+                && params.auto_resolve_freelancer_bps <= BPS_DENOMINATOR,
+            EscrowError::InvalidAutoResolveConfig
+        );
+        require!(
+            params.admin_transfer_delay >= 0,
+            EscrowError::InvalidAdminTransferDelay
+        );
+        require!(
+            params.velocity_window > 0,
+            EscrowError::InvalidVelocityWindow
+        );
+        require!(
+            params.lost_dispute_cooldown >= 0,
+            EscrowError::InvalidDisputeCooldown
+        );
+        require!(
+            params.unfunded_escrow_ttl > 0,
+            EscrowError::InvalidUnfundedEscrowTtl
+        );
+        require!(
+            params.large_escrow.threshold == 0
+                || params.large_escrow.review_window >= params.review_window,
+            EscrowError::InvalidLargeEscrowTier
+        );
+
+        self.treasury = params.treasury;
+        self.jurors_per_panel = params.jurors_per_panel;
+        self.juror_response_window = params.juror_response_window;
+        self.juror_slash_amount = params.juror_slash_amount;
+        self.min_juror_stake = params.min_juror_stake;
+        self.vote_commit_window = params.vote_commit_window;
+        self.vote_reveal_window = params.vote_reveal_window;
+        self.moderators = params.moderators;
+        self.tier_windows = params.tier_windows;
+        self.escalation_bonds = params.escalation_bonds;
+        self.review_window = params.review_window;
+        self.two_step_release_threshold = params.two_step_release_threshold;
+        self.release_confirm_delay = params.release_confirm_delay;
+        self.max_freeze_duration = params.max_freeze_duration;
+        self.role_transfer_requires_consent = params.role_transfer_requires_consent;
+        self.max_compressed_escrow_amount = params.max_compressed_escrow_amount;
+        self.unreputed_open_escrow_limit = params.unreputed_open_escrow_limit;
+        self.reputation_min_settled = params.reputation_min_settled;
+        self.platform_fee_bps = params.platform_fee_bps;
+        self.fee_splits = params.fee_splits;
+        self.insurance_pool = params.insurance_pool;
+        self.charity = params.charity;
+        self.charity_share_bps = params.charity_share_bps;
+        self.bid_bond_lamports = params.bid_bond_lamports;
+        self.bid_bond_lock_period = params.bid_bond_lock_period;
+        self.price_oracle = params.price_oracle;
+        self.max_price_age = params.max_price_age;
+        self.default_slash_bps = params.default_slash_bps;
+        self.emergency_refund_epoch = params.emergency_refund_epoch;
+        self.max_emergency_refunds_per_epoch = params.max_emergency_refunds_per_epoch;
+        self.max_emergency_refund_value_per_epoch = params.max_emergency_refund_value_per_epoch;
+        self.rate_limits = params.rate_limits;
+        self.admin_transfer_delay = params.admin_transfer_delay;
+        self.dual_approval_threshold = params.dual_approval_threshold;
+        self.large_escrow = params.large_escrow;
+        self.velocity_window = params.velocity_window;
+        self.unreputed_velocity_limit = params.unreputed_velocity_limit;
+        self.velocity_limit_step = params.velocity_limit_step;
+        self.lost_dispute_cooldown = params.lost_dispute_cooldown;
+        self.dispute_requires_trigger = params.dispute_requires_trigger;
+        self.max_open_disputes = params.max_open_disputes;
+        self.unfunded_escrow_ttl = params.unfunded_escrow_ttl;
+        self.max_revisions = params.max_revisions;
+        self.require_job_registration = params.require_job_registration;
+        self.badge_volume_mint = params.badge_volume_mint;
+        self.badge_volume_threshold = params.badge_volume_threshold;
+        self.auto_resolve_max_amount = params.auto_resolve_max_amount;
+        self.auto_resolve_cooling_off = params.auto_resolve_cooling_off;
+        self.auto_resolve_freelancer_bps = params.auto_resolve_freelancer_bps;
+        self.auto_resolve_fee = params.auto_resolve_fee;
+        self.mediators = params.mediators;
+        self.mediation_required = params.mediation_required;
+        self.moderator_fee_bps = params.moderator_fee_bps;
+        self.juror_fee_bps = params.juror_fee_bps;
+
+        Ok(())
+    }
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // admin
+        32 + // treasury
+        32 + // stake_mint
+        1 + // jurors_per_panel
+        8 + // juror_response_window
+        8 + // juror_slash_amount
+        8 + // min_juror_stake
+        8 + // vote_commit_window
+        8 + // vote_reveal_window
+        4 + Self::MAX_MODERATORS * 32 + // moderators (vec)
+        4 * 8 + // tier_windows
+        3 * 8 + // escalation_bonds
+        8 + // review_window
+        8 + // two_step_release_threshold
+        8 + // release_confirm_delay
+        8 + // max_freeze_duration
+        1 + // role_transfer_requires_consent
+        8 + // max_compressed_escrow_amount
+        4 + // unreputed_open_escrow_limit
+        4 + // reputation_min_settled
+        2 + // platform_fee_bps
+        4 + Self::MAX_FEE_SPLITS * FeeSplit::SIZE + // fee_splits (vec)
+        32 + // insurance_pool
+        32 + // charity
+        2 + // charity_share_bps
+        8 + // bid_bond_lamports
+        8 + // bid_bond_lock_period
+        32 + // price_oracle
+        8 + // max_price_age
+        2 + // default_slash_bps
+        8 + // emergency_refund_epoch
+        4 + // max_emergency_refunds_per_epoch
+        8 + // max_emergency_refund_value_per_epoch
+        RateLimits::SIZE + // rate_limits
+        8 + // admin_transfer_delay
+        3 * RateCounter::SIZE + // resolution/emergency_refund/config_update rates
+        1 + 32 + // pending_admin (Option<Pubkey>)
+        8 + // pending_admin_activates_at
+        8 + // dual_approval_threshold
+        LargeEscrowTier::SIZE + // large_escrow
+        8 + // velocity_window
+        8 + // unreputed_velocity_limit
+        8 + // velocity_limit_step
+        8 + // lost_dispute_cooldown
+        1 + // dispute_requires_trigger
+        4 + // max_open_disputes
+        8 + // unfunded_escrow_ttl
+        2 + // max_revisions
+        1 + // require_job_registration
+        32 + // badge_volume_mint
+        8 + // badge_volume_threshold
+        8 + // auto_resolve_max_amount
+        8 + // auto_resolve_cooling_off
+        2 + // auto_resolve_freelancer_bps
+        8 + // auto_resolve_fee
+        4 + Self::MAX_MEDIATORS * 32 + // mediators (vec)
+        1 + // mediation_required
+        2 + // moderator_fee_bps
+        2 + // juror_fee_bps
+        8 + // event_seq
+        1; // bump
+}
+
+/// Where one share of the platform fee goes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeeDestination {
+    Treasury,
+    /// The affiliate that referred the hirer; the treasury keeps it when there is none
+    Referrer,
+    InsurancePool,
+    Burn,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeSplit {
+    pub destination: FeeDestination,
+    pub bps: u16,
+}
+
+impl FeeSplit {
+    pub const SIZE: usize = 1 + 2;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfigParams {
+    pub treasury: Pubkey,
+    pub jurors_per_panel: u8,
+    pub juror_response_window: i64,
+    pub juror_slash_amount: u64,
+    pub min_juror_stake: u64,
+    pub vote_commit_window: i64,
+    pub vote_reveal_window: i64,
+    pub moderators: Vec<Pubkey>,
+    pub tier_windows: [i64; 4],
+    pub escalation_bonds: [u64; 3],
+    pub review_window: i64,
+    pub two_step_release_threshold: u64,
+    pub release_confirm_delay: i64,
+    pub max_freeze_duration: i64,
+    pub role_transfer_requires_consent: bool,
+    pub max_compressed_escrow_amount: u64,
+    pub unreputed_open_escrow_limit: u32,
+    pub reputation_min_settled: u32,
+    pub platform_fee_bps: u16,
+    pub fee_splits: Vec<FeeSplit>,
+    pub insurance_pool: Pubkey,
+    pub charity: Pubkey,
+    pub charity_share_bps: u16,
+    pub bid_bond_lamports: u64,
+    pub bid_bond_lock_period: i64,
+    pub price_oracle: Pubkey,
+    /// Oldest a price feed reading may be, in seconds, to be snapshotted on an escrow
+    pub max_price_age: i64,
+    pub default_slash_bps: u16,
+    pub emergency_refund_epoch: i64,
+    pub max_emergency_refunds_per_epoch: u32,
+    pub max_emergency_refund_value_per_epoch: u64,
+    pub rate_limits: RateLimits,
+    pub admin_transfer_delay: i64,
+    pub dual_approval_threshold: u64,
+    pub large_escrow: LargeEscrowTier,
+    pub velocity_window: i64,
+    pub unreputed_velocity_limit: u64,
+    pub velocity_limit_step: u64,
+    pub lost_dispute_cooldown: i64,
+    pub dispute_requires_trigger: bool,
+    pub max_open_disputes: u32,
+    pub unfunded_escrow_ttl: i64,
+    pub max_revisions: u16,
+    pub require_job_registration: bool,
+    pub badge_volume_mint: Pubkey,
+    pub badge_volume_threshold: u64,
+    pub auto_resolve_max_amount: u64,
+    pub auto_resolve_cooling_off: i64,
+    pub auto_resolve_freelancer_bps: u16,
+    pub auto_resolve_fee: u64,
+    pub mediators: Vec<Pubkey>,
+    pub mediation_required: bool,
+    pub moderator_fee_bps: u16,
+    pub juror_fee_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PooledJuror {
+    pub juror: Pubkey,
+    pub stake: u64,
+    pub open_assignments: u16,
+    /// Taxonomy category the juror specializes in
+    pub specialty: Option<u16>,
+}
+
+impl PooledJuror {
+    pub const SIZE: usize = 32 + 8 + 2 + 1 + 2;
+}
+
+#[account]
+pub struct JurorPool {
+    pub total_stake: u64,
+    pub jurors: Vec<PooledJuror>,
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl JurorPool {
+    pub const MAX_JURORS: usize = 64;
+
+    pub const SIZE: usize = 8 + // discriminator
+        8 + // total_stake
+        4 + Self::MAX_JURORS * PooledJuror::SIZE + // jurors (vec)
+        8 + // event_seq
+        1; // bump
+}
+
+#[account]
+pub struct JurorPanel {
+    pub escrow: Pubkey,
+    pub jurors: Vec<Pubkey>,
+    pub payer: Pubkey,
+    pub drawn_at: i64,
+    pub respond_by: i64,
+    pub commit_deadline: i64,
+    pub reveal_deadline: i64,
+    pub ruling_bps: Option<u16>,
+    pub bump: u8,
+}
+
+impl JurorPanel {
+    pub const MAX_JURORS: usize = 9;
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // escrow
+        4 + Self::MAX_JURORS * 32 + // jurors (vec)
+        32 + // payer
+        8 + // drawn_at
+        8 + // respond_by
+        8 + // commit_deadline
+        8 + // reveal_deadline
+        1 + 2 + // ruling_bps (Option<u16>)
+        1; // bump
+}
+
+#[account]
+pub struct JurorAssignment {
+    pub panel: Pubkey,
+    pub escrow: Pubkey,
+    pub juror: Pubkey,
+    /// The juror's stake when drawn, which weighs their vote; later top-ups do not count
+    pub stake: u64,
+    pub index: u8,
+    pub respond_by: i64,
+    pub responded: bool,
+    pub commitment: Option<[u8; 32]>,
+    pub revealed_bps: Option<u16>,
+    pub slashed: bool,
+    pub bump: u8,
+}
+
+impl JurorAssignment {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // panel
+        32 + // escrow
+        32 + // juror
+        8 + // stake
+        1 + // index
+        8 + // respond_by
+        1 + // responded
+        1 + 32 + // commitment (Option<[u8; 32]>)
+        1 + 2 + // revealed_bps (Option<u16>)
+        1 + // slashed
+        1; // bump
+
+    /// Whether an accepted juror is let off without voting: the escrow settled before the panel
+    /// ruled and the juror had not committed. A tally does not excuse jurors who never revealed.
+    pub fn is_excused(&self, escrow: &Escrow, panel: &JurorPanel) -> bool {
+        self.responded
+            && self.commitment.is_none()
+            && escrow.is_released
+            && panel.ruling_bps.is_none()
+    }
+}
+
+/// Derive the panel seed from the most recent slot hash, mixed with the escrow key
+fn panel_seed(slot_hashes: &AccountInfo, escrow: &Pubkey) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    // Layout: u64 entry count, then (u64 slot, [u8; 32] hash) entries, newest first
+    require!(data.len() >= 48, EscrowError::InvalidSlotHashes);
+    let recent = &data[16..48];
+    Ok(anchor_lang::solana_program::hash::hashv(&[recent, escrow.as_ref()]).to_bytes())
+}
+
+/// Pick `k` distinct pool indices, weighted by stake, skipping excluded keys. With a
+/// `specialty`, only its specialists are drawn from if there are at least `k` of them.
+fn draw_weighted(
+    jurors: &[PooledJuror],
+    k: usize,
+    seed: &[u8; 32],
+    exclude: &[Pubkey],
+    specialty: Option<u16>,
+) -> Result<Vec<usize>> {
+    let mut eligible: Vec<usize> = (0..jurors.len())
+        .filter(|&i| jurors[i].stake > 0 && !exclude.contains(&jurors[i].juror))
+        .collect();
+    require!(eligible.len() >= k, EscrowError::NotEnoughJurors);
+    if specialty.is_some() {
+        let specialists: Vec<usize> = eligible
+            .iter()
+            .copied()
+            .filter(|&i| jurors[i].specialty == specialty)
+            .collect();
+        if specialists.len() >= k {
+            eligible = specialists;
+        }
+    }
+
+    let mut remaining: u64 = eligible.iter().map(|&i| jurors[i].stake).sum();
+    let mut picks = Vec::with_capacity(k);
+    for round in 0..k {
+        let hash = anchor_lang::solana_program::hash::hashv(&[seed, &[round as u8]]);
+        let mut target = u64::from_le_bytes(hash.to_bytes()[..8].try_into().unwrap()) % remaining;
+        for &i in eligible.iter().filter(|i| !picks.contains(*i)) {
+            if target < jurors[i].stake {
+                picks.push(i);
+                remaining -= jurors[i].stake;
+                break;
+            }
+            target -= jurors[i].stake;
+        }
+    }
+
+    Ok(picks)
+}
+
+/// Basis-point denominator used for rulings and splits
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Commitment a juror submits before revealing `freelancer_bps`
+pub fn vote_commitment(freelancer_bps: u16, salt: &[u8; 32], juror: &Pubkey) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[&freelancer_bps.to_le_bytes(), salt, juror.as_ref()])
+        .to_bytes()
+}
+
+/// Hash of a job id as used in escrow and application seeds, so ids of any length fit
+pub fn job_id_hash(job_id: &str) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hash(job_id.as_bytes()).to_bytes()
+}
+
+/// Stake-weighted median of `(freelancer_bps, stake)` votes; `None` when no stake voted
+fn weighted_median(votes: &mut [(u16, u64)]) -> Option<u16> {
+    let total: u128 = votes.iter().map(|&(_, stake)| stake as u128).sum();
+    if total == 0 {
+        return None;
+    }
+
+    votes.sort_unstable_by_key(|&(bps, _)| bps);
+    let mut cumulative = 0u128;
+    for &(bps, stake) in votes.iter() {
+        cumulative += stake as u128;
+        if cumulative * 2 >= total {
+            return Some(bps);
+        }
+    }
+    None
+}
+
+/// Extra protections for escrows of at least `threshold` tokens: the hirer needs a KYC
+/// attestation from `kyc_attestor`, review runs for `review_window`, and release is two-step
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LargeEscrowTier {
+    pub threshold: u64,
+    pub kyc_attestor: Pubkey,
+    pub review_window: i64,
+}
+
+impl LargeEscrowTier {
+    pub const SIZE: usize = 8 + 32 + 8;
+}
+
+/// Per-window caps on privileged instructions; zero leaves an instruction unlimited
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RateLimits {
+    /// Window length in seconds
+    pub window: i64,
+    pub max_resolutions: u32,
+    pub max_emergency_refunds: u32,
+    pub max_config_updates: u32,
+}
+
+impl RateLimits {
+    pub const SIZE: usize = 8 + 4 + 4 + 4;
+}
+
+/// Uses of one privileged instruction in the window that opened at `window_start`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RateCounter {
+    pub window_start: i64,
+    pub count: u32,
+}
+
+impl RateCounter {
+    pub const SIZE: usize = 8 + 4;
+
+    /// Count one use, opening a fresh window once the last one has run its course
+    pub fn hit(&mut self, window: i64, max: u32, now: i64) -> Result<()> {
+        if now >= self.window_start.saturating_add(window) {
+            self.window_start = now;
+            self.count = 0;
+        }
+        require!(self.count < max, EscrowError::RateLimitExceeded);
+        self.count += 1;
+        Ok(())
+    }
+}
+
+/// The first of two approvals a large dispute ruling needs before it pays out
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct ResolutionApproval {
+    pub approver: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+}
+
+/// A mediator's non-binding suggestion of how to split a disputed escrow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct MediationRecommendation {
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+    /// Hash of the mediator's written reasoning, kept off-chain
+    pub note_hash: [u8; 32],
+    pub recorded_at: i64,
+}
+
+/// One offer of a settlement negotiation, as kept in the proposal's history
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SettlementOffer {
+    pub proposer: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+    pub proposed_at: i64,
+    pub withdrawn: bool,
+}
+
+impl SettlementOffer {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 1;
+}
+
+/// State transitions kept in an escrow's history
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default, InitSpace,
+)]
+pub enum EscrowAction {
+    #[default]
+    Created,
+    Funded,
+    Contributed,
+    ContributionWithdrawn,
+    ReleaseRequested,
+    SubmissionRejected,
+    ReleaseProposed,
+    Released,
+    HeldPaymentWithdrawn,
+    ClawedBack,
+    RefundRequested,
+    Refunded,
+    DisputeOpened,
+    DisputeEscalated,
+    DisputeResolved,
+    Settled,
+    Frozen,
+    Unfrozen,
+    RoleTransferred,
+    ChangeOrderAccepted,
+    GrantClaimed,
+    GrantRevoked,
+    RetainerRenewed,
+    Cancelled,
+    Defaulted,
+}
+
+impl EscrowAction {
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// Transitions that may not precede this one in the same slot
+    pub fn blocked_after(self) -> u32 {
+        match self {
+            Self::Refunded => Self::Funded.bit() | Self::Contributed.bit(),
+            Self::DisputeResolved | Self::Settled => {
+                Self::DisputeOpened.bit() | Self::DisputeEscalated.bit()
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// One state transition of an escrow; permissionless cranks are recorded with the program id
+/// as the actor
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default, InitSpace,
+)]
+pub struct HistoryEntry {
+    pub action: EscrowAction,
+    pub actor: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Ring buffer of an escrow's latest state transitions, the oldest overwritten first
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct EscrowHistory {
+    pub entries: [HistoryEntry; EscrowHistory::LEN],
+    /// Transitions recorded over the escrow's lifetime; the next lands at `total % LEN`
+    pub total: u32,
+}
+
+impl EscrowHistory {
+    pub const LEN: usize = 16;
+
+    pub fn record(&mut self, action: EscrowAction, actor: Pubkey) -> Result<()> {
+        self.entries[self.total as usize % Self::LEN] = HistoryEntry {
+            action,
+            actor,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        self.total = self.total.checked_add(1).ok_or(EscrowError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Entries still held, oldest first
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let held = (self.total as usize).min(Self::LEN);
+        let start = self.total as usize - held;
+        (start..self.total as usize)
+            .map(|seq| self.entries[seq % Self::LEN])
+            .collect()
+    }
+}
+
+/// Privileged instructions recorded in the audit log
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AdminAction {
+    UpdateConfig,
+    EmergencyRefund,
+    ResolveDispute,
+    ClawbackPayment,
+    FreezeEscrow,
+    UnfreezeEscrow,
+    CreateCoupon,
+    ProposeAdmin,
+    CancelAdminProposal,
+    AcceptAdmin,
+    SetMintConfig,
+    SetTaxonomyEntry,
+}
+
+/// How `resolve_dispute` divides the escrow amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DisputeRuling {
+    /// Explicit amounts, which must add up to the escrow amount
+    Split {
+        hirer_amount: u64,
+        freelancer_amount: u64,
+    },
+    Template(ResolutionTemplate),
+}
+
+impl DisputeRuling {
+    /// `(hirer_amount, freelancer_amount)` of an escrow of `amount`
+    pub fn amounts(&self, amount: u64) -> (u64, u64) {
+        match *self {
+            Self::Split {
+                hirer_amount,
+                freelancer_amount,
+            } => (hirer_amount, freelancer_amount),
+            Self::Template(template) => {
+                let freelancer_amount = (amount as u128 * template.freelancer_bps() as u128
+                    / BPS_DENOMINATOR as u128) as u64;
+                (amount - freelancer_amount, freelancer_amount)
+            }
+        }
+    }
+
+    pub fn template(&self) -> Option<ResolutionTemplate> {
+        match *self {
+            Self::Split { .. } => None,
+            Self::Template(template) => Some(template),
+        }
+    }
+}
+
+/// Canonical dispute outcomes, so common rulings need no hand-entered amounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResolutionTemplate {
+    FullRelease,
+    FullRefund,
+    EvenSplit,
+    /// 75% to the freelancer, 25% back to the funder
+    FreelancerFavored,
+    /// 25% to the freelancer, 75% back to the funder
+    HirerFavored,
+}
+
+impl ResolutionTemplate {
+    /// The freelancer's share of the escrow; rounding favors the funder
+    pub fn freelancer_bps(self) -> u16 {
+        match self {
+            Self::FullRelease => BPS_DENOMINATOR,
+            Self::FullRefund => 0,
+            Self::EvenSplit => BPS_DENOMINATOR / 2,
+            Self::FreelancerFavored => 7_500,
+            Self::HirerFavored => 2_500,
+        }
+    }
+}
+
+/// Why a dispute was opened, for routing, statistics and reason-specific policies
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DisputeReasonCode {
+    NonDelivery,
+    Quality,
+    ScopeDisagreement,
+    Late,
+    Unresponsive,
+    Fraud,
+}
+
+impl DisputeReasonCode {
+    pub const COUNT: usize = 6;
+
+    /// Whether the small-dispute policy may settle disputes of this kind; fraud claims
+    /// always go to a human
+    pub fn auto_resolvable(self) -> bool {
+        self != Self::Fraud
+    }
+}
+
+/// Why the admin had to refund an escrow outside its normal flows
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EmergencyRefundReason {
+    /// A dispute no one is left to rule on
+    StalledDispute,
+    /// Fraud found while the escrow was frozen
+    Fraud,
+    /// A party lost control of their keys
+    CompromisedKeys,
+    /// The freelancer walked away from the job after its deadline
+    Abandoned,
+    /// A court or regulator ordered the refund
+    LegalOrder,
+}
+
+/// What a party opens a dispute with. A short reason is kept inline; a full statement lives
+/// off-chain at `uri`, pinned by its hash
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub enum DisputeStatement {
+    Text(#[max_len(64)] String),
+    Linked {
+        hash: [u8; 32],
+        #[max_len(96)]
+        uri: String,
+    },
+}
+
+impl DisputeStatement {
+    /// Longest inline reason, in bytes
+    pub const MAX_TEXT_LEN: usize = 64;
+    /// Longest statement URI, in bytes
+    pub const MAX_URI_LEN: usize = 96;
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::Text(text) => require!(
+                text.len() <= Self::MAX_TEXT_LEN,
+                EscrowError::DisputeReasonTooLong
+            ),
+            Self::Linked { uri, .. } => require!(
+                !uri.is_empty() && uri.len() <= Self::MAX_URI_LEN,
+                EscrowError::InvalidStatementUri
+            ),
+        }
+        Ok(())
+    }
+}
+
+/// Rungs of the dispute resolution ladder, in escalation order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DisputeTier {
+    Mediation,
+    Moderator,
+    Panel,
+    Final,
+}
+
+impl DisputeTier {
+    pub fn next(self) -> Option<Self> {
+        match self {
+            DisputeTier::Mediation => Some(DisputeTier::Moderator),
+            DisputeTier::Moderator => Some(DisputeTier::Panel),
+            DisputeTier::Panel => Some(DisputeTier::Final),
+            DisputeTier::Final => None,
+        }
+    }
+}
+
+/// A proposed change of an escrow's scope, waiting for the other party to accept it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct ChangeOrder {
+    pub proposed_by: EscrowRole,
+    pub amount_delta: i64,
+    pub new_deadline: i64,
+    /// Hash of the off-chain description of the new scope
+    pub scope_hash: [u8; 32],
+    /// Increase the hirer already moved into the vault when proposing
+    pub deposited: u64,
+}
+
+/// Service levels agreed for an escrow, priced as basis points of the payout per breach
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct SlaTerms {
+    /// Longest a revision may take after a rejection
+    pub response_time: i64,
+    /// Each started period of late delivery is one breach
+    pub late_period: i64,
+    pub breach_bps: u16,
+    pub max_penalty_bps: u16,
+}
+
+/// SLA breaches counted at settlement and the penalty withheld for them
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SlaBreakdown {
+    pub delivery_breaches: u16,
+    pub response_breaches: u16,
+    pub penalty: u64,
+}
+
+/// Working days of an escrow: weekdays outside `weekend_mask` (bit 0 is Monday), less holidays
+/// given as day offsets from `first_day`, the UTC day the calendar was agreed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct BusinessCalendar {
+    pub weekend_mask: u8,
+    pub first_day: i64,
+    #[max_len(32)]
+    pub holidays: Vec<u16>,
+}
+
+impl BusinessCalendar {
+    pub const MAX_HOLIDAYS: usize = 32;
+    pub const SATURDAY_AND_SUNDAY: u8 = 0b0110_0000;
+
+    pub fn is_business_day(&self, day: i64) -> bool {
+        // The Unix epoch fell on a Thursday
+        let weekday = (day + 3).rem_euclid(7);
+        let holiday =
+            u16::try_from(day - self.first_day).is_ok_and(|offset| self.holidays.contains(&offset));
+        self.weekend_mask & (1 << weekday) == 0 && !holiday
+    }
+
+    /// Business time between `from` and a later `to`
+    pub fn business_time(&self, from: i64, to: i64) -> i64 {
+        let mut elapsed = 0;
+        let mut t = from;
+        while t < to {
+            let day = DailyStats::day_of(t);
+            let day_end = (day + 1) * DailyStats::SECONDS_PER_DAY;
+            if self.is_business_day(day) {
+                elapsed += day_end.min(to) - t;
+            }
+            t = day_end;
+        }
+        elapsed
+    }
+
+    /// When `duration` of business time has passed after `start`
+    pub fn add_business_time(&self, start: i64, duration: i64) -> Option<i64> {
+        let mut remaining = duration;
+        let mut t = start;
+        loop {
+            let day = DailyStats::day_of(t);
+            let day_end = (day + 1).checked_mul(DailyStats::SECONDS_PER_DAY)?;
+            if self.is_business_day(day) {
+                if remaining <= day_end - t {
+                    return t.checked_add(remaining);
+                }
+                remaining -= day_end - t;
+            }
+            t = day_end;
+        }
+    }
+}
+
+/// Milestones a wallet can claim a badge for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BadgeKind {
+    /// First payout received
+    FirstJob,
+    /// Ten payouts received
+    TenJobs,
+    /// Lifetime gross earnings of at least `badge_volume_threshold` in `badge_volume_mint`.
+    /// Mints have their own decimals and prices, so only the configured one counts.
+    HighVolume,
+    /// Ten payouts received without losing a dispute
+    ZeroDisputes,
+}
+
+impl BadgeKind {
+    pub const MANY_JOBS: u64 = 10;
+
+    /// Whether the owner of `earnings` and `reputation` has reached this milestone
+    pub fn is_earned(
+        self,
+        earnings: &FreelancerEarnings,
+        reputation: &Reputation,
+        config: &Config,
+    ) -> bool {
+        match self {
+            Self::FirstJob => earnings.payouts >= 1,
+            Self::TenJobs => earnings.payouts >= Self::MANY_JOBS,
+            Self::HighVolume => {
+                config.badge_volume_threshold > 0
+                    && earnings.mints.iter().any(|entry| {
+                        entry.mint == config.badge_volume_mint
+                            && entry.gross_earned >= config.badge_volume_threshold
+                    })
+            }
+            Self::ZeroDisputes => {
+                earnings.payouts >= Self::MANY_JOBS && reputation.disputes_lost == 0
+            }
+        }
+    }
+}
+
+/// The two lists of the platform taxonomy
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TaxonomyKind {
+    Category,
+    Skill,
+}
+
+/// Kind of work an escrow pays for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum EscrowCategory {
+    Design,
+    Development,
+    Writing,
+    Marketing,
+    Translation,
+    Video,
+    Data,
+    Other,
+}
+
+/// Party roles on an escrow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum EscrowRole {
+    Hirer,
+    Freelancer,
+}
+
+#[account]
+pub struct SessionKey {
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub permissions: u32,
+    pub max_amount: u64,
+    pub payer: Pubkey,
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl SessionKey {
+    pub const RELEASE_PAYMENT: u32 = 1 << 0;
+    pub const REQUEST_RELEASE: u32 = 1 << 1;
+    pub const CLAIM_AUTO_RELEASE: u32 = 1 << 2;
+    pub const INITIATE_DISPUTE: u32 = 1 << 3;
+    pub const ALL_PERMISSIONS: u32 = Self::RELEASE_PAYMENT
+        | Self::REQUEST_RELEASE
+        | Self::CLAIM_AUTO_RELEASE
+        | Self::INITIATE_DISPUTE;
+
+    /// Longest lifetime a session key may be registered for (7 days)
+    pub const MAX_DURATION: i64 = 7 * 24 * 60 * 60;
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // owner
+        32 + // session_key
+        8 + // expires_at
+        4 + // permissions
+        8 + // max_amount
+        32 + // payer
+        8 + // event_seq
+        1; // bump
+}
+
+/// Resolve the party an instruction acts for: the signer itself, or the owner of the
+/// session key the signer is using, provided the session allows `permission` for `amount`
+fn session_authority(
+    signer: &Signer,
+    session: &Option<Account<SessionKey>>,
+    permission: u32,
+    amount: u64,
+) -> Result<Pubkey> {
+    let Some(session) = session else {
+        return Ok(signer.key());
+    };
+
+    require_keys_eq!(
+        session.session_key,
+        signer.key(),
+        EscrowError::InvalidSessionKey
+    );
+    require!(
+        Clock::get()?.unix_timestamp < session.expires_at,
+        EscrowError::SessionExpired
+    );
+    require!(
+        session.permissions & permission != 0,
+        EscrowError::SessionPermissionDenied
+    );
+    require!(
+        amount <= session.max_amount,
+        EscrowError::SessionAmountExceeded
+    );
+
+    Ok(session.owner)
+}
+
+/// Append-only Merkle tree of micro-escrow leaves sharing one vault per mint.
+/// Only the root and the rightmost frontier are stored; settlements supply a proof
+/// against the current root and replace the leaf with the empty hash.
+#[account]
+pub struct CompressedTree {
+    pub mint: Pubkey,
+    pub root: [u8; 32],
+    pub next_index: u32,
+    pub filled_subtrees: [[u8; 32]; CompressedTree::DEPTH],
+    pub event_seq: u64,
+    pub bump: u8,
+}
+
+impl CompressedTree {
+    pub const DEPTH: usize = 20;
+
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // mint
+        32 + // root
+        4 + // next_index
+        Self::DEPTH * 32 + // filled_subtrees
+        8 + // event_seq
+        1; // bump
+
+    /// Append a leaf at `next_index`, updating the frontier and the root
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<()> {
+        require!(
+            (self.next_index as usize) < 1 << Self::DEPTH,
+            EscrowError::CompressedTreeFull
+        );
+
+        let zeros = merkle_zero_hashes();
+        let mut index = self.next_index;
+        let mut node = leaf;
+        for (level, zero) in zeros.iter().take(Self::DEPTH).enumerate() {
+            if index & 1 == 0 {
+                self.filled_subtrees[level] = node;
+                node = merkle_hash(&node, zero);
+            } else {
+                node = merkle_hash(&self.filled_subtrees[level], &node);
+            }
+            index >>= 1;
+        }
+
+        self.root = node;
+        self.next_index += 1;
+        Ok(())
+    }
+
+    /// Replace the leaf at `index` after proving `old_leaf` against the current root
+    pub fn replace(
+        &mut self,
+        index: u32,
+        old_leaf: [u8; 32],
+        new_leaf: [u8; 32],
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        require!(index < self.next_index, EscrowError::InvalidMerkleProof);
+        require!(proof.len() == Self::DEPTH, EscrowError::InvalidMerkleProof);
+
+        let mut old_node = old_leaf;
+        let mut new_node = new_leaf;
+        for (level, sibling) in proof.iter().enumerate() {
+            // Keep the append frontier in sync when the replaced path runs through it
+            if index >> level == (self.next_index >> level) & !1 {
+                self.filled_subtrees[level] = new_node;
+            }
+            if (index >> level) & 1 == 0 {
+                old_node = merkle_hash(&old_node, sibling);
+                new_node = merkle_hash(&new_node, sibling);
+            } else {
+                old_node = merkle_hash(sibling, &old_node);
+                new_node = merkle_hash(sibling, &new_node);
+            }
+        }
+        require!(old_node == self.root, EscrowError::InvalidMerkleProof);
+
+        self.root = new_node;
+        Ok(())
+    }
+}
+
+/// Terms of a compressed micro-escrow, stored off-chain and proven on settlement
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CompressedEscrowLeaf {
+    pub index: u32,
+    pub hirer: Pubkey,
+    pub freelancer: Pubkey,
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+impl CompressedEscrowLeaf {
+    pub fn hash(&self, tree: &Pubkey) -> [u8; 32] {
+        anchor_lang::solana_program::hash::hashv(&[
+            tree.as_ref(),
+            &self.index.to_le_bytes(),
+            self.hirer.as_ref(),
+            self.freelancer.as_ref(),
+            &self.amount.to_le_bytes(),
+            &self.deadline.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
+fn merkle_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[left, right]).to_bytes()
+}
+
+/// Roots of empty subtrees for every height up to the tree depth
+fn merkle_zero_hashes() -> [[u8; 32]; CompressedTree::DEPTH + 1] {
+    let mut zeros = [[0u8; 32]; CompressedTree::DEPTH + 1];
+    for level in 1..=CompressedTree::DEPTH {
+        zeros[level] = merkle_hash(&zeros[level - 1], &zeros[level - 1]);
+    }
+    zeros
+}
+
+/// Prove a compressed leaf, empty it, and pay its amount to the recipient account
+fn settle_compressed_leaf(
+    ctx: Context<SettleCompressedEscrow>,
+    leaf: &CompressedEscrowLeaf,
+    proof: &[[u8; 32]],
+) -> Result<()> {
+    let tree_info = ctx.accounts.tree.to_account_info();
+    let tree_key = ctx.accounts.tree.key();
+    let tree = &mut ctx.accounts.tree;
+
+    tree.replace(leaf.index, leaf.hash(&tree_key), [0u8; 32], proof)?;
+
+    let tree_seeds = &[
+        b"compressed_tree".as_ref(),
+        tree.mint.as_ref(),
+        &[tree.bump],
+    ];
+    let signer_seeds = &[&tree_seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.tree_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: tree_info,
+        },
+        signer_seeds,
+    );
+    transfer_tokens(cpi_ctx, leaf.amount, ctx.accounts.mint.decimals)?;
+
+    emit!(CompressedEscrowSettled {
+        version: EVENT_SCHEMA_VERSION,
+        event_seq: next_event_seq(&mut tree.event_seq),
+        tree: tree_key,
+        index: leaf.index,
+        recipient: ctx.accounts.recipient_token_account.owner,
+        amount: leaf.amount,
+        root: tree.root,
+    });
+
+    Ok(())
+}
+
+/// Shared setup of `initialize_escrow`, `create_escrow`, `initialize_grant` and
+/// `initialize_retainer`: fill in the escrow and list it for the hirer. The vault is funded in
+/// full here unless `funding_threshold_bps` is given, as `create_escrow` does, which leaves
+/// funding to `fund_escrow`.
+fn open_escrow(
+    ctx: &mut Context<InitializeEscrow>,
+    job_id: String,
+    amount: u64,
+    deadline: i64,
+    clawback_window: i64,
+    funding_threshold_bps: Option<u16>,
+) -> Result<()> {
+    require!(
+        job_id.len() <= Escrow::MAX_JOB_ID_LEN,
+        EscrowError::JobIdTooLong
+    );
+    require!(clawback_window >= 0, EscrowError::InvalidClawbackWindow);
+    require!(
+        ctx.accounts.tombstone.data_is_empty(),
+        EscrowError::EscrowRetired
+    );
+
+    // Mints the admin configured enforce their minimum and dust threshold
+    let mint_config = &ctx.accounts.mint_config;
+    let dust_threshold = if mint_config.owner == &crate::ID {
+        let mint_config = Account::<MintConfig>::try_from(mint_config)?;
+        require!(
+            amount >= mint_config.min_escrow_amount,
+            EscrowError::BelowMintMinimum
+        );
+        mint_config.dust_threshold
+    } else {
+        0
+    };
+
+    let escrow = &mut ctx.accounts.escrow;
     
-    pub token_program: Program<'info, Token>,
+    escrow.hirer = ctx.accounts.hirer.key();
+    escrow.freelancer = ctx.accounts.freelancer.key();
+    escrow.rent_payer = ctx.accounts.rent_payer.key();
+    escrow.vault = ctx.accounts.escrow_token_account.key();
+    escrow.mint = ctx.accounts.mint.key();
+    if let Some(destination) = &ctx.accounts.refund_destination {
+        require!(!destination.is_frozen(), EscrowError::RefundAccountFrozen);
+        escrow.refund_destination = Some(destination.key());
+    }
+    escrow.job_id_hash = job_id_hash(&job_id);
+    escrow.job_id = job_id;
+    escrow.amount = amount;
+    escrow.deadline = deadline;
+    escrow.is_released = false;
+    escrow.is_disputed = false;
+    escrow.dispute_tier = DisputeTier::Mediation;
+    escrow.tier_deadline = 0;
+    escrow.hirer_bond = 0;
+    escrow.freelancer_bond = 0;
+    escrow.ruling_freelancer_amount = None;
+    escrow.refund_requested_at = None;
+    escrow.release_requested_at = None;
+    escrow.release_proposed_at = None;
+    escrow.frozen_until = None;
+    escrow.first_frozen_at = None;
+    escrow.clawback_window = clawback_window;
+    escrow.held_until = None;
+    escrow.event_seq = 0;
+    escrow.nonce = 0;
+    escrow.dust_threshold = dust_threshold;
+    escrow.created_at = Clock::get()?.unix_timestamp;
+    escrow.bump = *ctx.bumps.get("escrow").unwrap();
+
+    // Hirers without a settlement record may only hold a limited number of open escrows
+    let index = &mut ctx.accounts.escrow_index;
+    let config = &ctx.accounts.config;
+    match &mut ctx.accounts.job_registration {
+        Some(registration) => {
+            registration.job_id_hash = escrow.job_id_hash;
+            registration.escrow = escrow.key();
+            registration.hirer = escrow.hirer;
+            registration.registered_at = escrow.created_at;
+            registration.bump = *ctx.bumps.get("job_registration").unwrap();
+        }
+        None => require!(
+            !config.require_job_registration,
+            EscrowError::JobRegistrationRequired
+        ),
+    }
+    if config.unreputed_open_escrow_limit > 0 && !index.is_reputed(config) {
+        require!(
+            index.open_escrows < config.unreputed_open_escrow_limit,
+            EscrowError::OpenEscrowLimitReached
+        );
+    }
+    index.record_volume(config, amount, escrow.created_at)?;
+    escrow.opened_by = escrow.hirer;
+    escrow.fee_bps = config.platform_fee_bps;
+    if let Some(category) = &ctx.accounts.category {
+        escrow.taxonomy_category = Some(category.index);
+        escrow.fee_bps = category.fee_bps.unwrap_or(escrow.fee_bps);
+    }
+
+    // Large escrows need a current KYC attestation and get the stricter release rules
+    escrow.is_large = config.is_large_escrow(amount);
+    if escrow.is_large {
+        require!(
+            matches!(&ctx.accounts.kyc_attestation, Some(kyc) if kyc.expires_at > escrow.created_at),
+            EscrowError::KycAttestationRequired
+        );
+    }
+    if let Some(coupon) = &mut ctx.accounts.coupon {
+        require!(
+            escrow.created_at < coupon.expires_at,
+            EscrowError::CouponExpired
+        );
+        require!(coupon.remaining_uses > 0, EscrowError::CouponExhausted);
+        coupon.remaining_uses -= 1;
+        escrow.fee_bps = escrow.fee_bps.saturating_sub(coupon.discount_bps);
+    }
+    escrow.hirer_seq = index.escrow_count;
+    index.escrow_count = index
+        .escrow_count
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+    index.open_escrows = index
+        .open_escrows
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    // Hirers onboarded by an affiliate must route the affiliate's fee share to its vault
+    escrow.affiliate = index.referred_by;
+    if let Some(referrer) = index.referred_by {
+        let affiliate = ctx
+            .accounts
+            .affiliate
+            .as_ref()
+            .ok_or(EscrowError::AffiliateAccountsMissing)?;
+        require_keys_eq!(
+            affiliate.key(),
+            referrer,
+            EscrowError::AffiliateAccountsMissing
+        );
+        require!(
+            ctx.accounts.affiliate_vault.is_some(),
+            EscrowError::AffiliateAccountsMissing
+        );
+    }
+
+    // List the escrow on the hirer's open page, moving to a fresh page once it fills up
+    let page = &mut ctx.accounts.escrow_page;
+    index.owner = escrow.hirer;
+    index.bump = *ctx.bumps.get("escrow_index").unwrap();
+    page.owner = escrow.hirer;
+    page.page_no = index.open_page;
+    page.bump = *ctx.bumps.get("escrow_page").unwrap();
+    page.escrows.push(escrow.key());
+    if page.escrows.len() == EscrowPage::MAX_ESCROWS {
+        index.open_page = index
+            .open_page
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+    }
+
+    // Escrows opened in a category are listed on its open page as well
+    if let Some(category) = &ctx.accounts.category {
+        let (Some(category_index), Some(category_page)) = (
+            ctx.accounts.category_index.as_mut(),
+            ctx.accounts.category_page.as_mut(),
+        ) else {
+            return err!(EscrowError::CategoryAccountsMissing);
+        };
+        category_index.category = category.key();
+        category_index.bump = *ctx.bumps.get("category_index").unwrap();
+        category_page.owner = category.key();
+        category_page.page_no = category_index.open_page;
+        category_page.bump = *ctx.bumps.get("category_page").unwrap();
+        category_page.escrows.push(escrow.key());
+        category_index.escrow_count = category_index
+            .escrow_count
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?;
+        if category_page.escrows.len() == EscrowPage::MAX_ESCROWS {
+            category_index.open_page = category_index
+                .open_page
+                .checked_add(1)
+                .ok_or(EscrowError::MathOverflow)?;
+        }
+    }
+
+    // Track the escrow's mint up front so payouts never run out of breakdown slots
+    let earnings = &mut ctx.accounts.freelancer_earnings;
+    earnings.owner = escrow.freelancer;
+    earnings.bump = *ctx.bumps.get("freelancer_earnings").unwrap();
+    earnings.mint_entry(ctx.accounts.mint.key())?;
+
+    ctx.accounts
+        .hirer_reputation
+        .open(escrow.hirer, *ctx.bumps.get("hirer_reputation").unwrap());
+    ctx.accounts.freelancer_reputation.open(
+        escrow.freelancer,
+        *ctx.bumps.get("freelancer_reputation").unwrap(),
+    );
+
+    let stats = &mut ctx.accounts.daily_stats;
+    stats.open(*ctx.bumps.get("daily_stats").unwrap())?;
+    stats.escrows_opened = stats
+        .escrows_opened
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+    stats.volume = stats
+        .volume
+        .checked_add(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    escrow.funder = escrow.hirer;
+    escrow.is_funded = funding_threshold_bps.is_none();
+    escrow.funding_threshold_bps = funding_threshold_bps.unwrap_or(BPS_DENOMINATOR);
+    if escrow.is_funded {
+        escrow.funded_amount = amount;
+    } else {
+        escrow.fund_by = Some(
+            escrow
+                .created_at
+                .checked_add(config.unfunded_escrow_ttl)
+                .ok_or(EscrowError::MathOverflow)?,
+        );
+    }
+    // The feed PDA is always passed, so a mint the oracle prices cannot skip its snapshot
+    let price_feed = &ctx.accounts.price_feed;
+    let created_price = if price_feed.owner == &crate::ID {
+        let price_feed = Account::<PriceFeed>::try_from(price_feed)?;
+        let snapshot = price_feed.fresh_snapshot(config.max_price_age, escrow.created_at)?;
+        Some((price_feed.mint, snapshot))
+    } else {
+        None
+    };
+    escrow.created_price = created_price.as_ref().map(|(_, snapshot)| snapshot.clone());
+    escrow.record_action(EscrowAction::Created, ctx.accounts.hirer.key())?;
+
+    // Transfer tokens from hirer to escrow account, unless funding is left to fund_escrow
+    if escrow.is_funded {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.hirer_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.hirer.to_account_info(),
+            },
+        );
+        transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+    }
+
+    emit!(EscrowCreated {
+        version: EVENT_SCHEMA_VERSION,
+        event_seq: next_event_seq(&mut escrow.event_seq),
+        escrow: escrow.key(),
+        hirer: escrow.hirer,
+        freelancer: escrow.freelancer,
+        job_id_hash: escrow.job_id_hash,
+        amount: escrow.amount,
+        deadline: escrow.deadline,
+    });
+
+    if let Some((mint, snapshot)) = created_price {
+        emit!(PriceSnapshotRecorded {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            mint,
+            at_settlement: false,
+            snapshot,
+        });
+    }
+
+    if let Some(coupon) = &ctx.accounts.coupon {
+        emit!(CouponRedeemed {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            coupon: coupon.key(),
+            fee_bps: escrow.fee_bps,
+            remaining_uses: coupon.remaining_uses,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check that the party opening a dispute may: losing a dispute bars a wallet from opening
+/// another until its cooldown ends, each wallet may only have so many disputes open at once,
+/// and optionally there must be delivered work or a missed deadline (`triggered`). Then count
+/// the dispute on the party's reputation and the day's statistics.
+fn open_dispute(
+    config: &Config,
+    reputation: &mut Reputation,
+    stats: &mut DailyStats,
+    stats_bump: u8,
+    reason_code: DisputeReasonCode,
+    triggered: bool,
+    now: i64,
+) -> Result<()> {
+    require!(
+        now >= reputation.dispute_cooldown_until,
+        EscrowError::DisputeCooldownActive
+    );
+    require!(
+        config.max_open_disputes == 0 || reputation.open_disputes < config.max_open_disputes,
+        EscrowError::TooManyOpenDisputes
+    );
+    require!(
+        triggered || !config.dispute_requires_trigger,
+        EscrowError::NoDisputeTrigger
+    );
+    reputation.open_disputes = reputation
+        .open_disputes
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+    reputation.disputes_initiated = reputation
+        .disputes_initiated
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    stats.open(stats_bump)?;
+    stats.disputes_opened = stats
+        .disputes_opened
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+    let by_reason = &mut stats.disputes_by_reason[reason_code as usize];
+    *by_reason = by_reason.checked_add(1).ok_or(EscrowError::MathOverflow)?;
+    Ok(())
+}
+
+/// Who opened a dispute over `amount`, and how much of it the ruling gave the freelancer
+struct DisputeOutcome {
+    disputed_by: Option<EscrowRole>,
+    amount: u64,
+    freelancer_amount: Option<u64>,
+}
+
+impl DisputeOutcome {
+    fn loser(&self) -> Option<EscrowRole> {
+        let amount = self.amount as u128;
+        match self.freelancer_amount.map(u128::from) {
+            Some(freelancer_amount) if freelancer_amount * 2 > amount => Some(EscrowRole::Hirer),
+            Some(freelancer_amount) if freelancer_amount * 2 < amount => {
+                Some(EscrowRole::Freelancer)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Free the open-dispute slot held by the party that opened the dispute, `disputed_by`
+fn close_dispute(
+    disputed_by: Option<EscrowRole>,
+    hirer_reputation: &mut Reputation,
+    freelancer_reputation: &mut Reputation,
+) {
+    let initiator = match disputed_by {
+        Some(EscrowRole::Hirer) => hirer_reputation,
+        Some(EscrowRole::Freelancer) => freelancer_reputation,
+        None => return,
+    };
+    initiator.open_disputes = initiator.open_disputes.saturating_sub(1);
+}
+
+/// Close the dispute and add its ruling to both parties' dispute statistics
+fn record_dispute_split(
+    outcome: &DisputeOutcome,
+    hirer_reputation: &mut Reputation,
+    freelancer_reputation: &mut Reputation,
+) -> Result<()> {
+    close_dispute(outcome.disputed_by, hirer_reputation, freelancer_reputation);
+    let freelancer_bps = match (outcome.freelancer_amount, outcome.amount) {
+        (Some(freelancer_amount), amount) if amount > 0 => {
+            let bps = freelancer_amount as u128 * BPS_DENOMINATOR as u128 / amount as u128;
+            bps.min(BPS_DENOMINATOR as u128) as u16
+        }
+        _ => 0,
+    };
+    freelancer_reputation.record_ruling(freelancer_bps)?;
+    hirer_reputation.record_ruling(BPS_DENOMINATOR - freelancer_bps)
+}
+
+/// Record the dispute split with `record_dispute_split` and start the cooldown of whichever
+/// party lost the ruling
+fn record_dispute_outcome(
+    outcome: &DisputeOutcome,
+    config: &Config,
+    hirer_reputation: &mut Reputation,
+    freelancer_reputation: &mut Reputation,
+) -> Result<()> {
+    record_dispute_split(outcome, hirer_reputation, freelancer_reputation)?;
+    let loser = match outcome.loser() {
+        Some(EscrowRole::Hirer) => hirer_reputation,
+        Some(EscrowRole::Freelancer) => freelancer_reputation,
+        None => return Ok(()),
+    };
+    loser.disputes_lost = loser
+        .disputes_lost
+        .checked_add(1)
+        .ok_or(EscrowError::MathOverflow)?;
+    loser.dispute_cooldown_until = Clock::get()?
+        .unix_timestamp
+        .checked_add(config.lost_dispute_cooldown)
+        .ok_or(EscrowError::MathOverflow)?;
+    Ok(())
+}
+
+/// Return `amount` of the vault to whoever funded the escrow. A crowdfunded escrow splits it
+/// across every contributor by their share of the funding, with rounding dust going to the
+/// last one; `remaining` then holds a `[Contribution, contributor token account]` pair per
+/// contributor.
+#[allow(clippy::too_many_arguments)]
+fn refund_funders<'info>(
+    escrow: &Account<'info, Escrow>,
+    escrow_info: &AccountInfo<'info>,
+    vault: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    funder_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    remaining: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let transfer = |to: &AccountInfo<'info>, amount: u64| {
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.clone(),
+                authority: escrow_info.clone(),
+            },
+            signer_seeds,
+        );
+        transfer_tokens(cpi_ctx, amount, mint.decimals)
+    };
+
+    if !escrow.is_crowdfunded {
+        return transfer(&funder_token_account.to_account_info(), amount);
+    }
+    require!(
+        remaining.len() == escrow.refund_account_count(),
+        EscrowError::InvalidContributionAccount
+    );
+
+    // Check every pair before paying any of them. Shares are taken of the contributions on
+    // record rather than the escrow's funded amount, which its caller may already have moved.
+    let mut contributions: Vec<(Pubkey, u64, &AccountInfo<'info>)> =
+        Vec::with_capacity(escrow.contributor_count as usize);
+    let mut contributed = 0u64;
+    for pair in remaining.chunks(2) {
+        let receipt = Account::<Contribution>::try_from(&pair[0])?;
+        let token_account = Account::<TokenAccount>::try_from(&pair[1])?;
+        require_keys_eq!(
+            receipt.escrow,
+            escrow.key(),
+            EscrowError::InvalidContributionAccount
+        );
+        require!(
+            contributions
+                .iter()
+                .all(|(contributor, _, _)| *contributor != receipt.contributor)
+                && token_account.owner == receipt.contributor
+                && token_account.mint == vault.mint,
+            EscrowError::InvalidContributionAccount
+        );
+        contributed = contributed
+            .checked_add(receipt.amount)
+            .ok_or(EscrowError::MathOverflow)?;
+        contributions.push((receipt.contributor, receipt.amount, &pair[1]));
+    }
+
+    let mut refunded = 0u64;
+    for (index, (_, contribution, token_account)) in contributions.iter().enumerate() {
+        let share = if index + 1 == contributions.len() {
+            amount - refunded
+        } else {
+            (amount as u128 * *contribution as u128 / contributed as u128) as u64
+        };
+        refunded += share;
+        if share > 0 {
+            transfer(token_account, share)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Move a change order's increase from the hirer into the vault; the hirer must be the signer
+fn deposit_change(ctx: &Context<ManageChangeOrder>, amount: u64) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.signer.key(),
+        ctx.accounts.escrow.hirer,
+        EscrowError::UnauthorizedParty
+    );
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.hirer_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.escrow_token_account.to_account_info(),
+            authority: ctx.accounts.signer.to_account_info(),
+        },
+    );
+    transfer_tokens(cpi_ctx, amount, ctx.accounts.mint.decimals)
+}
+
+/// Withhold `penalty`, the escrow's SLA penalty, at settlement: take it off the amount that is
+/// released, then refund it to the funder
+#[allow(clippy::too_many_arguments)]
+fn apply_sla_penalty<'info>(
+    escrow: &mut Account<'info, Escrow>,
+    escrow_info: &AccountInfo<'info>,
+    vault: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    funder_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    remaining: &[AccountInfo<'info>],
+    penalty: u64,
+) -> Result<()> {
+    if penalty == 0 {
+        return Ok(());
+    }
+    escrow.amount -= penalty;
+    escrow.funded_amount -= penalty;
+    let escrow_seeds = &[
+        b"escrow",
+        escrow.opened_by.as_ref(),
+        escrow.job_id_hash.as_ref(),
+        &[escrow.bump],
+    ];
+    refund_funders(
+        escrow,
+        escrow_info,
+        vault,
+        mint,
+        funder_token_account,
+        token_program,
+        remaining,
+        penalty,
+        &[&escrow_seeds[..]],
+    )
+}
+
+/// Fill in the tombstone of an escrow being closed. Its rent comes out of the escrow's own,
+/// so whoever sent the close is reimbursed for creating it.
+fn retire_escrow<'info>(
+    escrow: &Account<'info, Escrow>,
+    tombstone: &mut Account<'info, EscrowTombstone>,
+    payer: &AccountInfo<'info>,
+    bump: u8,
+) -> Result<()> {
+    tombstone.escrow = escrow.key();
+    tombstone.hirer = escrow.opened_by;
+    tombstone.job_id_hash = escrow.job_id_hash;
+    tombstone.closed_at = Clock::get()?.unix_timestamp;
+    tombstone.bump = bump;
+
+    let rent = tombstone.to_account_info().lamports();
+    **escrow.to_account_info().try_borrow_mut_lamports()? -= rent;
+    **payer.try_borrow_mut_lamports()? += rent;
+    Ok(())
+}
+
+/// Close the vault of a settled escrow, returning its rent to whoever paid it. A vault that
+/// still holds tokens, such as a held release or tokens sent to it directly, is left open.
+fn close_vault<'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &mut Account<'info, TokenAccount>,
+    rent_payer: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    vault.reload()?;
+    if vault.amount > 0 {
+        return Ok(());
+    }
+    let escrow_seeds = &[
+        b"escrow",
+        escrow.opened_by.as_ref(),
+        escrow.job_id_hash.as_ref(),
+        &[escrow.bump],
+    ];
+    let signer_seeds = &[&escrow_seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault.to_account_info(),
+            destination: rent_payer.clone(),
+            authority: escrow.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::close_account(cpi_ctx).map_err(token_error)
+}
+
+/// Take a closing escrow off its category's page, which must be passed if it has one
+fn unlist_from_category(
+    escrow: &Account<Escrow>,
+    page: Option<&mut Account<EscrowPage>>,
+) -> Result<()> {
+    match page {
+        Some(page) => {
+            let escrow_key = escrow.key();
+            page.escrows.retain(|listed| *listed != escrow_key);
+        }
+        None => require!(
+            escrow.taxonomy_category.is_none(),
+            EscrowError::CategoryAccountsMissing
+        ),
+    }
+    Ok(())
+}
+
+/// Re-rank the freelancer on the leaderboard in `fees`, if one was passed, by their
+/// lifetime gross in the payout `mint`
+fn rank_freelancer(
+    fees: &mut FeeAccounts,
+    earnings: &FreelancerEarnings,
+    mint: Pubkey,
+) -> Result<()> {
+    let Some(leaderboard) = fees.leaderboard.as_mut() else {
+        return Ok(());
+    };
+    require_keys_eq!(leaderboard.mint, mint, EscrowError::InvalidLeaderboard);
+    let volume = earnings
+        .mints
+        .iter()
+        .find(|entry| entry.mint == mint)
+        .map_or(0, |entry| entry.gross_earned);
+    leaderboard.rank(earnings.owner, volume);
+    Ok(())
+}
+
+/// Split an arbitration `fee` on a ruling over `amount` between the two sides in proportion to
+/// what each was awarded. Returns the hirer's and the freelancer's part
+fn arbitration_fee_shares(fee: u64, amount: u64, freelancer_amount: u64) -> (u64, u64) {
+    if amount == 0 {
+        return (0, 0);
+    }
+    let freelancer_fee = (fee as u128 * freelancer_amount as u128 / amount as u128) as u64;
+    (fee - freelancer_fee, freelancer_fee)
+}
+
+/// Pay `amount` out of the escrow vault to `arbitrator` for ruling on its dispute
+#[allow(clippy::too_many_arguments)]
+fn pay_arbitrator<'info>(
+    escrow_info: &AccountInfo<'info>,
+    vault: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    destination: &Account<'info, TokenAccount>,
+    arbitrator: &Pubkey,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    require!(
+        destination.owner == *arbitrator && destination.mint == vault.mint,
+        EscrowError::InvalidArbitratorAccount
+    );
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault.to_account_info(),
+            mint: mint.to_account_info(),
+            to: destination.to_account_info(),
+            authority: escrow_info.clone(),
+        },
+        signer_seeds,
+    );
+    transfer_tokens(cpi_ctx, amount, mint.decimals)
+}
+
+/// Load the freelancer's payout token account, opening their ATA on `payer` if it is missing
+#[allow(clippy::too_many_arguments)]
+fn load_payout_account<'info>(
+    escrow: &Escrow,
+    account: &AccountInfo<'info>,
+    freelancer: &AccountInfo<'info>,
+    mint: &Account<'info, Mint>,
+    payer: &AccountInfo<'info>,
+    associated_token_program: &Program<'info, AssociatedToken>,
+    token_program: &Program<'info, Token>,
+    system_program: &Program<'info, System>,
+) -> Result<Account<'info, TokenAccount>> {
+    if escrow.payout_destination.is_none() && account.data_is_empty() {
+        require_keys_eq!(
+            account.key(),
+            get_associated_token_address(&escrow.freelancer, &mint.key()),
+            EscrowError::InvalidPayoutDestination
+        );
+        associated_token::create(CpiContext::new(
+            associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: payer.clone(),
+                associated_token: account.clone(),
+                authority: freelancer.clone(),
+                mint: mint.to_account_info(),
+                system_program: system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+        ))?;
+    }
+    let account = Account::<TokenAccount>::try_from(account)?;
+    require_keys_eq!(account.mint, mint.key(), EscrowError::InvalidMint);
+    require!(
+        escrow.pays_to(&account),
+        EscrowError::InvalidPayoutDestination
+    );
+    Ok(account)
+}
+
+/// Pay `amount` out of the escrow vault to the freelancer, withholding the escrow's platform fee.
+/// The affiliate share of the fee accrues in the affiliate vault and the rest goes to the
+/// treasury. Every destination is checked and the payout booked before any tokens move; the
+/// fee withheld is `Escrow::platform_fee`, so callers can book it up front as well.
+#[allow(clippy::too_many_arguments)]
+fn pay_freelancer<'info>(
+    fees: &mut FeeAccounts<'info>,
+    escrow: &mut Account<'info, Escrow>,
+    config: &Config,
+    vault: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    freelancer_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let fee = escrow.platform_fee(amount);
+    // The treasury keeps whatever the other splits do not take, including rounding dust and the
+    // referrer share of escrows without an affiliate
+    let (mut affiliate_amount, mut insurance_amount, mut burn_amount) = (0u64, 0u64, 0u64);
+    for split in &config.fee_splits {
+        let share = (fee as u128 * split.bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        match split.destination {
+            FeeDestination::Treasury => {}
+            FeeDestination::Referrer if escrow.affiliate.is_some() => affiliate_amount += share,
+            FeeDestination::Referrer => {}
+            FeeDestination::InsurancePool => insurance_amount += share,
+            FeeDestination::Burn => burn_amount += share,
+        }
+    }
+    // Shares too small to be worth a transfer go to the treasury, and need no accounts passed
+    for share in [
+        &mut affiliate_amount,
+        &mut insurance_amount,
+        &mut burn_amount,
+    ] {
+        if *share < escrow.dust_threshold {
+            *share = 0;
+        }
+    }
+    let treasury_share = fee - affiliate_amount - insurance_amount - burn_amount;
+    // Hirers who opted in donate part of the treasury's cut to the configured charity
+    let charity_amount = if escrow.route_fee_to_charity {
+        (treasury_share as u128 * config.charity_share_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    } else {
+        0
+    };
+    let charity_amount = if charity_amount < escrow.dust_threshold {
+        0
+    } else {
+        charity_amount
+    };
+    let treasury_amount = treasury_share - charity_amount;
+
+    // A frozen destination would fail the transfer with an opaque token program error
+    require!(
+        !freelancer_token_account.is_frozen(),
+        EscrowError::PayoutAccountFrozen
+    );
+    let treasury = &fees.treasury_token_account;
+    require!(
+        treasury.owner == config.treasury && treasury.mint == vault.mint,
+        EscrowError::InvalidTreasuryAccount
+    );
+    let treasury = treasury.to_account_info();
+
+    let charity = if charity_amount > 0 {
+        let charity = fees
+            .charity_token_account
+            .as_ref()
+            .ok_or(EscrowError::InvalidCharityAccount)?;
+        require!(
+            charity.owner == config.charity && charity.mint == vault.mint,
+            EscrowError::InvalidCharityAccount
+        );
+        Some(charity.to_account_info())
+    } else {
+        None
+    };
+
+    let affiliate_vault = if affiliate_amount > 0 {
+        let (Some(affiliate), Some(affiliate_vault)) = (&mut fees.affiliate, &fees.affiliate_vault)
+        else {
+            return err!(EscrowError::AffiliateAccountsMissing);
+        };
+        require!(
+            Some(affiliate.key()) == escrow.affiliate
+                && affiliate_vault.owner == affiliate.key()
+                && affiliate_vault.mint == vault.mint,
+            EscrowError::AffiliateAccountsMissing
+        );
+        affiliate.total_accrued = affiliate
+            .total_accrued
+            .checked_add(affiliate_amount)
+            .ok_or(EscrowError::MathOverflow)?;
+        Some(affiliate_vault.to_account_info())
+    } else {
+        None
+    };
+
+    let insurance = if insurance_amount > 0 {
+        let insurance = fees
+            .insurance_token_account
+            .as_ref()
+            .ok_or(EscrowError::InvalidInsuranceAccount)?;
+        require!(
+            insurance.owner == config.insurance_pool && insurance.mint == vault.mint,
+            EscrowError::InvalidInsuranceAccount
+        );
+        Some(insurance.to_account_info())
+    } else {
+        None
+    };
+
+    let burn_mint = if burn_amount > 0 {
+        let mint = fees.mint.as_ref().ok_or(EscrowError::InvalidBurnMint)?;
+        require_keys_eq!(mint.key(), vault.mint, EscrowError::InvalidBurnMint);
+        Some(mint.to_account_info())
+    } else {
+        None
+    };
+
+    escrow.charity_donated = escrow
+        .charity_donated
+        .checked_add(charity_amount)
+        .ok_or(EscrowError::MathOverflow)?;
+    escrow
+        .uninvoiced
+        .record(amount, fee, Clock::get()?.unix_timestamp)?;
+
+    let snapshot = match &fees.price_feed {
+        Some(price_feed) => {
+            require_keys_eq!(price_feed.mint, vault.mint, EscrowError::InvalidPriceFeed);
+            let snapshot =
+                price_feed.fresh_snapshot(config.max_price_age, Clock::get()?.unix_timestamp)?;
+            escrow.settled_price = Some(snapshot.clone());
+            Some((price_feed.mint, snapshot))
+        }
+        None => None,
+    };
+
+    let escrow_seeds = &[
+        b"escrow",
+        escrow.opened_by.as_ref(),
+        escrow.job_id_hash.as_ref(),
+        &[escrow.bump],
+    ];
+    let signer_seeds = &[&escrow_seeds[..]];
+    let transfer = |to: AccountInfo<'info>, amount: u64| -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to,
+                authority: escrow.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_tokens(cpi_ctx, amount, mint.decimals)
+    };
+
+    transfer(freelancer_token_account.to_account_info(), amount - fee)?;
+    transfer(treasury, treasury_amount)?;
+    if let Some(charity) = charity {
+        transfer(charity, charity_amount)?;
+    }
+    if let Some(affiliate_vault) = affiliate_vault {
+        transfer(affiliate_vault, affiliate_amount)?;
+    }
+    if let Some(insurance) = insurance {
+        transfer(insurance, insurance_amount)?;
+    }
+    if let Some(mint) = burn_mint {
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Burn {
+                mint,
+                from: vault.to_account_info(),
+                authority: escrow.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::burn(cpi_ctx, burn_amount).map_err(token_error)?;
+    }
+
+    if let Some((mint, snapshot)) = snapshot {
+        emit!(PriceSnapshotRecorded {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            mint,
+            at_settlement: true,
+            snapshot,
+        });
+    }
+
+    if fee > 0 {
+        emit!(PlatformFeeCollected {
+            version: EVENT_SCHEMA_VERSION,
+            event_seq: next_event_seq(&mut escrow.event_seq),
+            escrow: escrow.key(),
+            fee,
+            treasury_amount,
+            affiliate: escrow.affiliate,
+            affiliate_amount,
+            charity_amount,
+            insurance_amount,
+            burn_amount,
+        });
+    }
+
+    Ok(())
+}
+
+/// `token::transfer_checked`, reporting the token program's common failures as escrow errors.
+/// A failed CPI aborts the whole transaction on chain, so the accounts are checked before the
+/// call; anything the token program still rejects is mapped by `token_error`.
+fn transfer_tokens<'info>(
+    cpi_ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let accounts = &cpi_ctx.accounts;
+    let from = TokenAccount::try_deserialize(&mut &accounts.from.try_borrow_data()?[..]);
+    let to = TokenAccount::try_deserialize(&mut &accounts.to.try_borrow_data()?[..]);
+    // Accounts that do not load are left for the token program to reject
+    if let (Ok(from), Ok(to)) = (from, to) {
+        require!(
+            !from.is_frozen() && !to.is_frozen(),
+            EscrowError::TokenAccountFrozen
+        );
+        require!(
+            from.mint == accounts.mint.key() && to.mint == accounts.mint.key(),
+            EscrowError::InvalidMint
+        );
+        let authority = accounts.authority.key();
+        if from.owner == authority {
+            require!(
+                from.amount >= amount,
+                EscrowError::InsufficientTokenBalance
+            );
+        } else {
+            require!(
+                from.delegate == COption::Some(authority),
+                EscrowError::TokenOwnerMismatch
+            );
+            require!(
+                from.amount >= amount && from.delegated_amount >= amount,
+                EscrowError::InsufficientTokenBalance
+            );
+        }
+    }
+    token::transfer_checked(cpi_ctx, amount, decimals).map_err(token_error)
+}
+
+/// Map an error a token CPI returned to the escrow error describing it, passing through any
+/// error without a counterpart
+fn token_error(error: Error) -> Error {
+    let Error::ProgramError(ref program_error) = error else {
+        return error;
+    };
+    let ProgramError::Custom(code) = program_error.program_error else {
+        return error;
+    };
+    if code == TokenError::InsufficientFunds as u32 {
+        error!(EscrowError::InsufficientTokenBalance)
+    } else if code == TokenError::AccountFrozen as u32 {
+        error!(EscrowError::TokenAccountFrozen)
+    } else if code == TokenError::OwnerMismatch as u32 {
+        error!(EscrowError::TokenOwnerMismatch)
+    } else if code == TokenError::MintMismatch as u32 {
+        error!(EscrowError::InvalidMint)
+    } else {
+        error
+    }
+}
+
+/// Schema version stamped on every event; bump it whenever an event layout changes.
+/// Version 2 carries job ids as their fixed-size hash instead of the string; version 3 adds
+/// the reason code to EmergencyRefundIssued; version 4 adds milestone dispute state to the
+/// milestones in MilestonesSet and MilestonesAmended.
+pub const EVENT_SCHEMA_VERSION: u8 = 4;
+
+/// Return the next sequence number of an account's event stream and advance it.
+/// Indexers detect missed events by watching for gaps in each stream.
+fn next_event_seq(seq: &mut u64) -> u64 {
+    let current = *seq;
+    *seq += 1;
+    current
+}
+
+/// Version of this program deployment, checked by `assert_min_version`
+pub const PROGRAM_VERSION: u16 = 1;
+
+/// Oldest client version whose instruction and account layouts this deployment still accepts
+pub const MIN_COMPATIBLE_VERSION: u16 = 1;
+
+#[event]
+pub struct EscrowCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub hirer: Pubkey,
+    pub freelancer: Pubkey,
+    /// `job_id_hash` of the job id, which is on the escrow account itself
+    pub job_id_hash: [u8; 32],
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct PaymentReleased {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub freelancer: Pubkey,
+    /// Released to the freelancer, after any SLA penalty
+    pub amount: u64,
+    pub released_by: Pubkey,
+    pub delivery_breaches: u16,
+    pub response_breaches: u16,
+    /// Withheld from the freelancer and refunded to the funder
+    pub sla_penalty: u64,
+}
+
+#[event]
+pub struct DisputeInitiated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub initiated_by: Pubkey,
+    pub reason_code: DisputeReasonCode,
+    pub reason: DisputeStatement,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+    /// The canonical resolution the ruling used, if any
+    pub template: Option<ResolutionTemplate>,
+    pub resolved_by: Pubkey,
+}
+
+#[event]
+pub struct EmergencyRefundIssued {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub amount: u64,
+    pub refunded_by: Pubkey,
+    pub reason: EmergencyRefundReason,
+}
+
+#[event]
+pub struct JurorRegistered {
+    pub version: u8,
+    pub event_seq: u64,
+    pub juror: Pubkey,
+    pub stake: u64,
+    pub total_stake: u64,
+}
+
+#[event]
+pub struct JurorDeregistered {
+    pub version: u8,
+    pub event_seq: u64,
+    pub juror: Pubkey,
+    pub stake: u64,
+}
+
+#[event]
+pub struct JurorsDrawn {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub panel: Pubkey,
+    pub jurors: Vec<Pubkey>,
+    pub respond_by: i64,
+    pub commit_deadline: i64,
+    pub reveal_deadline: i64,
+}
+
+#[event]
+pub struct JurorAssignmentAccepted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub juror: Pubkey,
+}
+
+#[event]
+pub struct JurorSlashed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub juror: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VoteCommitted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub juror: Pubkey,
+}
+
+#[event]
+pub struct VoteRevealed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub juror: Pubkey,
+    pub freelancer_bps: u16,
+}
+
+#[event]
+pub struct PanelVotesTallied {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub panel: Pubkey,
+    pub freelancer_bps: u16,
+    pub votes_counted: u8,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct DisputeEscalated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub escalated_by: Pubkey,
+    pub tier: DisputeTier,
+    pub bond: u64,
+    pub tier_deadline: i64,
+}
+
+#[event]
+pub struct EscalationBondsSettled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub to_hirer: u64,
+    pub to_freelancer: u64,
+}
+
+#[event]
+pub struct RefundRequested {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub hirer: Pubkey,
+    pub reason: String,
+}
+
+#[event]
+pub struct RefundApproved {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub hirer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReleaseRequested {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub freelancer: Pubkey,
+    pub review_ends_at: i64,
+}
+
+#[event]
+pub struct ReleaseProposed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub proposed_by: Pubkey,
+    pub confirmable_at: i64,
+}
+
+#[event]
+pub struct ReleaseProposalCancelled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct EscrowFrozen {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub frozen_by: Pubkey,
+    pub frozen_until: i64,
+}
+
+#[event]
+pub struct EscrowUnfrozen {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub unfrozen_by: Pubkey,
+}
+
+#[event]
+pub struct HeldPaymentWithdrawn {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub freelancer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PaymentClawedBack {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub hirer: Pubkey,
+    pub amount: u64,
+    pub clawed_back_by: Pubkey,
+    pub reason: String,
+}
+
+#[event]
+pub struct RoleTransferred {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub role: EscrowRole,
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+}
+
+#[event]
+pub struct BeneficiaryChanged {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub role: EscrowRole,
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    /// Token account the role's payouts or refunds now go to, if not the new wallet's own
+    pub destination: Option<Pubkey>,
+}
+
+#[event]
+pub struct SessionCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub permissions: u32,
+    pub max_amount: u64,
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub version: u8,
+    pub event_seq: u64,
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct CompressedEscrowCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub tree: Pubkey,
+    pub leaf: CompressedEscrowLeaf,
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct CompressedEscrowSettled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub tree: Pubkey,
+    pub index: u32,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct NonceAdvanced {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub advanced_by: Pubkey,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct AffiliateRegistered {
+    pub version: u8,
+    pub event_seq: u64,
+    pub affiliate: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct HirerReferred {
+    pub version: u8,
+    pub event_seq: u64,
+    pub affiliate: Pubkey,
+    pub hirer: Pubkey,
+}
+
+#[event]
+pub struct PlatformFeeCollected {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub fee: u64,
+    pub treasury_amount: u64,
+    pub affiliate: Option<Pubkey>,
+    pub affiliate_amount: u64,
+    pub charity_amount: u64,
+    pub insurance_amount: u64,
+    pub burn_amount: u64,
+}
+
+#[event]
+pub struct AffiliateRewardsClaimed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub affiliate: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CouponCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub coupon: Pubkey,
+    pub discount_bps: u16,
+    pub max_uses: u32,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct CouponRedeemed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub coupon: Pubkey,
+    pub fee_bps: u16,
+    pub remaining_uses: u32,
+}
+
+#[event]
+pub struct GrantCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub grantor: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub vesting_start: i64,
+    pub vesting_end: i64,
+    pub revocable_until: i64,
+}
+
+#[event]
+pub struct GrantClaimed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub total_claimed: u64,
+}
+
+#[event]
+pub struct GrantRevoked {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub refunded: u64,
+    pub vested: u64,
+}
+
+#[event]
+pub struct GrantCancelled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub paid: u64,
+    pub fee: u64,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct RetainerCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub period_amount: u64,
+    pub period_length: i64,
+    pub period_end: i64,
+    pub notice_period: i64,
+}
+
+#[event]
+pub struct RetainerAutoRenewEnabled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub allowance: u64,
+}
+
+#[event]
+pub struct RetainerCancelled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub ends_at: i64,
+}
+
+#[event]
+pub struct RetainerPeriodPaid {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub period: u32,
+    pub paid: u64,
+    pub fee: u64,
+    pub renewed: bool,
+    pub next_period_end: Option<i64>,
+}
+
+#[event]
+pub struct InvoiceIssued {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub invoice: Pubkey,
+    pub invoice_no: u32,
+    pub gross: u64,
+    pub fee: u64,
+    pub net: u64,
+}
+
+#[event]
+pub struct PriceFeedUpdated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub mint: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub published_at: i64,
+}
+
+#[event]
+pub struct PriceSnapshotRecorded {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub mint: Pubkey,
+    pub at_settlement: bool,
+    pub snapshot: PriceSnapshot,
+}
+
+#[event]
+pub struct StakePosted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub party: Pubkey,
+    pub stake: u64,
+}
+
+#[event]
+pub struct StakesSettled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub to_hirer: u64,
+    pub to_freelancer: u64,
+}
+
+#[event]
+pub struct FreelancerDefaulted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub freelancer: Pubkey,
+    pub refunded: u64,
+    pub slashed: u64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub activates_at: i64,
+}
+
+#[event]
+pub struct AdminProposalCancelled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub version: u8,
+    pub event_seq: u64,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
 }
 
-#[derive(Accounts)]
-pub struct InitiateDispute<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.job_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
-    
-    pub signer: Signer<'info>,
+#[event]
+pub struct ResolutionApproved {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub approver: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
 }
 
-#[derive(Accounts)]
-pub struct ResolveDispute<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.job_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
-    
-    pub admin: Signer<'info>,
-    
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        associated_token::mint = escrow_token_account.mint,
-        associated_token::authority = escrow.freelancer,
-    )]
-    pub freelancer_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        associated_token::mint = escrow_token_account.mint,
-        associated_token::authority = escrow.hirer,
-    )]
-    pub hirer_token_account: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct KycAttested {
+    pub version: u8,
+    pub event_seq: u64,
+    pub subject: Pubkey,
+    pub attestor: Pubkey,
+    pub expires_at: i64,
 }
 
-#[derive(Accounts)]
-pub struct EmergencyRefund<'info> {
-    #[account(
-        mut,
-        seeds = [b"escrow", escrow.job_id.as_bytes()],
-        bump = escrow.bump
-    )]
-    pub escrow: Account<'info, Escrow>,
-    
-    pub admin: Signer<'info>,
-    
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        associated_token::mint = escrow_token_account.mint,
-        associated_token::authority = escrow.hirer,
-    )]
-    pub hirer_token_account: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct EscrowFunded {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub funder: Pubkey,
+    /// This tranche
+    pub amount: u64,
+    pub funded_amount: u64,
+    pub total: u64,
 }
 
-#[account]
-pub struct Escrow {
-    pub hirer: Pubkey,
-    pub freelancer: Pubkey,
-    pub job_id: String,
+#[event]
+pub struct UnfundedEscrowClosed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub closed_by: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct SettledEscrowClosed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub closed_by: Pubkey,
+}
+
+#[event]
+pub struct ContributionReceived {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    /// The contributor's running total
+    pub contributed: u64,
+    pub funded_amount: u64,
+}
+
+#[event]
+pub struct ContributionWithdrawn {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub funded_amount: u64,
+}
+
+#[event]
+pub struct MilestonesSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub milestones: Vec<Milestone>,
+}
+
+#[event]
+pub struct MilestoneSubmitted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub index: u8,
+    pub review_ends_at: i64,
+}
+
+#[event]
+pub struct MilestonesAmended {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub milestones: Vec<Milestone>,
+}
+
+#[event]
+pub struct MilestoneReleased {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+    pub released_by: Pubkey,
+    pub late_penalty: u64,
+}
+
+#[event]
+pub struct MilestoneRefunded {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MilestoneDisputed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub index: u8,
+    pub disputed_by: EscrowRole,
+    pub reason_code: DisputeReasonCode,
+}
+
+#[event]
+pub struct MilestoneDisputeResolved {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub index: u8,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+    pub resolved_by: Pubkey,
+}
+
+#[event]
+pub struct MilestoneResolutionApproved {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub index: u8,
+    pub approver: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+}
+
+#[event]
+pub struct ChangeOrderProposed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub proposed_by: EscrowRole,
+    pub amount_delta: i64,
+    pub new_deadline: i64,
+    pub scope_hash: [u8; 32],
+}
+
+#[event]
+pub struct ChangeOrderAccepted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub accepted_by: EscrowRole,
     pub amount: u64,
     pub deadline: i64,
-    pub is_released: bool,
-    pub is_disputed: bool,
-    pub dispute_reason: Option<String>,
-    pub created_at: i64,
-    pub released_at: Option<i64>,
-    pub disputed_at: Option<i64>,
-    pub bump: u8,
+    pub scope_hash: [u8; 32],
+}
+
+#[event]
+pub struct ChangeOrderCancelled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub cancelled_by: EscrowRole,
+}
+
+#[event]
+pub struct SubmissionRejected {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub reason_hash: [u8; 32],
+    pub rejection_count: u16,
+}
+
+#[event]
+pub struct SlaTermsSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub terms: SlaTerms,
+}
+
+#[event]
+pub struct BusinessCalendarSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub calendar: BusinessCalendar,
+}
+
+#[event]
+pub struct PayoutDestinationSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub destination: Pubkey,
+}
+
+#[event]
+pub struct RefundDestinationSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub destination: Pubkey,
+}
+
+#[event]
+pub struct MintConfigSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub min_escrow_amount: u64,
+    pub dust_threshold: u64,
+}
+
+#[event]
+pub struct VaultMigrated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub legacy_vault: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EscrowMetadataSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub category: EscrowCategory,
+    pub title: String,
+}
+
+#[event]
+pub struct TaxonomyEntrySet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub entry: Pubkey,
+    pub kind: TaxonomyKind,
+    pub index: u16,
+    pub name: String,
+    pub fee_bps: Option<u16>,
+    pub active: bool,
+}
+
+#[event]
+pub struct EscrowSkillsSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub skills: Vec<u16>,
+}
+
+#[event]
+pub struct JurorSpecialtySet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub juror: Pubkey,
+    pub specialty: Option<u16>,
+}
+
+#[event]
+pub struct BadgeMinted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub owner: Pubkey,
+    pub kind: BadgeKind,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct DisputeAutoResolved {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+    /// Fixed auto-resolution fee taken from the hirer's share for the treasury
+    pub fee: u64,
+    pub triggered_by: Pubkey,
+}
+
+#[event]
+pub struct MediatorAssigned {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub mediator: Pubkey,
+    pub assigned_by: Pubkey,
+}
+
+#[event]
+pub struct MediationRecommended {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub mediator: Pubkey,
+    pub recommendation: MediationRecommendation,
+}
+
+#[event]
+pub struct SettlementProposed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub proposer: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+}
+
+#[event]
+pub struct SettlementAccepted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub accepted_by: Pubkey,
+}
+
+#[event]
+pub struct SettlementWithdrawn {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub proposer: Pubkey,
+    /// Offers made so far, the withdrawn one included
+    pub offers: u8,
+}
+
+#[event]
+pub struct SettlementExecuted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub proposer: Pubkey,
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+}
+
+#[event]
+pub struct ArbitratorPaid {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub arbitrator: Pubkey,
+    pub tier: DisputeTier,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CharityRoutingSet {
+    pub version: u8,
+    pub event_seq: u64,
+    pub escrow: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct JobApplied {
+    pub version: u8,
+    pub event_seq: u64,
+    pub application: Pubkey,
+    pub job_id_hash: [u8; 32],
+    pub applicant: Pubkey,
+    pub bond: u64,
+}
+
+#[event]
+pub struct BidBondRefunded {
+    pub version: u8,
+    pub event_seq: u64,
+    pub application: Pubkey,
+    pub job_id_hash: [u8; 32],
+    pub applicant: Pubkey,
+    pub bond: u64,
+}
+
+#[event]
+pub struct ContestCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub contest: Pubkey,
+    pub hirer: Pubkey,
+    pub judge: Pubkey,
+    pub prize: u64,
+    pub placements: Vec<u16>,
+    pub submission_deadline: i64,
+}
+
+#[event]
+pub struct ContestEntrySubmitted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub contest: Pubkey,
+    pub entry: Pubkey,
+    pub entrant: Pubkey,
+    pub submission_hash: [u8; 32],
+}
+
+#[event]
+pub struct ContestWinnersSelected {
+    pub version: u8,
+    pub event_seq: u64,
+    pub contest: Pubkey,
+    pub winners: Vec<Pubkey>,
+    pub prizes: Vec<u64>,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct PoolPayoutMade {
+    pub version: u8,
+    pub event_seq: u64,
+    pub contest: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PoolDistributed {
+    pub version: u8,
+    pub event_seq: u64,
+    pub contest: Pubkey,
+    pub recipients: u32,
+    pub total: u64,
 }
 
-impl Escrow {
-    pub const SIZE: usize = 8 + // discriminator
-        32 + // hirer
-        32 + // freelancer
-        4 + 64 + // job_id (string)
-        8 + // amount
-        8 + // deadline
-        1 + // is_released
-        1 + // is_disputed
-        1 + 4 + 200 + // dispute_reason (Option<String>)
-        8 + // created_at
-        1 + 8 + // released_at (Option<i64>)
-        1 + 8 + // disputed_at (Option<i64>)
-        1; // bump
+#[event]
+pub struct ContestCancelled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub contest: Pubkey,
+    pub refunded: u64,
 }
 
 #[event]
-pub struct EscrowCreated {
-    pub escrow: Pubkey,
+pub struct BountyCreated {
+    pub version: u8,
+    pub event_seq: u64,
+    pub bounty: Pubkey,
     pub hirer: Pubkey,
-    pub freelancer: Pubkey,
-    pub job_id: String,
-    pub amount: u64,
-    pub deadline: i64,
+    pub reward: u64,
+    pub claim_deadline: i64,
 }
 
 #[event]
-pub struct PaymentReleased {
-    pub escrow: Pubkey,
-    pub freelancer: Pubkey,
-    pub amount: u64,
-    pub released_by: Pubkey,
+pub struct BountyClaimSubmitted {
+    pub version: u8,
+    pub event_seq: u64,
+    pub bounty: Pubkey,
+    pub claim: Pubkey,
+    pub claimant: Pubkey,
+    pub proof_hash: [u8; 32],
 }
 
 #[event]
-pub struct DisputeInitiated {
-    pub escrow: Pubkey,
-    pub initiated_by: Pubkey,
-    pub reason: String,
+pub struct BountyAwarded {
+    pub version: u8,
+    pub event_seq: u64,
+    pub bounty: Pubkey,
+    pub claim: Pubkey,
+    pub claimant: Pubkey,
+    pub reward: u64,
 }
 
 #[event]
-pub struct DisputeResolved {
-    pub escrow: Pubkey,
-    pub hirer_amount: u64,
-    pub freelancer_amount: u64,
-    pub resolved_by: Pubkey,
+pub struct BountyClaimRejected {
+    pub version: u8,
+    pub event_seq: u64,
+    pub bounty: Pubkey,
+    pub claim: Pubkey,
+    pub claimant: Pubkey,
 }
 
 #[event]
-pub struct EmergencyRefundIssued {
-    pub escrow: Pubkey,
-    pub amount: u64,
-    pub refunded_by: Pubkey,
+pub struct BountyCancelled {
+    pub version: u8,
+    pub event_seq: u64,
+    pub bounty: Pubkey,
+    pub refunded: u64,
 }
 
 #[error_code]
@@ -446,4 +13938,676 @@ pub enum EscrowError {
     
     #[msg("Invalid split amount for dispute resolution")]
     InvalidSplitAmount,
-}
\ No newline at end of file
+    
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    
+    #[msg("Invalid juror panel size")]
+    InvalidPanelSize,
+    
+    #[msg("Invalid juror response window")]
+    InvalidResponseWindow,
+    
+    #[msg("Juror pool is full")]
+    JurorPoolFull,
+    
+    #[msg("Juror stake is below the minimum")]
+    StakeBelowMinimum,
+    
+    #[msg("Juror is not registered in the pool")]
+    JurorNotRegistered,
+    
+    #[msg("Juror still has open assignments")]
+    JurorHasOpenAssignments,
+    
+    #[msg("Not enough eligible jurors in the pool")]
+    NotEnoughJurors,
+    
+    #[msg("Unauthorized to draw a juror panel")]
+    UnauthorizedJurorDraw,
+    
+    #[msg("Invalid juror assignment account")]
+    InvalidAssignmentAccount,
+    
+    #[msg("Invalid SlotHashes sysvar data")]
+    InvalidSlotHashes,
+    
+    #[msg("Juror response window has closed")]
+    ResponseWindowClosed,
+    
+    #[msg("Juror response window is still open")]
+    ResponseWindowOpen,
+    
+    #[msg("Juror assignment has already been answered")]
+    AssignmentAlreadyAnswered,
+    
+    #[msg("Juror assignment is still open")]
+    AssignmentStillOpen,
+    
+    #[msg("Invalid vote commit or reveal window")]
+    InvalidVoteWindow,
+    
+    #[msg("Juror assignment has not been accepted")]
+    AssignmentNotAccepted,
+    
+    #[msg("Vote commit window has closed")]
+    CommitWindowClosed,
+    
+    #[msg("Vote commit window is still open")]
+    CommitWindowOpen,
+    
+    #[msg("Vote reveal window has closed")]
+    RevealWindowClosed,
+    
+    #[msg("Vote reveal window is still open")]
+    RevealWindowOpen,
+    
+    #[msg("No vote has been committed")]
+    VoteNotCommitted,
+    
+    #[msg("Vote has already been revealed")]
+    VoteAlreadyRevealed,
+    
+    #[msg("Revealed vote does not match the commitment")]
+    CommitmentMismatch,
+    
+    #[msg("Ruling must be between 0 and 10000 basis points")]
+    InvalidRuling,
+    
+    #[msg("No stake-weighted votes were revealed")]
+    NoRevealedVotes,
+    
+    #[msg("Too many moderators")]
+    TooManyModerators,
+    
+    #[msg("Invalid dispute tier windows")]
+    InvalidTierConfig,
+    
+    #[msg("Action is not allowed at the current dispute tier")]
+    WrongDisputeTier,
+    
+    #[msg("Unauthorized to resolve dispute at this tier")]
+    UnauthorizedResolution,
+    
+    #[msg("Dispute tier window has elapsed")]
+    TierWindowElapsed,
+    
+    #[msg("Dispute is already at the final tier")]
+    FinalTierReached,
+    
+    #[msg("Payment has not been released")]
+    NotReleased,
+    
+    #[msg("No escalation bonds to settle")]
+    NoEscalationBonds,
+    
+    #[msg("A refund has already been requested")]
+    RefundAlreadyRequested,
+    
+    #[msg("No refund has been requested")]
+    RefundNotRequested,
+    
+    #[msg("Invalid review window")]
+    InvalidReviewWindow,
+    
+    #[msg("A release has already been requested")]
+    ReleaseAlreadyRequested,
+    
+    #[msg("No release has been requested")]
+    ReleaseNotRequested,
+    
+    #[msg("Review window is still open")]
+    ReviewWindowOpen,
+    
+    #[msg("Invalid release confirmation delay")]
+    InvalidReleaseDelay,
+    
+    #[msg("A release has already been proposed")]
+    ReleaseAlreadyProposed,
+    
+    #[msg("Release must be proposed before it can be confirmed")]
+    ReleaseNotProposed,
+    
+    #[msg("Release confirmation delay has not elapsed")]
+    ReleaseConfirmDelayActive,
+    
+    #[msg("Escrow is frozen")]
+    EscrowFrozen,
+    
+    #[msg("Escrow is not frozen")]
+    EscrowNotFrozen,
+    
+    #[msg("Unauthorized moderation action")]
+    UnauthorizedModeration,
+    
+    #[msg("Invalid freeze duration")]
+    InvalidFreezeDuration,
+    
+    #[msg("Freeze would exceed the maximum freeze duration")]
+    FreezeLimitExceeded,
+    
+    #[msg("Invalid clawback window")]
+    InvalidClawbackWindow,
+    
+    #[msg("No released payment is being held")]
+    NoHeldPayment,
+    
+    #[msg("Clawback window is still open")]
+    ClawbackWindowOpen,
+    
+    #[msg("Clawback window has closed")]
+    ClawbackWindowClosed,
+    
+    #[msg("Only the hirer or freelancer can transfer their role")]
+    UnauthorizedRoleTransfer,
+    
+    #[msg("New role key must differ from both current parties")]
+    InvalidRoleTransfer,
+    
+    #[msg("Counterparty must co-sign the role transfer")]
+    CounterpartyConsentRequired,
+    
+    #[msg("Invalid session key expiry")]
+    InvalidSessionExpiry,
+    
+    #[msg("Invalid session key permissions")]
+    InvalidSessionPermissions,
+    
+    #[msg("Signer does not match the session key")]
+    InvalidSessionKey,
+    
+    #[msg("Session key has expired")]
+    SessionExpired,
+    
+    #[msg("Session key does not permit this action")]
+    SessionPermissionDenied,
+    
+    #[msg("Amount exceeds the session key limit")]
+    SessionAmountExceeded,
+    
+    #[msg("Amount is outside the compressed escrow range")]
+    InvalidCompressedAmount,
+    
+    #[msg("Compressed escrow tree is full")]
+    CompressedTreeFull,
+    
+    #[msg("Invalid Merkle proof for compressed escrow")]
+    InvalidMerkleProof,
+    
+    #[msg("Recipient token account does not belong to the expected party")]
+    InvalidCompressedRecipient,
+    
+    #[msg("Deadline has not been reached")]
+    DeadlineNotReached,
+    
+    #[msg("Only the hirer or freelancer may perform this action")]
+    UnauthorizedParty,
+    
+    #[msg("Approval nonce is stale or does not match the escrow")]
+    StaleNonce,
+    
+    #[msg("Program version is older than the client requires")]
+    ProgramVersionTooOld,
+    
+    #[msg("Client version is no longer compatible with this program")]
+    ClientVersionIncompatible,
+    
+    #[msg("Escrow vault holds less than the escrow still owes")]
+    VaultUnderfunded,
+    
+    #[msg("Escrow lamports do not cover rent and escalation bonds")]
+    BondsUnderfunded,
+    
+    #[msg("Escrow state flags contradict each other")]
+    InconsistentEscrowState,
+    
+    #[msg("Escrow is not listed on this page")]
+    EscrowNotListed,
+    
+    #[msg("Hirer has reached the open escrow limit for accounts without a settlement record")]
+    OpenEscrowLimitReached,
+    
+    #[msg("Freelancer earnings already track the maximum number of mints")]
+    EarningsMintLimitReached,
+    
+    #[msg("Fee basis points must not exceed 10000")]
+    InvalidFeeConfig,
+    
+    #[msg("Treasury token account does not belong to the treasury or the escrow mint")]
+    InvalidTreasuryAccount,
+    
+    #[msg("Affiliate accounts are missing or do not match the escrow's affiliate")]
+    AffiliateAccountsMissing,
+    
+    #[msg("Hirers can only join an affiliate before opening their first escrow")]
+    ReferralWindowClosed,
+    
+    #[msg("Hirer was already referred by an affiliate")]
+    AlreadyReferred,
+    
+    #[msg("Affiliates cannot refer themselves")]
+    SelfReferral,
+    
+    #[msg("Affiliate vault holds no rewards to claim")]
+    NoAffiliateRewards,
+    
+    #[msg("Coupon needs a discount of at most 10000 bps, at least one use and a future expiry")]
+    InvalidCoupon,
+    
+    #[msg("Coupon has expired")]
+    CouponExpired,
+    
+    #[msg("Coupon has no uses left")]
+    CouponExhausted,
+    
+    #[msg("Grant vesting must end after it starts, with a non-negative revocation window")]
+    InvalidGrantSchedule,
+    
+    #[msg("Grants vest on a schedule and do not support this action")]
+    GrantEscrow,
+    
+    #[msg("Escrow is not a grant")]
+    NotAGrant,
+    
+    #[msg("Nothing has vested since the last claim")]
+    NothingVested,
+    
+    #[msg("Grant revocation window has elapsed")]
+    RevocationWindowElapsed,
+    
+    #[msg("Charity token account is missing or does not belong to the charity or the escrow mint")]
+    InvalidCharityAccount,
+    
+    #[msg("Fee splits must sum to 10000 bps, with at most four of them")]
+    InvalidFeeSplits,
+    
+    #[msg("Insurance token account is missing or does not belong to the insurance pool or the escrow mint")]
+    InvalidInsuranceAccount,
+    
+    #[msg("Burning a fee share needs the escrow mint")]
+    InvalidBurnMint,
+    
+    #[msg("Bid bond lock period cannot be negative")]
+    InvalidBidBondConfig,
+    
+    #[msg("Job has already been awarded")]
+    JobAlreadyAwarded,
+    
+    #[msg("Bid bond is still locked")]
+    BidBondLocked,
+    
+    #[msg("Contest prize must be positive")]
+    InvalidContestPrize,
+    
+    #[msg("Contest submission deadline must be in the future")]
+    InvalidContestDeadline,
+    
+    #[msg("Contest placements must be non-empty, at most five and sum to 100%")]
+    InvalidPlacements,
+    
+    #[msg("Contest submissions are closed")]
+    SubmissionsClosed,
+    
+    #[msg("Contest submissions are still open")]
+    SubmissionsStillOpen,
+    
+    #[msg("The hirer and judge cannot enter their own contest")]
+    IneligibleEntrant,
+    
+    #[msg("Contest has already been settled")]
+    ContestSettled,
+    
+    #[msg("Invalid contest winner accounts")]
+    InvalidWinnerAccounts,
+    
+    #[msg("An entrant can only win one placement")]
+    DuplicateWinner,
+    
+    #[msg("Contest has entries and must be judged")]
+    ContestHasEntries,
+    
+    #[msg("Bounty reward must be positive")]
+    InvalidBountyReward,
+    
+    #[msg("Bounty claim deadline must be in the future")]
+    InvalidBountyDeadline,
+    
+    #[msg("Bounty has already been settled")]
+    BountySettled,
+    
+    #[msg("Bounty claim window has closed")]
+    ClaimWindowClosed,
+    
+    #[msg("Bounty claim window is still open")]
+    ClaimWindowOpen,
+    
+    #[msg("The hirer cannot claim their own bounty")]
+    IneligibleClaimant,
+    
+    #[msg("Bounty has open claims that must be approved or rejected")]
+    BountyHasOpenClaims,
+    
+    #[msg("Pool payouts must be non-empty and match the recipient accounts")]
+    InvalidPoolPayouts,
+    
+    #[msg("Pool payouts must add up to the prize")]
+    PoolNotBalanced,
+    
+    #[msg("Invalid retainer period or notice")]
+    InvalidRetainerTerms,
+    
+    #[msg("Escrow is not a retainer")]
+    NotARetainer,
+    
+    #[msg("Retainer period has not ended yet")]
+    RetainerPeriodActive,
+    
+    #[msg("Retainer notice has already been given")]
+    RetainerAlreadyCancelled,
+    
+    #[msg("No payouts since the last invoice")]
+    NothingToInvoice,
+    
+    #[msg("Price must be positive")]
+    InvalidPrice,
+    
+    #[msg("Only the configured price oracle can publish prices")]
+    UnauthorizedPriceOracle,
+    
+    #[msg("Price feed does not match the escrow mint")]
+    InvalidPriceFeed,
+    
+    #[msg("Stake must be positive")]
+    InvalidStake,
+    
+    #[msg("Dual deposit is already enabled")]
+    DualDepositAlreadyEnabled,
+    
+    #[msg("Escrow is not a dual-deposit escrow")]
+    NotDualDeposit,
+    
+    #[msg("Stake has already been posted")]
+    StakeAlreadyPosted,
+    
+    #[msg("No stakes to settle")]
+    NoStakes,
+    
+    #[msg("Default slash share cannot exceed 100%")]
+    InvalidSlashConfig,
+    
+    #[msg("Work has already been delivered")]
+    WorkDelivered,
+    
+    #[msg("Emergency refund epoch must be positive")]
+    InvalidEmergencyRefundEpoch,
+    
+    #[msg("Admin has reached the emergency refund count for this epoch")]
+    EmergencyRefundCountExceeded,
+    
+    #[msg("Admin has reached the emergency refund value for this epoch")]
+    EmergencyRefundValueExceeded,
+    
+    #[msg("Rate limit window must be positive")]
+    InvalidRateLimits,
+    
+    #[msg("Rate limit for this instruction reached; try again in the next window")]
+    RateLimitExceeded,
+    
+    #[msg("Admin transfer delay cannot be negative")]
+    InvalidAdminTransferDelay,
+    
+    #[msg("An admin handover is already pending")]
+    AdminProposalPending,
+    
+    #[msg("New admin must differ from the current admin")]
+    InvalidNewAdmin,
+    
+    #[msg("No admin handover is pending")]
+    NoPendingAdmin,
+    
+    #[msg("Only the proposed admin can accept the handover")]
+    UnauthorizedAdminAcceptance,
+    
+    #[msg("Admin handover delay has not passed yet")]
+    AdminTransferDelayActive,
+    
+    #[msg("Co-signed split does not match the pending approval")]
+    ResolutionSplitMismatch,
+    
+    #[msg("Large escrow review window must be at least the standard review window")]
+    InvalidLargeEscrowTier,
+    
+    #[msg("Large escrows require a current KYC attestation for the hirer")]
+    KycAttestationRequired,
+    
+    #[msg("Only the configured KYC attestor can attest")]
+    UnauthorizedKycAttestor,
+    
+    #[msg("Velocity window must be positive")]
+    InvalidVelocityWindow,
+    
+    #[msg("Hirer has reached the escrow volume allowed in this window")]
+    VelocityLimitExceeded,
+    
+    #[msg("Lost dispute cooldown cannot be negative")]
+    InvalidDisputeCooldown,
+    
+    #[msg("Wallet lost a dispute recently and is cooling down")]
+    DisputeCooldownActive,
+    
+    #[msg("Disputes need delivered work or a missed deadline")]
+    NoDisputeTrigger,
+    
+    #[msg("Wallet has too many disputes open")]
+    TooManyOpenDisputes,
+    
+    #[msg("Unfunded escrow TTL must be positive")]
+    InvalidUnfundedEscrowTtl,
+    
+    #[msg("Escrow has not been funded")]
+    EscrowNotFunded,
+    
+    #[msg("Escrow is already funded")]
+    EscrowAlreadyFunded,
+    
+    #[msg("Unfunded escrow has expired")]
+    UnfundedEscrowExpired,
+    
+    #[msg("Unfunded escrow has not expired yet")]
+    UnfundedEscrowNotExpired,
+    
+    #[msg("Funding threshold must be between 1 and 10000 basis points")]
+    InvalidFundingThreshold,
+    
+    #[msg("Tranche must be positive and within the unfunded remainder")]
+    InvalidFundingTranche,
+    
+    #[msg("Escrow has not been fully funded")]
+    EscrowNotFullyFunded,
+    
+    #[msg("Escrow is crowdfunded")]
+    CrowdfundedEscrow,
+    
+    #[msg("Escrow is not crowdfunded")]
+    NotCrowdfunded,
+    
+    #[msg("Contributions must be withdrawn first")]
+    ContributionsOutstanding,
+    
+    #[msg("Invalid or missing contribution account")]
+    InvalidContributionAccount,
+    
+    #[msg("Milestones need 1 to 8 nonzero amounts adding up to the escrow amount, with late penalties of at most 10000 basis points")]
+    InvalidMilestones,
+    
+    #[msg("Escrow is already split into milestones")]
+    MilestonesAlreadySet,
+    
+    #[msg("Escrows with a clawback window cannot be split into milestones")]
+    MilestonesUnsupported,
+    
+    #[msg("Not supported on retainer escrows")]
+    RetainerEscrow,
+    
+    #[msg("Escrow is split into milestones; act on a milestone instead")]
+    MilestoneEscrow,
+    
+    #[msg("Escrow has no milestone at this index")]
+    InvalidMilestone,
+    
+    #[msg("Milestone was already settled or is in dispute")]
+    MilestoneNotOpen,
+    
+    #[msg("Milestone is not in dispute")]
+    MilestoneNotDisputed,
+    
+    #[msg("A milestone of the escrow is in dispute")]
+    MilestoneDisputed,
+    
+    #[msg("Escrow is not split into milestones")]
+    NoMilestones,
+    
+    #[msg("Deadline must be in the future")]
+    InvalidDeadline,
+    
+    #[msg("Milestone is not past its deadline without a submission")]
+    MilestoneNotOverdue,
+    
+    #[msg("A change order is already pending")]
+    ChangeOrderPending,
+    
+    #[msg("No change order is pending")]
+    NoChangeOrder,
+    
+    #[msg("Change order would leave no amount in escrow")]
+    InvalidChangeOrder,
+    
+    #[msg("Revision limit reached, the submission can only be released or disputed")]
+    RevisionLimitReached,
+    
+    #[msg("Invalid SLA terms")]
+    InvalidSlaTerms,
+    
+    #[msg("Business calendar needs a working day each week and at most 32 holidays")]
+    InvalidBusinessCalendar,
+    
+    #[msg("Payout token account is frozen; set another payout destination")]
+    PayoutAccountFrozen,
+    
+    #[msg("Token account is not the freelancer's payout destination")]
+    InvalidPayoutDestination,
+    
+    #[msg("Escrow amount is below the minimum for this mint")]
+    BelowMintMinimum,
+    
+    #[msg("Token account is not the escrow's vault")]
+    InvalidVault,
+    
+    #[msg("Escrow already uses the program-derived vault")]
+    VaultAlreadyMigrated,
+    
+    #[msg("Escrows must register their job id in the job registry")]
+    JobRegistrationRequired,
+    
+    #[msg("An escrow with this job id was closed; job ids cannot be reused")]
+    EscrowRetired,
+    
+    #[msg("Escrow title is longer than 48 bytes")]
+    TitleTooLong,
+    
+    #[msg("Taxonomy entry name is longer than 32 bytes")]
+    TaxonomyNameTooLong,
+    
+    #[msg("Taxonomy entry is not an active entry of the expected kind")]
+    TaxonomyEntryUnavailable,
+    
+    #[msg("Escrows may be tagged with at most 4 skills")]
+    TooManySkills,
+    
+    #[msg("Category index and page accounts are required for escrows in a category")]
+    CategoryAccountsMissing,
+    
+    #[msg("Leaderboard is for a different mint than the payout")]
+    InvalidLeaderboard,
+    
+    #[msg("Badge milestone has not been reached")]
+    BadgeNotEarned,
+    
+    #[msg("Auto-resolution needs a non-negative cooling-off period and a freelancer share of at most 100%")]
+    InvalidAutoResolveConfig,
+    
+    #[msg("Escrow amount is above the auto-resolution threshold")]
+    AutoResolutionUnavailable,
+    
+    #[msg("Dispute cooling-off period has not passed yet")]
+    CoolingOffActive,
+    
+    #[msg("Too many mediators")]
+    TooManyMediators,
+    
+    #[msg("Signer or key is not a registered mediator for this dispute")]
+    NotAMediator,
+    
+    #[msg("Mediation must record a recommendation or run out its window before escalating")]
+    MediationIncomplete,
+    
+    #[msg("Only the parties or the assigned mediator may settle this dispute")]
+    UnauthorizedSettlement,
+    
+    #[msg("No settlement offer is open on this dispute")]
+    NoOpenSettlement,
+    
+    #[msg("Too many settlement offers on this dispute")]
+    TooManySettlementOffers,
+    
+    #[msg("Arbitration fees may not exceed 10% of the escrow")]
+    InvalidArbitrationFee,
+    
+    #[msg("Arbitrator token account missing or not owned by the arbitrator")]
+    InvalidArbitratorAccount,
+    
+    #[msg("Inline dispute reason too long, link a statement instead")]
+    DisputeReasonTooLong,
+    
+    #[msg("Dispute statement URI is empty or too long")]
+    InvalidStatementUri,
+    
+    #[msg("This action cannot follow the escrow's earlier action in the same slot")]
+    SameSlotAction,
+    
+    #[msg("Mint does not match the mint of the escrow")]
+    InvalidMint,
+    
+    #[msg("Refund token account is frozen; set another refund destination")]
+    RefundAccountFrozen,
+    
+    #[msg("Token account is not the funder's refund destination")]
+    InvalidRefundDestination,
+    
+    #[msg("Job id is longer than 64 bytes")]
+    JobIdTooLong,
+    
+    #[msg("Reason is longer than 64 bytes")]
+    ReasonTooLong,
+    
+    #[msg("Token account is frozen")]
+    TokenAccountFrozen,
+    
+    #[msg("Token account holds too few tokens for the transfer")]
+    InsufficientTokenBalance,
+    
+    #[msg("Token account is not owned by or delegated to the signer")]
+    TokenOwnerMismatch,
+    
+    #[msg("Emergency refunds need the escrow to be disputed, frozen or past its deadline")]
+    EmergencyRefundNotAllowed,
+    
+    #[msg("Price feed reading is older than the configured maximum age")]
+    StalePrice,
+    
+    #[msg("Maximum price age must be positive")]
+    InvalidMaxPriceAge,
+    
+    #[msg("Escrow still has a held payment, bond, stake or open vault")]
+    EscrowNotSettled,
+}
@@ -0,0 +1,68 @@
+-- Postgres schema the TaskFi indexer writes to.
+--
+-- Apply it once before piping the indexer's output into psql:
+--
+--   psql "$DATABASE_URL" -f schema.sql
+--   websocat wss://rpc.example | taskfi-indexer --sql - | psql "$DATABASE_URL"
+--
+-- Keys are base58 strings, token amounts are base units. Every statement the indexer emits
+-- is an idempotent upsert, so replaying a backfill that overlaps rows already stored is safe.
+
+-- One row per escrow, from its creation event onwards
+CREATE TABLE IF NOT EXISTS escrows (
+    address       TEXT PRIMARY KEY,
+    hirer         TEXT NOT NULL,
+    freelancer    TEXT NOT NULL,
+    -- SHA-256 of the job id, as the escrow's address is derived from
+    job_id_hash   BYTEA NOT NULL,
+    -- Amount funded at creation
+    amount        NUMERIC(20) NOT NULL,
+    -- Unix timestamp the work is due by
+    deadline      BIGINT NOT NULL,
+    status        TEXT NOT NULL
+        CHECK (status IN ('open', 'disputed', 'released', 'resolved', 'refunded', 'closed')),
+    -- Paid out to the freelancer so far, net of clawbacks
+    released      NUMERIC(20) NOT NULL DEFAULT 0,
+    -- Returned to the funder so far, including SLA penalties and dispute shares
+    refunded      NUMERIC(20) NOT NULL DEFAULT 0,
+    created_slot  BIGINT NOT NULL,
+    updated_slot  BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS escrows_hirer ON escrows (hirer);
+CREATE INDEX IF NOT EXISTS escrows_freelancer ON escrows (freelancer);
+CREATE INDEX IF NOT EXISTS escrows_status ON escrows (status);
+
+-- The dispute on an escrow, open until the resolved_* columns are set
+CREATE TABLE IF NOT EXISTS disputes (
+    escrow             TEXT PRIMARY KEY REFERENCES escrows (address),
+    initiated_by       TEXT NOT NULL,
+    reason_code        TEXT NOT NULL,
+    -- Rung of the resolution ladder it was last escalated to, if any
+    tier               TEXT,
+    opened_slot        BIGINT NOT NULL,
+    hirer_amount       NUMERIC(20),
+    freelancer_amount  NUMERIC(20),
+    resolved_by        TEXT,
+    resolved_slot      BIGINT
+);
+
+-- Every event the program emitted, in the order it did
+CREATE TABLE IF NOT EXISTS events (
+    signature    TEXT NOT NULL,
+    -- Position among the program's events in the transaction
+    event_index  INTEGER NOT NULL,
+    slot         BIGINT NOT NULL,
+    name         TEXT NOT NULL,
+    version      SMALLINT NOT NULL,
+    -- Sequence number in the emitting account's event stream; gaps mean missed events
+    event_seq    NUMERIC(20) NOT NULL,
+    -- Set for the escrow lifecycle events the indexer interprets
+    escrow       TEXT,
+    -- Discriminator and borsh body, decodable with taskfi_client::decode_event
+    data         BYTEA NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE INDEX IF NOT EXISTS events_escrow ON events (escrow, slot);
+CREATE INDEX IF NOT EXISTS events_name ON events (name, slot);
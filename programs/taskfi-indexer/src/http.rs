@@ -0,0 +1,208 @@
+//! The query API the frontend reads the index through.
+//!
+//! `GET /health` reports how far indexing has got; `GET /escrows` lists escrows, filtered by
+//! `hirer`, `freelancer` or `status`; `GET /escrows/{address}` returns one with its dispute;
+//! `GET /escrows/{address}/events` its events; `GET /disputes` every dispute, or only those
+//! still open with `open=true`. Responses are JSON.
+//!
+//! Queries read the in-memory `Index`, not Postgres, so they only cover the events indexed
+//! since the process started.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use anchor_lang::prelude::Pubkey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::index::{DisputeRow, EscrowRow, EscrowStatus, EventRow, Index};
+use crate::sql::hex;
+
+/// A response to send: status code and JSON body
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+fn escrow_json(row: &EscrowRow) -> Value {
+    json!({
+        "address": row.address.to_string(),
+        "hirer": row.hirer.to_string(),
+        "freelancer": row.freelancer.to_string(),
+        "job_id_hash": hex(&row.job_id_hash),
+        // Amounts are strings so u64 values survive JavaScript numbers
+        "amount": row.amount.to_string(),
+        "deadline": row.deadline,
+        "status": row.status.as_str(),
+        "released": row.released.to_string(),
+        "refunded": row.refunded.to_string(),
+        "created_slot": row.created_slot,
+        "updated_slot": row.updated_slot,
+    })
+}
+
+fn dispute_json(row: &DisputeRow) -> Value {
+    json!({
+        "escrow": row.escrow.to_string(),
+        "initiated_by": row.initiated_by.to_string(),
+        "reason_code": row.reason_code,
+        "tier": row.tier,
+        "opened_slot": row.opened_slot,
+        "resolution": row.resolution.as_ref().map(|resolution| json!({
+            "hirer_amount": resolution.hirer_amount.to_string(),
+            "freelancer_amount": resolution.freelancer_amount.to_string(),
+            "resolved_by": resolution.resolved_by.to_string(),
+            "resolved_slot": resolution.resolved_slot,
+        })),
+    })
+}
+
+fn event_json(row: &EventRow) -> Value {
+    json!({
+        "signature": row.signature,
+        "index": row.index,
+        "slot": row.slot,
+        "name": row.name,
+        "version": row.version,
+        "event_seq": row.event_seq.to_string(),
+        "escrow": row.escrow.map(|escrow| escrow.to_string()),
+        "data": STANDARD.encode(&row.data),
+    })
+}
+
+/// Answer a `GET` of `target`, a path with an optional query string
+pub fn route(index: &Index, target: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params: Vec<(&str, &str)> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    };
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match segments.as_slice() {
+        ["health"] => Response::ok(json!({
+            "last_signature": index.last_signature(),
+            "last_slot": index.last_slot(),
+            "escrows": index.escrows().count(),
+            "events": index.events().len(),
+        })),
+        ["escrows"] => {
+            let parse_key = |name| param(name).map(Pubkey::from_str).transpose();
+            let (Ok(hirer), Ok(freelancer)) = (parse_key("hirer"), parse_key("freelancer")) else {
+                return Response::error(400, "invalid public key");
+            };
+            let status = match param("status").map(EscrowStatus::parse) {
+                Some(None) => return Response::error(400, "unknown status"),
+                status => status.flatten(),
+            };
+            let escrows: Vec<Value> = index
+                .escrows()
+                .filter(|row| hirer.is_none() || hirer == Some(row.hirer))
+                .filter(|row| freelancer.is_none() || freelancer == Some(row.freelancer))
+                .filter(|row| status.is_none() || status == Some(row.status))
+                .map(escrow_json)
+                .collect();
+            Response::ok(Value::Array(escrows))
+        }
+        ["escrows", address, rest @ ..] => {
+            let Ok(address) = Pubkey::from_str(address) else {
+                return Response::error(400, "invalid public key");
+            };
+            match rest {
+                [] => match index.escrow(&address) {
+                    Some(row) => {
+                        let mut body = escrow_json(row);
+                        body["dispute"] = index.dispute(&address).map_or(Value::Null, dispute_json);
+                        Response::ok(body)
+                    }
+                    None => Response::error(404, "escrow not indexed"),
+                },
+                ["events"] => Response::ok(Value::Array(
+                    index
+                        .events()
+                        .iter()
+                        .filter(|row| row.escrow == Some(address))
+                        .map(event_json)
+                        .collect(),
+                )),
+                _ => Response::error(404, "not found"),
+            }
+        }
+        ["disputes"] => {
+            let open_only = param("open") == Some("true");
+            Response::ok(Value::Array(
+                index
+                    .disputes()
+                    .filter(|row| !open_only || row.resolution.is_none())
+                    .map(dispute_json)
+                    .collect(),
+            ))
+        }
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn handle(index: &Mutex<Index>, mut stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; requests carry no body
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => route(&index.lock().unwrap(), target),
+        (Some(_), Some(_)) => Response::error(405, "only GET is supported"),
+        _ => Response::error(400, "malformed request"),
+    };
+    let body = response.body.to_string();
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\n\
+         Access-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        body.len(),
+    )?;
+    stream.flush()
+}
+
+/// Serve the query API on `listener` until it fails, one connection at a time
+pub fn serve(listener: TcpListener, index: Arc<Mutex<Index>>) -> io::Result<()> {
+    for stream in listener.incoming() {
+        // A client that goes away mid-request only loses its own response
+        let _ = handle(&index, stream?);
+    }
+    Ok(())
+}
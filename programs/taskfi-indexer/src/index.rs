@@ -0,0 +1,330 @@
+//! The indexed view of escrows, disputes and events, built up from the program's events.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::Pubkey;
+use taskfi_client::{ProgramEvent, RawEvent};
+use taskfi_escrow::EscrowRole;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowStatus {
+    Open,
+    Disputed,
+    Released,
+    Resolved,
+    Refunded,
+    Closed,
+}
+
+impl EscrowStatus {
+    /// Name stored in the `status` column and returned by the query API
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EscrowStatus::Open => "open",
+            EscrowStatus::Disputed => "disputed",
+            EscrowStatus::Released => "released",
+            EscrowStatus::Resolved => "resolved",
+            EscrowStatus::Refunded => "refunded",
+            EscrowStatus::Closed => "closed",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        [
+            EscrowStatus::Open,
+            EscrowStatus::Disputed,
+            EscrowStatus::Released,
+            EscrowStatus::Resolved,
+            EscrowStatus::Refunded,
+            EscrowStatus::Closed,
+        ]
+        .into_iter()
+        .find(|candidate| candidate.as_str() == status)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowRow {
+    pub address: Pubkey,
+    pub hirer: Pubkey,
+    pub freelancer: Pubkey,
+    /// `taskfi_escrow::job_id_hash` of the job id
+    pub job_id_hash: [u8; 32],
+    pub amount: u64,
+    pub deadline: i64,
+    pub status: EscrowStatus,
+    /// Paid out to the freelancer so far, net of clawbacks
+    pub released: u64,
+    /// Returned to the funder so far
+    pub refunded: u64,
+    pub created_slot: u64,
+    pub updated_slot: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Resolution {
+    pub hirer_amount: u64,
+    pub freelancer_amount: u64,
+    pub resolved_by: Pubkey,
+    pub resolved_slot: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeRow {
+    pub escrow: Pubkey,
+    pub initiated_by: Pubkey,
+    pub reason_code: String,
+    pub tier: Option<String>,
+    pub opened_slot: u64,
+    pub resolution: Option<Resolution>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventRow {
+    pub signature: String,
+    pub index: usize,
+    pub slot: u64,
+    pub name: &'static str,
+    pub version: u8,
+    pub event_seq: u64,
+    /// Escrow the event is about, for the lifecycle events the index interprets
+    pub escrow: Option<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+/// A row the index wrote, for persisting it elsewhere
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Escrow(EscrowRow),
+    Dispute(DisputeRow),
+    Event(EventRow),
+}
+
+/// Escrows, disputes and events as the program's events describe them. Escrows created
+/// before the first event indexed are not tracked, though their later events are recorded.
+#[derive(Default)]
+pub struct Index {
+    escrows: BTreeMap<Pubkey, EscrowRow>,
+    disputes: BTreeMap<Pubkey, DisputeRow>,
+    events: Vec<EventRow>,
+    last_signature: Option<String>,
+    last_slot: u64,
+}
+
+impl Index {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn escrow(&self, address: &Pubkey) -> Option<&EscrowRow> {
+        self.escrows.get(address)
+    }
+
+    pub fn escrows(&self) -> impl Iterator<Item = &EscrowRow> {
+        self.escrows.values()
+    }
+
+    pub fn dispute(&self, escrow: &Pubkey) -> Option<&DisputeRow> {
+        self.disputes.get(escrow)
+    }
+
+    pub fn disputes(&self) -> impl Iterator<Item = &DisputeRow> {
+        self.disputes.values()
+    }
+
+    /// Events in the order they were indexed
+    pub fn events(&self) -> &[EventRow] {
+        &self.events
+    }
+
+    pub fn last_signature(&self) -> Option<&str> {
+        self.last_signature.as_deref()
+    }
+
+    pub fn last_slot(&self) -> u64 {
+        self.last_slot
+    }
+
+    /// Record `raw` and apply it to the escrow and dispute it is about, returning the rows it
+    /// wrote. Events this client cannot decode are still recorded under the name `unknown`.
+    pub fn apply(&mut self, raw: &RawEvent) -> Vec<Change> {
+        let decoded = raw.event().ok();
+        let mut changes = Vec::new();
+        let escrow = decoded
+            .as_ref()
+            .and_then(|event| self.apply_event(event, raw.slot, &mut changes));
+
+        let row = EventRow {
+            signature: raw.signature.clone(),
+            index: raw.index,
+            slot: raw.slot,
+            name: decoded.as_ref().map_or("unknown", ProgramEvent::name),
+            // Every event leads with its schema version and event sequence number
+            version: raw.data.get(8).copied().unwrap_or_default(),
+            event_seq: raw
+                .data
+                .get(9..17)
+                .map_or(0, |seq| u64::from_le_bytes(seq.try_into().unwrap())),
+            escrow,
+            data: raw.data.clone(),
+        };
+        self.events.push(row.clone());
+        changes.push(Change::Event(row));
+        self.last_signature = Some(raw.signature.clone());
+        self.last_slot = self.last_slot.max(raw.slot);
+        changes
+    }
+
+    /// Apply a lifecycle event, returning the escrow it concerns when it is one
+    fn apply_event(
+        &mut self,
+        event: &ProgramEvent,
+        slot: u64,
+        changes: &mut Vec<Change>,
+    ) -> Option<Pubkey> {
+        let escrow = match event {
+            ProgramEvent::EscrowCreated(event) => {
+                self.escrows.insert(
+                    event.escrow,
+                    EscrowRow {
+                        address: event.escrow,
+                        hirer: event.hirer,
+                        freelancer: event.freelancer,
+                        job_id_hash: event.job_id_hash,
+                        amount: event.amount,
+                        deadline: event.deadline,
+                        status: EscrowStatus::Open,
+                        released: 0,
+                        refunded: 0,
+                        created_slot: slot,
+                        updated_slot: slot,
+                    },
+                );
+                event.escrow
+            }
+            ProgramEvent::PaymentReleased(event) => {
+                self.update(&event.escrow, |row| {
+                    row.status = EscrowStatus::Released;
+                    row.released += event.amount;
+                    row.refunded += event.sla_penalty;
+                });
+                event.escrow
+            }
+            ProgramEvent::DisputeInitiated(event) => {
+                if self.escrows.contains_key(&event.escrow) {
+                    self.update(&event.escrow, |row| row.status = EscrowStatus::Disputed);
+                    self.disputes.insert(
+                        event.escrow,
+                        DisputeRow {
+                            escrow: event.escrow,
+                            initiated_by: event.initiated_by,
+                            reason_code: format!("{:?}", event.reason_code),
+                            tier: None,
+                            opened_slot: slot,
+                            resolution: None,
+                        },
+                    );
+                }
+                event.escrow
+            }
+            ProgramEvent::DisputeEscalated(event) => {
+                if let Some(dispute) = self.disputes.get_mut(&event.escrow) {
+                    dispute.tier = Some(format!("{:?}", event.tier));
+                }
+                event.escrow
+            }
+            ProgramEvent::DisputeResolved(event) => {
+                let resolution = (
+                    event.hirer_amount,
+                    event.freelancer_amount,
+                    event.resolved_by,
+                );
+                self.resolve(&event.escrow, resolution, slot);
+                event.escrow
+            }
+            ProgramEvent::DisputeAutoResolved(event) => {
+                let resolution = (
+                    event.hirer_amount,
+                    event.freelancer_amount,
+                    event.triggered_by,
+                );
+                self.resolve(&event.escrow, resolution, slot);
+                event.escrow
+            }
+            ProgramEvent::SettlementExecuted(event) => {
+                let resolution = (event.hirer_amount, event.freelancer_amount, event.proposer);
+                self.resolve(&event.escrow, resolution, slot);
+                event.escrow
+            }
+            ProgramEvent::EmergencyRefundIssued(event) => {
+                self.update(&event.escrow, |row| {
+                    row.status = EscrowStatus::Refunded;
+                    row.refunded += event.amount;
+                });
+                event.escrow
+            }
+            ProgramEvent::PaymentClawedBack(event) => {
+                self.update(&event.escrow, |row| {
+                    row.status = EscrowStatus::Refunded;
+                    row.released = row.released.saturating_sub(event.amount);
+                    row.refunded += event.amount;
+                });
+                event.escrow
+            }
+            ProgramEvent::UnfundedEscrowClosed(event) => {
+                self.update(&event.escrow, |row| {
+                    row.status = EscrowStatus::Closed;
+                    row.refunded += event.refunded;
+                });
+                event.escrow
+            }
+            ProgramEvent::RoleTransferred(event) => {
+                self.update(&event.escrow, |row| match event.role {
+                    EscrowRole::Hirer => row.hirer = event.new_key,
+                    EscrowRole::Freelancer => row.freelancer = event.new_key,
+                });
+                event.escrow
+            }
+            ProgramEvent::BeneficiaryChanged(event) => {
+                self.update(&event.escrow, |row| match event.role {
+                    EscrowRole::Hirer => row.hirer = event.new_key,
+                    EscrowRole::Freelancer => row.freelancer = event.new_key,
+                });
+                event.escrow
+            }
+            _ => return None,
+        };
+
+        if let Some(row) = self.escrows.get_mut(&escrow) {
+            row.updated_slot = row.updated_slot.max(slot);
+            changes.push(Change::Escrow(row.clone()));
+        }
+        if let Some(dispute) = self.disputes.get(&escrow) {
+            changes.push(Change::Dispute(dispute.clone()));
+        }
+        Some(escrow)
+    }
+
+    fn update(&mut self, escrow: &Pubkey, apply: impl FnOnce(&mut EscrowRow)) {
+        if let Some(row) = self.escrows.get_mut(escrow) {
+            apply(row);
+        }
+    }
+
+    /// Settle an escrow by ruling or agreement, closing its dispute if it has one
+    fn resolve(&mut self, escrow: &Pubkey, (hirer, freelancer, by): (u64, u64, Pubkey), slot: u64) {
+        self.update(escrow, |row| {
+            row.status = EscrowStatus::Resolved;
+            row.released += freelancer;
+            row.refunded += hirer;
+        });
+        if let Some(dispute) = self.disputes.get_mut(escrow) {
+            dispute.resolution = Some(Resolution {
+                hirer_amount: hirer,
+                freelancer_amount: freelancer,
+                resolved_by: by,
+                resolved_slot: slot,
+            });
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Off-chain indexer of the TaskFi escrow program, as a prototype.
+//!
+//! The `taskfi-indexer` binary follows the program's events through a
+//! `taskfi_client::EventStream`, keeps escrows, disputes and events in an `Index`, writes each
+//! change as Postgres upserts against `schema.sql` and serves the index over a small HTTP
+//! query API for the frontend.
+//!
+//! It is not a production indexer. It connects to neither an RPC node nor Postgres: logs
+//! come in on stdin and the SQL goes out as text for `psql` to run. Queries are answered
+//! from the in-memory `Index`, which starts empty on every run and never reads the tables
+//! back, and a dropped session cannot be backfilled.
+
+pub mod http;
+pub mod index;
+pub mod source;
+pub mod sql;
+
+pub use index::{Change, DisputeRow, EscrowRow, EscrowStatus, EventRow, Index};
+pub use sql::SqlWriter;
@@ -0,0 +1,107 @@
+//! `taskfi-indexer [--listen ADDR] [--sql PATH] [--subscribe-request]`
+//!
+//! Reads a `logsSubscribe` WebSocket session for the escrow program from stdin, one JSON
+//! message per line, and indexes the events in it. Each change is written as Postgres upserts
+//! to `--sql` (`-` for stdout, to pipe into `psql`), and the query API is served on `--listen`
+//! (127.0.0.1:8080 by default). `--subscribe-request` prints the message that opens the
+//! subscription, to send to the WebSocket first:
+//!
+//! ```text
+//! (taskfi-indexer --subscribe-request; cat) | websocat wss://rpc.example \
+//!     | taskfi-indexer --sql - | psql "$DATABASE_URL"
+//! ```
+//!
+//! This is a prototype: the index lives in memory and is rebuilt from nothing on each run,
+//! and transactions that land while the session is down are not backfilled.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use taskfi_client::stream::logs_subscribe_request;
+use taskfi_client::EventStream;
+use taskfi_indexer::source::LineSource;
+use taskfi_indexer::{http, Index, SqlWriter};
+
+struct Args {
+    listen: String,
+    sql: Option<String>,
+    subscribe_request: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        listen: "127.0.0.1:8080".to_string(),
+        sql: None,
+        subscribe_request: false,
+    };
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--listen" => args.listen = argv.next().ok_or("--listen needs an address")?,
+            "--sql" => args.sql = Some(argv.next().ok_or("--sql needs a path")?),
+            "--subscribe-request" => args.subscribe_request = true,
+            other => return Err(format!("unknown argument {other}")),
+        }
+    }
+    Ok(args)
+}
+
+fn run(args: Args) -> io::Result<()> {
+    if args.subscribe_request {
+        println!("{}", logs_subscribe_request(1, "confirmed"));
+        return Ok(());
+    }
+
+    let mut sql: Option<SqlWriter<Box<dyn Write>>> = match args.sql.as_deref() {
+        None => None,
+        Some("-") => Some(SqlWriter::new(Box::new(io::stdout()))),
+        Some(path) => Some(SqlWriter::new(Box::new(
+            OpenOptions::new().create(true).append(true).open(path)?,
+        ))),
+    };
+
+    let index = Arc::new(Mutex::new(Index::new()));
+    let listener = TcpListener::bind(&args.listen)?;
+    eprintln!("serving the query API on {}", listener.local_addr()?);
+    let served = Arc::clone(&index);
+    thread::spawn(move || http::serve(listener, served));
+
+    let stream = EventStream::new(LineSource::new(io::stdin().lock())).max_reconnects(0);
+    for event in stream {
+        let event = match event {
+            Ok(event) => event,
+            // The input ended; keep answering queries until stopped
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err),
+        };
+        let changes = index.lock().unwrap().apply(&event);
+        if let Some(sql) = sql.as_mut() {
+            sql.write(&changes)?;
+        }
+    }
+    eprintln!("input ended; still serving the query API");
+    loop {
+        thread::park();
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("taskfi-indexer: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Transaction logs read from a `logsSubscribe` session piped in as JSON lines.
+
+use std::io::{self, BufRead};
+
+use taskfi_client::stream::parse_logs_notification;
+use taskfi_client::{LogSource, TransactionLogs};
+
+/// A `LogSource` over lines of a WebSocket session, such as `websocat` writes them, one
+/// JSON-RPC message per line. Messages other than log notifications are skipped. A pipe
+/// cannot be reopened or asked for history, so the stream ends when the input does and
+/// nothing missed while the session was down is recovered.
+pub struct LineSource<R> {
+    lines: R,
+    ended: bool,
+}
+
+impl<R: BufRead> LineSource<R> {
+    pub fn new(lines: R) -> Self {
+        Self {
+            lines,
+            ended: false,
+        }
+    }
+}
+
+impl<R: BufRead> LogSource for LineSource<R> {
+    fn subscribe(&mut self) -> io::Result<()> {
+        if self.ended {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    fn next_logs(&mut self) -> io::Result<TransactionLogs> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.lines.read_line(&mut line)? == 0 {
+                self.ended = true;
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if let Some(logs) = parse_logs_notification(&line) {
+                return Ok(logs);
+            }
+        }
+    }
+
+    fn backfill(&mut self, _signature: &str) -> io::Result<Vec<TransactionLogs>> {
+        Ok(Vec::new())
+    }
+}
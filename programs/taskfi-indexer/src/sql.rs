@@ -0,0 +1,128 @@
+//! Postgres statements that persist the index's changes into the tables of `schema.sql`.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::index::{Change, DisputeRow, EscrowRow, EventRow};
+
+/// A string literal, with quotes doubled
+fn text(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn key(value: &Pubkey) -> String {
+    text(&value.to_string())
+}
+
+fn optional<T>(value: Option<T>, render: impl FnOnce(T) -> String) -> String {
+    value.map_or_else(|| "NULL".to_string(), render)
+}
+
+/// Lowercase hex digits of `data`
+pub(crate) fn hex(data: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 * data.len());
+    for byte in data {
+        write!(hex, "{byte:02x}").unwrap();
+    }
+    hex
+}
+
+fn bytea(data: &[u8]) -> String {
+    format!("'\\x{}'::bytea", hex(data))
+}
+
+/// The upsert that stores `change`; replaying it leaves the row as it is
+pub fn statement(change: &Change) -> String {
+    match change {
+        Change::Escrow(row) => escrow(row),
+        Change::Dispute(row) => dispute(row),
+        Change::Event(row) => event(row),
+    }
+}
+
+fn escrow(row: &EscrowRow) -> String {
+    format!(
+        "INSERT INTO escrows (address, hirer, freelancer, job_id_hash, amount, deadline, status, \
+         released, refunded, created_slot, updated_slot) VALUES ({}, {}, {}, {}, {}, {}, {}, {}, \
+         {}, {}, {}) ON CONFLICT (address) DO UPDATE SET hirer = EXCLUDED.hirer, \
+         freelancer = EXCLUDED.freelancer, status = EXCLUDED.status, \
+         released = EXCLUDED.released, refunded = EXCLUDED.refunded, \
+         updated_slot = EXCLUDED.updated_slot;",
+        key(&row.address),
+        key(&row.hirer),
+        key(&row.freelancer),
+        bytea(&row.job_id_hash),
+        row.amount,
+        row.deadline,
+        text(row.status.as_str()),
+        row.released,
+        row.refunded,
+        row.created_slot,
+        row.updated_slot,
+    )
+}
+
+fn dispute(row: &DisputeRow) -> String {
+    let resolution = row.resolution.as_ref();
+    format!(
+        "INSERT INTO disputes (escrow, initiated_by, reason_code, tier, opened_slot, \
+         hirer_amount, freelancer_amount, resolved_by, resolved_slot) VALUES ({}, {}, {}, {}, \
+         {}, {}, {}, {}, {}) ON CONFLICT (escrow) DO UPDATE SET tier = EXCLUDED.tier, \
+         hirer_amount = EXCLUDED.hirer_amount, freelancer_amount = EXCLUDED.freelancer_amount, \
+         resolved_by = EXCLUDED.resolved_by, resolved_slot = EXCLUDED.resolved_slot;",
+        key(&row.escrow),
+        key(&row.initiated_by),
+        text(&row.reason_code),
+        optional(row.tier.as_deref(), text),
+        row.opened_slot,
+        optional(resolution, |r| r.hirer_amount.to_string()),
+        optional(resolution, |r| r.freelancer_amount.to_string()),
+        optional(resolution, |r| key(&r.resolved_by)),
+        optional(resolution, |r| r.resolved_slot.to_string()),
+    )
+}
+
+fn event(row: &EventRow) -> String {
+    format!(
+        "INSERT INTO events (signature, event_index, slot, name, version, event_seq, escrow, \
+         data) VALUES ({}, {}, {}, {}, {}, {}, {}, {}) \
+         ON CONFLICT (signature, event_index) DO NOTHING;",
+        text(&row.signature),
+        row.index,
+        row.slot,
+        text(row.name),
+        row.version,
+        row.event_seq,
+        optional(row.escrow.as_ref(), key),
+        bytea(&row.data),
+    )
+}
+
+/// Writes each batch of changes as one transaction, for `psql` or a file to load later
+pub struct SqlWriter<W> {
+    out: W,
+}
+
+impl<W: Write> SqlWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    pub fn write(&mut self, changes: &[Change]) -> io::Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        writeln!(self.out, "BEGIN;")?;
+        for change in changes {
+            writeln!(self.out, "{}", statement(change))?;
+        }
+        writeln!(self.out, "COMMIT;")?;
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
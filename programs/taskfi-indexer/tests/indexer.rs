@@ -0,0 +1,293 @@
+use std::io::Cursor;
+
+use anchor_lang::prelude::Pubkey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::json;
+use taskfi_client::{EventStream, ProgramEvent, RawEvent};
+use taskfi_escrow::{DisputeResolved, EscrowCreated};
+use taskfi_indexer::http::route;
+use taskfi_indexer::source::LineSource;
+use taskfi_indexer::sql::statement;
+use taskfi_indexer::*;
+use taskfi_test_utils::*;
+
+/// Runs transactions and indexes the events each one emitted, as if read from its logs
+struct Indexed {
+    env: TestEnv,
+    index: Index,
+    changes: Vec<Change>,
+    transactions: u64,
+}
+
+impl Indexed {
+    fn new() -> (Self, Platform) {
+        let mut env = TestEnv::new();
+        let platform = Platform::setup(&mut env);
+        let indexed = Self {
+            env,
+            index: Index::new(),
+            changes: Vec::new(),
+            transactions: 0,
+        };
+        (indexed, platform)
+    }
+
+    /// Index the events of the transaction just processed
+    fn record(&mut self) {
+        self.transactions += 1;
+        let signature = format!("sig-{}", self.transactions);
+        for (index, data) in self.env.raw_events().to_vec().into_iter().enumerate() {
+            let raw = RawEvent {
+                signature: signature.clone(),
+                slot: self.env.slot(),
+                index,
+                data,
+            };
+            self.changes.extend(self.index.apply(&raw));
+        }
+    }
+}
+
+fn disputed_escrow(indexed: &mut Indexed, platform: &Platform, job_id: &str) -> EscrowFixture {
+    let env = &mut indexed.env;
+    let escrow = EscrowBuilder::new(job_id).create(env);
+    indexed.record();
+    let env = &mut indexed.env;
+    env.process(
+        escrow.initiate_dispute_ix(env, platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    indexed.record();
+    let env = &mut indexed.env;
+    env.process(escrow.escalate_ix(platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    indexed.record();
+    indexed.env.next_slot();
+    escrow
+}
+
+#[test]
+fn escrows_and_disputes_follow_their_events() {
+    let (mut indexed, platform) = Indexed::new();
+    let escrow = disputed_escrow(&mut indexed, &platform, "job-indexed");
+
+    let row = indexed.index.escrow(&escrow.escrow).unwrap().clone();
+    assert_eq!(row.status, EscrowStatus::Disputed);
+    assert_eq!(
+        (row.hirer, row.freelancer),
+        (escrow.hirer, escrow.freelancer)
+    );
+    assert_eq!(row.job_id_hash, taskfi_escrow::job_id_hash("job-indexed"));
+    let dispute = indexed.index.dispute(&escrow.escrow).unwrap();
+    assert_eq!(dispute.initiated_by, escrow.freelancer);
+    assert_eq!(dispute.tier.as_deref(), Some("Moderator"));
+    assert!(dispute.resolution.is_none());
+
+    indexed
+        .env
+        .process(
+            escrow.resolve_ix(&platform, &platform.moderator, 400_000, 600_000),
+            &[platform.moderator],
+        )
+        .unwrap();
+    let resolved = indexed.env.events::<DisputeResolved>().remove(0);
+    indexed.record();
+
+    let row = indexed.index.escrow(&escrow.escrow).unwrap();
+    assert_eq!(row.status, EscrowStatus::Resolved);
+    assert_eq!(row.released, resolved.freelancer_amount);
+    assert_eq!(row.refunded, resolved.hirer_amount);
+    let resolution = indexed
+        .index
+        .dispute(&escrow.escrow)
+        .unwrap()
+        .resolution
+        .clone()
+        .unwrap();
+    assert_eq!(resolution.resolved_by, platform.moderator);
+    assert_eq!(resolution.resolved_slot, indexed.env.slot());
+
+    // Every event is recorded, whether or not the index interprets it
+    let events = indexed.index.events();
+    assert!(events.iter().any(|event| event.name == "EscrowCreated"
+        && event.escrow == Some(escrow.escrow)
+        && event.version == taskfi_escrow::EVENT_SCHEMA_VERSION));
+    assert_eq!(
+        events.len(),
+        indexed
+            .changes
+            .iter()
+            .filter(|c| matches!(c, Change::Event(_)))
+            .count()
+    );
+}
+
+#[test]
+fn changes_are_written_as_idempotent_upserts() {
+    let (mut indexed, platform) = Indexed::new();
+    let escrow = disputed_escrow(&mut indexed, &platform, "job-sql");
+
+    let mut writer = SqlWriter::new(Vec::new());
+    writer.write(&indexed.changes).unwrap();
+    let sql = String::from_utf8(writer.into_inner()).unwrap();
+    assert!(sql.starts_with("BEGIN;\nINSERT INTO escrows"));
+    assert!(sql.ends_with("COMMIT;\n"));
+    assert!(sql.contains("ON CONFLICT (address) DO UPDATE"));
+    assert!(sql.contains(&format!("'{}'", escrow.escrow)));
+    assert!(sql.contains("'disputed'"));
+    assert!(sql.contains("ON CONFLICT (signature, event_index) DO NOTHING;"));
+
+    // Escrow rows are written before the disputes that reference them
+    let first_dispute = sql.find("INSERT INTO disputes").unwrap();
+    assert!(sql.find("INSERT INTO escrows").unwrap() < first_dispute);
+}
+
+#[test]
+fn text_is_quoted_for_postgres() {
+    let row = DisputeRow {
+        escrow: Pubkey::new_unique(),
+        initiated_by: Pubkey::new_unique(),
+        reason_code: "o'brien's reason".to_string(),
+        tier: None,
+        opened_slot: 3,
+        resolution: None,
+    };
+    assert!(statement(&Change::Dispute(row)).contains("'o''brien''s reason'"));
+
+    let event = EventRow {
+        signature: "sig".to_string(),
+        index: 0,
+        slot: 1,
+        name: "unknown",
+        version: 1,
+        event_seq: 0,
+        escrow: None,
+        data: vec![0xde, 0xad, 0x01],
+    };
+    let sql = statement(&Change::Event(event));
+    assert!(sql.contains("'\\xdead01'::bytea"));
+    assert!(sql.contains(", NULL, "));
+}
+
+#[test]
+fn query_api_serves_the_index() {
+    let (mut indexed, platform) = Indexed::new();
+    let disputed = disputed_escrow(&mut indexed, &platform, "job-api-disputed");
+    let open = EscrowBuilder::new("job-api-open").create(&mut indexed.env);
+    indexed.record();
+    let index = &indexed.index;
+
+    let response = route(index, &format!("/escrows/{}", disputed.escrow));
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body["status"], "disputed");
+    assert_eq!(response.body["dispute"]["tier"], "Moderator");
+    assert_eq!(response.body["amount"], DEFAULT_AMOUNT.to_string());
+    let hex: String = taskfi_escrow::job_id_hash("job-api-disputed")
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    assert_eq!(response.body["job_id_hash"], hex);
+
+    let response = route(index, "/escrows?status=open");
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+    assert_eq!(response.body[0]["address"], open.escrow.to_string());
+    let response = route(index, &format!("/escrows?hirer={}", disputed.hirer));
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let response = route(index, "/disputes?open=true");
+    assert_eq!(response.body[0]["escrow"], disputed.escrow.to_string());
+
+    let response = route(index, &format!("/escrows/{}/events", open.escrow));
+    let created = &response.body[0];
+    assert_eq!(created["name"], "EscrowCreated");
+    let data = STANDARD.decode(created["data"].as_str().unwrap()).unwrap();
+    let ProgramEvent::EscrowCreated(event) = taskfi_client::decode_event(&data).unwrap() else {
+        panic!("not an EscrowCreated event");
+    };
+    assert_eq!(event.escrow, open.escrow);
+
+    assert_eq!(route(index, "/health").body["escrows"], json!(2));
+    assert_eq!(route(index, "/escrows?status=lost").status, 400);
+    assert_eq!(route(index, "/escrows/not-a-key").status, 400);
+    assert_eq!(
+        route(index, &format!("/escrows/{}", Pubkey::new_unique())).status,
+        404
+    );
+    assert_eq!(route(index, "/contests").status, 404);
+}
+
+#[test]
+fn piped_websocket_sessions_are_read_until_they_end() {
+    let (mut indexed, _platform) = Indexed::new();
+    EscrowBuilder::new("job-piped").create(&mut indexed.env);
+    let created = indexed.env.events::<EscrowCreated>().remove(0);
+    let program = taskfi_escrow::ID;
+    let data = STANDARD.encode(anchor_lang::Event::data(&created));
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": "logsNotification",
+        "params": {
+            "result": {
+                "context": { "slot": 77 },
+                "value": {
+                    "signature": "sig-piped",
+                    "err": null,
+                    "logs": [
+                        format!("Program {program} invoke [1]"),
+                        format!("Program data: {data}"),
+                        format!("Program {program} success"),
+                    ],
+                },
+            },
+            "subscription": 1,
+        },
+    });
+    let session = format!(
+        "{}\n{notification}\n",
+        json!({ "jsonrpc": "2.0", "result": 1, "id": 1 })
+    );
+
+    let mut index = Index::new();
+    let mut stream = EventStream::new(LineSource::new(Cursor::new(session))).max_reconnects(0);
+    for event in stream.by_ref().take(1) {
+        index.apply(&event.unwrap());
+    }
+    let row = index.escrow(&created.escrow).unwrap();
+    assert_eq!(
+        (row.job_id_hash, row.created_slot),
+        (taskfi_escrow::job_id_hash("job-piped"), 77)
+    );
+    assert_eq!(index.last_signature(), Some("sig-piped"));
+
+    let end = stream.next().unwrap().unwrap_err();
+    assert_eq!(end.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn query_api_answers_over_http() {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let index = Arc::new(Mutex::new(Index::new()));
+    std::thread::spawn(move || http::serve(listener, index));
+
+    let get = |request: &str| {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+    let response = get("GET /health HTTP/1.1\r\nHost: indexer\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: application/json"));
+    assert!(response.ends_with(r#""escrows":0,"events":0,"last_signature":null,"last_slot":0}"#));
+
+    let response = get("POST /escrows HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+}
@@ -0,0 +1,2592 @@
+//! Platform and escrow fixtures with builders for every escrow instruction
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::{instruction::Instruction, system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use taskfi_escrow::{
+    accounts, instruction, BadgeKind, CategoryIndex, CompressedEscrowLeaf, Config, ConfigParams,
+    DisputeReasonCode, DisputeRuling, DisputeStatement, EmergencyRefundReason, EscrowCategory,
+    EscrowIndex, EscrowRole, JurorPanel, KycAttestation, LargeEscrowTier, Leaderboard,
+    MilestoneTerms, PoolPayout, PriceFeed, RateLimits, SettlementProposal, SlaTerms, TaxonomyKind,
+    BPS_DENOMINATOR,
+};
+
+use crate::pda;
+use crate::svm::{TestEnv, TransactionError};
+
+pub const DAY: i64 = 24 * 60 * 60;
+
+/// Decimals of every mint the fixtures create
+pub const MINT_DECIMALS: u8 = 6;
+
+/// Default escrow amount used by `EscrowBuilder`
+pub const DEFAULT_AMOUNT: u64 = 1_000_000;
+
+/// Escalation bonds per tier used by `default_config_params`, in lamports
+pub const ESCALATION_BONDS: [u64; 3] = [100_000_000, 200_000_000, 500_000_000];
+
+/// Build an escrow program instruction from Anchor's generated account and argument structs
+pub fn escrow_instruction(
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: taskfi_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+impl TestEnv {
+    /// Process a single instruction
+    pub fn process(
+        &mut self,
+        instruction: Instruction,
+        signers: &[Pubkey],
+    ) -> Result<(), TransactionError> {
+        self.process_transaction(&[instruction], signers)
+    }
+}
+
+/// Assert that a transaction failed with `expected` (an `EscrowError` or Anchor `ErrorCode`)
+#[track_caller]
+pub fn assert_error(
+    result: Result<(), TransactionError>,
+    expected: impl Into<anchor_lang::error::Error>,
+) {
+    let expected = anchor_lang::solana_program::program_error::ProgramError::from(expected.into());
+    match result {
+        Err(TransactionError::InstructionError { error, .. }) => assert_eq!(error, expected),
+        other => panic!("expected {expected:?}, got {other:?}"),
+    }
+}
+
+/// Config used by `Platform::setup`: a three-juror panel, one moderator and day-scale windows
+pub fn default_config_params(treasury: Pubkey, moderator: Pubkey) -> ConfigParams {
+    ConfigParams {
+        treasury,
+        jurors_per_panel: 3,
+        juror_response_window: DAY,
+        juror_slash_amount: 100,
+        min_juror_stake: 1_000,
+        vote_commit_window: DAY,
+        vote_reveal_window: DAY,
+        moderators: vec![moderator],
+        tier_windows: [3 * DAY, 3 * DAY, 7 * DAY, 14 * DAY],
+        escalation_bonds: ESCALATION_BONDS,
+        review_window: 3 * DAY,
+        two_step_release_threshold: 0,
+        release_confirm_delay: DAY,
+        max_freeze_duration: 7 * DAY,
+        role_transfer_requires_consent: false,
+        max_compressed_escrow_amount: 10_000,
+        unreputed_open_escrow_limit: 0,
+        reputation_min_settled: 0,
+        platform_fee_bps: 0,
+        fee_splits: vec![],
+        insurance_pool: Pubkey::default(),
+        charity: Pubkey::default(),
+        charity_share_bps: 0,
+        bid_bond_lamports: 0,
+        bid_bond_lock_period: 7 * DAY,
+        price_oracle: Pubkey::default(),
+        max_price_age: DAY,
+        default_slash_bps: 5_000,
+        emergency_refund_epoch: DAY,
+        max_emergency_refunds_per_epoch: 0,
+        max_emergency_refund_value_per_epoch: 0,
+        rate_limits: RateLimits {
+            window: 60 * 60,
+            max_resolutions: 0,
+            max_emergency_refunds: 0,
+            max_config_updates: 0,
+        },
+        admin_transfer_delay: 2 * DAY,
+        dual_approval_threshold: 0,
+        large_escrow: LargeEscrowTier {
+            threshold: 0,
+            kyc_attestor: Pubkey::default(),
+            review_window: 7 * DAY,
+        },
+        velocity_window: DAY,
+        unreputed_velocity_limit: 0,
+        velocity_limit_step: 0,
+        lost_dispute_cooldown: 0,
+        dispute_requires_trigger: false,
+        max_open_disputes: 0,
+        unfunded_escrow_ttl: 3 * DAY,
+        max_revisions: 0,
+        require_job_registration: false,
+        badge_volume_mint: Pubkey::default(),
+        badge_volume_threshold: 0,
+        auto_resolve_max_amount: 0,
+        auto_resolve_cooling_off: DAY,
+        auto_resolve_freelancer_bps: 0,
+        auto_resolve_fee: 0,
+        mediators: vec![],
+        mediation_required: false,
+        moderator_fee_bps: 0,
+        juror_fee_bps: 0,
+    }
+}
+
+/// The initialized platform config, juror pool and the wallets that administer them
+pub struct Platform {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub moderator: Pubkey,
+    pub charity: Pubkey,
+    pub insurance_pool: Pubkey,
+    pub stake_mint: Pubkey,
+    pub config: Pubkey,
+    pub juror_pool: Pubkey,
+    pub pool_vault: Pubkey,
+    pub treasury_stake_account: Pubkey,
+    pub audit_log: Pubkey,
+}
+
+impl Platform {
+    /// Initialize the platform with `default_config_params`
+    pub fn setup(env: &mut TestEnv) -> Self {
+        Self::setup_with(env, |_| {})
+    }
+
+    /// Initialize the platform after letting `configure` adjust the default params
+    pub fn setup_with(env: &mut TestEnv, configure: impl FnOnce(&mut ConfigParams)) -> Self {
+        let admin = env.create_wallet();
+        let treasury = env.create_wallet();
+        let moderator = env.create_wallet();
+        let charity = env.create_wallet();
+        let insurance_pool = env.create_wallet();
+        let stake_mint = env.create_mint(MINT_DECIMALS);
+        let juror_pool = pda::juror_pool();
+        let platform = Self {
+            admin,
+            treasury,
+            moderator,
+            charity,
+            insurance_pool,
+            stake_mint,
+            config: pda::config(),
+            juror_pool,
+            pool_vault: pda::ata(&juror_pool, &stake_mint),
+            treasury_stake_account: env.create_token_account(&treasury, &stake_mint, 0),
+            audit_log: pda::audit_log(),
+        };
+
+        let mut params = default_config_params(treasury, moderator);
+        params.charity = charity;
+        params.insurance_pool = insurance_pool;
+        configure(&mut params);
+        let ix = escrow_instruction(
+            accounts::InitializeConfig {
+                config: platform.config,
+                juror_pool: platform.juror_pool,
+                audit_log: platform.audit_log,
+                stake_mint,
+                pool_vault: platform.pool_vault,
+                admin,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::InitializeConfig { params },
+        );
+        env.process(ix, &[admin]).expect("initialize_config failed");
+        platform
+    }
+
+    pub fn update_config_ix(&self, params: ConfigParams) -> Instruction {
+        escrow_instruction(
+            accounts::UpdateConfig {
+                config: self.config,
+                audit_log: self.audit_log,
+                admin: self.admin,
+            },
+            instruction::UpdateConfig { params },
+        )
+    }
+
+    pub fn propose_new_admin_ix(&self, new_admin: Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::ProposeAdmin {
+                config: self.config,
+                audit_log: self.audit_log,
+                admin: self.admin,
+            },
+            instruction::ProposeNewAdmin { new_admin },
+        )
+    }
+
+    pub fn cancel_admin_proposal_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::ProposeAdmin {
+                config: self.config,
+                audit_log: self.audit_log,
+                admin: self.admin,
+            },
+            instruction::CancelAdminProposal {},
+        )
+    }
+
+    pub fn accept_admin_ix(&self, new_admin: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::AcceptAdmin {
+                config: self.config,
+                audit_log: self.audit_log,
+                new_admin: *new_admin,
+            },
+            instruction::AcceptAdmin {},
+        )
+    }
+
+    /// A funded wallet holding `stake` stake-mint tokens, not yet registered
+    pub fn create_juror(&self, env: &mut TestEnv, stake: u64) -> Juror {
+        let wallet = env.create_wallet();
+        let token_account = env.create_token_account(&wallet, &self.stake_mint, stake);
+        Juror {
+            wallet,
+            token_account,
+        }
+    }
+
+    /// Create `count` jurors and register each with `stake`
+    pub fn register_jurors(&self, env: &mut TestEnv, count: usize, stake: u64) -> Vec<Juror> {
+        (0..count)
+            .map(|_| {
+                let juror = self.create_juror(env, stake);
+                env.process(self.register_juror_ix(&juror, stake), &[juror.wallet])
+                    .expect("register_juror failed");
+                juror
+            })
+            .collect()
+    }
+
+    pub fn register_juror_ix(&self, juror: &Juror, amount: u64) -> Instruction {
+        escrow_instruction(
+            accounts::RegisterJuror {
+                config: self.config,
+                juror_pool: self.juror_pool,
+                juror: juror.wallet,
+                juror_token_account: juror.token_account,
+                stake_mint: self.stake_mint,
+                pool_vault: self.pool_vault,
+                token_program: spl_token::ID,
+            },
+            instruction::RegisterJuror { amount },
+        )
+    }
+
+    pub fn deregister_juror_ix(&self, juror: &Juror) -> Instruction {
+        escrow_instruction(
+            accounts::DeregisterJuror {
+                config: self.config,
+                juror_pool: self.juror_pool,
+                juror: juror.wallet,
+                juror_token_account: juror.token_account,
+                stake_mint: self.stake_mint,
+                pool_vault: self.pool_vault,
+                token_program: spl_token::ID,
+            },
+            instruction::DeregisterJuror {},
+        )
+    }
+
+    pub fn create_session_ix(
+        &self,
+        owner: &Pubkey,
+        session_key: &Pubkey,
+        expires_at: i64,
+        permissions: u32,
+        max_amount: u64,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::CreateSession {
+                session: pda::session(owner, session_key),
+                owner: *owner,
+                payer: *owner,
+                system_program: system_program::ID,
+            },
+            instruction::CreateSession {
+                session_key: *session_key,
+                expires_at,
+                permissions,
+                max_amount,
+            },
+        )
+    }
+
+    pub fn revoke_session_ix(&self, owner: &Pubkey, session_key: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::RevokeSession {
+                session: pda::session(owner, session_key),
+                owner: *owner,
+                payer: *owner,
+            },
+            instruction::RevokeSession {},
+        )
+    }
+
+    pub fn create_coupon_ix(
+        &self,
+        code_hash: [u8; 32],
+        discount_bps: u16,
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::CreateCoupon {
+                config: self.config,
+                audit_log: self.audit_log,
+                coupon: pda::coupon(&code_hash),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            instruction::CreateCoupon {
+                code_hash,
+                discount_bps,
+                max_uses,
+                expires_at,
+            },
+        )
+    }
+
+    pub fn apply_to_job_ix(&self, hirer: &Pubkey, job_id: &str, applicant: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::ApplyToJob {
+                config: self.config,
+                application: pda::application(hirer, job_id, applicant),
+                escrow: pda::escrow(hirer, job_id),
+                applicant: *applicant,
+                system_program: system_program::ID,
+            },
+            instruction::ApplyToJob {
+                job_id: job_id.to_string(),
+                hirer: *hirer,
+            },
+        )
+    }
+
+    pub fn refund_bid_bond_ix(
+        &self,
+        hirer: &Pubkey,
+        job_id: &str,
+        applicant: &Pubkey,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::RefundBidBond {
+                config: self.config,
+                application: pda::application(hirer, job_id, applicant),
+                escrow: pda::escrow(hirer, job_id),
+                applicant: *applicant,
+            },
+            instruction::RefundBidBond {},
+        )
+    }
+
+    pub fn update_price_feed_ix(
+        &self,
+        oracle: &Pubkey,
+        mint: &Pubkey,
+        price: i64,
+        expo: i32,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::UpdatePriceFeed {
+                config: self.config,
+                price_feed: pda::price_feed(mint),
+                mint: *mint,
+                oracle: *oracle,
+                system_program: system_program::ID,
+            },
+            instruction::UpdatePriceFeed { price, expo },
+        )
+    }
+
+    pub fn set_mint_config_ix(
+        &self,
+        mint: &Pubkey,
+        min_escrow_amount: u64,
+        dust_threshold: u64,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::SetMintConfig {
+                config: self.config,
+                audit_log: self.audit_log,
+                mint_config: pda::mint_config(mint),
+                mint: *mint,
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            instruction::SetMintConfig {
+                min_escrow_amount,
+                dust_threshold,
+            },
+        )
+    }
+
+    pub fn set_taxonomy_entry_ix(
+        &self,
+        kind: TaxonomyKind,
+        index: u16,
+        name: &str,
+        fee_bps: Option<u16>,
+        active: bool,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::SetTaxonomyEntry {
+                config: self.config,
+                audit_log: self.audit_log,
+                entry: pda::taxonomy(kind, index),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            instruction::SetTaxonomyEntry {
+                kind,
+                index,
+                name: name.to_string(),
+                fee_bps,
+                active,
+            },
+        )
+    }
+
+    /// Specialize `juror` in the taxonomy category at `category`, or clear it with `None`
+    pub fn set_juror_specialty_ix(&self, juror: &Juror, category: Option<Pubkey>) -> Instruction {
+        escrow_instruction(
+            accounts::SetJurorSpecialty {
+                juror_pool: self.juror_pool,
+                juror: juror.wallet,
+                category,
+            },
+            instruction::SetJurorSpecialty {},
+        )
+    }
+
+    pub fn attest_kyc_ix(
+        &self,
+        attestor: &Pubkey,
+        subject: Pubkey,
+        expires_at: i64,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::AttestKyc {
+                config: self.config,
+                kyc_attestation: pda::kyc(&subject),
+                attestor: *attestor,
+                system_program: system_program::ID,
+            },
+            instruction::AttestKyc {
+                subject,
+                expires_at,
+            },
+        )
+    }
+
+    pub fn register_affiliate_ix(&self, owner: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::RegisterAffiliate {
+                affiliate: pda::affiliate(owner),
+                owner: *owner,
+                system_program: system_program::ID,
+            },
+            instruction::RegisterAffiliate {},
+        )
+    }
+
+    /// `hirer` joins the affiliate registered by `owner`
+    pub fn join_affiliate_ix(&self, hirer: &Pubkey, owner: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::JoinAffiliate {
+                affiliate: pda::affiliate(owner),
+                escrow_index: pda::escrow_index(hirer),
+                hirer: *hirer,
+                system_program: system_program::ID,
+            },
+            instruction::JoinAffiliate {},
+        )
+    }
+
+    pub fn claim_affiliate_rewards_ix(&self, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+        let affiliate = pda::affiliate(owner);
+        escrow_instruction(
+            accounts::ClaimAffiliateRewards {
+                affiliate,
+                owner: *owner,
+                mint: *mint,
+                affiliate_vault: pda::ata(&affiliate, mint),
+                owner_token_account: pda::ata(owner, mint),
+                token_program: spl_token::ID,
+            },
+            instruction::ClaimAffiliateRewards {},
+        )
+    }
+
+    /// Create the compressed tree and shared vault for `mint`
+    pub fn claim_badge_ix(&self, owner: &Pubkey, kind: BadgeKind) -> Instruction {
+        let badge_mint = pda::badge(owner, kind);
+        escrow_instruction(
+            accounts::ClaimBadge {
+                config: self.config,
+                earnings: pda::earnings(owner),
+                reputation: pda::reputation(owner),
+                badge_mint,
+                badge_account: pda::ata(owner, &badge_mint),
+                owner: *owner,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::ClaimBadge { kind },
+        )
+    }
+
+    pub fn initialize_leaderboard(&self, env: &mut TestEnv, mint: &Pubkey) -> Pubkey {
+        let leaderboard = pda::leaderboard(mint);
+        let ix = escrow_instruction(
+            accounts::InitializeLeaderboard {
+                config: self.config,
+                leaderboard,
+                mint: *mint,
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeLeaderboard {},
+        );
+        env.process(ix, &[self.admin])
+            .expect("initialize_leaderboard failed");
+        leaderboard
+    }
+
+    pub fn initialize_compressed_tree(&self, env: &mut TestEnv, mint: &Pubkey) -> Pubkey {
+        let tree = pda::compressed_tree(mint);
+        let ix = escrow_instruction(
+            accounts::InitializeCompressedTree {
+                config: self.config,
+                tree,
+                mint: *mint,
+                tree_vault: pda::ata(&tree, mint),
+                admin: self.admin,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::InitializeCompressedTree {},
+        );
+        env.process(ix, &[self.admin])
+            .expect("initialize_compressed_tree failed");
+        tree
+    }
+
+    pub fn create_compressed_escrow_ix(
+        &self,
+        mint: &Pubkey,
+        hirer: &Pubkey,
+        freelancer: &Pubkey,
+        amount: u64,
+        deadline: i64,
+    ) -> Instruction {
+        let tree = pda::compressed_tree(mint);
+        escrow_instruction(
+            accounts::CreateCompressedEscrow {
+                config: self.config,
+                tree,
+                mint: *mint,
+                tree_vault: pda::ata(&tree, mint),
+                hirer: *hirer,
+                hirer_token_account: pda::ata(hirer, mint),
+                token_program: spl_token::ID,
+            },
+            instruction::CreateCompressedEscrow {
+                freelancer: *freelancer,
+                amount,
+                deadline,
+            },
+        )
+    }
+
+    fn settle_compressed_accounts(
+        &self,
+        mint: &Pubkey,
+        hirer: &Pubkey,
+        recipient: &Pubkey,
+    ) -> accounts::SettleCompressedEscrow {
+        let tree = pda::compressed_tree(mint);
+        accounts::SettleCompressedEscrow {
+            tree,
+            mint: *mint,
+            tree_vault: pda::ata(&tree, mint),
+            hirer: *hirer,
+            recipient_token_account: pda::ata(recipient, mint),
+            token_program: spl_token::ID,
+        }
+    }
+
+    pub fn release_compressed_escrow_ix(
+        &self,
+        mint: &Pubkey,
+        leaf: &CompressedEscrowLeaf,
+        proof: Vec<[u8; 32]>,
+    ) -> Instruction {
+        escrow_instruction(
+            self.settle_compressed_accounts(mint, &leaf.hirer, &leaf.freelancer),
+            instruction::ReleaseCompressedEscrow {
+                leaf: leaf.clone(),
+                proof,
+            },
+        )
+    }
+
+    pub fn refund_compressed_escrow_ix(
+        &self,
+        mint: &Pubkey,
+        leaf: &CompressedEscrowLeaf,
+        proof: Vec<[u8; 32]>,
+    ) -> Instruction {
+        escrow_instruction(
+            self.settle_compressed_accounts(mint, &leaf.hirer, &leaf.hirer),
+            instruction::RefundCompressedEscrow {
+                leaf: leaf.clone(),
+                proof,
+            },
+        )
+    }
+}
+
+/// A juror wallet and its stake-mint token account
+pub struct Juror {
+    pub wallet: Pubkey,
+    pub token_account: Pubkey,
+}
+
+/// Builder for a funded escrow between two fresh wallets
+pub struct EscrowBuilder {
+    job_id: String,
+    hirer: Option<Pubkey>,
+    freelancer: Option<Pubkey>,
+    mint: Option<Pubkey>,
+    amount: u64,
+    deadline_in: i64,
+    clawback_window: i64,
+    coupon: Option<Pubkey>,
+    grant: Option<(i64, i64)>,
+    retainer: Option<(i64, i64)>,
+    unfunded: Option<u16>,
+    funder: Option<Pubkey>,
+    rent_payer: Option<Pubkey>,
+    refund_destination: Option<Pubkey>,
+    register_job_id: bool,
+    category: Option<Pubkey>,
+}
+
+impl EscrowBuilder {
+    pub fn new(job_id: &str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            hirer: None,
+            freelancer: None,
+            mint: None,
+            amount: DEFAULT_AMOUNT,
+            deadline_in: 7 * DAY,
+            clawback_window: 0,
+            coupon: None,
+            grant: None,
+            retainer: None,
+            unfunded: None,
+            funder: None,
+            rent_payer: None,
+            refund_destination: None,
+            register_job_id: false,
+            category: None,
+        }
+    }
+
+    /// Fund the escrow from an existing wallet instead of a fresh one
+    pub fn hirer(mut self, hirer: Pubkey) -> Self {
+        self.hirer = Some(hirer);
+        self
+    }
+
+    /// Award the escrow to an existing wallet instead of a fresh one
+    pub fn freelancer(mut self, freelancer: Pubkey) -> Self {
+        self.freelancer = Some(freelancer);
+        self
+    }
+
+    /// Denominate the escrow in an existing mint instead of a fresh one
+    pub fn mint(mut self, mint: Pubkey) -> Self {
+        self.mint = Some(mint);
+        self
+    }
+
+    pub fn amount(mut self, amount: u64) -> Self {
+        self.amount = amount;
+        self
+    }
+
+    /// Seconds from now until the escrow deadline
+    pub fn deadline_in(mut self, seconds: i64) -> Self {
+        self.deadline_in = seconds;
+        self
+    }
+
+    pub fn clawback_window(mut self, seconds: i64) -> Self {
+        self.clawback_window = seconds;
+        self
+    }
+
+    /// Redeem the fee discount coupon at `coupon`
+    pub fn coupon(mut self, coupon: Pubkey) -> Self {
+        self.coupon = Some(coupon);
+        self
+    }
+
+    /// Open the escrow in the taxonomy category at `category`
+    pub fn category(mut self, category: Pubkey) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Create a grant vesting over `vesting` seconds from now instead of a job escrow
+    pub fn grant(mut self, vesting: i64, revocation_window: i64) -> Self {
+        self.grant = Some((vesting, revocation_window));
+        self
+    }
+
+    /// Create a retainer billing `amount` every `period` seconds instead of a job escrow
+    pub fn retainer(mut self, period: i64, notice_period: i64) -> Self {
+        self.retainer = Some((period, notice_period));
+        self
+    }
+
+    /// Send `create_escrow` instead, leaving funding to `fund_ix`
+    pub fn unfunded(self) -> Self {
+        self.funding_threshold(BPS_DENOMINATOR)
+    }
+
+    /// Send `create_escrow`, letting work start once `bps` of the amount is funded
+    pub fn funding_threshold(mut self, bps: u16) -> Self {
+        self.unfunded = Some(bps);
+        self
+    }
+
+    /// Send `create_escrow` with a sponsor who funds it and receives its refunds
+    pub fn funder(mut self, funder: Pubkey) -> Self {
+        self.funder = Some(funder);
+        self.unfunded.get_or_insert(BPS_DENOMINATOR);
+        self
+    }
+
+    /// Have another wallet pay the rent of the escrow and its vault
+    pub fn rent_payer(mut self, rent_payer: Pubkey) -> Self {
+        self.rent_payer = Some(rent_payer);
+        self
+    }
+
+    /// Send refunds to the token account at `destination` instead of the funder's own
+    pub fn refund_destination(mut self, destination: Pubkey) -> Self {
+        self.refund_destination = Some(destination);
+        self
+    }
+
+    /// Claim the job id in the platform-wide job registry
+    pub fn register_job_id(mut self) -> Self {
+        self.register_job_id = true;
+        self
+    }
+
+    /// Create wallets, a mint and token accounts, then send `initialize_escrow`
+    pub fn try_create(self, env: &mut TestEnv) -> (EscrowFixture, Result<(), TransactionError>) {
+        let hirer = self.hirer.unwrap_or_else(|| env.create_wallet());
+        let freelancer = self.freelancer.unwrap_or_else(|| env.create_wallet());
+        let mint = self.mint.unwrap_or_else(|| env.create_mint(MINT_DECIMALS));
+        let escrow = pda::escrow(&hirer, &self.job_id);
+        let price_feed = pda::price_feed(&mint);
+        let price_feed = env.try_get::<PriceFeed>(&price_feed).map(|_| price_feed);
+        let leaderboard = pda::leaderboard(&mint);
+        let leaderboard = env
+            .try_get::<Leaderboard>(&leaderboard)
+            .map(|_| leaderboard);
+        let kyc_attestation = pda::kyc(&hirer);
+        let kyc_attestation = env
+            .try_get::<KycAttestation>(&kyc_attestation)
+            .map(|_| kyc_attestation);
+        let escrow_index = pda::escrow_index(&hirer);
+        let index = env.try_get::<EscrowIndex>(&escrow_index);
+        let open_page = index.as_ref().map_or(0, |index| index.open_page);
+        let affiliate = index.and_then(|index| index.referred_by);
+        let category_page = self.category.map(|category| {
+            let open_page = env
+                .try_get::<CategoryIndex>(&pda::category_index(&category))
+                .map_or(0, |index| index.open_page);
+            pda::category_page(&category, open_page)
+        });
+        let treasury = env.get::<Config>(&pda::config()).treasury;
+        let funder = self.funder.unwrap_or(hirer);
+        let rent_payer = self.rent_payer.unwrap_or(hirer);
+        let (hirer_token_account, funder_token_account) = if funder == hirer {
+            let account = env.create_token_account(&hirer, &mint, self.amount);
+            (account, account)
+        } else {
+            (
+                env.create_token_account(&hirer, &mint, 0),
+                env.create_token_account(&funder, &mint, self.amount),
+            )
+        };
+        let fixture = EscrowFixture {
+            escrow,
+            index: escrow_index,
+            page: pda::escrow_page(&hirer, open_page),
+            category_page,
+            earnings: pda::earnings(&freelancer),
+            vault: pda::vault(&escrow),
+            treasury_token_account: env.create_token_account(&treasury, &mint, 0),
+            affiliate,
+            affiliate_vault: affiliate.map(|affiliate| pda::ata(&affiliate, &mint)),
+            charity_token_account: None,
+            insurance_token_account: None,
+            price_feed,
+            leaderboard,
+            mint,
+            hirer,
+            freelancer,
+            funder,
+            rent_payer,
+            hirer_token_account,
+            funder_token_account,
+            freelancer_token_account: env.create_token_account(&freelancer, &mint, 0),
+            amount: self.amount,
+            job_id: self.job_id,
+        };
+
+        let accounts = accounts::InitializeEscrow {
+            config: pda::config(),
+            escrow,
+            hirer,
+            rent_payer,
+            escrow_index,
+            escrow_page: fixture.page,
+            freelancer_earnings: fixture.earnings,
+            daily_stats: pda::daily_stats(env.now()),
+            hirer_reputation: pda::reputation(&hirer),
+            freelancer_reputation: pda::reputation(&freelancer),
+            freelancer,
+            mint,
+            hirer_token_account: fixture.hirer_token_account,
+            refund_destination: self.refund_destination,
+            escrow_token_account: fixture.vault,
+            affiliate: fixture.affiliate,
+            affiliate_vault: fixture.affiliate_vault,
+            coupon: self.coupon,
+            price_feed: pda::price_feed(&mint),
+            kyc_attestation,
+            job_registration: self
+                .register_job_id
+                .then(|| pda::job_registration(&fixture.job_id)),
+            tombstone: pda::tombstone(&escrow),
+            mint_config: pda::mint_config(&mint),
+            category: self.category,
+            category_index: self.category.as_ref().map(pda::category_index),
+            category_page: fixture.category_page,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            rent: sysvar::rent::ID,
+        };
+        let ix = match (self.grant, self.retainer) {
+            (Some((vesting, revocation_window)), _) => escrow_instruction(
+                accounts,
+                instruction::InitializeGrant {
+                    job_id: fixture.job_id.clone(),
+                    amount: self.amount,
+                    vesting_start: env.now(),
+                    vesting_end: env.now() + vesting,
+                    revocation_window,
+                },
+            ),
+            (None, Some((period_length, notice_period))) => escrow_instruction(
+                accounts,
+                instruction::InitializeRetainer {
+                    job_id: fixture.job_id.clone(),
+                    period_amount: self.amount,
+                    period_length,
+                    notice_period,
+                },
+            ),
+            (None, None) if self.unfunded.is_some() => escrow_instruction(
+                accounts,
+                instruction::CreateEscrow {
+                    job_id: fixture.job_id.clone(),
+                    amount: self.amount,
+                    deadline: env.now() + self.deadline_in,
+                    clawback_window: self.clawback_window,
+                    funding_threshold_bps: self.unfunded.unwrap(),
+                    funder: self.funder,
+                },
+            ),
+            (None, None) => escrow_instruction(
+                accounts,
+                instruction::InitializeEscrow {
+                    job_id: fixture.job_id.clone(),
+                    amount: self.amount,
+                    deadline: env.now() + self.deadline_in,
+                    clawback_window: self.clawback_window,
+                },
+            ),
+        };
+        let result = if rent_payer == hirer {
+            env.process(ix, &[hirer])
+        } else {
+            env.process(ix, &[hirer, rent_payer])
+        };
+        (fixture, result)
+    }
+
+    pub fn create(self, env: &mut TestEnv) -> EscrowFixture {
+        let (fixture, result) = self.try_create(env);
+        result.expect("initialize_escrow failed");
+        fixture
+    }
+}
+
+/// Addresses of a created escrow, with builders for the instructions that act on it
+pub struct EscrowFixture {
+    pub job_id: String,
+    pub escrow: Pubkey,
+    /// Counter and page directory of the hirer that opened the escrow
+    pub index: Pubkey,
+    /// Page of the hirer's escrow index the escrow was listed on
+    pub page: Pubkey,
+    /// Page of its category's index, for escrows opened in a taxonomy category
+    pub category_page: Option<Pubkey>,
+    /// Lifetime earnings tracker of the freelancer
+    pub earnings: Pubkey,
+    pub vault: Pubkey,
+    pub treasury_token_account: Pubkey,
+    /// Affiliate that referred the hirer, with its vault for the escrow mint
+    pub affiliate: Option<Pubkey>,
+    pub affiliate_vault: Option<Pubkey>,
+    /// Charity token account, passed once the hirer routes part of the fee to charity
+    pub charity_token_account: Option<Pubkey>,
+    /// Insurance pool token account, passed when the fee splits include the pool
+    pub insurance_token_account: Option<Pubkey>,
+    /// Price feed of the mint, if one was published before the escrow was created
+    pub price_feed: Option<Pubkey>,
+    /// Leaderboard of the mint, if one was initialized before the escrow was created
+    pub leaderboard: Option<Pubkey>,
+    pub mint: Pubkey,
+    pub hirer: Pubkey,
+    pub freelancer: Pubkey,
+    /// Funds the escrow and receives its refunds; the hirer unless a sponsor was named
+    pub funder: Pubkey,
+    /// Paid the rent of the escrow and its vault; the hirer unless another payer was named
+    pub rent_payer: Pubkey,
+    pub hirer_token_account: Pubkey,
+    pub funder_token_account: Pubkey,
+    pub freelancer_token_account: Pubkey,
+    pub amount: u64,
+}
+
+impl EscrowFixture {
+    pub fn fee_accounts(&self) -> accounts::FeeAccounts {
+        accounts::FeeAccounts {
+            treasury_token_account: self.treasury_token_account,
+            affiliate: self.affiliate,
+            affiliate_vault: self.affiliate_vault,
+            charity_token_account: self.charity_token_account,
+            insurance_token_account: self.insurance_token_account,
+            mint: Some(self.mint),
+            price_feed: self.price_feed,
+            leaderboard: self.leaderboard,
+        }
+    }
+
+    pub fn panel(&self) -> Pubkey {
+        pda::panel(&self.escrow)
+    }
+
+    pub fn assignment(&self, index: u8) -> Pubkey {
+        pda::assignment(&self.panel(), index)
+    }
+
+    pub fn release_ix(&self, platform: &Platform, signer: &Pubkey) -> Instruction {
+        self.release_with_session_ix(platform, signer, None)
+    }
+
+    fn release_accounts(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        session: Option<Pubkey>,
+    ) -> accounts::ReleasePayment {
+        accounts::ReleasePayment {
+            config: platform.config,
+            escrow: self.escrow,
+            escrow_index: self.index,
+            freelancer_earnings: self.earnings,
+            signer: *signer,
+            session,
+            platform_admin: platform.admin,
+            mint: self.mint,
+            escrow_token_account: self.vault,
+            rent_payer: self.rent_payer,
+            freelancer_token_account: self.freelancer_token_account,
+            freelancer: self.freelancer,
+            funder_token_account: self.funder_token_account,
+            fees: self.fee_accounts(),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn release_with_session_ix(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        session: Option<Pubkey>,
+    ) -> Instruction {
+        escrow_instruction(
+            self.release_accounts(platform, signer, session),
+            instruction::ReleasePayment {},
+        )
+    }
+
+    pub fn initiate_dispute_ix(
+        &self,
+        env: &TestEnv,
+        platform: &Platform,
+        signer: &Pubkey,
+    ) -> Instruction {
+        self.initiate_dispute_with_ix(
+            env,
+            platform,
+            signer,
+            DisputeReasonCode::NonDelivery,
+            DisputeStatement::Text("work not delivered".to_string()),
+        )
+    }
+
+    /// Open a dispute as `signer` under `reason_code`, giving `reason` as the statement
+    pub fn initiate_dispute_with_ix(
+        &self,
+        env: &TestEnv,
+        platform: &Platform,
+        signer: &Pubkey,
+        reason_code: DisputeReasonCode,
+        reason: DisputeStatement,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::InitiateDispute {
+                config: platform.config,
+                escrow: self.escrow,
+                signer: *signer,
+                session: None,
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                daily_stats: pda::daily_stats(env.now()),
+                system_program: system_program::ID,
+            },
+            instruction::InitiateDispute {
+                reason_code,
+                reason,
+            },
+        )
+    }
+
+    pub fn escalate_ix(&self, platform: &Platform, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::EscalateDispute {
+                config: platform.config,
+                escrow: self.escrow,
+                signer: *signer,
+                system_program: system_program::ID,
+            },
+            instruction::EscalateDispute {},
+        )
+    }
+
+    /// Attach `mediator` to the dispute's mediation stage, signed by `moderator`
+    pub fn assign_mediator_ix(
+        &self,
+        platform: &Platform,
+        moderator: &Pubkey,
+        mediator: &Pubkey,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::AssignMediator {
+                config: platform.config,
+                escrow: self.escrow,
+                moderator: *moderator,
+            },
+            instruction::AssignMediator {
+                mediator: *mediator,
+            },
+        )
+    }
+
+    /// Record `mediator`'s non-binding split of the disputed escrow
+    pub fn recommend_ix(
+        &self,
+        mediator: &Pubkey,
+        hirer_amount: u64,
+        freelancer_amount: u64,
+        note_hash: [u8; 32],
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::RecommendResolution {
+                escrow: self.escrow,
+                mediator: *mediator,
+            },
+            instruction::RecommendResolution {
+                hirer_amount,
+                freelancer_amount,
+                note_hash,
+            },
+        )
+    }
+
+    /// Propose a settlement split of the dispute, signed by `proposer`
+    pub fn propose_settlement_ix(
+        &self,
+        proposer: &Pubkey,
+        hirer_amount: u64,
+        freelancer_amount: u64,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::ProposeSettlement {
+                escrow: self.escrow,
+                settlement: pda::settlement(&self.escrow),
+                proposer: *proposer,
+                system_program: system_program::ID,
+            },
+            instruction::ProposeSettlement {
+                hirer_amount,
+                freelancer_amount,
+            },
+        )
+    }
+
+    /// Withdraw the open settlement offer, signed by the `proposer` who made it
+    pub fn withdraw_settlement_ix(&self, proposer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::WithdrawSettlement {
+                escrow: self.escrow,
+                settlement: pda::settlement(&self.escrow),
+                proposer: *proposer,
+            },
+            instruction::WithdrawSettlement {},
+        )
+    }
+
+    /// Accept the pending settlement proposal as `signer`
+    pub fn accept_settlement_ix(
+        &self,
+        env: &TestEnv,
+        platform: &Platform,
+        signer: &Pubkey,
+    ) -> Instruction {
+        let settlement = pda::settlement(&self.escrow);
+        let rent_payer = env
+            .try_get::<SettlementProposal>(&settlement)
+            .map_or(*signer, |proposal| proposal.rent_payer);
+        escrow_instruction(
+            accounts::AcceptSettlement {
+                config: platform.config,
+                escrow: self.escrow,
+                settlement,
+                rent_payer,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                signer: *signer,
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                escrow_rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                funder_token_account: self.funder_token_account,
+                fees: self.fee_accounts(),
+                token_program: spl_token::ID,
+            },
+            instruction::AcceptSettlement {},
+        )
+    }
+
+    /// Settle the dispute by the configured small-amount policy, triggered by `signer`
+    pub fn auto_resolve_ix(&self, platform: &Platform, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::AutoResolveDispute {
+                config: platform.config,
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                signer: *signer,
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                funder_token_account: self.funder_token_account,
+                fees: self.fee_accounts(),
+                token_program: spl_token::ID,
+            },
+            instruction::AutoResolveDispute {},
+        )
+    }
+
+    pub fn resolve_ix(
+        &self,
+        platform: &Platform,
+        resolver: &Pubkey,
+        hirer_amount: u64,
+        freelancer_amount: u64,
+    ) -> Instruction {
+        self.resolve_with_ix(
+            platform,
+            resolver,
+            DisputeRuling::Split {
+                hirer_amount,
+                freelancer_amount,
+            },
+        )
+    }
+
+    /// Rule on the dispute with `ruling`, which may be a canonical template
+    pub fn resolve_with_ix(
+        &self,
+        platform: &Platform,
+        resolver: &Pubkey,
+        ruling: DisputeRuling,
+    ) -> Instruction {
+        self.resolve_paying_ix(platform, resolver, ruling, None)
+    }
+
+    /// Rule on the dispute with `ruling`, paying any moderator fee to `arbitrator_token_account`
+    pub fn resolve_paying_ix(
+        &self,
+        platform: &Platform,
+        resolver: &Pubkey,
+        ruling: DisputeRuling,
+        arbitrator_token_account: Option<Pubkey>,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::ResolveDispute {
+                config: platform.config,
+                audit_log: platform.audit_log,
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                admin: *resolver,
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                freelancer: self.freelancer,
+                fees: self.fee_accounts(),
+                funder_token_account: self.funder_token_account,
+                arbitrator_token_account,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            instruction::ResolveDispute { ruling },
+        )
+    }
+
+    pub fn settle_bonds_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::SettleEscalationBonds {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                freelancer: self.freelancer,
+            },
+            instruction::SettleEscalationBonds {},
+        )
+    }
+
+    pub fn enable_dual_deposit_ix(&self, stake: u64) -> Instruction {
+        escrow_instruction(
+            accounts::EnableDualDeposit {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                system_program: system_program::ID,
+            },
+            instruction::EnableDualDeposit { stake },
+        )
+    }
+
+    pub fn post_freelancer_stake_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::PostFreelancerStake {
+                escrow: self.escrow,
+                freelancer: self.freelancer,
+                system_program: system_program::ID,
+            },
+            instruction::PostFreelancerStake {},
+        )
+    }
+
+    pub fn settle_stakes_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::SettleEscalationBonds {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                freelancer: self.freelancer,
+            },
+            instruction::SettleStakes {},
+        )
+    }
+
+    fn change_order_accounts(&self, signer: &Pubkey) -> accounts::ManageChangeOrder {
+        accounts::ManageChangeOrder {
+            config: pda::config(),
+            escrow: self.escrow,
+            signer: *signer,
+            hirer_token_account: self.hirer_token_account,
+            funder_token_account: self.funder_token_account,
+            mint: self.mint,
+            escrow_token_account: self.vault,
+            token_program: spl_token::ID,
+        }
+    }
+
+    /// Propose moving the deadline `deadline_in` seconds from now and changing the amount
+    pub fn propose_change_order_ix(
+        &self,
+        env: &TestEnv,
+        signer: &Pubkey,
+        amount_delta: i64,
+        deadline_in: i64,
+    ) -> Instruction {
+        escrow_instruction(
+            self.change_order_accounts(signer),
+            instruction::ProposeChangeOrder {
+                amount_delta,
+                new_deadline: env.now() + deadline_in,
+                scope_hash: [7; 32],
+            },
+        )
+    }
+
+    pub fn accept_change_order_ix(&self, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            self.change_order_accounts(signer),
+            instruction::AcceptChangeOrder {},
+        )
+    }
+
+    pub fn cancel_change_order_ix(&self, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            self.change_order_accounts(signer),
+            instruction::CancelChangeOrder {},
+        )
+    }
+
+    pub fn enable_crowdfunding_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::EnableCrowdfunding {
+                escrow: self.escrow,
+                hirer: self.hirer,
+            },
+            instruction::EnableCrowdfunding {},
+        )
+    }
+
+    /// Contribute from `contributor`'s token account for the escrow mint
+    pub fn contribute_ix(&self, contributor: &Pubkey, amount: u64) -> Instruction {
+        escrow_instruction(
+            accounts::Contribute {
+                escrow: self.escrow,
+                contribution: pda::contribution(&self.escrow, contributor),
+                contributor: *contributor,
+                contributor_token_account: pda::ata(contributor, &self.mint),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+            },
+            instruction::Contribute { amount },
+        )
+    }
+
+    /// Receipt and token account of each contributor, appended to a refunding instruction
+    /// of a crowdfunded escrow so the refund is split across them
+    pub fn contributor_accounts(&self, contributors: &[Pubkey]) -> Vec<AccountMeta> {
+        contributors
+            .iter()
+            .flat_map(|contributor| {
+                [
+                    AccountMeta::new_readonly(pda::contribution(&self.escrow, contributor), false),
+                    AccountMeta::new(pda::ata(contributor, &self.mint), false),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn withdraw_contribution_ix(&self, contributor: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::WithdrawContribution {
+                escrow: self.escrow,
+                contribution: pda::contribution(&self.escrow, contributor),
+                contributor: *contributor,
+                contributor_token_account: pda::ata(contributor, &self.mint),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                token_program: spl_token::ID,
+            },
+            instruction::WithdrawContribution {},
+        )
+    }
+
+    /// Split the escrow into `milestones`, signed by both parties
+    pub fn set_milestones_ix(&self, milestones: &[MilestoneTerms]) -> Instruction {
+        escrow_instruction(
+            accounts::SetMilestones {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                freelancer: self.freelancer,
+            },
+            instruction::SetMilestones {
+                milestones: milestones.to_vec(),
+            },
+        )
+    }
+
+    /// Replace the milestones not started yet with `milestones`, signed by both parties
+    pub fn amend_milestones_ix(&self, milestones: &[MilestoneTerms]) -> Instruction {
+        escrow_instruction(
+            accounts::SetMilestones {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                freelancer: self.freelancer,
+            },
+            instruction::AmendMilestones {
+                milestones: milestones.to_vec(),
+            },
+        )
+    }
+
+    pub fn submit_milestone_ix(&self, platform: &Platform, index: u8) -> Instruction {
+        escrow_instruction(
+            accounts::RequestRelease {
+                config: platform.config,
+                escrow: self.escrow,
+                signer: self.freelancer,
+                session: None,
+            },
+            instruction::SubmitMilestone { index },
+        )
+    }
+
+    pub fn release_milestone_ix(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        index: u8,
+    ) -> Instruction {
+        escrow_instruction(
+            self.release_accounts(platform, signer, None),
+            instruction::ReleaseMilestone { index },
+        )
+    }
+
+    pub fn claim_milestone_ix(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        index: u8,
+    ) -> Instruction {
+        escrow_instruction(
+            self.claim_accounts(platform, signer, None),
+            instruction::ClaimMilestone { index },
+        )
+    }
+
+    pub fn dispute_milestone_ix(
+        &self,
+        env: &TestEnv,
+        platform: &Platform,
+        signer: &Pubkey,
+        index: u8,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::DisputeMilestone {
+                config: platform.config,
+                escrow: self.escrow,
+                signer: *signer,
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                daily_stats: pda::daily_stats(env.now()),
+                system_program: system_program::ID,
+            },
+            instruction::DisputeMilestone {
+                index,
+                reason_code: DisputeReasonCode::Quality,
+            },
+        )
+    }
+
+    /// Rule on the disputed milestone `index`, giving the freelancer `freelancer_amount` of it
+    pub fn resolve_milestone_dispute_ix(
+        &self,
+        platform: &Platform,
+        resolver: &Pubkey,
+        index: u8,
+        freelancer_amount: u64,
+    ) -> Instruction {
+        self.resolve_milestone_paying_ix(platform, resolver, index, freelancer_amount, None)
+    }
+
+    /// Rule on the disputed milestone `index`, paying any moderator fee to
+    /// `arbitrator_token_account`
+    pub fn resolve_milestone_paying_ix(
+        &self,
+        platform: &Platform,
+        resolver: &Pubkey,
+        index: u8,
+        freelancer_amount: u64,
+        arbitrator_token_account: Option<Pubkey>,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::ResolveMilestoneDispute {
+                config: platform.config,
+                audit_log: platform.audit_log,
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                admin: *resolver,
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                freelancer: self.freelancer,
+                funder_token_account: self.funder_token_account,
+                arbitrator_token_account,
+                fees: self.fee_accounts(),
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            instruction::ResolveMilestoneDispute {
+                index,
+                freelancer_amount,
+            },
+        )
+    }
+
+    pub fn refund_overdue_milestone_ix(&self, index: u8) -> Instruction {
+        escrow_instruction(
+            accounts::RefundOverdueMilestone {
+                escrow: self.escrow,
+                escrow_index: self.index,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                funder_token_account: self.funder_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::RefundOverdueMilestone { index },
+        )
+    }
+
+    pub fn trigger_default_ix(&self, platform: &Platform) -> Instruction {
+        escrow_instruction(
+            accounts::TriggerDefault {
+                config: platform.config,
+                escrow: self.escrow,
+                escrow_index: self.index,
+                hirer: self.hirer,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                funder_token_account: self.funder_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::TriggerDefault {},
+        )
+    }
+
+    pub fn emergency_refund_ix(
+        &self,
+        platform: &Platform,
+        admin: &Pubkey,
+        reason: EmergencyRefundReason,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::EmergencyRefund {
+                config: platform.config,
+                audit_log: platform.audit_log,
+                escrow: self.escrow,
+                escrow_index: self.index,
+                refund_usage: pda::admin_refund_usage(admin),
+                admin: *admin,
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                funder_token_account: self.funder_token_account,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::EmergencyRefund { reason },
+        )
+    }
+
+    pub fn request_refund_ix(&self) -> Instruction {
+        self.request_refund_with_ix("project cancelled")
+    }
+
+    /// Ask for a refund giving `reason`
+    pub fn request_refund_with_ix(&self, reason: &str) -> Instruction {
+        escrow_instruction(
+            accounts::RequestRefund {
+                escrow: self.escrow,
+                hirer: self.hirer,
+            },
+            instruction::RequestRefund {
+                reason: reason.to_string(),
+            },
+        )
+    }
+
+    pub fn approve_refund_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::ApproveRefund {
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer: self.freelancer,
+                hirer: self.hirer,
+                rent_payer: self.rent_payer,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                funder_token_account: self.funder_token_account,
+                escrow_page: self.page,
+                category_page: self.category_page,
+                token_program: spl_token::ID,
+                tombstone: pda::tombstone(&self.escrow),
+                system_program: system_program::ID,
+            },
+            instruction::ApproveRefund {},
+        )
+    }
+
+    pub fn fund_ix(&self, amount: u64) -> Instruction {
+        escrow_instruction(
+            accounts::FundEscrow {
+                escrow: self.escrow,
+                funder: self.funder,
+                funder_token_account: self.funder_token_account,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                token_program: spl_token::ID,
+            },
+            instruction::FundEscrow { amount },
+        )
+    }
+
+    pub fn close_unfunded_ix(&self, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::CloseUnfundedEscrow {
+                escrow: self.escrow,
+                escrow_index: self.index,
+                escrow_page: self.page,
+                category_page: self.category_page,
+                signer: *signer,
+                hirer: self.hirer,
+                rent_payer: self.rent_payer,
+                freelancer: self.freelancer,
+                funder_token_account: self.funder_token_account,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                token_program: spl_token::ID,
+                tombstone: pda::tombstone(&self.escrow),
+                system_program: system_program::ID,
+            },
+            instruction::CloseUnfundedEscrow {},
+        )
+    }
+
+    pub fn close_settled_ix(&self, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::CloseSettledEscrow {
+                escrow: self.escrow,
+                escrow_page: self.page,
+                category_page: self.category_page,
+                panel: self.panel(),
+                signer: *signer,
+                rent_payer: self.rent_payer,
+                escrow_token_account: self.vault,
+                tombstone: pda::tombstone(&self.escrow),
+                system_program: system_program::ID,
+            },
+            instruction::CloseSettledEscrow {},
+        )
+    }
+
+    /// Close a settled escrow whose dispute went to a panel, passing each juror assignment
+    pub fn close_settled_after_panel_ix(&self, signer: &Pubkey, panel: &JurorPanel) -> Instruction {
+        let mut ix = self.close_settled_ix(signer);
+        ix.accounts.extend(
+            (0..panel.jurors.len() as u8)
+                .map(|i| AccountMeta::new_readonly(self.assignment(i), false)),
+        );
+        ix
+    }
+
+    /// Agree SLA terms, signed by both parties
+    pub fn set_sla_terms_ix(&self, terms: SlaTerms) -> Instruction {
+        escrow_instruction(
+            accounts::SetSlaTerms {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                freelancer: self.freelancer,
+            },
+            instruction::SetSlaTerms { terms },
+        )
+    }
+
+    /// Agree a business calendar, signed by both parties
+    pub fn set_business_calendar_ix(&self, weekend_mask: u8, holidays: &[u16]) -> Instruction {
+        escrow_instruction(
+            accounts::SetBusinessCalendar {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                freelancer: self.freelancer,
+            },
+            instruction::SetBusinessCalendar {
+                weekend_mask,
+                holidays: holidays.to_vec(),
+            },
+        )
+    }
+
+    pub fn reject_submission_ix(&self, reason_hash: [u8; 32]) -> Instruction {
+        escrow_instruction(
+            accounts::RejectSubmission {
+                config: pda::config(),
+                escrow: self.escrow,
+                hirer: self.hirer,
+            },
+            instruction::RejectSubmission { reason_hash },
+        )
+    }
+
+    /// Move the escrow's funds from `legacy_vault` onto its program-derived vault
+    pub fn migrate_vault_ix(&self, legacy_vault: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::MigrateVault {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                mint: self.mint,
+                legacy_vault: *legacy_vault,
+                vault: pda::vault(&self.escrow),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::MigrateVault {},
+        )
+    }
+
+    pub fn set_metadata_ix(&self, category: EscrowCategory, title: &str) -> Instruction {
+        escrow_instruction(
+            accounts::SetEscrowMetadata {
+                escrow: self.escrow,
+                hirer: self.hirer,
+            },
+            instruction::SetEscrowMetadata {
+                category,
+                title: title.to_string(),
+            },
+        )
+    }
+
+    /// Tag the escrow with the taxonomy skills at `skills`
+    pub fn set_skills_ix(&self, skills: &[Pubkey]) -> Instruction {
+        let mut ix = escrow_instruction(
+            accounts::SetEscrowMetadata {
+                escrow: self.escrow,
+                hirer: self.hirer,
+            },
+            instruction::SetEscrowSkills {},
+        );
+        ix.accounts.extend(
+            skills
+                .iter()
+                .map(|skill| AccountMeta::new_readonly(*skill, false)),
+        );
+        ix
+    }
+
+    pub fn set_refund_destination_ix(&self, destination: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::SetRefundDestination {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                funder: self.funder,
+                destination: *destination,
+            },
+            instruction::SetRefundDestination {},
+        )
+    }
+
+    pub fn set_payout_destination_ix(&self, destination: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::SetPayoutDestination {
+                escrow: self.escrow,
+                freelancer: self.freelancer,
+                escrow_token_account: self.vault,
+                destination: *destination,
+            },
+            instruction::SetPayoutDestination {},
+        )
+    }
+
+    pub fn request_release_ix(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        session: Option<Pubkey>,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::RequestRelease {
+                config: platform.config,
+                escrow: self.escrow,
+                signer: *signer,
+                session,
+            },
+            instruction::RequestRelease {},
+        )
+    }
+
+    fn claim_accounts(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        session: Option<Pubkey>,
+    ) -> accounts::ClaimAutoRelease {
+        accounts::ClaimAutoRelease {
+            config: platform.config,
+            escrow: self.escrow,
+            escrow_index: self.index,
+            freelancer_earnings: self.earnings,
+            signer: *signer,
+            session,
+            mint: self.mint,
+            escrow_token_account: self.vault,
+            rent_payer: self.rent_payer,
+            freelancer_token_account: self.freelancer_token_account,
+            funder_token_account: self.funder_token_account,
+            fees: self.fee_accounts(),
+            token_program: spl_token::ID,
+        }
+    }
+
+    pub fn claim_auto_release_ix(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        session: Option<Pubkey>,
+    ) -> Instruction {
+        escrow_instruction(
+            self.claim_accounts(platform, signer, session),
+            instruction::ClaimAutoRelease {},
+        )
+    }
+
+    fn propose_accounts(&self, platform: &Platform, signer: &Pubkey) -> accounts::ProposeRelease {
+        accounts::ProposeRelease {
+            config: platform.config,
+            escrow: self.escrow,
+            signer: *signer,
+        }
+    }
+
+    pub fn propose_release_ix(&self, platform: &Platform, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            self.propose_accounts(platform, signer),
+            instruction::ProposeRelease {},
+        )
+    }
+
+    pub fn cancel_release_proposal_ix(&self, platform: &Platform, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            self.propose_accounts(platform, signer),
+            instruction::CancelReleaseProposal {},
+        )
+    }
+
+    fn moderate_accounts(
+        &self,
+        platform: &Platform,
+        moderator: &Pubkey,
+    ) -> accounts::ModerateEscrow {
+        accounts::ModerateEscrow {
+            config: platform.config,
+            audit_log: platform.audit_log,
+            escrow: self.escrow,
+            moderator: *moderator,
+        }
+    }
+
+    pub fn freeze_ix(&self, platform: &Platform, moderator: &Pubkey, duration: i64) -> Instruction {
+        escrow_instruction(
+            self.moderate_accounts(platform, moderator),
+            instruction::FreezeEscrow { duration },
+        )
+    }
+
+    pub fn unfreeze_ix(&self, platform: &Platform, moderator: &Pubkey) -> Instruction {
+        escrow_instruction(
+            self.moderate_accounts(platform, moderator),
+            instruction::UnfreezeEscrow {},
+        )
+    }
+
+    pub fn withdraw_held_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::WithdrawHeldPayment {
+                config: pda::config(),
+                escrow: self.escrow,
+                freelancer_earnings: self.earnings,
+                freelancer: self.freelancer,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                fees: self.fee_accounts(),
+                token_program: spl_token::ID,
+            },
+            instruction::WithdrawHeldPayment {},
+        )
+    }
+
+    pub fn set_charity_routing_ix(&self, enabled: bool) -> Instruction {
+        escrow_instruction(
+            accounts::SetCharityRouting {
+                escrow: self.escrow,
+                hirer: self.hirer,
+            },
+            instruction::SetCharityRouting { enabled },
+        )
+    }
+
+    pub fn claim_vested_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::ClaimVested {
+                config: pda::config(),
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                freelancer: self.freelancer,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                fees: self.fee_accounts(),
+                token_program: spl_token::ID,
+            },
+            instruction::ClaimVested {},
+        )
+    }
+
+    pub fn revoke_grant_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::RevokeGrant {
+                escrow: self.escrow,
+                escrow_index: self.index,
+                hirer: self.hirer,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                hirer_token_account: self.hirer_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::RevokeGrant {},
+        )
+    }
+
+    pub fn cancel_grant_ix(&self, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::CancelGrant {
+                config: pda::config(),
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                signer: *signer,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                hirer_token_account: self.hirer_token_account,
+                fees: self.fee_accounts(),
+                token_program: spl_token::ID,
+            },
+            instruction::CancelGrant {},
+        )
+    }
+
+    pub fn enable_auto_renew_ix(&self, max_periods: u32) -> Instruction {
+        escrow_instruction(
+            accounts::EnableRetainerAutoRenew {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                escrow_token_account: self.vault,
+                hirer_token_account: self.hirer_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::EnableRetainerAutoRenew { max_periods },
+        )
+    }
+
+    pub fn cancel_retainer_ix(&self, signer: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::CancelRetainer {
+                escrow: self.escrow,
+                signer: *signer,
+            },
+            instruction::CancelRetainer {},
+        )
+    }
+
+    pub fn renew_retainer_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::RenewRetainer {
+                config: pda::config(),
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                hirer_token_account: self.hirer_token_account,
+                fees: self.fee_accounts(),
+                token_program: spl_token::ID,
+            },
+            instruction::RenewRetainer {},
+        )
+    }
+
+    pub fn issue_invoice_ix(&self, invoice_no: u32, line_items_hash: [u8; 32]) -> Instruction {
+        escrow_instruction(
+            accounts::IssueInvoice {
+                escrow: self.escrow,
+                invoice: pda::invoice(&self.escrow, invoice_no),
+                freelancer: self.freelancer,
+                system_program: system_program::ID,
+            },
+            instruction::IssueInvoice { line_items_hash },
+        )
+    }
+
+    pub fn clawback_ix(&self, platform: &Platform) -> Instruction {
+        escrow_instruction(
+            accounts::ClawbackPayment {
+                config: platform.config,
+                audit_log: platform.audit_log,
+                escrow: self.escrow,
+                admin: platform.admin,
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                funder_token_account: self.funder_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::ClawbackPayment {
+                reason: "chargeback".to_string(),
+            },
+        )
+    }
+
+    pub fn transfer_role_ix(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        new_key: &Pubkey,
+        counterparty: Option<Pubkey>,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::TransferRole {
+                config: platform.config,
+                escrow: self.escrow,
+                signer: *signer,
+                counterparty,
+                new_key_earnings: pda::earnings(new_key),
+                new_key_reputation: pda::reputation(new_key),
+                system_program: system_program::ID,
+            },
+            instruction::TransferRole { new_key: *new_key },
+        )
+    }
+
+    pub fn change_beneficiary_ix(
+        &self,
+        role: EscrowRole,
+        new_key: &Pubkey,
+        destination: Option<Pubkey>,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::ChangeBeneficiary {
+                escrow: self.escrow,
+                hirer: self.hirer,
+                freelancer: self.freelancer,
+                destination,
+                new_key_earnings: pda::earnings(new_key),
+                new_key_reputation: pda::reputation(new_key),
+                system_program: system_program::ID,
+            },
+            instruction::ChangeBeneficiary {
+                role,
+                new_key: *new_key,
+            },
+        )
+    }
+
+    pub fn advance_nonce_ix(&self, signer: &Pubkey, nonce: u64) -> Instruction {
+        escrow_instruction(
+            accounts::AdvanceNonce {
+                escrow: self.escrow,
+                signer: *signer,
+            },
+            instruction::AdvanceNonce { nonce },
+        )
+    }
+
+    pub fn assert_invariants_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::AssertInvariants {
+                escrow: self.escrow,
+                escrow_token_account: self.vault,
+            },
+            instruction::AssertInvariants {},
+        )
+    }
+
+    /// Draw a panel; the platform's `jurors_per_panel` assignment PDAs are appended
+    pub fn draw_jurors_ix(
+        &self,
+        platform: &Platform,
+        signer: &Pubkey,
+        panel_size: u8,
+    ) -> Instruction {
+        let mut ix = escrow_instruction(
+            accounts::DrawJurors {
+                config: platform.config,
+                juror_pool: platform.juror_pool,
+                escrow: self.escrow,
+                panel: self.panel(),
+                signer: *signer,
+                payer: *signer,
+                recent_slothashes: sysvar::slot_hashes::ID,
+                system_program: system_program::ID,
+            },
+            instruction::DrawJurors {},
+        );
+        ix.accounts
+            .extend((0..panel_size).map(|i| AccountMeta::new(self.assignment(i), false)));
+        ix
+    }
+
+    pub fn accept_assignment_ix(&self, index: u8, juror: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::AcceptAssignment {
+                escrow: self.escrow,
+                assignment: self.assignment(index),
+                juror: *juror,
+            },
+            instruction::AcceptAssignment {},
+        )
+    }
+
+    fn cast_vote_accounts(&self, index: u8, juror: &Pubkey) -> accounts::CastVote {
+        accounts::CastVote {
+            escrow: self.escrow,
+            panel: self.panel(),
+            assignment: self.assignment(index),
+            juror: *juror,
+        }
+    }
+
+    pub fn commit_vote_ix(&self, index: u8, juror: &Pubkey, commitment: [u8; 32]) -> Instruction {
+        escrow_instruction(
+            self.cast_vote_accounts(index, juror),
+            instruction::CommitVote { commitment },
+        )
+    }
+
+    pub fn reveal_vote_ix(
+        &self,
+        index: u8,
+        juror: &Pubkey,
+        freelancer_bps: u16,
+        salt: [u8; 32],
+    ) -> Instruction {
+        escrow_instruction(
+            self.cast_vote_accounts(index, juror),
+            instruction::RevealVote {
+                freelancer_bps,
+                salt,
+            },
+        )
+    }
+
+    pub fn slash_juror_ix(&self, platform: &Platform, index: u8) -> Instruction {
+        escrow_instruction(
+            accounts::SlashJuror {
+                config: platform.config,
+                juror_pool: platform.juror_pool,
+                escrow: self.escrow,
+                panel: self.panel(),
+                assignment: self.assignment(index),
+                stake_mint: platform.stake_mint,
+                pool_vault: platform.pool_vault,
+                treasury_token_account: platform.treasury_stake_account,
+                token_program: spl_token::ID,
+            },
+            instruction::SlashJuror {},
+        )
+    }
+
+    pub fn close_assignment_ix(
+        &self,
+        platform: &Platform,
+        index: u8,
+        payer: &Pubkey,
+    ) -> Instruction {
+        escrow_instruction(
+            accounts::CloseJurorAssignment {
+                juror_pool: platform.juror_pool,
+                escrow: self.escrow,
+                panel: self.panel(),
+                assignment: self.assignment(index),
+                payer: *payer,
+            },
+            instruction::CloseJurorAssignment {},
+        )
+    }
+
+    /// Tally the panel; every assignment PDA of `panel` is appended
+    pub fn tally_ix(&self, panel: &JurorPanel) -> Instruction {
+        let mut ix = escrow_instruction(
+            accounts::TallyPanelVotes {
+                config: pda::config(),
+                escrow: self.escrow,
+                escrow_index: self.index,
+                freelancer_earnings: self.earnings,
+                panel: self.panel(),
+                hirer_reputation: pda::reputation(&self.hirer),
+                freelancer_reputation: pda::reputation(&self.freelancer),
+                mint: self.mint,
+                escrow_token_account: self.vault,
+                rent_payer: self.rent_payer,
+                freelancer_token_account: self.freelancer_token_account,
+                fees: self.fee_accounts(),
+                funder_token_account: self.funder_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::TallyPanelVotes {},
+        );
+        ix.accounts.extend(
+            (0..panel.jurors.len() as u8)
+                .map(|i| AccountMeta::new_readonly(self.assignment(i), false)),
+        );
+        ix
+    }
+
+    /// Tally the panel's votes, passing each juror's token account for the juror fee
+    pub fn tally_paying_ix(&self, panel: &JurorPanel) -> Instruction {
+        let mut ix = self.tally_ix(panel);
+        ix.accounts.extend(
+            panel
+                .jurors
+                .iter()
+                .map(|juror| AccountMeta::new(pda::ata(juror, &self.mint), false)),
+        );
+        ix
+    }
+}
+
+/// Addresses of a funded contest, with builders for the instructions that act on it
+pub struct ContestFixture {
+    pub contest_id: String,
+    pub contest: Pubkey,
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub hirer: Pubkey,
+    pub hirer_token_account: Pubkey,
+    pub judge: Pubkey,
+}
+
+impl ContestFixture {
+    /// Fund a contest from a fresh hirer with a fresh mint, closing submissions after
+    /// `submission_window` seconds. The judge defaults to the hirer.
+    pub fn try_create(
+        env: &mut TestEnv,
+        contest_id: &str,
+        prize: u64,
+        placements: &[u16],
+        submission_window: i64,
+        judge: Option<Pubkey>,
+    ) -> (Self, Result<(), TransactionError>) {
+        let hirer = env.create_wallet();
+        let mint = env.create_mint(MINT_DECIMALS);
+        let contest = pda::contest(contest_id);
+        let fixture = ContestFixture {
+            contest_id: contest_id.to_string(),
+            contest,
+            vault: pda::ata(&contest, &mint),
+            mint,
+            hirer,
+            hirer_token_account: env.create_token_account(&hirer, &mint, prize),
+            judge: judge.unwrap_or(hirer),
+        };
+        let ix = escrow_instruction(
+            accounts::CreateContest {
+                contest,
+                hirer,
+                mint,
+                hirer_token_account: fixture.hirer_token_account,
+                contest_vault: fixture.vault,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::CreateContest {
+                contest_id: fixture.contest_id.clone(),
+                prize,
+                placements: placements.to_vec(),
+                submission_deadline: env.now() + submission_window,
+                judge,
+            },
+        );
+        let result = env.process(ix, &[hirer]);
+        (fixture, result)
+    }
+
+    pub fn create(
+        env: &mut TestEnv,
+        contest_id: &str,
+        prize: u64,
+        placements: &[u16],
+        submission_window: i64,
+    ) -> Self {
+        let (fixture, result) =
+            Self::try_create(env, contest_id, prize, placements, submission_window, None);
+        result.expect("create_contest failed");
+        fixture
+    }
+
+    /// Enter a fresh wallet, with an empty token account for its prize
+    pub fn enter(&self, env: &mut TestEnv, submission_hash: [u8; 32]) -> Pubkey {
+        let entrant = env.create_wallet();
+        env.create_token_account(&entrant, &self.mint, 0);
+        env.process(self.submit_entry_ix(&entrant, submission_hash), &[entrant])
+            .expect("submit_contest_entry failed");
+        entrant
+    }
+
+    pub fn submit_entry_ix(&self, entrant: &Pubkey, submission_hash: [u8; 32]) -> Instruction {
+        escrow_instruction(
+            accounts::SubmitContestEntry {
+                contest: self.contest,
+                entry: pda::contest_entry(&self.contest, entrant),
+                entrant: *entrant,
+                system_program: system_program::ID,
+            },
+            instruction::SubmitContestEntry { submission_hash },
+        )
+    }
+
+    /// Award placements to `winners` in order, first place first
+    pub fn select_winners_ix(&self, judge: &Pubkey, winners: &[Pubkey]) -> Instruction {
+        let mut ix = escrow_instruction(
+            accounts::SelectContestWinners {
+                contest: self.contest,
+                judge: *judge,
+                mint: self.mint,
+                contest_vault: self.vault,
+                hirer_token_account: self.hirer_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::SelectContestWinners {},
+        );
+        ix.accounts.extend(winners.iter().flat_map(|winner| {
+            [
+                AccountMeta::new_readonly(pda::contest_entry(&self.contest, winner), false),
+                AccountMeta::new(pda::ata(winner, &self.mint), false),
+            ]
+        }));
+        ix
+    }
+
+    /// Pay the prize pool out as `(recipient, amount)` pairs
+    pub fn distribute_pool_ix(&self, judge: &Pubkey, payouts: &[(Pubkey, u64)]) -> Instruction {
+        let mut ix = escrow_instruction(
+            accounts::DistributePool {
+                contest: self.contest,
+                judge: *judge,
+                mint: self.mint,
+                contest_vault: self.vault,
+                token_program: spl_token::ID,
+            },
+            instruction::DistributePool {
+                payouts: payouts
+                    .iter()
+                    .map(|(recipient, amount)| PoolPayout {
+                        recipient: *recipient,
+                        amount: *amount,
+                    })
+                    .collect(),
+            },
+        );
+        ix.accounts.extend(
+            payouts
+                .iter()
+                .map(|(recipient, _)| AccountMeta::new(pda::ata(recipient, &self.mint), false)),
+        );
+        ix
+    }
+
+    pub fn cancel_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::CancelContest {
+                contest: self.contest,
+                hirer: self.hirer,
+                mint: self.mint,
+                contest_vault: self.vault,
+                hirer_token_account: self.hirer_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::CancelContest {},
+        )
+    }
+}
+
+/// Addresses of a funded bounty, with builders for the instructions that act on it
+pub struct BountyFixture {
+    pub bounty_id: String,
+    pub bounty: Pubkey,
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub hirer: Pubkey,
+    pub hirer_token_account: Pubkey,
+}
+
+impl BountyFixture {
+    /// Fund a bounty from a fresh hirer with a fresh mint, open to claims for `claim_window`
+    /// seconds
+    pub fn create(env: &mut TestEnv, bounty_id: &str, reward: u64, claim_window: i64) -> Self {
+        let hirer = env.create_wallet();
+        let mint = env.create_mint(MINT_DECIMALS);
+        let bounty = pda::bounty(bounty_id);
+        let fixture = BountyFixture {
+            bounty_id: bounty_id.to_string(),
+            bounty,
+            vault: pda::ata(&bounty, &mint),
+            mint,
+            hirer,
+            hirer_token_account: env.create_token_account(&hirer, &mint, reward),
+        };
+        let ix = escrow_instruction(
+            accounts::CreateBounty {
+                bounty,
+                hirer,
+                mint,
+                hirer_token_account: fixture.hirer_token_account,
+                bounty_vault: fixture.vault,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::CreateBounty {
+                bounty_id: fixture.bounty_id.clone(),
+                reward,
+                claim_deadline: env.now() + claim_window,
+            },
+        );
+        env.process(ix, &[hirer]).expect("create_bounty failed");
+        fixture
+    }
+
+    /// Claim from a fresh wallet, with an empty token account for the reward
+    pub fn claim(&self, env: &mut TestEnv, proof_hash: [u8; 32]) -> Pubkey {
+        let claimant = env.create_wallet();
+        env.create_token_account(&claimant, &self.mint, 0);
+        env.process(self.submit_claim_ix(&claimant, proof_hash), &[claimant])
+            .expect("submit_bounty_claim failed");
+        claimant
+    }
+
+    pub fn submit_claim_ix(&self, claimant: &Pubkey, proof_hash: [u8; 32]) -> Instruction {
+        escrow_instruction(
+            accounts::SubmitBountyClaim {
+                bounty: self.bounty,
+                claim: pda::bounty_claim(&self.bounty, claimant),
+                claimant: *claimant,
+                system_program: system_program::ID,
+            },
+            instruction::SubmitBountyClaim { proof_hash },
+        )
+    }
+
+    pub fn approve_ix(&self, claimant: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::ApproveBountyClaim {
+                bounty: self.bounty,
+                claim: pda::bounty_claim(&self.bounty, claimant),
+                hirer: self.hirer,
+                mint: self.mint,
+                bounty_vault: self.vault,
+                claimant_token_account: pda::ata(claimant, &self.mint),
+                token_program: spl_token::ID,
+            },
+            instruction::ApproveBountyClaim {},
+        )
+    }
+
+    pub fn reject_ix(&self, claimant: &Pubkey) -> Instruction {
+        escrow_instruction(
+            accounts::RejectBountyClaim {
+                bounty: self.bounty,
+                claim: pda::bounty_claim(&self.bounty, claimant),
+                hirer: self.hirer,
+                claimant: *claimant,
+            },
+            instruction::RejectBountyClaim {},
+        )
+    }
+
+    pub fn cancel_ix(&self) -> Instruction {
+        escrow_instruction(
+            accounts::CancelBounty {
+                bounty: self.bounty,
+                hirer: self.hirer,
+                mint: self.mint,
+                bounty_vault: self.vault,
+                hirer_token_account: self.hirer_token_account,
+                token_program: spl_token::ID,
+            },
+            instruction::CancelBounty {},
+        )
+    }
+}
+
+pub fn assert_min_version_ix(min_version: u16) -> Instruction {
+    escrow_instruction(
+        accounts::AssertVersion {},
+        instruction::AssertMinVersion { min_version },
+    )
+}
@@ -0,0 +1,16 @@
+//! Test fixtures for the TaskFi escrow program.
+//!
+//! `TestEnv` runs the program natively with the SPL programs it calls, so end-to-end tests
+//! execute real instruction handlers, CPIs and account constraints without a validator.
+//! The fixtures set up mints and token accounts, the platform config and funded escrows,
+//! and build every escrow instruction.
+
+pub mod fixtures;
+pub mod merkle;
+pub mod pda;
+pub mod svm;
+pub mod token;
+
+pub use fixtures::*;
+pub use merkle::TreeMirror;
+pub use svm::{AccountState, TestEnv, TransactionError, GENESIS_TIMESTAMP};
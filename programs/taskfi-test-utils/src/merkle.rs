@@ -0,0 +1,70 @@
+//! Off-chain mirror of a compressed-escrow tree, used to build settlement proofs
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+use taskfi_escrow::{CompressedEscrowLeaf, CompressedTree};
+
+/// Every leaf of one compressed tree, replayed from its events
+pub struct TreeMirror {
+    tree: Pubkey,
+    leaves: Vec<[u8; 32]>,
+}
+
+impl TreeMirror {
+    pub fn new(tree: Pubkey) -> Self {
+        Self {
+            tree,
+            leaves: Vec::new(),
+        }
+    }
+
+    pub fn append(&mut self, leaf: &CompressedEscrowLeaf) {
+        assert_eq!(
+            leaf.index as usize,
+            self.leaves.len(),
+            "leaves must be appended in order"
+        );
+        self.leaves.push(leaf.hash(&self.tree));
+    }
+
+    /// Record that a leaf was settled and emptied on chain
+    pub fn settle(&mut self, index: u32) {
+        self.leaves[index as usize] = [0u8; 32];
+    }
+
+    /// Sibling path from the leaf at `index` up to the root
+    pub fn proof(&self, index: u32) -> Vec<[u8; 32]> {
+        let mut proof = Vec::with_capacity(CompressedTree::DEPTH);
+        let mut level = self.leaves.clone();
+        let mut zero = [0u8; 32];
+        let mut position = index as usize;
+        for _ in 0..CompressedTree::DEPTH {
+            proof.push(level.get(position ^ 1).copied().unwrap_or(zero));
+            level = level
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&zero)))
+                .collect();
+            zero = hash_pair(&zero, &zero);
+            position >>= 1;
+        }
+        proof
+    }
+
+    /// Root the on-chain tree should report
+    pub fn root(&self) -> [u8; 32] {
+        let mut level = self.leaves.clone();
+        let mut zero = [0u8; 32];
+        for _ in 0..CompressedTree::DEPTH {
+            level = level
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&zero)))
+                .collect();
+            zero = hash_pair(&zero, &zero);
+        }
+        level.first().copied().unwrap_or(zero)
+    }
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[left, right]).to_bytes()
+}
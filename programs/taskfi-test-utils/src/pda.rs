@@ -0,0 +1,164 @@
+//! Program-derived addresses used by the escrow program
+
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{job_id_hash, BadgeKind, DailyStats, TaxonomyKind};
+
+fn find(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &taskfi_escrow::ID).0
+}
+
+pub fn config() -> Pubkey {
+    find(&[b"config"])
+}
+
+pub fn juror_pool() -> Pubkey {
+    find(&[b"juror_pool"])
+}
+
+pub fn audit_log() -> Pubkey {
+    find(&[b"audit_log"])
+}
+
+pub fn escrow(hirer: &Pubkey, job_id: &str) -> Pubkey {
+    find(&[b"escrow", hirer.as_ref(), &job_id_hash(job_id)])
+}
+
+/// Job id registry entry, resolving a job id to its escrow
+pub fn job_registration(job_id: &str) -> Pubkey {
+    find(&[b"job", &job_id_hash(job_id)])
+}
+
+/// Left behind by a closed escrow so its address cannot be opened again
+pub fn tombstone(escrow: &Pubkey) -> Pubkey {
+    find(&[b"tombstone", escrow.as_ref()])
+}
+
+pub fn escrow_index(owner: &Pubkey) -> Pubkey {
+    find(&[b"escrow_index", owner.as_ref()])
+}
+
+pub fn escrow_page(owner: &Pubkey, page_no: u32) -> Pubkey {
+    find(&[b"escrow_page", owner.as_ref(), &page_no.to_le_bytes()])
+}
+
+pub fn earnings(freelancer: &Pubkey) -> Pubkey {
+    find(&[b"earnings", freelancer.as_ref()])
+}
+
+pub fn application(hirer: &Pubkey, job_id: &str, applicant: &Pubkey) -> Pubkey {
+    find(&[
+        b"application",
+        hirer.as_ref(),
+        &job_id_hash(job_id),
+        applicant.as_ref(),
+    ])
+}
+
+pub fn invoice(escrow: &Pubkey, invoice_no: u32) -> Pubkey {
+    find(&[b"invoice", escrow.as_ref(), &invoice_no.to_le_bytes()])
+}
+
+pub fn affiliate(owner: &Pubkey) -> Pubkey {
+    find(&[b"affiliate", owner.as_ref()])
+}
+
+pub fn coupon(code_hash: &[u8; 32]) -> Pubkey {
+    find(&[b"coupon", code_hash])
+}
+
+pub fn price_feed(mint: &Pubkey) -> Pubkey {
+    find(&[b"price_feed", mint.as_ref()])
+}
+
+/// Mint of the badge `owner` claimed for `kind`
+pub fn badge(owner: &Pubkey, kind: BadgeKind) -> Pubkey {
+    find(&[b"badge", owner.as_ref(), &[kind as u8]])
+}
+
+pub fn leaderboard(mint: &Pubkey) -> Pubkey {
+    find(&[b"leaderboard", mint.as_ref()])
+}
+
+pub fn mint_config(mint: &Pubkey) -> Pubkey {
+    find(&[b"mint_config", mint.as_ref()])
+}
+
+pub fn category_index(category: &Pubkey) -> Pubkey {
+    find(&[b"category_index", category.as_ref()])
+}
+
+pub fn category_page(category: &Pubkey, page_no: u32) -> Pubkey {
+    find(&[b"category_page", category.as_ref(), &page_no.to_le_bytes()])
+}
+
+pub fn settlement(escrow: &Pubkey) -> Pubkey {
+    find(&[b"settlement", escrow.as_ref()])
+}
+
+pub fn taxonomy(kind: TaxonomyKind, index: u16) -> Pubkey {
+    find(&[b"taxonomy", &[kind as u8], &index.to_le_bytes()])
+}
+
+/// Stats bucket for the day containing `unix_timestamp`
+pub fn daily_stats(unix_timestamp: i64) -> Pubkey {
+    let day = DailyStats::day_of(unix_timestamp);
+    find(&[b"daily_stats", &day.to_le_bytes()])
+}
+
+pub fn bounty(bounty_id: &str) -> Pubkey {
+    find(&[b"bounty", bounty_id.as_bytes()])
+}
+
+pub fn bounty_claim(bounty: &Pubkey, claimant: &Pubkey) -> Pubkey {
+    find(&[b"bounty_claim", bounty.as_ref(), claimant.as_ref()])
+}
+
+pub fn contest(contest_id: &str) -> Pubkey {
+    find(&[b"contest", contest_id.as_bytes()])
+}
+
+pub fn contest_entry(contest: &Pubkey, entrant: &Pubkey) -> Pubkey {
+    find(&[b"contest_entry", contest.as_ref(), entrant.as_ref()])
+}
+
+pub fn panel(escrow: &Pubkey) -> Pubkey {
+    find(&[b"panel", escrow.as_ref()])
+}
+
+pub fn assignment(panel: &Pubkey, index: u8) -> Pubkey {
+    find(&[b"assignment", panel.as_ref(), &[index]])
+}
+
+pub fn session(owner: &Pubkey, session_key: &Pubkey) -> Pubkey {
+    find(&[b"session", owner.as_ref(), session_key.as_ref()])
+}
+
+pub fn admin_refund_usage(admin: &Pubkey) -> Pubkey {
+    find(&[b"admin_refunds", admin.as_ref()])
+}
+
+pub fn reputation(owner: &Pubkey) -> Pubkey {
+    find(&[b"reputation", owner.as_ref()])
+}
+
+pub fn kyc(subject: &Pubkey) -> Pubkey {
+    find(&[b"kyc", subject.as_ref()])
+}
+
+pub fn contribution(escrow: &Pubkey, contributor: &Pubkey) -> Pubkey {
+    find(&[b"contribution", escrow.as_ref(), contributor.as_ref()])
+}
+
+/// Program-derived token account holding an escrow's funds
+pub fn vault(escrow: &Pubkey) -> Pubkey {
+    find(&[b"vault", escrow.as_ref()])
+}
+
+pub fn compressed_tree(mint: &Pubkey) -> Pubkey {
+    find(&[b"compressed_tree", mint.as_ref()])
+}
+
+/// Associated token account of `owner` for `mint`
+pub fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address(owner, mint)
+}
@@ -0,0 +1,790 @@
+//! In-process runtime that executes the escrow program natively alongside the SPL Token,
+//! Associated Token and System programs.
+//!
+//! Accounts are serialized into the same input layout the BPF loader uses, so `realloc`,
+//! `assign` and Anchor's `close` behave as they do on chain. Cross-program invocations are
+//! routed through the `solana_program` syscall stubs, which also check signer and writable
+//! privileges and the runtime's ownership rules at every call boundary. Compute budgets and
+//! rent exemption are not enforced.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use anchor_lang::solana_program::{
+    account_info::AccountInfo,
+    bpf_loader,
+    clock::Clock,
+    entrypoint::{
+        deserialize, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER,
+    },
+    epoch_schedule::EpochSchedule,
+    hash::{hashv, Hash},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_stubs::{self, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    slot_hashes::SlotHashes,
+    system_instruction::SystemInstruction,
+    system_program, sysvar,
+};
+use anchor_lang::{AccountDeserialize, AccountSerialize, Event};
+
+/// Unix timestamp the runtime clock starts at
+pub const GENESIS_TIMESTAMP: i64 = 1_700_000_000;
+
+type ProcessFn = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+/// `NativeLoader1111111111111111111111111111111`, owner of the builtin programs
+const NATIVE_LOADER_ID: Pubkey = Pubkey::new_from_array([
+    5, 135, 132, 191, 20, 139, 164, 40, 47, 176, 18, 87, 72, 136, 169, 241, 83, 160, 125, 173, 247,
+    101, 192, 69, 92, 154, 151, 3, 128, 0, 0, 0,
+]);
+
+/// Stored state of a single account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountState {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+/// Why a transaction was rejected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionError {
+    /// An instruction marks an account as signer that was not in the signer set
+    MissingSignature(Pubkey),
+    /// An instruction targets a program the runtime does not know
+    UnknownProgram(Pubkey),
+    /// An instruction returned an error
+    InstructionError { index: usize, error: ProgramError },
+    /// An instruction broke a runtime rule (lamport balance, ownership, privileges)
+    RuntimeViolation { index: usize, reason: String },
+    /// An instruction panicked, which aborts the transaction on chain
+    Panicked { index: usize, message: String },
+}
+
+impl TransactionError {
+    /// The program error an instruction failed with, if any
+    pub fn program_error(&self) -> Option<&ProgramError> {
+        match self {
+            TransactionError::InstructionError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Accounts, clock and output of the simulated cluster
+pub struct TestEnv {
+    accounts: HashMap<Pubkey, AccountState>,
+    clock: Clock,
+    rent: Rent,
+    logs: Vec<String>,
+    events: Vec<Vec<u8>>,
+    instructions: Vec<Instruction>,
+}
+
+impl Default for TestEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestEnv {
+    /// A fresh cluster with the escrow, token, associated token and system programs loaded
+    pub fn new() -> Self {
+        install_stubs();
+
+        let mut env = Self {
+            accounts: HashMap::new(),
+            clock: Clock {
+                slot: 1,
+                epoch_start_timestamp: GENESIS_TIMESTAMP,
+                epoch: 0,
+                leader_schedule_epoch: 0,
+                unix_timestamp: GENESIS_TIMESTAMP,
+            },
+            rent: Rent::default(),
+            logs: Vec::new(),
+            events: Vec::new(),
+            instructions: Vec::new(),
+        };
+
+        for (program_id, loader) in [
+            (taskfi_escrow::ID, bpf_loader::ID),
+            (spl_token::ID, bpf_loader::ID),
+            (spl_associated_token_account::ID, bpf_loader::ID),
+            (system_program::ID, NATIVE_LOADER_ID),
+        ] {
+            env.set_account(
+                program_id,
+                AccountState {
+                    lamports: 1,
+                    data: Vec::new(),
+                    owner: loader,
+                    executable: true,
+                },
+            );
+        }
+        env.refresh_sysvars();
+        env
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn rent(&self) -> &Rent {
+        &self.rent
+    }
+
+    /// Current unix timestamp of the cluster clock
+    pub fn now(&self) -> i64 {
+        self.clock.unix_timestamp
+    }
+
+    /// Current slot of the cluster clock
+    pub fn slot(&self) -> u64 {
+        self.clock.slot
+    }
+
+    /// Move the clock to `unix_timestamp`, advancing the slot so panel draws see fresh entropy
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        assert!(
+            unix_timestamp >= self.clock.unix_timestamp,
+            "the clock cannot move backwards"
+        );
+        self.clock.slot += 1;
+        self.clock.unix_timestamp = unix_timestamp;
+        self.refresh_sysvars();
+    }
+
+    /// Move to the next slot without letting time pass, as between two transactions sent
+    /// back to back
+    pub fn next_slot(&mut self) {
+        self.clock.slot += 1;
+        self.refresh_sysvars();
+    }
+
+    /// Advance the clock by `seconds`
+    pub fn advance(&mut self, seconds: i64) {
+        self.warp_to(self.clock.unix_timestamp + seconds);
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&AccountState> {
+        self.accounts.get(key)
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: AccountState) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn remove_account(&mut self, key: &Pubkey) {
+        self.accounts.remove(key);
+    }
+
+    /// Serialize an Anchor account over an existing one, keeping its size, lamports and owner
+    pub fn set<T: AccountSerialize>(&mut self, key: &Pubkey, value: &T) {
+        let account = self
+            .accounts
+            .get_mut(key)
+            .unwrap_or_else(|| panic!("account {key} does not exist"));
+        value
+            .try_serialize(&mut account.data.as_mut_slice())
+            .unwrap();
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |a| a.lamports)
+    }
+
+    /// Credit `lamports` to a system-owned wallet, creating it if needed
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        self.accounts
+            .entry(*key)
+            .or_insert_with(|| AccountState {
+                owner: system_program::ID,
+                ..AccountState::default()
+            })
+            .lamports += lamports;
+    }
+
+    /// Deserialize an Anchor account, or `None` if it does not exist
+    pub fn try_get<T: AccountDeserialize>(&self, key: &Pubkey) -> Option<T> {
+        let account = self.accounts.get(key)?;
+        T::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    /// Deserialize an Anchor account, panicking if it is missing or malformed
+    pub fn get<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        self.try_get(key)
+            .unwrap_or_else(|| panic!("account {key} is missing or has the wrong type"))
+    }
+
+    /// Logs of the last processed transaction
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    /// Instructions of the last processed transaction, as sent
+    pub fn last_instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Every event emitted by the last processed transaction, still encoded, in order
+    pub fn raw_events(&self) -> &[Vec<u8>] {
+        &self.events
+    }
+
+    /// Events of type `E` emitted by the last processed transaction, in order
+    pub fn events<E: Event>(&self) -> Vec<E> {
+        self.events
+            .iter()
+            .filter(|data| data.len() >= 8 && data[..8] == E::DISCRIMINATOR)
+            .filter_map(|data| E::try_from_slice(&data[8..]).ok())
+            .collect()
+    }
+
+    /// Execute `instructions` atomically; on failure no account changes are kept
+    pub fn process_transaction(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[Pubkey],
+    ) -> Result<(), TransactionError> {
+        RUNTIME.with(|runtime| {
+            let mut runtime = runtime.borrow_mut();
+            runtime.clock = self.clock.clone();
+            runtime.rent = self.rent;
+            runtime.logs.clear();
+            runtime.events.clear();
+        });
+        self.instructions = instructions.to_vec();
+
+        let mut working = self.accounts.clone();
+        let mut result = Ok(());
+        for (index, instruction) in instructions.iter().enumerate() {
+            result = execute_top_level(&mut working, index, instruction, signers);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        RUNTIME.with(|runtime| {
+            let mut runtime = runtime.borrow_mut();
+            self.logs = std::mem::take(&mut runtime.logs);
+            self.events = std::mem::take(&mut runtime.events);
+            runtime.frames.clear();
+            runtime.violation = None;
+        });
+        if result.is_ok() {
+            self.accounts = working;
+        }
+        result
+    }
+
+    fn refresh_sysvars(&mut self) {
+        let sysvar_account = |data: Vec<u8>| AccountState {
+            lamports: 1,
+            data,
+            owner: sysvar::ID,
+            executable: false,
+        };
+
+        let clock = bincode::serialize(&self.clock).unwrap();
+        let rent = bincode::serialize(&self.rent).unwrap();
+        // Newest first, like the real sysvar
+        let entries: Vec<(u64, Hash)> = (0..8u64)
+            .filter_map(|back| self.clock.slot.checked_sub(back))
+            .map(|slot| (slot, hashv(&[b"slot", &slot.to_le_bytes()])))
+            .collect();
+        let slot_hashes = bincode::serialize(&SlotHashes::new(&entries)).unwrap();
+
+        self.set_account(sysvar::clock::ID, sysvar_account(clock));
+        self.set_account(sysvar::rent::ID, sysvar_account(rent));
+        self.set_account(sysvar::slot_hashes::ID, sysvar_account(slot_hashes));
+    }
+}
+
+#[derive(Default)]
+struct RuntimeState {
+    clock: Clock,
+    rent: Rent,
+    logs: Vec<String>,
+    events: Vec<Vec<u8>>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    frames: Vec<Frame>,
+    violation: Option<String>,
+}
+
+/// One level of the invocation stack and the account state it started from
+struct Frame {
+    program_id: Pubkey,
+    snapshots: Vec<Snapshot>,
+}
+
+#[derive(Clone)]
+struct Snapshot {
+    key: Pubkey,
+    lamports: u64,
+    owner: Pubkey,
+    data: Vec<u8>,
+    is_writable: bool,
+}
+
+thread_local! {
+    static RUNTIME: RefCell<RuntimeState> = RefCell::new(RuntimeState::default());
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(NativeStubs));
+    });
+}
+
+fn processor(program_id: &Pubkey) -> Option<ProcessFn> {
+    if *program_id == taskfi_escrow::ID {
+        Some(taskfi_escrow::entry)
+    } else if *program_id == spl_token::ID {
+        Some(spl_token::processor::Processor::process)
+    } else if *program_id == spl_associated_token_account::ID {
+        Some(spl_associated_token_account::processor::process_instruction)
+    } else if *program_id == system_program::ID {
+        Some(process_system_instruction)
+    } else {
+        None
+    }
+}
+
+fn execute_top_level(
+    accounts: &mut HashMap<Pubkey, AccountState>,
+    index: usize,
+    instruction: &Instruction,
+    signers: &[Pubkey],
+) -> Result<(), TransactionError> {
+    if let Some(meta) = instruction
+        .accounts
+        .iter()
+        .find(|meta| meta.is_signer && !signers.contains(&meta.pubkey))
+    {
+        return Err(TransactionError::MissingSignature(meta.pubkey));
+    }
+    if processor(&instruction.program_id).is_none() {
+        return Err(TransactionError::UnknownProgram(instruction.program_id));
+    }
+
+    let mut input = serialize_input(accounts, instruction);
+    // SAFETY: `input` was laid out by `serialize_input` in the loader's format and outlives
+    // every `AccountInfo` borrowed from it below.
+    let (_, infos, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        execute(&instruction.program_id, &infos, &instruction.data)
+    }));
+    let violation = RUNTIME.with(|runtime| {
+        let mut runtime = runtime.borrow_mut();
+        runtime.frames.clear();
+        runtime.violation.take()
+    });
+
+    match outcome {
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default();
+            return Err(TransactionError::Panicked { index, message });
+        }
+        Ok(Err(_)) if violation.is_some() => {
+            return Err(TransactionError::RuntimeViolation {
+                index,
+                reason: violation.unwrap(),
+            });
+        }
+        Ok(Err(error)) => return Err(TransactionError::InstructionError { index, error }),
+        Ok(Ok(())) => {}
+    }
+
+    for info in &infos {
+        let account = AccountState {
+            lamports: info.lamports(),
+            data: info.data.borrow().to_vec(),
+            owner: *info.owner,
+            executable: info.executable,
+        };
+        if account.lamports == 0 {
+            accounts.remove(info.key);
+        } else {
+            accounts.insert(*info.key, account);
+        }
+    }
+    drop(infos);
+    drop(input);
+    Ok(())
+}
+
+/// Run one program invocation and verify what it did to its accounts
+fn execute(program_id: &Pubkey, infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let process = processor(program_id).ok_or(ProgramError::IncorrectProgramId)?;
+    let snapshots = snapshot(infos);
+
+    RUNTIME.with(|runtime| {
+        let mut runtime = runtime.borrow_mut();
+        runtime.frames.push(Frame {
+            program_id: *program_id,
+            snapshots: snapshots.clone(),
+        });
+        runtime.return_data = None;
+    });
+
+    let result = process(program_id, infos, data);
+    let frame = RUNTIME.with(|runtime| runtime.borrow_mut().frames.pop().unwrap());
+    result?;
+
+    verify_changes(&frame, infos, true)?;
+
+    // The caller's view now starts from what this invocation left behind
+    RUNTIME.with(|runtime| {
+        let mut runtime = runtime.borrow_mut();
+        let Some(parent) = runtime.frames.last_mut() else {
+            return;
+        };
+        for before in &snapshots {
+            let Some(info) = infos.iter().find(|info| *info.key == before.key) else {
+                continue;
+            };
+            let Some(entry) = parent.snapshots.iter_mut().find(|s| s.key == before.key) else {
+                continue;
+            };
+            entry.lamports = entry.lamports + info.lamports() - before.lamports;
+            let data = info.data.borrow();
+            if *info.owner != before.owner || data[..] != before.data[..] {
+                entry.owner = *info.owner;
+                entry.data = data.to_vec();
+            }
+        }
+    });
+    Ok(())
+}
+
+fn snapshot(infos: &[AccountInfo]) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = Vec::with_capacity(infos.len());
+    for info in infos {
+        if let Some(existing) = snapshots.iter_mut().find(|s| s.key == *info.key) {
+            existing.is_writable |= info.is_writable;
+            continue;
+        }
+        snapshots.push(Snapshot {
+            key: *info.key,
+            lamports: info.lamports(),
+            owner: *info.owner,
+            data: info.data.borrow().to_vec(),
+            is_writable: info.is_writable,
+        });
+    }
+    snapshots
+}
+
+/// Enforce the runtime's account rules for the program that owns `frame`
+fn verify_changes(frame: &Frame, infos: &[AccountInfo], check_balance: bool) -> ProgramResult {
+    let mut before_total: u128 = 0;
+    let mut after_total: u128 = 0;
+
+    for before in &frame.snapshots {
+        let Some(info) = infos.iter().find(|info| *info.key == before.key) else {
+            continue;
+        };
+        let lamports = info.lamports();
+        let owner = *info.owner;
+        let data = info.data.borrow();
+        let data_changed = data[..] != before.data[..];
+        let owns = before.owner == frame.program_id;
+        before_total += before.lamports as u128;
+        after_total += lamports as u128;
+
+        let changed = lamports != before.lamports || owner != before.owner || data_changed;
+        let reason = if changed && !before.is_writable {
+            Some("modified a read-only account")
+        } else if owner != before.owner && !owns {
+            Some("changed the owner of an account it does not own")
+        } else if lamports < before.lamports && !owns {
+            Some("debited lamports from an account it does not own")
+        } else if data_changed && !owns {
+            Some("modified data of an account it does not own")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return Err(violation(format!(
+                "program {} {reason}: {}",
+                frame.program_id, before.key
+            )));
+        }
+    }
+
+    if check_balance && before_total != after_total {
+        return Err(violation(format!(
+            "program {} did not conserve lamports ({before_total} -> {after_total})",
+            frame.program_id
+        )));
+    }
+    Ok(())
+}
+
+fn violation(reason: String) -> ProgramError {
+    RUNTIME.with(|runtime| {
+        let mut runtime = runtime.borrow_mut();
+        runtime.logs.push(format!("Runtime violation: {reason}"));
+        runtime.violation.get_or_insert(reason);
+    });
+    ProgramError::InvalidAccountData
+}
+
+/// Lay out the instruction's accounts exactly as the BPF loader serializes program input
+fn serialize_input(
+    accounts: &HashMap<Pubkey, AccountState>,
+    instruction: &Instruction,
+) -> Vec<u64> {
+    let mut bytes: Vec<u8> = Vec::new();
+    bytes.extend_from_slice(&(instruction.accounts.len() as u64).to_le_bytes());
+
+    let mut seen: Vec<Pubkey> = Vec::new();
+    for meta in &instruction.accounts {
+        if let Some(position) = seen.iter().position(|key| *key == meta.pubkey) {
+            bytes.push(position as u8);
+            bytes.extend_from_slice(&[0u8; 7]);
+            seen.push(meta.pubkey);
+            continue;
+        }
+        seen.push(meta.pubkey);
+
+        // Duplicate metas share one serialized account carrying the union of their privileges
+        let (is_signer, is_writable) = instruction
+            .accounts
+            .iter()
+            .filter(|m| m.pubkey == meta.pubkey)
+            .fold((false, false), |(s, w), m| {
+                (s || m.is_signer, w || m.is_writable)
+            });
+        let account = accounts.get(&meta.pubkey).cloned().unwrap_or(AccountState {
+            owner: system_program::ID,
+            ..AccountState::default()
+        });
+
+        bytes.push(NON_DUP_MARKER);
+        bytes.push(is_signer as u8);
+        bytes.push(is_writable as u8);
+        bytes.push(account.executable as u8);
+        bytes.extend_from_slice(&[0u8; 4]);
+        bytes.extend_from_slice(meta.pubkey.as_ref());
+        bytes.extend_from_slice(account.owner.as_ref());
+        bytes.extend_from_slice(&account.lamports.to_le_bytes());
+        bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&account.data);
+        bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        let padding = (BPF_ALIGN_OF_U128 - bytes.len() % BPF_ALIGN_OF_U128) % BPF_ALIGN_OF_U128;
+        bytes.resize(bytes.len() + padding, 0);
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+    }
+
+    bytes.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&instruction.data);
+    bytes.extend_from_slice(instruction.program_id.as_ref());
+
+    // A u64 backing store keeps the lamport and length fields aligned
+    let mut input = vec![0u64; bytes.len().div_ceil(size_of::<u64>())];
+    // SAFETY: `input` spans at least `bytes.len()` bytes.
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), input.as_mut_ptr() as *mut u8, bytes.len());
+    }
+    input
+}
+
+struct NativeStubs;
+
+impl SyscallStubs for NativeStubs {
+    fn sol_log(&self, message: &str) {
+        println!("Program log: {message}");
+        RUNTIME.with(|runtime| runtime.borrow_mut().logs.push(message.to_string()));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        RUNTIME.with(|runtime| runtime.borrow_mut().events.push(fields.concat()));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = RUNTIME.with(|runtime| runtime.borrow().frames.last().map(|f| f.program_id));
+        let caller = caller.ok_or(ProgramError::InvalidArgument)?;
+
+        // Changes the caller made so far become visible to the callee, so check them first
+        let caller_frame = RUNTIME.with(|runtime| {
+            let runtime = runtime.borrow();
+            let frame = runtime.frames.last().unwrap();
+            Frame {
+                program_id: frame.program_id,
+                snapshots: frame.snapshots.clone(),
+            }
+        });
+        verify_changes(&caller_frame, account_infos, false)?;
+
+        let mut callee_infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            if meta.is_signer && !info.is_signer {
+                let signed = signers_seeds.iter().any(|seeds| {
+                    Pubkey::create_program_address(seeds, &caller)
+                        .is_ok_and(|address| address == meta.pubkey)
+                });
+                if !signed {
+                    return Err(violation(format!(
+                        "program {caller} escalated signer privilege for {}",
+                        meta.pubkey
+                    )));
+                }
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(violation(format!(
+                    "program {caller} escalated writable privilege for {}",
+                    meta.pubkey
+                )));
+            }
+
+            let mut info = info.clone();
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            callee_infos.push(info);
+        }
+
+        execute(&instruction.program_id, &callee_infos, &instruction.data)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = RUNTIME.with(|runtime| runtime.borrow().clock.clone());
+        // SAFETY: the caller passes a pointer to a `Clock`.
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let rent = RUNTIME.with(|runtime| runtime.borrow().rent);
+        // SAFETY: the caller passes a pointer to a `Rent`.
+        unsafe { *(var_addr as *mut Rent) = rent };
+        0
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: the caller passes a pointer to an `EpochSchedule`.
+        unsafe { *(var_addr as *mut EpochSchedule) = EpochSchedule::default() };
+        0
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RUNTIME.with(|runtime| runtime.borrow().return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RUNTIME.with(|runtime| {
+            let mut runtime = runtime.borrow_mut();
+            let program_id = runtime
+                .frames
+                .last()
+                .map(|f| f.program_id)
+                .unwrap_or_default();
+            runtime.return_data = (!data.is_empty()).then(|| (program_id, data.to_vec()));
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        RUNTIME.with(|runtime| runtime.borrow().frames.len() as u64)
+    }
+}
+
+/// The subset of the System program the escrow flows rely on
+fn process_system_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let account = |index: usize| {
+        accounts
+            .get(index)
+            .ok_or(ProgramError::NotEnoughAccountKeys)
+    };
+
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            let from = account(0)?;
+            let to = account(1)?;
+            if !from.is_signer || !to.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if to.lamports() > 0 || !to.data_is_empty() || *to.owner != system_program::ID {
+                // SystemError::AccountAlreadyInUse
+                return Err(ProgramError::Custom(0));
+            }
+            move_lamports(from, to, lamports)?;
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+        }
+        SystemInstruction::Transfer { lamports } => {
+            let from = account(0)?;
+            let to = account(1)?;
+            if !from.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if !from.data_is_empty() || *from.owner != system_program::ID {
+                return Err(ProgramError::InvalidArgument);
+            }
+            move_lamports(from, to, lamports)?;
+        }
+        SystemInstruction::Allocate { space } => {
+            let target = account(0)?;
+            if !target.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if !target.data_is_empty() || *target.owner != system_program::ID {
+                return Err(ProgramError::Custom(0));
+            }
+            target.realloc(space as usize, true)?;
+        }
+        SystemInstruction::Assign { owner } => {
+            let target = account(0)?;
+            if !target.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            target.assign(&owner);
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if from.lamports() < lamports {
+        // SystemError::ResultWithNegativeLamports
+        return Err(ProgramError::Custom(1));
+    }
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
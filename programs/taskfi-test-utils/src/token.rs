@@ -0,0 +1,130 @@
+//! Mint and token account setup that writes SPL state directly instead of sending transactions
+
+use anchor_lang::solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey};
+use spl_token::state::{Account, AccountState as TokenAccountState, Mint};
+
+use crate::pda;
+use crate::svm::{AccountState, TestEnv};
+
+/// Lamports every new test wallet starts with
+pub const WALLET_LAMPORTS: u64 = 100_000_000_000;
+
+impl TestEnv {
+    /// A funded, system-owned wallet
+    pub fn create_wallet(&mut self) -> Pubkey {
+        let wallet = Pubkey::new_unique();
+        self.airdrop(&wallet, WALLET_LAMPORTS);
+        wallet
+    }
+
+    /// A new initialized mint with no supply
+    pub fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let state = Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        self.write_packed(mint, state);
+        mint
+    }
+
+    /// The associated token account of `owner` for `mint`, holding `amount` freshly minted tokens
+    pub fn create_token_account(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        self.write_token_account(pda::ata(owner, mint), owner, mint, amount)
+    }
+
+    /// A token account of `owner` for `mint` at a fresh address that is not their associated
+    /// token account, holding `amount` freshly minted tokens
+    pub fn create_auxiliary_token_account(
+        &mut self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        self.write_token_account(Pubkey::new_unique(), owner, mint, amount)
+    }
+
+    fn write_token_account(
+        &mut self,
+        address: Pubkey,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let state = Account {
+            mint: *mint,
+            owner: *owner,
+            amount: 0,
+            delegate: COption::None,
+            state: TokenAccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        self.write_packed(address, state);
+        self.mint_tokens(&address, amount);
+        address
+    }
+
+    /// Mint `amount` tokens into an existing token account
+    pub fn mint_tokens(&mut self, token_account: &Pubkey, amount: u64) {
+        let mut account = self.token_account(token_account);
+        account.amount += amount;
+        let mut mint = self.mint(&account.mint);
+        mint.supply += amount;
+        self.write_packed(account.mint, mint);
+        self.write_packed(*token_account, account);
+    }
+
+    /// Overwrite a token account's balance without touching the mint supply
+    pub fn set_token_balance(&mut self, token_account: &Pubkey, amount: u64) {
+        let mut account = self.token_account(token_account);
+        account.amount = amount;
+        self.write_packed(*token_account, account);
+    }
+
+    /// Freeze a token account as the mint's freeze authority would
+    pub fn freeze_token_account(&mut self, token_account: &Pubkey) {
+        let mut account = self.token_account(token_account);
+        account.state = TokenAccountState::Frozen;
+        self.write_packed(*token_account, account);
+    }
+
+    pub fn token_account(&self, address: &Pubkey) -> Account {
+        let account = self
+            .account(address)
+            .unwrap_or_else(|| panic!("token account {address} does not exist"));
+        Account::unpack(&account.data).unwrap()
+    }
+
+    pub fn mint(&self, address: &Pubkey) -> Mint {
+        let account = self
+            .account(address)
+            .unwrap_or_else(|| panic!("mint {address} does not exist"));
+        Mint::unpack(&account.data).unwrap()
+    }
+
+    /// Token balance of an account, or zero if it does not exist
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        self.account(address)
+            .and_then(|account| Account::unpack(&account.data).ok())
+            .map_or(0, |account| account.amount)
+    }
+
+    fn write_packed<T: Pack>(&mut self, address: Pubkey, state: T) {
+        let mut data = vec![0u8; T::LEN];
+        T::pack(state, &mut data).unwrap();
+        self.set_account(
+            address,
+            AccountState {
+                lamports: self.rent().minimum_balance(T::LEN),
+                data,
+                owner: spl_token::ID,
+                executable: false,
+            },
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use taskfi_escrow::{
+    AdminAction, AdminProposed, AuditLog, Config, EmergencyRefundReason, EscrowError,
+};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+fn default_params(platform: &Platform) -> taskfi_escrow::ConfigParams {
+    let mut params = default_config_params(platform.treasury, platform.moderator);
+    params.charity = platform.charity;
+    params.insurance_pool = platform.insurance_pool;
+    params
+}
+
+#[test]
+fn privileged_actions_are_recorded_in_the_audit_log() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-audit")
+        .deadline_in(0)
+        .create(&mut env);
+    env.advance(1);
+
+    env.process(
+        escrow.freeze_ix(&platform, &platform.moderator, DAY),
+        &[platform.moderator],
+    )
+    .unwrap();
+    env.process(
+        escrow.unfreeze_ix(&platform, &platform.moderator),
+        &[platform.moderator],
+    )
+    .unwrap();
+    env.process(
+        escrow.emergency_refund_ix(&platform, &platform.admin, EmergencyRefundReason::Abandoned),
+        &[platform.admin],
+    )
+    .unwrap();
+
+    let log: AuditLog = env.get(&platform.audit_log);
+    assert_eq!(log.total, 3);
+    let recorded: Vec<_> = log
+        .entries
+        .iter()
+        .map(|entry| (entry.actor, entry.action, entry.target))
+        .collect();
+    assert_eq!(
+        recorded,
+        vec![
+            (platform.moderator, AdminAction::FreezeEscrow, escrow.escrow),
+            (
+                platform.moderator,
+                AdminAction::UnfreezeEscrow,
+                escrow.escrow
+            ),
+            (platform.admin, AdminAction::EmergencyRefund, escrow.escrow),
+        ]
+    );
+    assert_eq!(log.entries[2].timestamp, env.now());
+}
+
+#[test]
+fn audit_log_overwrites_the_oldest_entries() {
+    let (mut env, platform) = setup();
+    let total = AuditLog::CAPACITY as i64 + 3;
+    for i in 0..total {
+        let mut params = default_params(&platform);
+        params.review_window = DAY + i;
+        env.process(platform.update_config_ix(params), &[platform.admin])
+            .unwrap();
+    }
+
+    let log: AuditLog = env.get(&platform.audit_log);
+    assert_eq!(log.total, total as u64);
+    assert_eq!(log.entries.len(), AuditLog::CAPACITY);
+    assert_eq!(log.head, 3);
+    assert!(log
+        .entries
+        .iter()
+        .all(|entry| entry.action == AdminAction::UpdateConfig && entry.target == platform.config));
+}
+
+#[test]
+fn privileged_instructions_are_rate_limited() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.rate_limits.max_emergency_refunds = 1;
+        params.rate_limits.max_config_updates = 2;
+    });
+
+    let first = EscrowBuilder::new("job-rate-1")
+        .deadline_in(0)
+        .create(&mut env);
+    let second = EscrowBuilder::new("job-rate-2")
+        .deadline_in(0)
+        .create(&mut env);
+    env.advance(1);
+    env.process(
+        first.emergency_refund_ix(&platform, &platform.admin, EmergencyRefundReason::Abandoned),
+        &[platform.admin],
+    )
+    .unwrap();
+    let result = env.process(
+        second.emergency_refund_ix(&platform, &platform.admin, EmergencyRefundReason::Abandoned),
+        &[platform.admin],
+    );
+    assert_error(result, EscrowError::RateLimitExceeded);
+
+    // Config updates count against the limits in force before the update
+    let mut params = default_params(&platform);
+    params.rate_limits.max_emergency_refunds = 1;
+    params.rate_limits.max_config_updates = 2;
+    for review_window in [DAY, DAY + 1] {
+        params.review_window = review_window;
+        env.process(platform.update_config_ix(params.clone()), &[platform.admin])
+            .unwrap();
+    }
+    params.review_window = DAY + 2;
+    let result = env.process(platform.update_config_ix(params), &[platform.admin]);
+    assert_error(result, EscrowError::RateLimitExceeded);
+
+    env.advance(60 * 60);
+    env.process(
+        second.emergency_refund_ix(&platform, &platform.admin, EmergencyRefundReason::Abandoned),
+        &[platform.admin],
+    )
+    .unwrap();
+}
+
+#[test]
+fn admin_handover_waits_out_the_delay() {
+    let (mut env, mut platform) = setup();
+    let new_admin = env.create_wallet();
+
+    let result = env.process(platform.accept_admin_ix(&new_admin), &[new_admin]);
+    assert_error(result, EscrowError::NoPendingAdmin);
+
+    env.process(platform.propose_new_admin_ix(new_admin), &[platform.admin])
+        .unwrap();
+    let proposed = env.events::<AdminProposed>();
+    assert_eq!(proposed[0].activates_at, env.now() + 2 * DAY);
+
+    let result = env.process(platform.accept_admin_ix(&new_admin), &[new_admin]);
+    assert_error(result, EscrowError::AdminTransferDelayActive);
+    let stranger = env.create_wallet();
+    env.advance(2 * DAY);
+    let result = env.process(platform.accept_admin_ix(&stranger), &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedAdminAcceptance);
+
+    env.process(platform.accept_admin_ix(&new_admin), &[new_admin])
+        .unwrap();
+    let config: Config = env.get(&platform.config);
+    assert_eq!(config.admin, new_admin);
+    assert_eq!(config.pending_admin, None);
+
+    // The old key has lost its powers; the new one holds them
+    let result = env.process(
+        platform.update_config_ix(default_params(&platform)),
+        &[platform.admin],
+    );
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintHasOne);
+    platform.admin = new_admin;
+    env.process(
+        platform.update_config_ix(default_params(&platform)),
+        &[new_admin],
+    )
+    .unwrap();
+
+    let log: AuditLog = env.get(&platform.audit_log);
+    assert_eq!(log.entries[1].action, AdminAction::AcceptAdmin);
+}
+
+#[test]
+fn pending_admin_handover_can_be_cancelled() {
+    let (mut env, platform) = setup();
+    let new_admin = env.create_wallet();
+    env.process(platform.propose_new_admin_ix(new_admin), &[platform.admin])
+        .unwrap();
+
+    let other = env.create_wallet();
+    let result = env.process(platform.propose_new_admin_ix(other), &[platform.admin]);
+    assert_error(result, EscrowError::AdminProposalPending);
+
+    env.process(platform.cancel_admin_proposal_ix(), &[platform.admin])
+        .unwrap();
+    env.advance(2 * DAY);
+    let result = env.process(platform.accept_admin_ix(&new_admin), &[new_admin]);
+    assert_error(result, EscrowError::NoPendingAdmin);
+}
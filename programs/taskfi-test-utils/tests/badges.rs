@@ -0,0 +1,125 @@
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{BadgeKind, BadgeMinted, EscrowError, Reputation};
+use taskfi_test_utils::*;
+
+/// Pay `freelancer` for `jobs` released escrows of `amount` each, all in `mint`
+fn complete_jobs(
+    env: &mut TestEnv,
+    platform: &Platform,
+    freelancer: Pubkey,
+    mint: Pubkey,
+    jobs: usize,
+    amount: u64,
+) {
+    for i in 0..jobs {
+        let escrow = EscrowBuilder::new(&format!("job-badge-{i}"))
+            .mint(mint)
+            .freelancer(freelancer)
+            .amount(amount)
+            .create(env);
+        env.process(escrow.release_ix(platform, &escrow.hirer), &[escrow.hirer])
+            .unwrap();
+    }
+}
+
+#[test]
+fn badges_are_frozen_one_of_one_mints() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let freelancer = env.create_wallet();
+    let mint = env.create_mint(MINT_DECIMALS);
+    complete_jobs(&mut env, &platform, freelancer, mint, 1, 1_000);
+
+    env.process(
+        platform.claim_badge_ix(&freelancer, BadgeKind::FirstJob),
+        &[freelancer],
+    )
+    .unwrap();
+    let badge = pda::badge(&freelancer, BadgeKind::FirstJob);
+    let minted = env.events::<BadgeMinted>().remove(0);
+    assert_eq!((minted.owner, minted.mint), (freelancer, badge));
+
+    let mint = env.mint(&badge);
+    assert_eq!((mint.supply, mint.decimals), (1, 0));
+    assert!(mint.mint_authority.is_none());
+    let holding = env.token_account(&pda::ata(&freelancer, &badge));
+    assert_eq!(holding.amount, 1);
+    assert!(holding.is_frozen());
+
+    // Each badge can be claimed once
+    env.advance(1);
+    let result = env.process(
+        platform.claim_badge_ix(&freelancer, BadgeKind::FirstJob),
+        &[freelancer],
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn badges_need_their_milestone() {
+    let mut env = TestEnv::new();
+    let usd = env.create_mint(MINT_DECIMALS);
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.badge_volume_mint = usd;
+        params.badge_volume_threshold = 50_000;
+    });
+    let freelancer = env.create_wallet();
+    complete_jobs(&mut env, &platform, freelancer, usd, 9, 5_000);
+
+    for kind in [
+        BadgeKind::TenJobs,
+        BadgeKind::HighVolume,
+        BadgeKind::ZeroDisputes,
+    ] {
+        let result = env.process(platform.claim_badge_ix(&freelancer, kind), &[freelancer]);
+        assert_error(result, EscrowError::BadgeNotEarned);
+    }
+
+    // Volume in other mints is not counted, whatever its base units add up to
+    let other = env.create_mint(MINT_DECIMALS);
+    complete_jobs(&mut env, &platform, freelancer, other, 1, 100_000);
+    let result = env.process(
+        platform.claim_badge_ix(&freelancer, BadgeKind::HighVolume),
+        &[freelancer],
+    );
+    assert_error(result, EscrowError::BadgeNotEarned);
+
+    complete_jobs(&mut env, &platform, freelancer, usd, 1, 5_000);
+    for kind in [
+        BadgeKind::TenJobs,
+        BadgeKind::HighVolume,
+        BadgeKind::ZeroDisputes,
+    ] {
+        env.process(platform.claim_badge_ix(&freelancer, kind), &[freelancer])
+            .unwrap();
+        assert_eq!(
+            env.token_balance(&pda::ata(&freelancer, &pda::badge(&freelancer, kind))),
+            1
+        );
+    }
+}
+
+#[test]
+fn lost_disputes_rule_out_the_zero_disputes_badge() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let freelancer = env.create_wallet();
+    let mint = env.create_mint(MINT_DECIMALS);
+    complete_jobs(&mut env, &platform, freelancer, mint, 10, 1_000);
+
+    let mut reputation: Reputation = env.get(&pda::reputation(&freelancer));
+    reputation.disputes_lost = 1;
+    env.set(&pda::reputation(&freelancer), &reputation);
+    let result = env.process(
+        platform.claim_badge_ix(&freelancer, BadgeKind::ZeroDisputes),
+        &[freelancer],
+    );
+    assert_error(result, EscrowError::BadgeNotEarned);
+
+    // High volume badges stay unavailable while the threshold is unset
+    let result = env.process(
+        platform.claim_badge_ix(&freelancer, BadgeKind::HighVolume),
+        &[freelancer],
+    );
+    assert_error(result, EscrowError::BadgeNotEarned);
+}
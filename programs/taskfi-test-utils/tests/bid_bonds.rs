@@ -0,0 +1,120 @@
+use taskfi_escrow::{BidBondRefunded, Escrow, EscrowError, JobApplication};
+use taskfi_test_utils::*;
+
+const BID_BOND: u64 = 50_000_000;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.bid_bond_lamports = BID_BOND;
+    });
+    (env, platform)
+}
+
+#[test]
+fn applying_locks_the_bid_bond() {
+    let (mut env, platform) = setup();
+    let hirer = env.create_wallet();
+    let applicant = env.create_wallet();
+    let balance = env.lamports(&applicant);
+
+    env.process(
+        platform.apply_to_job_ix(&hirer, "job-apply", &applicant),
+        &[applicant],
+    )
+    .unwrap();
+    let application = pda::application(&hirer, "job-apply", &applicant);
+    let state: JobApplication = env.get(&application);
+    assert_eq!((state.applicant, state.bond), (applicant, BID_BOND));
+    assert_eq!(
+        env.lamports(&application),
+        env.rent().minimum_balance(JobApplication::SIZE) + BID_BOND
+    );
+    assert!(env.lamports(&applicant) <= balance - env.lamports(&application));
+
+    let result = env.process(
+        platform.refund_bid_bond_ix(&hirer, "job-apply", &applicant),
+        &[],
+    );
+    assert_error(result, EscrowError::BidBondLocked);
+
+    let too_long = "j".repeat(Escrow::MAX_JOB_ID_LEN + 1);
+    let result = env.process(
+        platform.apply_to_job_ix(&hirer, &too_long, &applicant),
+        &[applicant],
+    );
+    assert_error(result, EscrowError::JobIdTooLong);
+}
+
+#[test]
+fn losing_applicants_are_refunded_once_the_job_is_awarded() {
+    let (mut env, platform) = setup();
+    let hirer = env.create_wallet();
+    let winner = env.create_wallet();
+    let loser = env.create_wallet();
+    for applicant in [winner, loser] {
+        env.process(
+            platform.apply_to_job_ix(&hirer, "job-award", &applicant),
+            &[applicant],
+        )
+        .unwrap();
+    }
+    let escrow = EscrowBuilder::new("job-award")
+        .hirer(hirer)
+        .freelancer(winner)
+        .create(&mut env);
+
+    let late = env.create_wallet();
+    let result = env.process(
+        platform.apply_to_job_ix(&hirer, "job-award", &late),
+        &[late],
+    );
+    assert_error(result, EscrowError::JobAlreadyAwarded);
+
+    let application = pda::application(&hirer, "job-award", &loser);
+    let locked = env.lamports(&application);
+    let balance = env.lamports(&loser);
+    env.process(
+        platform.refund_bid_bond_ix(&hirer, "job-award", &loser),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(env.lamports(&loser), balance + locked);
+    assert_eq!(env.events::<BidBondRefunded>()[0].bond, BID_BOND);
+
+    // The awarded applicant's bond stays locked until the escrow settles
+    let result = env.process(
+        platform.refund_bid_bond_ix(&hirer, "job-award", &winner),
+        &[],
+    );
+    assert_error(result, EscrowError::BidBondLocked);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    env.process(
+        platform.refund_bid_bond_ix(&hirer, "job-award", &winner),
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn bonds_unlock_when_a_job_is_never_awarded() {
+    let (mut env, platform) = setup();
+    let hirer = env.create_wallet();
+    let applicant = env.create_wallet();
+    env.process(
+        platform.apply_to_job_ix(&hirer, "job-stale", &applicant),
+        &[applicant],
+    )
+    .unwrap();
+
+    env.advance(7 * DAY);
+    env.process(
+        platform.refund_bid_bond_ix(&hirer, "job-stale", &applicant),
+        &[],
+    )
+    .unwrap();
+    assert!(env
+        .try_get::<JobApplication>(&pda::application(&hirer, "job-stale", &applicant))
+        .is_none());
+}
@@ -0,0 +1,87 @@
+use taskfi_escrow::{Bounty, BountyAwarded, BountyClaim, EscrowError};
+use taskfi_test_utils::*;
+
+const REWARD: u64 = 500_000;
+
+#[test]
+fn claims_close_at_the_deadline() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let bounty = BountyFixture::create(&mut env, "bounty-window", REWARD, DAY);
+    assert_eq!(env.token_balance(&bounty.vault), REWARD);
+
+    let claimant = bounty.claim(&mut env, [3; 32]);
+    let claim: BountyClaim = env.get(&pda::bounty_claim(&bounty.bounty, &claimant));
+    assert_eq!(claim.proof_hash, [3; 32]);
+
+    let result = env.process(bounty.submit_claim_ix(&claimant, [4; 32]), &[claimant]);
+    assert!(result.is_err());
+    let result = env.process(
+        bounty.submit_claim_ix(&bounty.hirer, [5; 32]),
+        &[bounty.hirer],
+    );
+    assert_error(result, EscrowError::IneligibleClaimant);
+
+    env.advance(DAY);
+    let late = env.create_wallet();
+    let result = env.process(bounty.submit_claim_ix(&late, [6; 32]), &[late]);
+    assert_error(result, EscrowError::ClaimWindowClosed);
+}
+
+#[test]
+fn only_one_of_several_simultaneous_claims_is_paid() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let bounty = BountyFixture::create(&mut env, "bounty-race", REWARD, DAY);
+    let first = bounty.claim(&mut env, [1; 32]);
+    let second = bounty.claim(&mut env, [2; 32]);
+    assert_eq!(env.get::<Bounty>(&bounty.bounty).open_claims, 2);
+
+    // Both approvals land in the same transaction; the second finds the bounty settled
+    let result = env.process_transaction(
+        &[bounty.approve_ix(&second), bounty.approve_ix(&first)],
+        &[bounty.hirer],
+    );
+    assert!(result.is_err());
+    assert_eq!(env.token_balance(&bounty.vault), REWARD);
+
+    env.process(bounty.approve_ix(&second), &[bounty.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&pda::ata(&second, &bounty.mint)), REWARD);
+    assert_eq!(env.events::<BountyAwarded>()[0].claimant, second);
+    let state: Bounty = env.get(&bounty.bounty);
+    assert_eq!((state.awarded_to, state.open_claims), (Some(second), 1));
+
+    let result = env.process(bounty.approve_ix(&first), &[bounty.hirer]);
+    assert_error(result, EscrowError::BountySettled);
+    let third = env.create_wallet();
+    let result = env.process(bounty.submit_claim_ix(&third, [7; 32]), &[third]);
+    assert_error(result, EscrowError::BountySettled);
+
+    // Leftover claims are closed by rejecting them
+    env.process(bounty.reject_ix(&first), &[bounty.hirer])
+        .unwrap();
+    assert!(env
+        .try_get::<BountyClaim>(&pda::bounty_claim(&bounty.bounty, &first))
+        .is_none());
+}
+
+#[test]
+fn unclaimed_bounties_are_refunded_after_the_deadline() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let bounty = BountyFixture::create(&mut env, "bounty-cancel", REWARD, DAY);
+    let claimant = bounty.claim(&mut env, [8; 32]);
+
+    let result = env.process(bounty.cancel_ix(), &[bounty.hirer]);
+    assert_error(result, EscrowError::ClaimWindowOpen);
+    env.advance(DAY);
+    let result = env.process(bounty.cancel_ix(), &[bounty.hirer]);
+    assert_error(result, EscrowError::BountyHasOpenClaims);
+
+    env.process(bounty.reject_ix(&claimant), &[bounty.hirer])
+        .unwrap();
+    env.process(bounty.cancel_ix(), &[bounty.hirer]).unwrap();
+    assert_eq!(env.token_balance(&bounty.hirer_token_account), REWARD);
+    assert_eq!(env.token_balance(&bounty.vault), 0);
+}
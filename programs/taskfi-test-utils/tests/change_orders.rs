@@ -0,0 +1,110 @@
+use taskfi_escrow::{ChangeOrderAccepted, Escrow, EscrowError, EscrowRole};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+#[test]
+fn hirer_increase_is_deposited_on_proposal() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-scope-up").create(&mut env);
+    env.mint_tokens(&escrow.hirer_token_account, 500_000);
+
+    let ix = escrow.propose_change_order_ix(&env, &escrow.hirer, 500_000, 14 * DAY);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT + 500_000);
+    let ix = escrow.propose_change_order_ix(&env, &escrow.freelancer, 0, DAY);
+    let result = env.process(ix, &[escrow.freelancer]);
+    assert_error(result, EscrowError::ChangeOrderPending);
+
+    // The proposer cannot accept its own change order
+    let result = env.process(
+        escrow.accept_change_order_ix(&escrow.hirer),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::UnauthorizedParty);
+    env.process(
+        escrow.accept_change_order_ix(&escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.amount, DEFAULT_AMOUNT + 500_000);
+    assert_eq!(state.deadline, env.now() + 14 * DAY);
+    assert!(state.pending_change.is_none());
+    let accepted = env.events::<ChangeOrderAccepted>();
+    assert_eq!(accepted[0].accepted_by, EscrowRole::Freelancer);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+}
+
+#[test]
+fn freelancer_increase_is_paid_on_acceptance() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-scope-ask").create(&mut env);
+    env.mint_tokens(&escrow.hirer_token_account, 250_000);
+
+    let ix = escrow.propose_change_order_ix(&env, &escrow.freelancer, 250_000, 10 * DAY);
+    env.process(ix, &[escrow.freelancer]).unwrap();
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT);
+
+    env.process(
+        escrow.accept_change_order_ix(&escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT + 250_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 0);
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).funded_amount,
+        DEFAULT_AMOUNT + 250_000
+    );
+}
+
+#[test]
+fn decreases_refund_the_funder_and_cancels_return_deposits() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-scope-down").create(&mut env);
+
+    let too_much = -(DEFAULT_AMOUNT as i64);
+    let ix = escrow.propose_change_order_ix(&env, &escrow.hirer, too_much, DAY);
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidChangeOrder);
+
+    let ix = escrow.propose_change_order_ix(&env, &escrow.hirer, -200_000, DAY);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    env.process(
+        escrow.accept_change_order_ix(&escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 200_000);
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT - 200_000);
+
+    // Declining a deposited increase hands the deposit back
+    let ix = escrow.propose_change_order_ix(&env, &escrow.hirer, 100_000, DAY);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 100_000);
+    env.process(
+        escrow.cancel_change_order_ix(&escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 200_000);
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).amount,
+        DEFAULT_AMOUNT - 200_000
+    );
+
+    let result = env.process(
+        escrow.accept_change_order_ix(&escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::NoChangeOrder);
+}
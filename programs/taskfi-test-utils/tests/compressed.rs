@@ -0,0 +1,179 @@
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{
+    CompressedEscrowCreated, CompressedEscrowLeaf, CompressedEscrowSettled, CompressedTree,
+    EscrowError,
+};
+use taskfi_test_utils::*;
+
+/// A platform with a compressed tree for a fresh mint and one funded hirer
+struct Setup {
+    env: TestEnv,
+    platform: Platform,
+    mint: Pubkey,
+    tree: Pubkey,
+    mirror: TreeMirror,
+    hirer: Pubkey,
+    freelancer: Pubkey,
+}
+
+fn setup() -> Setup {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let mint = env.create_mint(MINT_DECIMALS);
+    let tree = platform.initialize_compressed_tree(&mut env, &mint);
+    let hirer = env.create_wallet();
+    let freelancer = env.create_wallet();
+    env.create_token_account(&hirer, &mint, 100_000);
+    env.create_token_account(&freelancer, &mint, 0);
+    Setup {
+        env,
+        platform,
+        mint,
+        tree,
+        mirror: TreeMirror::new(tree),
+        hirer,
+        freelancer,
+    }
+}
+
+impl Setup {
+    /// Create a leaf and mirror it, returning the leaf as emitted
+    fn create(&mut self, amount: u64, deadline: i64) -> CompressedEscrowLeaf {
+        let ix = self.platform.create_compressed_escrow_ix(
+            &self.mint,
+            &self.hirer,
+            &self.freelancer,
+            amount,
+            deadline,
+        );
+        self.env.process(ix, &[self.hirer]).unwrap();
+        let created = self.env.events::<CompressedEscrowCreated>().remove(0);
+        self.mirror.append(&created.leaf);
+        assert_eq!(created.root, self.mirror.root());
+        created.leaf
+    }
+}
+
+#[test]
+fn new_tree_reports_the_empty_root() {
+    let s = setup();
+    let tree: CompressedTree = s.env.get(&s.tree);
+    assert_eq!(tree.mint, s.mint);
+    assert_eq!(tree.next_index, 0);
+    assert_eq!(tree.root, s.mirror.root());
+}
+
+#[test]
+fn creating_leaves_moves_funds_into_the_tree_vault() {
+    let mut s = setup();
+    let deadline = s.env.now() + DAY;
+    let first = s.create(1_000, deadline);
+    let second = s.create(2_500, deadline);
+
+    assert_eq!((first.index, second.index), (0, 1));
+    assert_eq!(second.hirer, s.hirer);
+    assert_eq!(second.freelancer, s.freelancer);
+    assert_eq!(s.env.token_balance(&pda::ata(&s.tree, &s.mint)), 3_500);
+    assert_eq!(s.env.token_balance(&pda::ata(&s.hirer, &s.mint)), 96_500);
+
+    let tree: CompressedTree = s.env.get(&s.tree);
+    assert_eq!(tree.next_index, 2);
+    assert_eq!(tree.root, s.mirror.root());
+}
+
+#[test]
+fn amounts_are_capped_by_config() {
+    let mut s = setup();
+    let deadline = s.env.now() + DAY;
+    for amount in [0, 10_001] {
+        let ix = s.platform.create_compressed_escrow_ix(
+            &s.mint,
+            &s.hirer,
+            &s.freelancer,
+            amount,
+            deadline,
+        );
+        let result = s.env.process(ix, &[s.hirer]);
+        assert_error(result, EscrowError::InvalidCompressedAmount);
+    }
+}
+
+#[test]
+fn release_pays_the_freelancer_with_a_valid_proof() {
+    let mut s = setup();
+    let deadline = s.env.now() + DAY;
+    let first = s.create(1_000, deadline);
+    let second = s.create(2_000, deadline);
+
+    let ix =
+        s.platform
+            .release_compressed_escrow_ix(&s.mint, &second, s.mirror.proof(second.index));
+    s.env.process(ix, &[s.hirer]).unwrap();
+    s.mirror.settle(second.index);
+
+    assert_eq!(
+        s.env.token_balance(&pda::ata(&s.freelancer, &s.mint)),
+        2_000
+    );
+    let settled = s.env.events::<CompressedEscrowSettled>();
+    assert_eq!(settled[0].recipient, s.freelancer);
+    assert_eq!(settled[0].root, s.mirror.root());
+    assert_eq!(s.env.get::<CompressedTree>(&s.tree).root, s.mirror.root());
+
+    // The settled leaf is empty now, so the same proof cannot be replayed
+    let ix =
+        s.platform
+            .release_compressed_escrow_ix(&s.mint, &second, s.mirror.proof(second.index));
+    let result = s.env.process(ix, &[s.hirer]);
+    assert_error(result, EscrowError::InvalidMerkleProof);
+
+    // Proofs for sibling leaves stay valid after the update
+    let ix = s
+        .platform
+        .release_compressed_escrow_ix(&s.mint, &first, s.mirror.proof(first.index));
+    s.env.process(ix, &[s.hirer]).unwrap();
+    assert_eq!(
+        s.env.token_balance(&pda::ata(&s.freelancer, &s.mint)),
+        3_000
+    );
+}
+
+#[test]
+fn tampered_leaves_fail_the_proof() {
+    let mut s = setup();
+    let leaf = s.create(1_000, s.env.now() + DAY);
+
+    let mut inflated = leaf.clone();
+    inflated.amount = 5_000;
+    let ix =
+        s.platform
+            .release_compressed_escrow_ix(&s.mint, &inflated, s.mirror.proof(leaf.index));
+    let result = s.env.process(ix, &[s.hirer]);
+    assert_error(result, EscrowError::InvalidMerkleProof);
+
+    let stranger = s.env.create_wallet();
+    let mut stolen = leaf.clone();
+    stolen.hirer = stranger;
+    let ix = s
+        .platform
+        .release_compressed_escrow_ix(&s.mint, &stolen, s.mirror.proof(leaf.index));
+    let result = s.env.process(ix, &[stranger]);
+    assert_error(result, EscrowError::InvalidMerkleProof);
+}
+
+#[test]
+fn refund_waits_for_the_deadline() {
+    let mut s = setup();
+    let leaf = s.create(4_000, s.env.now() + DAY);
+
+    let ix = s
+        .platform
+        .refund_compressed_escrow_ix(&s.mint, &leaf, s.mirror.proof(leaf.index));
+    let result = s.env.process(ix.clone(), &[s.hirer]);
+    assert_error(result, EscrowError::DeadlineNotReached);
+
+    s.env.warp_to(leaf.deadline + 1);
+    s.env.process(ix, &[s.hirer]).unwrap();
+    assert_eq!(s.env.token_balance(&pda::ata(&s.hirer, &s.mint)), 100_000);
+    assert_eq!(s.env.token_balance(&pda::ata(&s.tree, &s.mint)), 0);
+}
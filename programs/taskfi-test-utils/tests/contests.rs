@@ -0,0 +1,177 @@
+use taskfi_escrow::{
+    Contest, ContestEntry, ContestWinnersSelected, EscrowError, PoolDistributed, PoolPayoutMade,
+};
+use taskfi_test_utils::*;
+
+const PRIZE: u64 = 1_000_000;
+
+/// First place takes 60%, second 30% and third 10%
+const PODIUM: [u16; 3] = [6_000, 3_000, 1_000];
+
+#[test]
+fn placements_must_split_the_whole_prize() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    for placements in [&[][..], &[6_000, 3_000], &[2_000; 6]] {
+        let (_, result) =
+            ContestFixture::try_create(&mut env, "contest-bad", PRIZE, placements, DAY, None);
+        assert_error(result, EscrowError::InvalidPlacements);
+    }
+}
+
+#[test]
+fn entries_close_at_the_deadline() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let contest = ContestFixture::create(&mut env, "contest-entries", PRIZE, &PODIUM, DAY);
+    assert_eq!(env.token_balance(&contest.vault), PRIZE);
+
+    let entrant = contest.enter(&mut env, [7; 32]);
+    let entry: ContestEntry = env.get(&pda::contest_entry(&contest.contest, &entrant));
+    assert_eq!(entry.submission_hash, [7; 32]);
+
+    let result = env.process(
+        contest.submit_entry_ix(&contest.hirer, [1; 32]),
+        &[contest.hirer],
+    );
+    assert_error(result, EscrowError::IneligibleEntrant);
+
+    let result = env.process(
+        contest.select_winners_ix(&contest.judge, &[entrant]),
+        &[contest.judge],
+    );
+    assert_error(result, EscrowError::SubmissionsStillOpen);
+
+    env.advance(DAY);
+    let late = env.create_wallet();
+    let result = env.process(contest.submit_entry_ix(&late, [2; 32]), &[late]);
+    assert_error(result, EscrowError::SubmissionsClosed);
+    assert_eq!(env.get::<Contest>(&contest.contest).entry_count, 1);
+}
+
+#[test]
+fn winners_split_the_prize_by_placement() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let contest = ContestFixture::create(&mut env, "contest-podium", PRIZE, &PODIUM, DAY);
+    let entrants: Vec<_> = (0..4).map(|i| contest.enter(&mut env, [i; 32])).collect();
+    env.advance(DAY);
+
+    let stranger = env.create_wallet();
+    let result = env.process(
+        contest.select_winners_ix(&stranger, &entrants[..3]),
+        &[stranger],
+    );
+    assert!(result.is_err());
+    let result = env.process(
+        contest.select_winners_ix(&contest.judge, &[entrants[0], entrants[0]]),
+        &[contest.judge],
+    );
+    assert_error(result, EscrowError::DuplicateWinner);
+
+    let podium = [entrants[2], entrants[0], entrants[3]];
+    env.process(
+        contest.select_winners_ix(&contest.judge, &podium),
+        &[contest.judge],
+    )
+    .unwrap();
+    for (winner, prize) in podium.iter().zip([600_000, 300_000, 100_000]) {
+        assert_eq!(env.token_balance(&pda::ata(winner, &contest.mint)), prize);
+    }
+    assert_eq!(env.token_balance(&pda::ata(&entrants[1], &contest.mint)), 0);
+    assert_eq!(env.token_balance(&contest.vault), 0);
+    assert_eq!(env.events::<ContestWinnersSelected>()[0].winners, podium);
+
+    let result = env.process(
+        contest.select_winners_ix(&contest.judge, &podium),
+        &[contest.judge],
+    );
+    assert_error(result, EscrowError::ContestSettled);
+}
+
+#[test]
+fn a_judge_awards_fewer_places_than_offered() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let judge = env.create_wallet();
+    let (contest, result) =
+        ContestFixture::try_create(&mut env, "contest-judged", PRIZE, &PODIUM, DAY, Some(judge));
+    result.unwrap();
+    let entrant = contest.enter(&mut env, [9; 32]);
+    env.advance(DAY);
+
+    let result = env.process(
+        contest.select_winners_ix(&contest.hirer, &[entrant]),
+        &[contest.hirer],
+    );
+    assert!(result.is_err());
+    env.process(contest.select_winners_ix(&judge, &[entrant]), &[judge])
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&pda::ata(&entrant, &contest.mint)),
+        600_000
+    );
+    assert_eq!(env.token_balance(&contest.hirer_token_account), 400_000);
+}
+
+#[test]
+fn contests_without_entries_are_refunded() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let contest = ContestFixture::create(&mut env, "contest-empty", PRIZE, &PODIUM, DAY);
+
+    let result = env.process(contest.cancel_ix(), &[contest.hirer]);
+    assert_error(result, EscrowError::SubmissionsStillOpen);
+    env.advance(DAY);
+    env.process(contest.cancel_ix(), &[contest.hirer]).unwrap();
+    assert_eq!(env.token_balance(&contest.hirer_token_account), PRIZE);
+    assert!(env.get::<Contest>(&contest.contest).is_settled);
+}
+
+#[test]
+fn pools_pay_arbitrary_shares_that_add_up_to_the_prize() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let contest = ContestFixture::create(&mut env, "hackathon", PRIZE, &[10_000], DAY);
+    let teams: Vec<_> = (0..3).map(|i| contest.enter(&mut env, [i; 32])).collect();
+    let mentor = env.create_wallet();
+    env.create_token_account(&mentor, &contest.mint, 0);
+    env.advance(DAY);
+
+    let unbalanced = [(teams[0], 500_000), (teams[1], 400_000)];
+    let result = env.process(
+        contest.distribute_pool_ix(&contest.judge, &unbalanced),
+        &[contest.judge],
+    );
+    assert_error(result, EscrowError::PoolNotBalanced);
+
+    let payouts = [
+        (teams[0], 450_000),
+        (teams[1], 300_000),
+        (teams[2], 150_000),
+        (mentor, 100_000),
+    ];
+    env.process(
+        contest.distribute_pool_ix(&contest.judge, &payouts),
+        &[contest.judge],
+    )
+    .unwrap();
+    for (recipient, amount) in payouts {
+        assert_eq!(
+            env.token_balance(&pda::ata(&recipient, &contest.mint)),
+            amount
+        );
+    }
+    assert_eq!(env.token_balance(&contest.vault), 0);
+
+    let paid = env.events::<PoolPayoutMade>();
+    assert_eq!(paid.len(), 4);
+    assert_eq!((paid[3].recipient, paid[3].amount), (mentor, 100_000));
+    assert_eq!(env.events::<PoolDistributed>()[0].total, PRIZE);
+
+    let result = env.process(
+        contest.select_winners_ix(&contest.judge, &teams[..1]),
+        &[contest.judge],
+    );
+    assert_error(result, EscrowError::ContestSettled);
+}
@@ -0,0 +1,202 @@
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{
+    Contribution, ContributionReceived, EmergencyRefundIssued, EmergencyRefundReason, Escrow,
+    EscrowError,
+};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+/// A created escrow open to contributions, funded once half of it is in
+fn crowdfunded(env: &mut TestEnv, job_id: &str) -> EscrowFixture {
+    let escrow = EscrowBuilder::new(job_id)
+        .amount(10_000)
+        .funding_threshold(5_000)
+        .create(env);
+    env.process(escrow.enable_crowdfunding_ix(), &[escrow.hirer])
+        .unwrap();
+    escrow
+}
+
+/// A fresh wallet holding `balance` of the escrow mint
+fn contributor(env: &mut TestEnv, escrow: &EscrowFixture, balance: u64) -> Pubkey {
+    let wallet = env.create_wallet();
+    env.create_token_account(&wallet, &escrow.mint, balance);
+    wallet
+}
+
+#[test]
+fn contributions_are_receipted_per_wallet() {
+    let (mut env, _platform) = setup();
+    let escrow = crowdfunded(&mut env, "job-crowd");
+    let alice = contributor(&mut env, &escrow, 10_000);
+    let bob = contributor(&mut env, &escrow, 10_000);
+
+    let result = env.process(escrow.fund_ix(1_000), &[escrow.hirer]);
+    assert_error(result, EscrowError::CrowdfundedEscrow);
+
+    for (wallet, amount) in [(alice, 2_000), (bob, 3_000), (alice, 1_000)] {
+        env.process(escrow.contribute_ix(&wallet, amount), &[wallet])
+            .unwrap();
+    }
+    let receipt: Contribution = env.get(&pda::contribution(&escrow.escrow, &alice));
+    assert_eq!((receipt.contributor, receipt.amount), (alice, 3_000));
+    assert_eq!(
+        env.get::<Contribution>(&pda::contribution(&escrow.escrow, &bob))
+            .amount,
+        3_000
+    );
+    assert_eq!(env.events::<ContributionReceived>()[0].contributed, 3_000);
+
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.contributor_count, 2);
+    assert_eq!(state.funded_amount, 6_000);
+    assert!(state.is_funded);
+    assert_eq!(env.token_balance(&escrow.vault), 6_000);
+
+    let result = env.process(escrow.contribute_ix(&bob, 4_001), &[bob]);
+    assert_error(result, EscrowError::InvalidFundingTranche);
+}
+
+#[test]
+fn contributions_can_be_withdrawn_before_work_starts() {
+    let (mut env, _platform) = setup();
+    let escrow = crowdfunded(&mut env, "job-pledge");
+    let alice = contributor(&mut env, &escrow, 10_000);
+    let bob = contributor(&mut env, &escrow, 10_000);
+    env.process(escrow.contribute_ix(&alice, 2_000), &[alice])
+        .unwrap();
+
+    let result = env.process(escrow.close_unfunded_ix(&escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::ContributionsOutstanding);
+
+    env.process(escrow.withdraw_contribution_ix(&alice), &[alice])
+        .unwrap();
+    assert_eq!(env.token_balance(&pda::ata(&alice, &escrow.mint)), 10_000);
+    assert!(env
+        .account(&pda::contribution(&escrow.escrow, &alice))
+        .is_none());
+
+    // Once the threshold is met, contributions stay in until the funding TTL lapses
+    env.process(escrow.contribute_ix(&bob, 5_000), &[bob])
+        .unwrap();
+    let result = env.process(escrow.withdraw_contribution_ix(&bob), &[bob]);
+    assert_error(result, EscrowError::UnfundedEscrowNotExpired);
+
+    let fund_by = env.get::<Escrow>(&escrow.escrow).fund_by.unwrap();
+    env.warp_to(fund_by + 1);
+    env.process(escrow.withdraw_contribution_ix(&bob), &[bob])
+        .unwrap();
+    env.process(escrow.close_unfunded_ix(&escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert!(env.account(&escrow.escrow).is_none());
+}
+
+#[test]
+fn emergency_refunds_return_only_what_was_contributed() {
+    let (mut env, platform) = setup();
+    let escrow = crowdfunded(&mut env, "job-crowd-emergency");
+    let alice = contributor(&mut env, &escrow, 2_000);
+    let bob = contributor(&mut env, &escrow, 4_000);
+    for (wallet, amount) in [(alice, 2_000), (bob, 4_000)] {
+        env.process(escrow.contribute_ix(&wallet, amount), &[wallet])
+            .unwrap();
+    }
+    assert!(!env.get::<Escrow>(&escrow.escrow).is_fully_funded());
+
+    env.warp_to(env.get::<Escrow>(&escrow.escrow).deadline + 1);
+    let mut ix =
+        escrow.emergency_refund_ix(&platform, &platform.admin, EmergencyRefundReason::Abandoned);
+    ix.accounts
+        .extend(escrow.contributor_accounts(&[alice, bob]));
+    env.process(ix, &[platform.admin]).unwrap();
+
+    assert_eq!(env.events::<EmergencyRefundIssued>()[0].amount, 6_000);
+    assert_eq!(balance(&env, &escrow, &alice), 2_000);
+    assert_eq!(balance(&env, &escrow, &bob), 4_000);
+    assert!(env.account(&escrow.vault).is_none());
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+}
+
+/// A crowdfunded escrow filled by fresh contributors giving `amounts`, in order
+fn filled(env: &mut TestEnv, job_id: &str, amounts: &[u64]) -> (EscrowFixture, Vec<Pubkey>) {
+    let escrow = crowdfunded(env, job_id);
+    let contributors: Vec<Pubkey> = amounts
+        .iter()
+        .map(|&amount| {
+            let wallet = contributor(env, &escrow, amount);
+            env.process(escrow.contribute_ix(&wallet, amount), &[wallet])
+                .unwrap();
+            wallet
+        })
+        .collect();
+    assert!(env.get::<Escrow>(&escrow.escrow).is_fully_funded());
+    (escrow, contributors)
+}
+
+fn balance(env: &TestEnv, escrow: &EscrowFixture, wallet: &Pubkey) -> u64 {
+    env.token_balance(&pda::ata(wallet, &escrow.mint))
+}
+
+#[test]
+fn mutual_refunds_go_back_to_each_contributor() {
+    let (mut env, _platform) = setup();
+    let (escrow, contributors) = filled(&mut env, "job-crowd-refund", &[3_000, 7_000]);
+    let (alice, bob) = (contributors[0], contributors[1]);
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+
+    for wrong in [vec![alice], vec![alice, alice], vec![bob, escrow.hirer]] {
+        let mut ix = escrow.approve_refund_ix();
+        ix.accounts.extend(escrow.contributor_accounts(&wrong));
+        let result = env.process(ix, &[escrow.freelancer]);
+        assert!(result.is_err());
+    }
+    let mut ix = escrow.approve_refund_ix();
+    ix.accounts.extend(escrow.contributor_accounts(&[alice]));
+    let result = env.process(ix, &[escrow.freelancer]);
+    assert_error(result, EscrowError::InvalidContributionAccount);
+
+    let mut ix = escrow.approve_refund_ix();
+    ix.accounts
+        .extend(escrow.contributor_accounts(&[bob, alice]));
+    env.process(ix, &[escrow.freelancer]).unwrap();
+    assert_eq!(balance(&env, &escrow, &alice), 3_000);
+    assert_eq!(balance(&env, &escrow, &bob), 7_000);
+    // The hirer never funded it, so keeps only the balance it started with
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 10_000);
+}
+
+#[test]
+fn dispute_rulings_split_the_hirer_side_pro_rata() {
+    let (mut env, platform) = setup();
+    let (escrow, contributors) = filled(&mut env, "job-crowd-dispute", &[1_000, 2_000, 7_000]);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    env.next_slot();
+    let mut ix = escrow.resolve_ix(&platform, &platform.moderator, 4_999, 5_001);
+    ix.accounts
+        .extend(escrow.contributor_accounts(&contributors));
+    env.process(ix, &[platform.moderator]).unwrap();
+
+    // 4_999 split 1:2:7 rounds down, with the dust going to the last contributor
+    let refunds: Vec<u64> = contributors
+        .iter()
+        .map(|wallet| balance(&env, &escrow, wallet))
+        .collect();
+    assert_eq!(refunds, vec![499, 999, 3_501]);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+}
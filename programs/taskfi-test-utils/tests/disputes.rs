@@ -0,0 +1,945 @@
+use taskfi_escrow::{
+    ArbitratorPaid, DailyStats, DisputeAutoResolved, DisputeEscalated, DisputeReasonCode,
+    DisputeResolved, DisputeRuling, DisputeStatement, DisputeTier, EmergencyRefundReason,
+    EscalationBondsSettled, Escrow, EscrowError, MediationRecommended, Reputation,
+    ResolutionApproved, ResolutionTemplate,
+};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+/// Open a dispute and escalate it to the moderator tier, with the hirer posting the bond, then
+/// move past the slot the dispute was escalated in
+fn disputed_at_moderator(env: &mut TestEnv, platform: &Platform, job_id: &str) -> EscrowFixture {
+    let escrow = EscrowBuilder::new(job_id).create(env);
+    env.process(
+        escrow.initiate_dispute_ix(env, platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(escrow.escalate_ix(platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    env.next_slot();
+    escrow
+}
+
+#[test]
+fn only_parties_can_open_a_dispute() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-open").create(&mut env);
+    let stranger = env.create_wallet();
+
+    let result = env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &stranger),
+        &[stranger],
+    );
+    assert_error(result, EscrowError::UnauthorizedDispute);
+
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert!(state.is_disputed);
+    assert_eq!(state.dispute_tier, DisputeTier::Mediation);
+    assert_eq!(state.tier_deadline, env.now() + 3 * DAY);
+
+    let result = env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::AlreadyDisputed);
+
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn long_reasons_are_linked_rather_than_inlined() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-statement").create(&mut env);
+
+    let essay = DisputeStatement::Text("x".repeat(DisputeStatement::MAX_TEXT_LEN + 1));
+    let result = env.process(
+        escrow.initiate_dispute_with_ix(
+            &env,
+            &platform,
+            &escrow.hirer,
+            DisputeReasonCode::Quality,
+            essay,
+        ),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::DisputeReasonTooLong);
+    let unlinked = DisputeStatement::Linked {
+        hash: [3; 32],
+        uri: String::new(),
+    };
+    let result = env.process(
+        escrow.initiate_dispute_with_ix(
+            &env,
+            &platform,
+            &escrow.hirer,
+            DisputeReasonCode::Quality,
+            unlinked,
+        ),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::InvalidStatementUri);
+
+    let statement = DisputeStatement::Linked {
+        hash: [3; 32],
+        uri: "ar://".to_string() + &"s".repeat(DisputeStatement::MAX_URI_LEN - 5),
+    };
+    env.process(
+        escrow.initiate_dispute_with_ix(
+            &env,
+            &platform,
+            &escrow.hirer,
+            DisputeReasonCode::Quality,
+            statement.clone(),
+        ),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).dispute_reason,
+        Some(statement)
+    );
+}
+
+#[test]
+fn mediation_tier_cannot_be_ruled_on() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-mediation").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    let result = env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 0, DEFAULT_AMOUNT),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::WrongDisputeTier);
+}
+
+#[test]
+fn mediators_record_non_binding_recommendations() {
+    let mut env = TestEnv::new();
+    let mediator = env.create_wallet();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.mediators = vec![mediator];
+        params.mediation_required = true;
+    });
+    let escrow = EscrowBuilder::new("job-mediator").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    // Only moderators attach mediators, and only ones the config registers
+    let result = env.process(
+        escrow.assign_mediator_ix(&platform, &escrow.hirer, &mediator),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::UnauthorizedModeration);
+    let result = env.process(
+        escrow.assign_mediator_ix(&platform, &platform.moderator, &escrow.hirer),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::NotAMediator);
+    env.process(
+        escrow.assign_mediator_ix(&platform, &platform.moderator, &mediator),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    // Escalation waits for the mediator while the window is open
+    let result = env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::MediationIncomplete);
+
+    let result = env.process(
+        escrow.recommend_ix(&platform.moderator, 0, DEFAULT_AMOUNT, [7; 32]),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::NotAMediator);
+    let result = env.process(
+        escrow.recommend_ix(&mediator, 1, DEFAULT_AMOUNT, [7; 32]),
+        &[mediator],
+    );
+    assert_error(result, EscrowError::InvalidSplitAmount);
+    env.process(
+        escrow.recommend_ix(&mediator, 400, DEFAULT_AMOUNT - 400, [7; 32]),
+        &[mediator],
+    )
+    .unwrap();
+    let recommended = env.events::<MediationRecommended>().remove(0);
+    assert_eq!(recommended.mediator, mediator);
+    assert_eq!(recommended.recommendation.hirer_amount, 400);
+    assert_eq!(recommended.recommendation.note_hash, [7; 32]);
+
+    // The recommendation binds nobody: funds stay put and the dispute may still escalate
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.mediation, Some(recommended.recommendation));
+    assert!(!state.is_released);
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let result = env.process(
+        escrow.recommend_ix(&mediator, 0, DEFAULT_AMOUNT, [7; 32]),
+        &[mediator],
+    );
+    assert_error(result, EscrowError::WrongDisputeTier);
+}
+
+#[test]
+fn required_mediation_lapses_with_its_window() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| params.mediation_required = true);
+    let escrow = EscrowBuilder::new("job-mediation-lapse").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+
+    env.advance(3 * DAY + 1);
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).dispute_tier,
+        DisputeTier::Moderator
+    );
+}
+
+#[test]
+fn escalation_moves_up_the_ladder_and_holds_bonds() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-escalate").create(&mut env);
+
+    let result = env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::NotInDispute);
+
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let escrow_lamports = env.lamports(&escrow.escrow);
+    let hirer_lamports = env.lamports(&escrow.hirer);
+
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let escalated = env.events::<DisputeEscalated>();
+    assert_eq!(escalated[0].tier, DisputeTier::Moderator);
+    assert_eq!(escalated[0].bond, ESCALATION_BONDS[0]);
+    assert_eq!(
+        env.lamports(&escrow.hirer),
+        hirer_lamports - ESCALATION_BONDS[0]
+    );
+
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.dispute_tier, DisputeTier::Final);
+    assert_eq!(state.hirer_bond, ESCALATION_BONDS[0] + ESCALATION_BONDS[2]);
+    assert_eq!(state.freelancer_bond, ESCALATION_BONDS[1]);
+    assert_eq!(
+        env.lamports(&escrow.escrow),
+        escrow_lamports + ESCALATION_BONDS.iter().sum::<u64>()
+    );
+
+    let result = env.process(
+        escrow.escalate_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::FinalTierReached);
+}
+
+#[test]
+fn moderator_splits_the_escrow() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-split");
+
+    let result = env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 1, DEFAULT_AMOUNT),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::InvalidSplitAmount);
+
+    let stranger = env.create_wallet();
+    let result = env.process(
+        escrow.resolve_ix(&platform, &stranger, 400_000, 600_000),
+        &[stranger],
+    );
+    assert_error(result, EscrowError::UnauthorizedResolution);
+
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 400_000, 600_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 400_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 600_000);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+    let resolved = env.events::<DisputeResolved>();
+    assert_eq!(resolved[0].resolved_by, platform.moderator);
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).ruling_freelancer_amount,
+        Some(600_000)
+    );
+}
+
+#[test]
+fn rulings_open_a_missing_freelancer_token_account() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-ruling-no-ata");
+    env.remove_account(&escrow.freelancer_token_account);
+
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 400_000, 600_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    assert_eq!(
+        env.token_account(&escrow.freelancer_token_account).owner,
+        escrow.freelancer
+    );
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 600_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 400_000);
+}
+
+#[test]
+fn moderator_window_times_out() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-timeout");
+
+    env.advance(3 * DAY + 1);
+    let result = env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 0, DEFAULT_AMOUNT),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::TierWindowElapsed);
+}
+
+#[test]
+fn moderators_are_paid_from_the_escrow() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| params.moderator_fee_bps = 500);
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-moderator-fee");
+    let ruling = DisputeRuling::Split {
+        hirer_amount: 400_000,
+        freelancer_amount: 600_000,
+    };
+
+    let result = env.process(
+        escrow.resolve_paying_ix(&platform, &platform.moderator, ruling, None),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::InvalidArbitratorAccount);
+    let result = env.process(
+        escrow.resolve_paying_ix(
+            &platform,
+            &platform.moderator,
+            ruling,
+            Some(escrow.hirer_token_account),
+        ),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::InvalidArbitratorAccount);
+
+    let moderator_account = env.create_token_account(&platform.moderator, &escrow.mint, 0);
+    env.process(
+        escrow.resolve_paying_ix(
+            &platform,
+            &platform.moderator,
+            ruling,
+            Some(moderator_account),
+        ),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    // The 5% fee comes out of both awards in proportion to their size
+    assert_eq!(env.token_balance(&moderator_account), 50_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 380_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 570_000);
+    let paid = env.events::<ArbitratorPaid>().remove(0);
+    assert_eq!(paid.arbitrator, platform.moderator);
+    assert_eq!(paid.tier, DisputeTier::Moderator);
+    assert_eq!(paid.amount, 50_000);
+    let resolved = env.events::<DisputeResolved>().remove(0);
+    assert_eq!(resolved.freelancer_amount, 600_000);
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).ruling_freelancer_amount,
+        Some(600_000)
+    );
+}
+
+#[test]
+fn only_the_admin_rules_at_the_final_tier() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-final");
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    env.next_slot();
+    let result = env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 0, DEFAULT_AMOUNT),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::UnauthorizedResolution);
+
+    env.process(
+        escrow.resolve_ix(&platform, &platform.admin, DEFAULT_AMOUNT, 0),
+        &[platform.admin],
+    )
+    .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn frozen_dispute_cannot_be_resolved() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-frozen-dispute");
+    env.process(
+        escrow.freeze_ix(&platform, &platform.moderator, DAY),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    let result = env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 0, DEFAULT_AMOUNT),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::EscrowFrozen);
+
+    env.process(
+        escrow.unfreeze_ix(&platform, &platform.moderator),
+        &[platform.moderator],
+    )
+    .unwrap();
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 0, DEFAULT_AMOUNT),
+        &[platform.moderator],
+    )
+    .unwrap();
+}
+
+#[test]
+fn losing_party_forfeits_escalation_bonds() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-bonds");
+
+    let result = env.process(escrow.settle_bonds_ix(), &[]);
+    assert_error(result, EscrowError::NotReleased);
+
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 100_000, 900_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    env.process(escrow.settle_bonds_ix(), &[]).unwrap();
+
+    let settled = env.events::<EscalationBondsSettled>();
+    assert_eq!(settled[0].to_hirer, 0);
+    assert_eq!(settled[0].to_freelancer, ESCALATION_BONDS[0]);
+    assert_eq!(env.lamports(&escrow.hirer), hirer_lamports);
+    assert_eq!(
+        env.lamports(&escrow.freelancer),
+        freelancer_lamports + ESCALATION_BONDS[0]
+    );
+
+    let result = env.process(escrow.settle_bonds_ix(), &[]);
+    assert_error(result, EscrowError::NoEscalationBonds);
+}
+
+#[test]
+fn even_split_refunds_both_bonds() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-even");
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.next_slot();
+    env.process(
+        escrow.resolve_ix(&platform, &platform.admin, 500_000, 500_000),
+        &[platform.admin],
+    )
+    .unwrap();
+
+    env.process(escrow.settle_bonds_ix(), &[]).unwrap();
+    let settled = env.events::<EscalationBondsSettled>();
+    assert_eq!(
+        settled[0].to_hirer,
+        ESCALATION_BONDS[0] + ESCALATION_BONDS[2]
+    );
+    assert_eq!(settled[0].to_freelancer, ESCALATION_BONDS[1]);
+}
+
+#[test]
+fn large_rulings_need_a_second_approver() {
+    let mut env = TestEnv::new();
+    let second_moderator = env.create_wallet();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.moderators.push(second_moderator);
+        params.dual_approval_threshold = DEFAULT_AMOUNT;
+    });
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-dual");
+
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 400_000, 600_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT);
+    let approved = env.events::<ResolutionApproved>();
+    assert_eq!(approved[0].approver, platform.moderator);
+
+    // The first approver alone cannot complete the ruling, only revise it
+    env.advance(1);
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 300_000, 700_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+    assert!(!env.get::<Escrow>(&escrow.escrow).is_released);
+
+    let result = env.process(
+        escrow.resolve_ix(&platform, &second_moderator, 400_000, 600_000),
+        &[second_moderator],
+    );
+    assert_error(result, EscrowError::ResolutionSplitMismatch);
+
+    env.process(
+        escrow.resolve_ix(&platform, &second_moderator, 300_000, 700_000),
+        &[second_moderator],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 300_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 700_000);
+    let state: Escrow = env.get(&escrow.escrow);
+    assert!(state.is_released);
+    assert_eq!(state.pending_resolution, None);
+}
+
+#[test]
+fn the_admin_can_co_sign_a_moderator_ruling() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.dual_approval_threshold = DEFAULT_AMOUNT;
+    });
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-dual-admin");
+
+    let result = env.process(
+        escrow.resolve_ix(&platform, &platform.admin, 0, DEFAULT_AMOUNT),
+        &[platform.admin],
+    );
+    assert_error(result, EscrowError::UnauthorizedResolution);
+
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 0, DEFAULT_AMOUNT),
+        &[platform.moderator],
+    )
+    .unwrap();
+    env.process(
+        escrow.resolve_ix(&platform, &platform.admin, 0, DEFAULT_AMOUNT),
+        &[platform.admin],
+    )
+    .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn losing_a_dispute_starts_a_cooldown() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.lost_dispute_cooldown = 7 * DAY;
+    });
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-cooldown");
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 900_000, 100_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    let lost: Reputation = env.get(&pda::reputation(&escrow.freelancer));
+    assert_eq!(lost.disputes_lost, 1);
+    assert_eq!(lost.dispute_cooldown_until, env.now() + 7 * DAY);
+    assert_eq!(
+        env.get::<Reputation>(&pda::reputation(&escrow.hirer))
+            .disputes_lost,
+        0
+    );
+
+    // The losing freelancer cannot dispute their next job until the cooldown ends
+    let next = EscrowBuilder::new("job-cooldown-next")
+        .freelancer(escrow.freelancer)
+        .create(&mut env);
+    let result = env.process(
+        next.initiate_dispute_ix(&env, &platform, &next.freelancer),
+        &[next.freelancer],
+    );
+    assert_error(result, EscrowError::DisputeCooldownActive);
+    env.process(
+        next.initiate_dispute_ix(&env, &platform, &next.hirer),
+        &[next.hirer],
+    )
+    .unwrap();
+}
+
+#[test]
+fn templates_rule_without_hand_entered_amounts() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-template");
+    env.process(
+        escrow.resolve_with_ix(
+            &platform,
+            &platform.moderator,
+            DisputeRuling::Template(ResolutionTemplate::FreelancerFavored),
+        ),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 750_000);
+    let resolved = env.events::<DisputeResolved>().remove(0);
+    assert_eq!(
+        (resolved.hirer_amount, resolved.freelancer_amount),
+        (250_000, 750_000)
+    );
+    assert_eq!(
+        resolved.template,
+        Some(ResolutionTemplate::FreelancerFavored)
+    );
+
+    // Odd amounts round in the funder's favor
+    let even = DisputeRuling::Template(ResolutionTemplate::EvenSplit);
+    assert_eq!(even.amounts(1_001), (501, 500));
+    let refund = DisputeRuling::Template(ResolutionTemplate::FullRefund);
+    assert_eq!(refund.amounts(1_001), (1_001, 0));
+}
+
+#[test]
+fn rulings_feed_both_parties_dispute_statistics() {
+    let (mut env, platform) = setup();
+    let escrow = disputed_at_moderator(&mut env, &platform, "job-stats");
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 900_000, 100_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    let freelancer: Reputation = env.get(&pda::reputation(&escrow.freelancer));
+    assert_eq!(freelancer.disputes_initiated, 1);
+    assert_eq!(
+        (freelancer.disputes_resolved, freelancer.disputes_won),
+        (1, 0)
+    );
+    assert_eq!(freelancer.average_awarded_bps(), Some(1_000));
+    let hirer: Reputation = env.get(&pda::reputation(&escrow.hirer));
+    assert_eq!(hirer.disputes_initiated, 0);
+    assert_eq!((hirer.disputes_resolved, hirer.disputes_won), (1, 1));
+    assert_eq!(hirer.average_awarded_bps(), Some(9_000));
+
+    // An even split counts as resolved for both sides without a winner
+    let next = EscrowBuilder::new("job-stats-even")
+        .hirer(escrow.hirer)
+        .create(&mut env);
+    env.process(
+        next.initiate_dispute_ix(&env, &platform, &next.hirer),
+        &[next.hirer],
+    )
+    .unwrap();
+    env.process(
+        next.escalate_ix(&platform, &next.freelancer),
+        &[next.freelancer],
+    )
+    .unwrap();
+    env.next_slot();
+    env.process(
+        next.resolve_ix(&platform, &platform.moderator, 500_000, 500_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+    let hirer: Reputation = env.get(&pda::reputation(&escrow.hirer));
+    assert_eq!(hirer.disputes_initiated, 1);
+    assert_eq!((hirer.disputes_resolved, hirer.disputes_won), (2, 1));
+    assert_eq!(hirer.average_awarded_bps(), Some(7_000));
+}
+
+#[test]
+fn small_disputes_resolve_by_policy_after_cooling_off() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.auto_resolve_max_amount = 10_000;
+        params.auto_resolve_cooling_off = DAY;
+        params.auto_resolve_freelancer_bps = 2_000;
+        params.auto_resolve_fee = 500;
+    });
+    let escrow = EscrowBuilder::new("job-auto")
+        .amount(10_000)
+        .create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    let result = env.process(
+        escrow.auto_resolve_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::CoolingOffActive);
+
+    env.advance(DAY);
+    let stranger = env.create_wallet();
+    let result = env.process(escrow.auto_resolve_ix(&platform, &stranger), &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedDispute);
+
+    env.process(
+        escrow.auto_resolve_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 2_000);
+    assert_eq!(env.token_balance(&escrow.funder_token_account), 7_500);
+    assert_eq!(env.token_balance(&escrow.treasury_token_account), 500);
+    let resolved = env.events::<DisputeAutoResolved>().remove(0);
+    assert_eq!(
+        (
+            resolved.hirer_amount,
+            resolved.freelancer_amount,
+            resolved.fee
+        ),
+        (7_500, 2_000, 500)
+    );
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+}
+
+#[test]
+fn fraud_claims_are_never_auto_resolved() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.auto_resolve_max_amount = 10_000;
+        params.auto_resolve_cooling_off = DAY;
+    });
+    let escrow = EscrowBuilder::new("job-fraud")
+        .amount(10_000)
+        .create(&mut env);
+    env.process(
+        escrow.initiate_dispute_with_ix(
+            &env,
+            &platform,
+            &escrow.hirer,
+            DisputeReasonCode::Fraud,
+            DisputeStatement::Text("stolen work".to_string()),
+        ),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).dispute_reason_code,
+        Some(DisputeReasonCode::Fraud)
+    );
+    let stats: DailyStats = env.get(&pda::daily_stats(env.now()));
+    assert_eq!(
+        stats.disputes_by_reason[DisputeReasonCode::Fraud as usize],
+        1
+    );
+
+    env.advance(DAY);
+    let result = env.process(
+        escrow.auto_resolve_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::AutoResolutionUnavailable);
+}
+
+#[test]
+fn larger_disputes_cannot_be_auto_resolved() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.auto_resolve_max_amount = 10_000;
+    });
+    let escrow = EscrowBuilder::new("job-auto-large")
+        .amount(10_001)
+        .create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.advance(DAY);
+    let result = env.process(
+        escrow.auto_resolve_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::AutoResolutionUnavailable);
+}
+
+#[test]
+fn disputes_can_require_delivery_or_a_missed_deadline() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.dispute_requires_trigger = true;
+    });
+    let delivered = EscrowBuilder::new("job-trigger-delivered").create(&mut env);
+    let result = env.process(
+        delivered.initiate_dispute_ix(&env, &platform, &delivered.hirer),
+        &[delivered.hirer],
+    );
+    assert_error(result, EscrowError::NoDisputeTrigger);
+
+    env.process(
+        delivered.request_release_ix(&platform, &delivered.freelancer, None),
+        &[delivered.freelancer],
+    )
+    .unwrap();
+    env.process(
+        delivered.initiate_dispute_ix(&env, &platform, &delivered.hirer),
+        &[delivered.hirer],
+    )
+    .unwrap();
+
+    let overdue = EscrowBuilder::new("job-trigger-overdue")
+        .deadline_in(DAY)
+        .create(&mut env);
+    env.advance(DAY + 1);
+    env.process(
+        overdue.initiate_dispute_ix(&env, &platform, &overdue.freelancer),
+        &[overdue.freelancer],
+    )
+    .unwrap();
+}
+
+#[test]
+fn open_disputes_are_capped_per_wallet() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.max_open_disputes = 1;
+    });
+    let hirer = env.create_wallet();
+    let first = EscrowBuilder::new("job-open-cap-0")
+        .hirer(hirer)
+        .create(&mut env);
+    let second = EscrowBuilder::new("job-open-cap-1")
+        .hirer(hirer)
+        .create(&mut env);
+
+    env.process(first.initiate_dispute_ix(&env, &platform, &hirer), &[hirer])
+        .unwrap();
+    let result = env.process(
+        second.initiate_dispute_ix(&env, &platform, &hirer),
+        &[hirer],
+    );
+    assert_error(result, EscrowError::TooManyOpenDisputes);
+
+    // The counterparty's own allowance is untouched
+    env.process(
+        second.initiate_dispute_ix(&env, &platform, &second.freelancer),
+        &[second.freelancer],
+    )
+    .unwrap();
+
+    // Settling the first dispute frees the hirer's slot
+    env.process(
+        first.emergency_refund_ix(
+            &platform,
+            &platform.admin,
+            EmergencyRefundReason::StalledDispute,
+        ),
+        &[platform.admin],
+    )
+    .unwrap();
+    let reputation: Reputation = env.get(&pda::reputation(&hirer));
+    assert_eq!(reputation.open_disputes, 0);
+    let third = EscrowBuilder::new("job-open-cap-2")
+        .hirer(hirer)
+        .create(&mut env);
+    env.process(third.initiate_dispute_ix(&env, &platform, &hirer), &[hirer])
+        .unwrap();
+}
+
+#[test]
+fn rulings_cannot_land_in_the_slot_the_dispute_escalated() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-same-slot").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    let resolve = escrow.resolve_ix(&platform, &platform.moderator, 0, DEFAULT_AMOUNT);
+    let result = env.process(resolve.clone(), &[platform.moderator]);
+    assert_error(result, EscrowError::SameSlotAction);
+
+    env.next_slot();
+    env.process(resolve, &[platform.moderator]).unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert!(state.is_released);
+    assert_eq!(state.last_action_slot, env.slot());
+}
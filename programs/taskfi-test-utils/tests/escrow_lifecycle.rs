@@ -0,0 +1,1860 @@
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{
+    job_id_hash, AdminRefundUsage, BeneficiaryChanged, BusinessCalendar, DailyStats,
+    EmergencyRefundIssued, EmergencyRefundReason, Escrow, EscrowAction, EscrowCategory,
+    EscrowCreated, EscrowError, EscrowFunded, EscrowHistory, EscrowIndex, EscrowMetadataSet,
+    EscrowPage, EscrowRole, EscrowTombstone, FreelancerEarnings, JobRegistration, PaymentReleased,
+    PayoutDestinationSet, RefundApproved, RefundDestinationSet, ReleaseRequested, SessionKey,
+    SettledEscrowClosed, SubmissionRejected, VaultMigrated, EVENT_SCHEMA_VERSION, PROGRAM_VERSION,
+};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+#[test]
+fn initialize_escrow_funds_the_vault() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-init")
+        .amount(5_000)
+        .create(&mut env);
+
+    assert_eq!(env.token_balance(&escrow.vault), 5_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 0);
+
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.hirer, escrow.hirer);
+    assert_eq!(state.freelancer, escrow.freelancer);
+    assert_eq!(state.amount, 5_000);
+    assert!(!state.is_released && !state.is_disputed);
+
+    let created = env.events::<EscrowCreated>();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].version, EVENT_SCHEMA_VERSION);
+    assert_eq!(created[0].event_seq, 0);
+}
+
+#[test]
+fn job_ids_longer_than_the_escrow_holds_are_rejected() {
+    let (mut env, _platform) = setup();
+    let longest = "j".repeat(Escrow::MAX_JOB_ID_LEN);
+    let escrow = EscrowBuilder::new(&longest).create(&mut env);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).job_id, longest);
+
+    let too_long = "j".repeat(Escrow::MAX_JOB_ID_LEN + 1);
+    let (_, result) = EscrowBuilder::new(&too_long).try_create(&mut env);
+    assert_error(result, EscrowError::JobIdTooLong);
+}
+
+#[test]
+fn duplicate_job_id_is_rejected() {
+    let (mut env, _platform) = setup();
+    let hirer = env.create_wallet();
+    EscrowBuilder::new("job-dup").hirer(hirer).create(&mut env);
+
+    let (_, result) = EscrowBuilder::new("job-dup")
+        .hirer(hirer)
+        .try_create(&mut env);
+    assert!(matches!(
+        result,
+        Err(TransactionError::InstructionError { .. })
+    ));
+}
+
+#[test]
+fn job_ids_only_need_to_be_unique_per_hirer() {
+    let (mut env, _platform) = setup();
+    let first = EscrowBuilder::new("job-shared").create(&mut env);
+    let second = EscrowBuilder::new("job-shared").create(&mut env);
+
+    assert_ne!(first.escrow, second.escrow);
+    assert_eq!(second.escrow, pda::escrow(&second.hirer, "job-shared"));
+    let state: Escrow = env.get(&second.escrow);
+    assert_eq!(state.job_id_hash, job_id_hash("job-shared"));
+
+    // Hashing the id keeps ids longer than a 32-byte seed usable
+    let long_id = "job-".repeat(16);
+    EscrowBuilder::new(&long_id).create(&mut env);
+}
+
+#[test]
+fn hirer_release_pays_freelancer() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-release").create(&mut env);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert!(env.account(&escrow.vault).is_none());
+    let state: Escrow = env.get(&escrow.escrow);
+    assert!(state.is_released);
+    assert_eq!(state.released_at, Some(env.now()));
+
+    let released = env.events::<PaymentReleased>();
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].released_by, escrow.hirer);
+    assert_eq!(released[0].event_seq, 1);
+
+    // The release closed the vault, so a second one has nothing to pay out of
+    let again = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(again, anchor_lang::error::ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn escrows_keep_a_history_of_their_transitions() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-history").create(&mut env);
+    let created_at = env.now();
+
+    env.advance(60);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    let history = env.get::<Escrow>(&escrow.escrow).history;
+    let entries: Vec<_> = history
+        .entries()
+        .iter()
+        .map(|entry| (entry.action, entry.actor, entry.timestamp))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (EscrowAction::Created, escrow.hirer, created_at),
+            (EscrowAction::Released, escrow.hirer, created_at + 60),
+        ]
+    );
+}
+
+#[test]
+fn escrow_history_keeps_the_latest_transitions() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-history-wrap").create(&mut env);
+    let moderator = platform.moderator;
+
+    for _ in 0..10 {
+        env.process(escrow.freeze_ix(&platform, &moderator, 60), &[moderator])
+            .unwrap();
+        env.process(escrow.unfreeze_ix(&platform, &moderator), &[moderator])
+            .unwrap();
+        env.advance(1);
+    }
+
+    let history = env.get::<Escrow>(&escrow.escrow).history;
+    assert_eq!(history.total, 21);
+    let entries = history.entries();
+    assert_eq!(entries.len(), EscrowHistory::LEN);
+    // Creation and the first two freezes have been overwritten
+    assert_eq!(entries[0].action, EscrowAction::Frozen);
+    assert_eq!(
+        entries[EscrowHistory::LEN - 1].action,
+        EscrowAction::Unfrozen
+    );
+    assert!(entries.iter().all(|entry| entry.actor == moderator));
+}
+
+#[test]
+fn admin_can_release_but_strangers_cannot() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-admin").create(&mut env);
+    let stranger = env.create_wallet();
+
+    let result = env.process(escrow.release_ix(&platform, &stranger), &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedRelease);
+
+    let result = env.process(
+        escrow.release_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::UnauthorizedRelease);
+
+    env.process(
+        escrow.release_ix(&platform, &platform.admin),
+        &[platform.admin],
+    )
+    .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn unsigned_instructions_are_rejected() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-unsigned").create(&mut env);
+
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[]);
+    assert_eq!(
+        result,
+        Err(TransactionError::MissingSignature(escrow.hirer))
+    );
+}
+
+#[test]
+fn failed_transactions_leave_no_changes() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-atomic").create(&mut env);
+    let stranger = env.create_wallet();
+
+    let result = env.process_transaction(
+        &[
+            escrow.release_ix(&platform, &escrow.hirer),
+            escrow.release_ix(&platform, &stranger),
+        ],
+        &[escrow.hirer, stranger],
+    );
+    assert!(result.is_err());
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT);
+    assert!(!env.get::<Escrow>(&escrow.escrow).is_released);
+}
+
+#[test]
+fn job_registry_resolves_and_reserves_job_ids() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-registered")
+        .register_job_id()
+        .create(&mut env);
+    let registration: JobRegistration = env.get(&pda::job_registration("job-registered"));
+    assert_eq!(registration.escrow, escrow.escrow);
+    assert_eq!(registration.hirer, escrow.hirer);
+
+    // A registered id cannot be claimed again, even by another hirer
+    let (_, result) = EscrowBuilder::new("job-registered")
+        .register_job_id()
+        .try_create(&mut env);
+    assert!(result.is_err());
+}
+
+#[test]
+fn platform_can_require_registered_job_ids() {
+    let mut env = TestEnv::new();
+    Platform::setup_with(&mut env, |params| params.require_job_registration = true);
+
+    let (_, result) = EscrowBuilder::new("job-unregistered").try_create(&mut env);
+    assert_error(result, EscrowError::JobRegistrationRequired);
+    EscrowBuilder::new("job-unregistered")
+        .register_job_id()
+        .create(&mut env);
+}
+
+#[test]
+fn hirers_label_escrows_with_a_category_and_title() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-labelled").create(&mut env);
+
+    env.process(
+        escrow.set_metadata_ix(EscrowCategory::Design, "Logo refresh"),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.category, Some(EscrowCategory::Design));
+    assert_eq!(state.title, "Logo refresh");
+    assert_eq!(
+        env.events::<EscrowMetadataSet>()[0].category,
+        EscrowCategory::Design
+    );
+
+    let long_title = "x".repeat(Escrow::MAX_TITLE_LEN + 1);
+    let result = env.process(
+        escrow.set_metadata_ix(EscrowCategory::Design, &long_title),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::TitleTooLong);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    let result = env.process(
+        escrow.set_metadata_ix(EscrowCategory::Writing, "Too late"),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::AlreadyReleased);
+}
+
+#[test]
+fn mutual_refund_returns_funds_and_closes_accounts() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-refund").create(&mut env);
+
+    let result = env.process(escrow.approve_refund_ix(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::RefundNotRequested);
+
+    let essay = "x".repeat(Escrow::MAX_REASON_LEN + 1);
+    let result = env.process(escrow.request_refund_with_ix(&essay), &[escrow.hirer]);
+    assert_error(result, EscrowError::ReasonTooLong);
+
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    let result = env.process(escrow.request_refund_ix(), &[escrow.hirer]);
+    assert_error(result, EscrowError::RefundAlreadyRequested);
+
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    let rent = env.lamports(&escrow.escrow) + env.lamports(&escrow.vault);
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert!(env.account(&escrow.escrow).is_none());
+    assert!(env.account(&escrow.vault).is_none());
+    // All of the rent comes back except what the tombstone left behind holds
+    let tombstone = env.lamports(&pda::tombstone(&escrow.escrow));
+    assert_eq!(
+        env.lamports(&escrow.hirer),
+        hirer_lamports + rent - tombstone
+    );
+    assert_eq!(env.events::<RefundApproved>()[0].amount, DEFAULT_AMOUNT);
+}
+
+#[test]
+fn rent_goes_back_to_whoever_paid_it() {
+    let (mut env, _platform) = setup();
+    let sponsor = env.create_wallet();
+    let sponsor_lamports = env.lamports(&sponsor);
+    let escrow = EscrowBuilder::new("job-rent")
+        .rent_payer(sponsor)
+        .create(&mut env);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).rent_payer, sponsor);
+    let rent = env.lamports(&escrow.escrow) + env.lamports(&escrow.vault);
+    assert_eq!(env.lamports(&sponsor), sponsor_lamports - rent);
+
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    // Closing must return the rent to its payer, not to the hirer
+    let mut ix = escrow.approve_refund_ix();
+    ix.accounts[4].pubkey = escrow.hirer;
+    let result = env.process(ix, &[escrow.freelancer]);
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintAddress);
+
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+    let tombstone = env.lamports(&pda::tombstone(&escrow.escrow));
+    assert_eq!(env.lamports(&sponsor), sponsor_lamports - tombstone);
+    assert_eq!(env.lamports(&escrow.hirer), hirer_lamports);
+}
+
+#[test]
+fn settlement_closes_the_vault_on_its_rent_payer() {
+    let (mut env, platform) = setup();
+    let sponsor = env.create_wallet();
+    let escrow = EscrowBuilder::new("job-vault-rent")
+        .rent_payer(sponsor)
+        .create(&mut env);
+    let vault_rent = env.lamports(&escrow.vault);
+    let sponsor_lamports = env.lamports(&sponsor);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert!(env.account(&escrow.vault).is_none());
+    assert_eq!(env.lamports(&sponsor), sponsor_lamports + vault_rent);
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+}
+
+#[test]
+fn settled_escrows_close_on_their_rent_payer_behind_a_tombstone() {
+    let (mut env, platform) = setup();
+    let sponsor = env.create_wallet();
+    let escrow = EscrowBuilder::new("job-settled-close")
+        .rent_payer(sponsor)
+        .create(&mut env);
+    let cleaner = env.create_wallet();
+
+    let result = env.process(escrow.close_settled_ix(&cleaner), &[cleaner]);
+    assert_error(result, EscrowError::NotReleased);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    let escrow_rent = env.lamports(&escrow.escrow);
+    let sponsor_lamports = env.lamports(&sponsor);
+    let cleaner_lamports = env.lamports(&cleaner);
+
+    env.process(escrow.close_settled_ix(&cleaner), &[cleaner])
+        .unwrap();
+    let closed = env.events::<SettledEscrowClosed>();
+    assert_eq!(closed[0].escrow, escrow.escrow);
+    assert_eq!(closed[0].closed_by, cleaner);
+    assert!(env.account(&escrow.escrow).is_none());
+    let tombstone: EscrowTombstone = env.get(&pda::tombstone(&escrow.escrow));
+    assert_eq!(tombstone.escrow, escrow.escrow);
+    let tombstone_rent = env.lamports(&pda::tombstone(&escrow.escrow));
+    assert_eq!(
+        env.lamports(&sponsor),
+        sponsor_lamports + escrow_rent - tombstone_rent
+    );
+    assert_eq!(env.lamports(&cleaner), cleaner_lamports);
+    assert!(!env
+        .get::<EscrowPage>(&escrow.page)
+        .escrows
+        .contains(&escrow.escrow));
+
+    let (_, result) = EscrowBuilder::new("job-settled-close")
+        .hirer(escrow.hirer)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::EscrowRetired);
+}
+
+#[test]
+fn escrows_holding_a_payment_stay_open() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-settled-held")
+        .clawback_window(2 * DAY)
+        .create(&mut env);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    let result = env.process(escrow.close_settled_ix(&escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::EscrowNotSettled);
+
+    env.advance(2 * DAY);
+    env.process(escrow.withdraw_held_ix(), &[escrow.freelancer])
+        .unwrap();
+    env.process(escrow.close_settled_ix(&escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert!(env.account(&escrow.escrow).is_none());
+}
+
+#[test]
+fn escrow_funds_sit_in_a_program_derived_vault() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-vault").create(&mut env);
+
+    assert_eq!(escrow.vault, pda::vault(&escrow.escrow));
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).vault, escrow.vault);
+    assert_eq!(env.token_account(&escrow.vault).owner, escrow.escrow);
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT);
+    assert!(env
+        .account(&pda::ata(&escrow.escrow, &escrow.mint))
+        .is_none());
+}
+
+#[test]
+fn payouts_are_checked_against_the_escrow_mint() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-mint").create(&mut env);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).mint, escrow.mint);
+
+    let other_mint = env.create_mint(MINT_DECIMALS);
+    let mut ix = escrow.release_ix(&platform, &escrow.hirer);
+    let meta = ix
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == escrow.mint);
+    meta.unwrap().pubkey = other_mint;
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidMint);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert!(env.token_balance(&escrow.freelancer_token_account) > 0);
+}
+
+#[test]
+fn legacy_escrows_migrate_onto_the_derived_vault() {
+    let (mut env, platform) = setup();
+    let mut escrow = EscrowBuilder::new("job-legacy-vault").create(&mut env);
+
+    // Rewind the escrow to how older versions left it, with its funds in its ATA
+    let mut state: Escrow = env.get(&escrow.escrow);
+    state.vault = Pubkey::default();
+    env.set(&escrow.escrow, &state);
+    env.remove_account(&escrow.vault);
+    let legacy = env.create_token_account(&escrow.escrow, &escrow.mint, DEFAULT_AMOUNT);
+
+    let release = |escrow: &EscrowFixture| escrow.release_ix(&platform, &escrow.hirer);
+    escrow.vault = legacy;
+    let result = env.process(release(&escrow), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidVault);
+
+    env.process(escrow.migrate_vault_ix(&legacy), &[escrow.hirer])
+        .unwrap();
+    let migrated = env.events::<VaultMigrated>().remove(0);
+    assert_eq!(migrated.amount, DEFAULT_AMOUNT);
+    assert!(env.account(&legacy).is_none());
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.vault, pda::vault(&escrow.escrow));
+    assert_eq!(state.rent_payer, escrow.hirer);
+
+    escrow.vault = pda::vault(&escrow.escrow);
+    env.process(release(&escrow), &[escrow.hirer]).unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn closed_escrows_cannot_be_reopened_under_their_job_id() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-retired").create(&mut env);
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+
+    let tombstone: EscrowTombstone = env.get(&pda::tombstone(&escrow.escrow));
+    assert_eq!(tombstone.escrow, escrow.escrow);
+    assert_eq!(tombstone.job_id_hash, job_id_hash("job-retired"));
+    // The escrow's own rent covers the tombstone, so closing costs the freelancer nothing
+    assert_eq!(env.lamports(&escrow.freelancer), freelancer_lamports);
+
+    let (_, result) = EscrowBuilder::new("job-retired")
+        .hirer(escrow.hirer)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::EscrowRetired);
+}
+
+#[test]
+fn created_escrows_hold_no_funds_until_funded() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-unfunded")
+        .amount(5_000)
+        .unfunded()
+        .create(&mut env);
+
+    let state: Escrow = env.get(&escrow.escrow);
+    assert!(!state.is_funded);
+    assert_eq!(state.fund_by, Some(state.created_at + 3 * DAY));
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 5_000);
+
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::EscrowNotFullyFunded);
+    let result = env.process(
+        escrow.close_unfunded_ix(&escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::UnfundedEscrowNotExpired);
+
+    env.process(escrow.fund_ix(5_000), &[escrow.hirer]).unwrap();
+    assert_eq!(env.token_balance(&escrow.vault), 5_000);
+    let state: Escrow = env.get(&escrow.escrow);
+    assert!(state.is_funded);
+    assert_eq!(state.fund_by, None);
+
+    let result = env.process(escrow.fund_ix(1), &[escrow.hirer]);
+    assert_error(result, EscrowError::EscrowAlreadyFunded);
+    let result = env.process(escrow.close_unfunded_ix(&escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::EscrowAlreadyFunded);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 5_000);
+}
+
+#[test]
+fn installments_gate_work_on_the_threshold_and_payout_on_the_total() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-tranches")
+        .amount(10_000)
+        .funding_threshold(5_000)
+        .create(&mut env);
+
+    let result = env.process(escrow.fund_ix(10_001), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidFundingTranche);
+    env.process(escrow.fund_ix(4_000), &[escrow.hirer]).unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.funded_bps(), 4_000);
+    assert!(!state.is_funded);
+    let ix = escrow.request_release_ix(&platform, &escrow.freelancer, None);
+    let result = env.process(ix.clone(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::EscrowNotFunded);
+
+    env.process(escrow.fund_ix(2_000), &[escrow.hirer]).unwrap();
+    assert!(env.get::<Escrow>(&escrow.escrow).is_funded);
+    assert_eq!(env.events::<EscrowFunded>()[0].funded_amount, 6_000);
+    env.process(ix, &[escrow.freelancer]).unwrap();
+
+    // Started work keeps the hirer from closing the escrow until the TTL lapses
+    let result = env.process(escrow.close_unfunded_ix(&escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::UnfundedEscrowNotExpired);
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::EscrowNotFullyFunded);
+
+    env.process(escrow.fund_ix(4_000), &[escrow.hirer]).unwrap();
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).fund_by, None);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 10_000);
+}
+
+#[test]
+fn token_program_failures_surface_as_escrow_errors() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-token-errors")
+        .amount(5_000)
+        .unfunded()
+        .create(&mut env);
+
+    env.set_token_balance(&escrow.hirer_token_account, 4_999);
+    let result = env.process(escrow.fund_ix(5_000), &[escrow.hirer]);
+    assert_error(result, EscrowError::InsufficientTokenBalance);
+    env.set_token_balance(&escrow.hirer_token_account, 5_000);
+
+    let mut ix = escrow.fund_ix(5_000);
+    ix.accounts[2].pubkey = escrow.freelancer_token_account;
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, EscrowError::TokenOwnerMismatch);
+
+    env.freeze_token_account(&escrow.hirer_token_account);
+    let result = env.process(escrow.fund_ix(5_000), &[escrow.hirer]);
+    assert_error(result, EscrowError::TokenAccountFrozen);
+}
+
+#[test]
+fn sponsors_fund_and_receive_refunds_while_the_hirer_approves() {
+    let (mut env, platform) = setup();
+    let sponsor = env.create_wallet();
+    let escrow = EscrowBuilder::new("job-sponsored")
+        .funder(sponsor)
+        .create(&mut env);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).funder, sponsor);
+
+    let mut ix = escrow.fund_ix(DEFAULT_AMOUNT);
+    ix.accounts[1].pubkey = escrow.hirer;
+    ix.accounts[2].pubkey = escrow.hirer_token_account;
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintHasOne);
+    env.process(escrow.fund_ix(DEFAULT_AMOUNT), &[sponsor])
+        .unwrap();
+    assert_eq!(env.events::<EscrowFunded>()[0].funder, sponsor);
+
+    let result = env.process(escrow.release_ix(&platform, &sponsor), &[sponsor]);
+    assert_error(result, EscrowError::UnauthorizedRelease);
+
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.funder_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 0);
+}
+
+#[test]
+fn refunds_follow_the_refund_destination_set_at_creation() {
+    let (mut env, _platform) = setup();
+    let mint = env.create_mint(MINT_DECIMALS);
+    let treasury = env.create_wallet();
+    let company = env.create_token_account(&treasury, &mint, 0);
+    let mut escrow = EscrowBuilder::new("job-refund-destination")
+        .mint(mint)
+        .refund_destination(company)
+        .create(&mut env);
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).refund_destination,
+        Some(company)
+    );
+
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    let result = env.process(escrow.approve_refund_ix(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::InvalidRefundDestination);
+
+    escrow.funder_token_account = company;
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+    assert_eq!(env.token_balance(&company), DEFAULT_AMOUNT);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 0);
+}
+
+#[test]
+fn sponsors_co_sign_a_new_refund_destination() {
+    let (mut env, _platform) = setup();
+    let sponsor = env.create_wallet();
+    let mut escrow = EscrowBuilder::new("job-sponsor-refund-destination")
+        .funder(sponsor)
+        .create(&mut env);
+    env.process(escrow.fund_ix(DEFAULT_AMOUNT), &[sponsor])
+        .unwrap();
+    let treasury = env.create_wallet();
+    let company = env.create_token_account(&treasury, &escrow.mint, 0);
+
+    let mut ix = escrow.set_refund_destination_ix(&company);
+    ix.accounts[2].is_signer = false;
+    assert!(env.process(ix, &[escrow.hirer]).is_err());
+
+    env.process(
+        escrow.set_refund_destination_ix(&company),
+        &[escrow.hirer, sponsor],
+    )
+    .unwrap();
+    let set = env.events::<RefundDestinationSet>().remove(0);
+    assert_eq!(set.destination, company);
+
+    let sponsor_account = escrow.funder_token_account;
+    escrow.funder_token_account = company;
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+    assert_eq!(env.token_balance(&company), DEFAULT_AMOUNT);
+    assert_eq!(env.token_balance(&sponsor_account), 0);
+}
+
+#[test]
+fn expired_unfunded_escrows_can_be_closed_by_anyone() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-stale").unfunded().create(&mut env);
+    env.process(escrow.fund_ix(DEFAULT_AMOUNT / 2), &[escrow.hirer])
+        .unwrap();
+
+    let fund_by = env.get::<Escrow>(&escrow.escrow).fund_by.unwrap();
+    env.warp_to(fund_by + 1);
+    let result = env.process(escrow.fund_ix(DEFAULT_AMOUNT), &[escrow.hirer]);
+    assert_error(result, EscrowError::UnfundedEscrowExpired);
+
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    let rent = env.lamports(&escrow.escrow) + env.lamports(&escrow.vault);
+    let stranger = env.create_wallet();
+    env.process(escrow.close_unfunded_ix(&stranger), &[stranger])
+        .unwrap();
+
+    assert!(env.account(&escrow.escrow).is_none());
+    assert!(env.account(&escrow.vault).is_none());
+    let tombstone = env.lamports(&pda::tombstone(&escrow.escrow));
+    assert_eq!(
+        env.lamports(&escrow.hirer),
+        hirer_lamports + rent - tombstone
+    );
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+    let page: EscrowPage = env.get(&escrow.page);
+    assert!(!page.escrows.contains(&escrow.escrow));
+}
+
+#[test]
+fn emergency_refund_returns_funds_to_hirer() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-emergency").create(&mut env);
+    let reason = EmergencyRefundReason::Abandoned;
+
+    let result = env.process(
+        escrow.emergency_refund_ix(&platform, &escrow.hirer, reason),
+        &[escrow.hirer],
+    );
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintHasOne);
+
+    // A running escrow is not the admin's to refund
+    let refund = escrow.emergency_refund_ix(&platform, &platform.admin, reason);
+    let result = env.process(refund.clone(), &[platform.admin]);
+    assert_error(result, EscrowError::EmergencyRefundNotAllowed);
+
+    env.advance(7 * DAY + 1);
+    env.process(refund, &[platform.admin]).unwrap();
+
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+    assert!(env.account(&escrow.vault).is_none());
+    let issued = env.events::<EmergencyRefundIssued>();
+    assert_eq!(
+        (issued[0].amount, issued[0].reason),
+        (DEFAULT_AMOUNT, reason)
+    );
+}
+
+#[test]
+fn emergency_refunds_reach_disputed_and_frozen_escrows() {
+    let (mut env, platform) = setup();
+    let disputed = EscrowBuilder::new("job-emergency-disputed").create(&mut env);
+    let frozen = EscrowBuilder::new("job-emergency-frozen").create(&mut env);
+
+    env.process(
+        disputed.initiate_dispute_ix(&env, &platform, &disputed.hirer),
+        &[disputed.hirer],
+    )
+    .unwrap();
+    env.process(
+        frozen.freeze_ix(&platform, &platform.moderator, DAY),
+        &[platform.moderator],
+    )
+    .unwrap();
+    env.next_slot();
+
+    for (escrow, reason) in [
+        (&disputed, EmergencyRefundReason::StalledDispute),
+        (&frozen, EmergencyRefundReason::Fraud),
+    ] {
+        env.process(
+            escrow.emergency_refund_ix(&platform, &platform.admin, reason),
+            &[platform.admin],
+        )
+        .unwrap();
+        assert_eq!(
+            env.token_balance(&escrow.hirer_token_account),
+            DEFAULT_AMOUNT
+        );
+        assert_eq!(env.events::<EmergencyRefundIssued>()[0].reason, reason);
+    }
+}
+
+#[test]
+fn emergency_refunds_are_capped_per_admin_epoch() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.max_emergency_refunds_per_epoch = 2;
+        params.max_emergency_refund_value_per_epoch = 2 * DEFAULT_AMOUNT;
+    });
+    let refund = |env: &mut TestEnv, job_id: &str, amount: u64| {
+        let escrow = EscrowBuilder::new(job_id)
+            .amount(amount)
+            .deadline_in(0)
+            .create(env);
+        env.advance(1);
+        let reason = EmergencyRefundReason::Abandoned;
+        let ix = escrow.emergency_refund_ix(&platform, &platform.admin, reason);
+        env.process(ix, &[platform.admin])
+    };
+
+    refund(&mut env, "job-cap-1", DEFAULT_AMOUNT).unwrap();
+    let result = refund(&mut env, "job-cap-2", DEFAULT_AMOUNT + 1);
+    assert_error(result, EscrowError::EmergencyRefundValueExceeded);
+    refund(&mut env, "job-cap-3", DEFAULT_AMOUNT).unwrap();
+    let result = refund(&mut env, "job-cap-4", 1);
+    assert_error(result, EscrowError::EmergencyRefundCountExceeded);
+
+    let usage: AdminRefundUsage = env.get(&pda::admin_refund_usage(&platform.admin));
+    assert_eq!((usage.refunds, usage.value), (2, 2 * DEFAULT_AMOUNT));
+
+    // A new epoch starts a fresh tally
+    env.advance(DAY);
+    refund(&mut env, "job-cap-5", DEFAULT_AMOUNT).unwrap();
+}
+
+#[test]
+fn auto_release_after_review_window() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-auto").create(&mut env);
+    let freelancer = escrow.freelancer;
+
+    let result = env.process(
+        escrow.claim_auto_release_ix(&platform, &freelancer, None),
+        &[freelancer],
+    );
+    assert_error(result, EscrowError::ReleaseNotRequested);
+
+    let result = env.process(
+        escrow.request_release_ix(&platform, &escrow.hirer, None),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::UnauthorizedRelease);
+
+    env.process(
+        escrow.request_release_ix(&platform, &freelancer, None),
+        &[freelancer],
+    )
+    .unwrap();
+
+    env.advance(3 * DAY - 1);
+    let result = env.process(
+        escrow.claim_auto_release_ix(&platform, &freelancer, None),
+        &[freelancer],
+    );
+    assert_error(result, EscrowError::ReviewWindowOpen);
+
+    env.advance(1);
+    env.process(
+        escrow.claim_auto_release_ix(&platform, &freelancer, None),
+        &[freelancer],
+    )
+    .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn rejected_submissions_restart_the_review_window() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-revise").create(&mut env);
+    let freelancer = escrow.freelancer;
+
+    let result = env.process(escrow.reject_submission_ix([1; 32]), &[escrow.hirer]);
+    assert_error(result, EscrowError::ReleaseNotRequested);
+
+    let request = escrow.request_release_ix(&platform, &freelancer, None);
+    env.process(request.clone(), &[freelancer]).unwrap();
+    env.advance(2 * DAY);
+    env.process(escrow.reject_submission_ix([1; 32]), &[escrow.hirer])
+        .unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.release_requested_at, None);
+    assert_eq!(state.rejection_count, 1);
+    assert_eq!(state.last_rejection_hash, Some([1; 32]));
+    assert_eq!(env.events::<SubmissionRejected>()[0].rejection_count, 1);
+
+    // The resubmission gets a full review window of its own
+    env.process(request, &[freelancer]).unwrap();
+    env.advance(2 * DAY);
+    let claim = escrow.claim_auto_release_ix(&platform, &freelancer, None);
+    let result = env.process(claim.clone(), &[freelancer]);
+    assert_error(result, EscrowError::ReviewWindowOpen);
+    env.advance(DAY);
+    env.process(claim, &[freelancer]).unwrap();
+}
+
+#[test]
+fn revision_limit_stops_further_rejections() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| params.max_revisions = 1);
+    let escrow = EscrowBuilder::new("job-revisions").create(&mut env);
+    let request = escrow.request_release_ix(&platform, &escrow.freelancer, None);
+
+    env.process(request.clone(), &[escrow.freelancer]).unwrap();
+    env.process(escrow.reject_submission_ix([1; 32]), &[escrow.hirer])
+        .unwrap();
+    env.process(request, &[escrow.freelancer]).unwrap();
+    let result = env.process(escrow.reject_submission_ix([2; 32]), &[escrow.hirer]);
+    assert_error(result, EscrowError::RevisionLimitReached);
+
+    env.advance(3 * DAY);
+    env.process(
+        escrow.claim_auto_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+}
+
+#[test]
+fn business_calendars_skip_weekends_and_holidays() {
+    let (mut env, platform) = setup();
+    // Noon on Friday 2026-01-02, with Monday the 5th off
+    let friday_noon = 20_455 * DAY + DAY / 2;
+    env.warp_to(friday_noon);
+    let escrow = EscrowBuilder::new("job-business-days").create(&mut env);
+    let both = [escrow.hirer, escrow.freelancer];
+
+    let ix = escrow.set_business_calendar_ix(0x7f, &[]);
+    let result = env.process(ix, &both);
+    assert_error(result, EscrowError::InvalidBusinessCalendar);
+    let ix = escrow.set_business_calendar_ix(BusinessCalendar::SATURDAY_AND_SUNDAY, &[3]);
+    env.process(ix, &both).unwrap();
+
+    env.process(
+        escrow.request_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let review_ends_at = env.events::<ReleaseRequested>()[0].review_ends_at;
+    assert_eq!(review_ends_at, friday_noon + 6 * DAY);
+
+    let claim = escrow.claim_auto_release_ix(&platform, &escrow.freelancer, None);
+    env.warp_to(review_ends_at - 1);
+    let result = env.process(claim.clone(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::ReviewWindowOpen);
+    env.warp_to(review_ends_at);
+    env.process(claim, &[escrow.freelancer]).unwrap();
+}
+
+#[test]
+fn dispute_blocks_auto_release() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-auto-dispute").create(&mut env);
+    let freelancer = escrow.freelancer;
+
+    env.process(
+        escrow.request_release_ix(&platform, &freelancer, None),
+        &[freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    env.advance(3 * DAY);
+    let result = env.process(
+        escrow.claim_auto_release_ix(&platform, &freelancer, None),
+        &[freelancer],
+    );
+    assert_error(result, EscrowError::InDispute);
+}
+
+#[test]
+fn large_release_needs_matured_proposal() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.two_step_release_threshold = DEFAULT_AMOUNT;
+    });
+    let escrow = EscrowBuilder::new("job-two-step").create(&mut env);
+    let hirer = escrow.hirer;
+
+    let result = env.process(escrow.release_ix(&platform, &hirer), &[hirer]);
+    assert_error(result, EscrowError::ReleaseNotProposed);
+
+    env.process(escrow.propose_release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+    let result = env.process(escrow.release_ix(&platform, &hirer), &[hirer]);
+    assert_error(result, EscrowError::ReleaseConfirmDelayActive);
+
+    env.process(
+        escrow.cancel_release_proposal_ix(&platform, &hirer),
+        &[hirer],
+    )
+    .unwrap();
+    env.advance(DAY);
+    let result = env.process(escrow.release_ix(&platform, &hirer), &[hirer]);
+    assert_error(result, EscrowError::ReleaseNotProposed);
+
+    env.process(escrow.propose_release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+    env.advance(DAY);
+    env.process(escrow.release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn large_escrows_need_kyc_and_stricter_release() {
+    let mut env = TestEnv::new();
+    let attestor = env.create_wallet();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.large_escrow.threshold = DEFAULT_AMOUNT;
+        params.large_escrow.kyc_attestor = attestor;
+    });
+    let hirer = env.create_wallet();
+
+    let (_, result) = EscrowBuilder::new("job-large-nokyc")
+        .hirer(hirer)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::KycAttestationRequired);
+
+    let result = env.process(
+        platform.attest_kyc_ix(&hirer, hirer, env.now() + 30 * DAY),
+        &[hirer],
+    );
+    assert_error(result, EscrowError::UnauthorizedKycAttestor);
+    env.process(
+        platform.attest_kyc_ix(&attestor, hirer, env.now() + 30 * DAY),
+        &[attestor],
+    )
+    .unwrap();
+
+    // Smaller escrows keep the standard rules
+    let small = EscrowBuilder::new("job-large-small")
+        .amount(DEFAULT_AMOUNT - 1)
+        .create(&mut env);
+    assert!(!env.get::<Escrow>(&small.escrow).is_large);
+
+    let escrow = EscrowBuilder::new("job-large")
+        .hirer(hirer)
+        .create(&mut env);
+    assert!(env.get::<Escrow>(&escrow.escrow).is_large);
+
+    // Review runs for the large tier's window rather than the standard one
+    env.process(
+        escrow.request_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.advance(3 * DAY);
+    let result = env.process(
+        escrow.claim_auto_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::ReviewWindowOpen);
+
+    // Release is two-step even though no two-step threshold is configured
+    let result = env.process(escrow.release_ix(&platform, &hirer), &[hirer]);
+    assert_error(result, EscrowError::ReleaseNotProposed);
+    env.process(escrow.propose_release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+    env.advance(DAY);
+    env.process(escrow.release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+
+    // An expired attestation no longer qualifies
+    env.process(
+        platform.attest_kyc_ix(&attestor, hirer, env.now()),
+        &[attestor],
+    )
+    .unwrap();
+    let (_, result) = EscrowBuilder::new("job-large-expired")
+        .hirer(hirer)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::KycAttestationRequired);
+}
+
+#[test]
+fn small_release_skips_two_step() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.two_step_release_threshold = DEFAULT_AMOUNT + 1;
+    });
+    let escrow = EscrowBuilder::new("job-small").create(&mut env);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+}
+
+#[test]
+fn held_payment_can_be_withdrawn_after_clawback_window() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-held")
+        .clawback_window(2 * DAY)
+        .create(&mut env);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT);
+
+    let result = env.process(escrow.withdraw_held_ix(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::ClawbackWindowOpen);
+    assert_eq!(env.get::<FreelancerEarnings>(&escrow.earnings).payouts, 0);
+
+    env.advance(2 * DAY);
+    env.process(escrow.withdraw_held_ix(), &[escrow.freelancer])
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+
+    assert_eq!(
+        env.get::<FreelancerEarnings>(&escrow.earnings).gross_earned,
+        DEFAULT_AMOUNT
+    );
+    // The vault stayed open for the held payment and closes with its withdrawal
+    assert!(env.account(&escrow.vault).is_none());
+
+    let result = env.process(escrow.withdraw_held_ix(), &[escrow.freelancer]);
+    assert_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn releases_accumulate_freelancer_earnings() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-earnings").create(&mut env);
+    let earnings: FreelancerEarnings = env.get(&escrow.earnings);
+    assert_eq!(earnings.owner, escrow.freelancer);
+    assert_eq!(earnings.mints[0].mint, escrow.mint);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    let earnings: FreelancerEarnings = env.get(&escrow.earnings);
+    assert_eq!(
+        (earnings.gross_earned, earnings.fees_paid),
+        (DEFAULT_AMOUNT, 0)
+    );
+    assert_eq!(earnings.payouts, 1);
+    assert_eq!(earnings.mints[0].gross_earned, DEFAULT_AMOUNT);
+}
+
+#[test]
+fn admin_claws_back_held_payment() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-clawback")
+        .clawback_window(2 * DAY)
+        .create(&mut env);
+
+    let result = env.process(escrow.clawback_ix(&platform), &[platform.admin]);
+    assert_error(result, EscrowError::NoHeldPayment);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    env.process(escrow.clawback_ix(&platform), &[platform.admin])
+        .unwrap();
+
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 0);
+}
+
+#[test]
+fn clawback_closes_with_the_window() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-clawback-late")
+        .clawback_window(DAY)
+        .create(&mut env);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    env.advance(DAY);
+    let result = env.process(escrow.clawback_ix(&platform), &[platform.admin]);
+    assert_error(result, EscrowError::ClawbackWindowClosed);
+}
+
+#[test]
+fn moderator_freeze_blocks_release_until_lifted() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-freeze").create(&mut env);
+    let moderator = platform.moderator;
+
+    let result = env.process(
+        escrow.freeze_ix(&platform, &escrow.hirer, DAY),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::UnauthorizedModeration);
+
+    let result = env.process(
+        escrow.freeze_ix(&platform, &moderator, 8 * DAY),
+        &[moderator],
+    );
+    assert_error(result, EscrowError::FreezeLimitExceeded);
+
+    env.process(escrow.freeze_ix(&platform, &moderator, DAY), &[moderator])
+        .unwrap();
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::EscrowFrozen);
+
+    env.process(escrow.unfreeze_ix(&platform, &moderator), &[moderator])
+        .unwrap();
+    let result = env.process(escrow.unfreeze_ix(&platform, &moderator), &[moderator]);
+    assert_error(result, EscrowError::EscrowNotFrozen);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+}
+
+#[test]
+fn freeze_expires_on_its_own() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-freeze-expiry").create(&mut env);
+
+    env.process(
+        escrow.freeze_ix(&platform, &platform.admin, DAY),
+        &[platform.admin],
+    )
+    .unwrap();
+    env.advance(DAY);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+}
+
+#[test]
+fn freelancer_role_moves_to_new_wallet() {
+    let (mut env, platform) = setup();
+    let mut escrow = EscrowBuilder::new("job-role").create(&mut env);
+    let new_wallet = env.create_wallet();
+
+    let stranger = env.create_wallet();
+    let result = env.process(
+        escrow.transfer_role_ix(&platform, &stranger, &new_wallet, None),
+        &[stranger],
+    );
+    assert_error(result, EscrowError::UnauthorizedRoleTransfer);
+
+    let result = env.process(
+        escrow.transfer_role_ix(&platform, &escrow.freelancer, &escrow.hirer, None),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::InvalidRoleTransfer);
+
+    env.process(
+        escrow.transfer_role_ix(&platform, &escrow.freelancer, &new_wallet, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.freelancer, new_wallet);
+    assert_eq!(state.nonce, 1);
+
+    escrow.freelancer = new_wallet;
+    escrow.earnings = pda::earnings(&new_wallet);
+    escrow.freelancer_token_account = env.create_token_account(&new_wallet, &escrow.mint, 0);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn role_transfer_can_require_counterparty_consent() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.role_transfer_requires_consent = true;
+    });
+    let escrow = EscrowBuilder::new("job-role-consent").create(&mut env);
+    let new_wallet = env.create_wallet();
+
+    let result = env.process(
+        escrow.transfer_role_ix(&platform, &escrow.hirer, &new_wallet, None),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::CounterpartyConsentRequired);
+
+    env.process(
+        escrow.transfer_role_ix(
+            &platform,
+            &escrow.hirer,
+            &new_wallet,
+            Some(escrow.freelancer),
+        ),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).hirer, new_wallet);
+}
+
+#[test]
+fn beneficiary_changes_need_both_parties_and_reset_payout_routing() {
+    let (mut env, platform) = setup();
+    let mut escrow = EscrowBuilder::new("job-beneficiary").create(&mut env);
+    let backup = env.create_wallet();
+    let destination = env.create_token_account(&backup, &escrow.mint, 0);
+    env.process(
+        escrow.set_payout_destination_ix(&destination),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+
+    let substitute = env.create_wallet();
+    let mut ix = escrow.change_beneficiary_ix(EscrowRole::Freelancer, &substitute, None);
+    ix.accounts[2].is_signer = false;
+    assert!(env.process(ix, &[escrow.hirer]).is_err());
+
+    let ix = escrow.change_beneficiary_ix(EscrowRole::Freelancer, &escrow.hirer, None);
+    let result = env.process(ix, &[escrow.hirer, escrow.freelancer]);
+    assert_error(result, EscrowError::InvalidRoleTransfer);
+
+    let ix = escrow.change_beneficiary_ix(EscrowRole::Freelancer, &substitute, None);
+    env.process(ix, &[escrow.hirer, escrow.freelancer]).unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.freelancer, substitute);
+    assert_eq!(state.payout_destination, None);
+    let changed = env.events::<BeneficiaryChanged>().remove(0);
+    assert_eq!(
+        (changed.role, changed.old_key, changed.new_key),
+        (EscrowRole::Freelancer, escrow.freelancer, substitute)
+    );
+
+    // The old wallet's destination no longer receives payouts; the substitute's own accounts do
+    escrow.freelancer = substitute;
+    escrow.earnings = pda::earnings(&substitute);
+    escrow.freelancer_token_account = destination;
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidPayoutDestination);
+    escrow.freelancer_token_account = env.create_token_account(&substitute, &escrow.mint, 0);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn a_new_hirer_takes_over_refunds_at_the_destination_given() {
+    let (mut env, _platform) = setup();
+    let mut escrow = EscrowBuilder::new("job-acquired").create(&mut env);
+    let acquirer = env.create_wallet();
+    let treasury = env.create_token_account(&acquirer, &escrow.mint, 0);
+
+    let ix = escrow.change_beneficiary_ix(EscrowRole::Hirer, &acquirer, Some(treasury));
+    env.process(ix, &[escrow.hirer, escrow.freelancer]).unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!((state.hirer, state.funder), (acquirer, acquirer));
+    assert_eq!(state.refund_destination, Some(treasury));
+
+    escrow.hirer = acquirer;
+    escrow.funder_token_account = treasury;
+    env.process(escrow.request_refund_ix(), &[acquirer])
+        .unwrap();
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+    assert_eq!(env.token_balance(&treasury), DEFAULT_AMOUNT);
+}
+
+#[test]
+fn session_key_releases_within_its_scope() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-session").create(&mut env);
+    let hirer = escrow.hirer;
+    let session_key = env.create_wallet();
+    let session = pda::session(&hirer, &session_key);
+    let expires_at = env.now() + DAY;
+
+    let result = env.process(
+        platform.create_session_ix(
+            &hirer,
+            &session_key,
+            env.now() + 8 * DAY,
+            SessionKey::RELEASE_PAYMENT,
+            u64::MAX,
+        ),
+        &[hirer],
+    );
+    assert_error(result, EscrowError::InvalidSessionExpiry);
+
+    // Too small a cap, then the right permission missing
+    env.process(
+        platform.create_session_ix(
+            &hirer,
+            &session_key,
+            expires_at,
+            SessionKey::RELEASE_PAYMENT,
+            1,
+        ),
+        &[hirer],
+    )
+    .unwrap();
+    let result = env.process(
+        escrow.release_with_session_ix(&platform, &session_key, Some(session)),
+        &[session_key],
+    );
+    assert_error(result, EscrowError::SessionAmountExceeded);
+    env.process(platform.revoke_session_ix(&hirer, &session_key), &[hirer])
+        .unwrap();
+    assert!(env.account(&session).is_none());
+
+    env.process(
+        platform.create_session_ix(
+            &hirer,
+            &session_key,
+            expires_at,
+            SessionKey::INITIATE_DISPUTE,
+            u64::MAX,
+        ),
+        &[hirer],
+    )
+    .unwrap();
+    let result = env.process(
+        escrow.release_with_session_ix(&platform, &session_key, Some(session)),
+        &[session_key],
+    );
+    assert_error(result, EscrowError::SessionPermissionDenied);
+    env.process(platform.revoke_session_ix(&hirer, &session_key), &[hirer])
+        .unwrap();
+
+    env.process(
+        platform.create_session_ix(
+            &hirer,
+            &session_key,
+            expires_at,
+            SessionKey::RELEASE_PAYMENT,
+            u64::MAX,
+        ),
+        &[hirer],
+    )
+    .unwrap();
+    env.process(
+        escrow.release_with_session_ix(&platform, &session_key, Some(session)),
+        &[session_key],
+    )
+    .unwrap();
+    assert_eq!(env.events::<PaymentReleased>()[0].released_by, hirer);
+}
+
+#[test]
+fn expired_session_is_rejected() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-session-expired").create(&mut env);
+    let freelancer = escrow.freelancer;
+    let session_key = env.create_wallet();
+    let session = pda::session(&freelancer, &session_key);
+
+    env.process(
+        platform.create_session_ix(
+            &freelancer,
+            &session_key,
+            env.now() + DAY,
+            SessionKey::REQUEST_RELEASE | SessionKey::CLAIM_AUTO_RELEASE,
+            u64::MAX,
+        ),
+        &[freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.request_release_ix(&platform, &session_key, Some(session)),
+        &[session_key],
+    )
+    .unwrap();
+
+    env.advance(3 * DAY);
+    let result = env.process(
+        escrow.claim_auto_release_ix(&platform, &session_key, Some(session)),
+        &[session_key],
+    );
+    assert_error(result, EscrowError::SessionExpired);
+
+    // A different key cannot ride on someone else's session
+    let other = env.create_wallet();
+    let result = env.process(
+        escrow.claim_auto_release_ix(&platform, &other, Some(session)),
+        &[other],
+    );
+    assert_error(result, EscrowError::InvalidSessionKey);
+}
+
+#[test]
+fn advancing_the_nonce_rejects_stale_values() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-nonce").create(&mut env);
+
+    env.process(escrow.advance_nonce_ix(&escrow.hirer, 0), &[escrow.hirer])
+        .unwrap();
+    let result = env.process(
+        escrow.advance_nonce_ix(&escrow.freelancer, 0),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::StaleNonce);
+    env.process(
+        escrow.advance_nonce_ix(&escrow.freelancer, 1),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+
+    let stranger = env.create_wallet();
+    let result = env.process(escrow.advance_nonce_ix(&stranger, 2), &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedParty);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).nonce, 2);
+}
+
+#[test]
+fn version_guard_checks_both_directions() {
+    let mut env = TestEnv::new();
+    let payer = env.create_wallet();
+
+    env.process(assert_min_version_ix(PROGRAM_VERSION), &[payer])
+        .unwrap();
+    let result = env.process(assert_min_version_ix(PROGRAM_VERSION + 1), &[payer]);
+    assert_error(result, EscrowError::ProgramVersionTooOld);
+    let result = env.process(assert_min_version_ix(0), &[payer]);
+    assert_error(result, EscrowError::ClientVersionIncompatible);
+}
+
+#[test]
+fn event_sequence_numbers_have_no_gaps() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-seq").create(&mut env);
+
+    env.process(
+        escrow.request_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    assert_eq!(env.events::<PaymentReleased>()[0].event_seq, 2);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).event_seq, 3);
+}
+
+#[test]
+fn invariant_check_flags_an_underfunded_vault() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-invariants").create(&mut env);
+    let monitor = env.create_wallet();
+
+    env.process(escrow.assert_invariants_ix(), &[monitor])
+        .unwrap();
+    env.set_token_balance(&escrow.vault, DEFAULT_AMOUNT - 1);
+    let result = env.process(escrow.assert_invariants_ix(), &[monitor]);
+    assert_error(result, EscrowError::VaultUnderfunded);
+
+    env.set_token_balance(&escrow.vault, DEFAULT_AMOUNT);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    env.process(escrow.assert_invariants_ix(), &[monitor])
+        .unwrap();
+}
+
+#[test]
+fn invariant_check_only_counts_what_has_been_funded() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-invariants-unfunded")
+        .unfunded()
+        .create(&mut env);
+    let monitor = env.create_wallet();
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+
+    env.process(escrow.assert_invariants_ix(), &[monitor])
+        .unwrap();
+    env.process(escrow.fund_ix(DEFAULT_AMOUNT), &[escrow.hirer])
+        .unwrap();
+    env.set_token_balance(&escrow.vault, DEFAULT_AMOUNT - 1);
+    let result = env.process(escrow.assert_invariants_ix(), &[monitor]);
+    assert_error(result, EscrowError::VaultUnderfunded);
+}
+
+#[test]
+fn escrows_are_listed_on_fixed_size_pages() {
+    let (mut env, _platform) = setup();
+    let hirer = env.create_wallet();
+    let escrows: Vec<_> = (0..=EscrowPage::MAX_ESCROWS)
+        .map(|i| {
+            EscrowBuilder::new(&format!("job-page-{i}"))
+                .hirer(hirer)
+                .create(&mut env)
+        })
+        .collect();
+
+    let index: EscrowIndex = env.get(&pda::escrow_index(&hirer));
+    assert_eq!(index.open_page, 1);
+    let first: EscrowPage = env.get(&pda::escrow_page(&hirer, 0));
+    assert_eq!(first.escrows.len(), EscrowPage::MAX_ESCROWS);
+    assert_eq!(first.escrows[0], escrows[0].escrow);
+    let second: EscrowPage = env.get(&pda::escrow_page(&hirer, 1));
+    assert_eq!(second.page_no, 1);
+    assert_eq!(
+        second.escrows,
+        vec![escrows[EscrowPage::MAX_ESCROWS].escrow]
+    );
+
+    let refunded = &escrows[3];
+    env.process(refunded.request_refund_ix(), &[hirer]).unwrap();
+    env.process(refunded.approve_refund_ix(), &[refunded.freelancer])
+        .unwrap();
+    let first: EscrowPage = env.get(&pda::escrow_page(&hirer, 0));
+    assert_eq!(first.escrows.len(), EscrowPage::MAX_ESCROWS - 1);
+    assert!(!first.escrows.contains(&refunded.escrow));
+}
+
+#[test]
+fn unreputed_hirers_are_capped_on_open_escrows() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.unreputed_open_escrow_limit = 2;
+        params.reputation_min_settled = 1;
+    });
+    let hirer = env.create_wallet();
+    let first = EscrowBuilder::new("job-cap-0")
+        .hirer(hirer)
+        .create(&mut env);
+    let second = EscrowBuilder::new("job-cap-1")
+        .hirer(hirer)
+        .create(&mut env);
+    assert_eq!(env.get::<Escrow>(&second.escrow).hirer_seq, 1);
+
+    let (_, result) = EscrowBuilder::new("job-cap-2")
+        .hirer(hirer)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::OpenEscrowLimitReached);
+
+    env.process(first.release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+    let index: EscrowIndex = env.get(&first.index);
+    assert_eq!((index.open_escrows, index.settled_escrows), (1, 1));
+
+    // One settlement earns the hirer an exemption from the cap
+    EscrowBuilder::new("job-cap-2")
+        .hirer(hirer)
+        .create(&mut env);
+    EscrowBuilder::new("job-cap-3")
+        .hirer(hirer)
+        .create(&mut env);
+    let index: EscrowIndex = env.get(&first.index);
+    assert_eq!((index.escrow_count, index.open_escrows), (4, 3));
+}
+
+#[test]
+fn unreputed_hirers_are_limited_on_volume_per_window() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.reputation_min_settled = 2;
+        params.unreputed_velocity_limit = 2 * DEFAULT_AMOUNT;
+        params.velocity_limit_step = DEFAULT_AMOUNT;
+    });
+    let hirer = env.create_wallet();
+    let escrow = |job_id: &str| EscrowBuilder::new(job_id).hirer(hirer);
+
+    let first = escrow("job-velocity-0").create(&mut env);
+    escrow("job-velocity-1").create(&mut env);
+    let (_, result) = escrow("job-velocity-2").try_create(&mut env);
+    assert_error(result, EscrowError::VelocityLimitExceeded);
+
+    // A settlement raises the allowance by one step
+    env.process(first.release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+    escrow("job-velocity-2").create(&mut env);
+    let (_, result) = escrow("job-velocity-3").try_create(&mut env);
+    assert_error(result, EscrowError::VelocityLimitExceeded);
+
+    // The volume resets once the window has passed
+    env.advance(DAY);
+    escrow("job-velocity-3").create(&mut env);
+    let index: EscrowIndex = env.get(&first.index);
+    assert_eq!(index.velocity_window_volume, DEFAULT_AMOUNT);
+}
+
+#[test]
+fn daily_stats_bucket_activity_by_day() {
+    let (mut env, platform) = setup();
+    let first = EscrowBuilder::new("job-stats-0").create(&mut env);
+    EscrowBuilder::new("job-stats-1")
+        .amount(2_000)
+        .create(&mut env);
+    env.process(
+        first.initiate_dispute_ix(&env, &platform, &first.hirer),
+        &[first.hirer],
+    )
+    .unwrap();
+
+    let today: DailyStats = env.get(&pda::daily_stats(env.now()));
+    assert_eq!(today.day, DailyStats::day_of(env.now()));
+    assert_eq!(today.volume, DEFAULT_AMOUNT + 2_000);
+    assert_eq!((today.escrows_opened, today.disputes_opened), (2, 1));
+
+    env.advance(DAY);
+    EscrowBuilder::new("job-stats-2").create(&mut env);
+    let tomorrow: DailyStats = env.get(&pda::daily_stats(env.now()));
+    assert_eq!(tomorrow.day, today.day + 1);
+    assert_eq!(
+        (tomorrow.volume, tomorrow.escrows_opened),
+        (DEFAULT_AMOUNT, 1)
+    );
+}
+
+#[test]
+fn release_opens_a_missing_freelancer_token_account() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-no-ata").create(&mut env);
+    env.remove_account(&escrow.freelancer_token_account);
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    let vault_rent = env.lamports(&escrow.vault);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    let account = env.token_account(&escrow.freelancer_token_account);
+    assert_eq!(
+        (account.owner, account.mint),
+        (escrow.freelancer, escrow.mint)
+    );
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+    // The signer pays the new account's rent, and gets the closed vault's back as its payer
+    let rent = env.lamports(&escrow.freelancer_token_account);
+    assert_eq!(
+        env.lamports(&escrow.hirer),
+        hirer_lamports - rent + vault_rent
+    );
+}
+
+#[test]
+fn payouts_go_to_any_token_account_the_recipient_owns() {
+    let (mut env, platform) = setup();
+    let mut escrow = EscrowBuilder::new("job-any-account").create(&mut env);
+
+    let stranger = env.create_wallet();
+    escrow.freelancer_token_account =
+        env.create_auxiliary_token_account(&stranger, &escrow.mint, 0);
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidPayoutDestination);
+
+    let deposit = env.create_auxiliary_token_account(&escrow.freelancer, &escrow.mint, 0);
+    escrow.freelancer_token_account = deposit;
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&deposit), DEFAULT_AMOUNT);
+
+    // Refunds land in any account of the funder's as well
+    let mut escrow = EscrowBuilder::new("job-any-refund-account").create(&mut env);
+    let refund_account = env.create_auxiliary_token_account(&escrow.hirer, &escrow.mint, 0);
+    escrow.funder_token_account = refund_account;
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+    assert_eq!(env.token_balance(&refund_account), DEFAULT_AMOUNT);
+}
+
+#[test]
+fn frozen_freelancer_account_blocks_release_until_redirected() {
+    let (mut env, platform) = setup();
+    let mut escrow = EscrowBuilder::new("job-frozen-ata").create(&mut env);
+    env.freeze_token_account(&escrow.freelancer_token_account);
+
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::PayoutAccountFrozen);
+
+    // A frozen account cannot be chosen as the new destination either
+    let frozen = escrow.freelancer_token_account;
+    let result = env.process(
+        escrow.set_payout_destination_ix(&frozen),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::PayoutAccountFrozen);
+
+    let backup = env.create_wallet();
+    let destination = env.create_token_account(&backup, &escrow.mint, 0);
+    env.process(
+        escrow.set_payout_destination_ix(&destination),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let set = env.events::<PayoutDestinationSet>().remove(0);
+    assert_eq!(set.destination, destination);
+
+    // Once redirected, the associated token account no longer receives payouts
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidPayoutDestination);
+
+    escrow.freelancer_token_account = destination;
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&destination), DEFAULT_AMOUNT);
+    assert_eq!(env.token_balance(&frozen), 0);
+}
+
+#[test]
+fn only_the_freelancer_sets_the_payout_destination() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-payout-auth").create(&mut env);
+    let mut ix = escrow.set_payout_destination_ix(&escrow.hirer_token_account);
+    ix.accounts[1].pubkey = escrow.hirer;
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintHasOne);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).payout_destination, None);
+}
+
+#[test]
+fn emergency_refunds_wait_out_the_funding_slot() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-fund-refund")
+        .unfunded()
+        .deadline_in(0)
+        .create(&mut env);
+    env.advance(1);
+    let reason = EmergencyRefundReason::Abandoned;
+
+    let result = env.process_transaction(
+        &[
+            escrow.fund_ix(DEFAULT_AMOUNT),
+            escrow.emergency_refund_ix(&platform, &platform.admin, reason),
+        ],
+        &[escrow.hirer, platform.admin],
+    );
+    assert_error(result, EscrowError::SameSlotAction);
+
+    env.process(escrow.fund_ix(DEFAULT_AMOUNT), &[escrow.hirer])
+        .unwrap();
+    let refund = escrow.emergency_refund_ix(&platform, &platform.admin, reason);
+    let result = env.process(refund.clone(), &[platform.admin]);
+    assert_error(result, EscrowError::SameSlotAction);
+
+    env.next_slot();
+    env.process(refund, &[platform.admin]).unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
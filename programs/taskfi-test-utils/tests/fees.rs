@@ -0,0 +1,381 @@
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{
+    Affiliate, CouponRedeemed, Escrow, EscrowError, EscrowIndex, FeeDestination, FeeSplit,
+    FreelancerEarnings, MintConfig, PlatformFeeCollected,
+};
+use taskfi_test_utils::*;
+
+/// A platform charging a 2% fee, a quarter of which goes to the referring affiliate
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.platform_fee_bps = 200;
+        params.fee_splits = vec![
+            FeeSplit {
+                destination: FeeDestination::Treasury,
+                bps: 7_500,
+            },
+            FeeSplit {
+                destination: FeeDestination::Referrer,
+                bps: 2_500,
+            },
+        ];
+    });
+    (env, platform)
+}
+
+/// Register an affiliate and have a fresh hirer join it
+fn referred_hirer(env: &mut TestEnv, platform: &Platform) -> (Pubkey, Pubkey) {
+    let affiliate = env.create_wallet();
+    let hirer = env.create_wallet();
+    env.process(platform.register_affiliate_ix(&affiliate), &[affiliate])
+        .unwrap();
+    env.process(platform.join_affiliate_ix(&hirer, &affiliate), &[hirer])
+        .unwrap();
+    (affiliate, hirer)
+}
+
+#[test]
+fn release_withholds_the_platform_fee() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-fee").create(&mut env);
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 980_000);
+    assert_eq!(env.token_balance(&escrow.treasury_token_account), 20_000);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+
+    let collected = env.events::<PlatformFeeCollected>();
+    assert_eq!((collected[0].fee, collected[0].affiliate), (20_000, None));
+    let earnings: FreelancerEarnings = env.get(&escrow.earnings);
+    assert_eq!(
+        (earnings.gross_earned, earnings.fees_paid),
+        (DEFAULT_AMOUNT, 20_000)
+    );
+}
+
+#[test]
+fn fees_only_reach_the_configured_treasury() {
+    let (mut env, platform) = setup();
+    let mut escrow = EscrowBuilder::new("job-fee-treasury").create(&mut env);
+    let stranger = env.create_wallet();
+    escrow.treasury_token_account = env.create_token_account(&stranger, &escrow.mint, 0);
+
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidTreasuryAccount);
+}
+
+#[test]
+fn dispute_rulings_charge_the_fee_on_the_freelancer_share() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-fee-ruling").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.next_slot();
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 500_000, 500_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 500_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 490_000);
+    assert_eq!(env.token_balance(&escrow.treasury_token_account), 10_000);
+}
+
+#[test]
+fn affiliates_earn_a_share_of_referred_fees() {
+    let (mut env, platform) = setup();
+    let (affiliate, hirer) = referred_hirer(&mut env, &platform);
+    let escrow = EscrowBuilder::new("job-affiliate")
+        .hirer(hirer)
+        .create(&mut env);
+    assert_eq!(escrow.affiliate, Some(pda::affiliate(&affiliate)));
+
+    env.process(escrow.release_ix(&platform, &hirer), &[hirer])
+        .unwrap();
+    let vault = escrow.affiliate_vault.unwrap();
+    assert_eq!(env.token_balance(&vault), 5_000);
+    assert_eq!(env.token_balance(&escrow.treasury_token_account), 15_000);
+
+    let account: Affiliate = env.get(&pda::affiliate(&affiliate));
+    assert_eq!((account.referred_hirers, account.total_accrued), (1, 5_000));
+
+    let payout = env.create_token_account(&affiliate, &escrow.mint, 0);
+    let claim = platform.claim_affiliate_rewards_ix(&affiliate, &escrow.mint);
+    env.process(claim.clone(), &[affiliate]).unwrap();
+    assert_eq!(env.token_balance(&payout), 5_000);
+    assert_eq!(
+        env.get::<Affiliate>(&pda::affiliate(&affiliate))
+            .total_claimed,
+        5_000
+    );
+
+    let result = env.process(claim, &[affiliate]);
+    assert_error(result, EscrowError::NoAffiliateRewards);
+}
+
+#[test]
+fn referred_escrows_must_route_the_affiliate_share() {
+    let (mut env, platform) = setup();
+    let (_, hirer) = referred_hirer(&mut env, &platform);
+    assert!(env
+        .get::<EscrowIndex>(&pda::escrow_index(&hirer))
+        .referred_by
+        .is_some());
+
+    let mut escrow = EscrowBuilder::new("job-affiliate-skip")
+        .hirer(hirer)
+        .create(&mut env);
+    escrow.affiliate = None;
+    escrow.affiliate_vault = None;
+    let ix = escrow.release_ix(&platform, &hirer);
+    let result = env.process(ix, &[hirer]);
+    assert_error(result, EscrowError::AffiliateAccountsMissing);
+}
+
+#[test]
+fn hirers_join_an_affiliate_before_their_first_escrow() {
+    let (mut env, platform) = setup();
+    let affiliate = env.create_wallet();
+    env.process(platform.register_affiliate_ix(&affiliate), &[affiliate])
+        .unwrap();
+
+    let result = env.process(
+        platform.join_affiliate_ix(&affiliate, &affiliate),
+        &[affiliate],
+    );
+    assert_error(result, EscrowError::SelfReferral);
+
+    let hirer = env.create_wallet();
+    EscrowBuilder::new("job-late-join")
+        .hirer(hirer)
+        .create(&mut env);
+    let result = env.process(platform.join_affiliate_ix(&hirer, &affiliate), &[hirer]);
+    assert_error(result, EscrowError::ReferralWindowClosed);
+}
+
+#[test]
+fn coupons_discount_the_fee_until_used_up() {
+    let (mut env, platform) = setup();
+    let code_hash = [7u8; 32];
+    let coupon = pda::coupon(&code_hash);
+    env.process(
+        platform.create_coupon_ix(code_hash, 150, 1, env.now() + DAY),
+        &[platform.admin],
+    )
+    .unwrap();
+
+    let escrow = EscrowBuilder::new("job-coupon")
+        .coupon(coupon)
+        .create(&mut env);
+    let redeemed = env.events::<CouponRedeemed>();
+    assert_eq!((redeemed[0].fee_bps, redeemed[0].remaining_uses), (50, 0));
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&escrow.treasury_token_account), 5_000);
+
+    let (_, result) = EscrowBuilder::new("job-coupon-again")
+        .coupon(coupon)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::CouponExhausted);
+}
+
+#[test]
+fn expired_coupons_cannot_be_redeemed() {
+    let (mut env, platform) = setup();
+    let code_hash = [9u8; 32];
+    env.process(
+        platform.create_coupon_ix(code_hash, 200, 5, env.now() + DAY),
+        &[platform.admin],
+    )
+    .unwrap();
+
+    env.advance(DAY);
+    let (_, result) = EscrowBuilder::new("job-coupon-expired")
+        .coupon(pda::coupon(&code_hash))
+        .try_create(&mut env);
+    assert_error(result, EscrowError::CouponExpired);
+
+    let stranger = env.create_wallet();
+    let mut ix = platform.create_coupon_ix([1u8; 32], 200, 5, env.now() + DAY);
+    ix.accounts[3].pubkey = stranger;
+    let result = env.process(ix, &[stranger]);
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintHasOne);
+}
+
+#[test]
+fn hirers_can_route_part_of_the_fee_to_charity() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.platform_fee_bps = 200;
+        params.charity_share_bps = 5_000;
+    });
+    let mut escrow = EscrowBuilder::new("job-charity").create(&mut env);
+    env.process(escrow.set_charity_routing_ix(true), &[escrow.hirer])
+        .unwrap();
+
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidCharityAccount);
+
+    let charity = env.create_token_account(&platform.charity, &escrow.mint, 0);
+    escrow.charity_token_account = Some(charity);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&charity), 10_000);
+    assert_eq!(env.token_balance(&escrow.treasury_token_account), 10_000);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).charity_donated, 10_000);
+    assert_eq!(
+        env.events::<PlatformFeeCollected>()[0].charity_amount,
+        10_000
+    );
+
+    let result = env.process(escrow.set_charity_routing_ix(false), &[escrow.hirer]);
+    assert_error(result, EscrowError::AlreadyReleased);
+}
+
+#[test]
+fn fee_splits_fund_the_insurance_pool_and_burn() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.platform_fee_bps = 1_000;
+        params.fee_splits = vec![
+            FeeSplit {
+                destination: FeeDestination::Treasury,
+                bps: 5_000,
+            },
+            FeeSplit {
+                destination: FeeDestination::Referrer,
+                bps: 2_000,
+            },
+            FeeSplit {
+                destination: FeeDestination::InsurancePool,
+                bps: 2_000,
+            },
+            FeeSplit {
+                destination: FeeDestination::Burn,
+                bps: 1_000,
+            },
+        ];
+    });
+    let mut escrow = EscrowBuilder::new("job-splits").create(&mut env);
+    escrow.insurance_token_account =
+        Some(env.create_token_account(&platform.insurance_pool, &escrow.mint, 0));
+    let supply = env.mint(&escrow.mint).supply;
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    // Without an affiliate the referrer share stays with the treasury
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 900_000);
+    assert_eq!(env.token_balance(&escrow.treasury_token_account), 70_000);
+    assert_eq!(
+        env.token_balance(&escrow.insurance_token_account.unwrap()),
+        20_000
+    );
+    assert_eq!(env.mint(&escrow.mint).supply, supply - 10_000);
+    let collected = env.events::<PlatformFeeCollected>();
+    assert_eq!(
+        (collected[0].insurance_amount, collected[0].burn_amount),
+        (20_000, 10_000)
+    );
+}
+
+#[test]
+fn fee_splits_must_cover_the_whole_fee() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let mut params = default_config_params(platform.treasury, platform.moderator);
+    params.fee_splits = vec![FeeSplit {
+        destination: FeeDestination::Treasury,
+        bps: 9_000,
+    }];
+    let result = env.process(platform.update_config_ix(params), &[platform.admin]);
+    assert_error(result, EscrowError::InvalidFeeSplits);
+}
+
+#[test]
+fn mint_minimums_follow_each_mint_decimals() {
+    let (mut env, platform) = setup();
+    let usdc = env.create_mint(6);
+    let token = env.create_mint(9);
+    for (mint, minimum) in [(usdc, 1_000_000), (token, 1_000_000_000)] {
+        env.process(
+            platform.set_mint_config_ix(&mint, minimum, 0),
+            &[platform.admin],
+        )
+        .unwrap();
+    }
+    let config: MintConfig = env.get(&pda::mint_config(&token));
+    assert_eq!(
+        (config.decimals, config.min_escrow_amount),
+        (9, 1_000_000_000)
+    );
+
+    // One whole USDC clears its floor, while the same base units of the 9-decimal token do not
+    EscrowBuilder::new("job-min-usdc")
+        .mint(usdc)
+        .amount(1_000_000)
+        .create(&mut env);
+    let (_, result) = EscrowBuilder::new("job-min-token")
+        .mint(token)
+        .amount(1_000_000)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::BelowMintMinimum);
+    EscrowBuilder::new("job-min-token")
+        .mint(token)
+        .amount(1_000_000_000)
+        .create(&mut env);
+
+    // Mints without a config keep accepting any amount
+    EscrowBuilder::new("job-min-other")
+        .amount(1)
+        .create(&mut env);
+}
+
+#[test]
+fn dust_fee_shares_go_to_the_treasury() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.platform_fee_bps = 1_000;
+        params.fee_splits = vec![
+            FeeSplit {
+                destination: FeeDestination::Treasury,
+                bps: 8_000,
+            },
+            FeeSplit {
+                destination: FeeDestination::InsurancePool,
+                bps: 2_000,
+            },
+        ];
+    });
+    let mint = env.create_mint(MINT_DECIMALS);
+    let escrow = |job_id: &str| EscrowBuilder::new(job_id).mint(mint).amount(10_000);
+
+    // Without a dust threshold the 200 token insurance share needs the pool's account
+    let plain = escrow("job-dust-plain").create(&mut env);
+    let result = env.process(plain.release_ix(&platform, &plain.hirer), &[plain.hirer]);
+    assert_error(result, EscrowError::InvalidInsuranceAccount);
+
+    env.process(
+        platform.set_mint_config_ix(&mint, 0, 500),
+        &[platform.admin],
+    )
+    .unwrap();
+    let dusty = escrow("job-dust").create(&mut env);
+    env.process(dusty.release_ix(&platform, &dusty.hirer), &[dusty.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&dusty.freelancer_token_account), 9_000);
+    assert_eq!(env.token_balance(&dusty.treasury_token_account), 1_000);
+    let collected = env.events::<PlatformFeeCollected>();
+    assert_eq!(collected[0].insurance_amount, 0);
+}
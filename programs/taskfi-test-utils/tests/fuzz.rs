@@ -0,0 +1,393 @@
+//! Randomized instruction sequences against the escrow state machine.
+//!
+//! Every case drives a few escrows through random instructions sent by random signers,
+//! then checks that no unauthorized signer ever succeeded and that each escrow's tokens
+//! and bonds still add up. Set `TASKFI_FUZZ_CASES` to run more cases and `TASKFI_FUZZ_SEED`
+//! to replay a single failing case.
+
+use std::env;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use taskfi_escrow::{EmergencyRefundReason, Escrow, BPS_DENOMINATOR};
+use taskfi_test_utils::*;
+
+const DEFAULT_CASES: u64 = 48;
+const STEPS_PER_CASE: usize = 60;
+const ESCROWS_PER_CASE: usize = 3;
+
+/// SplitMix64, so every case is reproducible from its seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Actor {
+    Hirer,
+    Freelancer,
+    Admin,
+    Moderator,
+    Stranger,
+}
+
+const ACTORS: [Actor; 5] = [
+    Actor::Hirer,
+    Actor::Freelancer,
+    Actor::Admin,
+    Actor::Moderator,
+    Actor::Stranger,
+];
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Fund { amount: u64 },
+    Release,
+    RequestRelease,
+    ClaimAutoRelease,
+    ProposeRelease,
+    CancelReleaseProposal,
+    InitiateDispute,
+    Escalate,
+    Resolve { freelancer_amount: u64, skew: u64 },
+    SettleBonds,
+    RequestRefund,
+    ApproveRefund,
+    EmergencyRefund,
+    Freeze { duration: i64 },
+    Unfreeze,
+    WithdrawHeld,
+    Clawback,
+    Warp { seconds: i64 },
+}
+
+impl Action {
+    fn random(rng: &mut Rng, amount: u64) -> Self {
+        match rng.below(18) {
+            17 => Action::Fund {
+                amount: 1 + rng.below(amount),
+            },
+            0 => Action::Release,
+            1 => Action::RequestRelease,
+            2 => Action::ClaimAutoRelease,
+            3 => Action::ProposeRelease,
+            4 => Action::CancelReleaseProposal,
+            5 => Action::InitiateDispute,
+            6 => Action::Escalate,
+            7 => Action::Resolve {
+                freelancer_amount: rng.below(amount + 1),
+                skew: rng.pick(&[0, 0, 0, 1]),
+            },
+            8 => Action::SettleBonds,
+            9 => Action::RequestRefund,
+            10 => Action::ApproveRefund,
+            11 => Action::EmergencyRefund,
+            12 => Action::Freeze {
+                duration: rng.pick(&[-1, 0, DAY, 3 * DAY, 8 * DAY]),
+            },
+            13 => Action::Unfreeze,
+            14 => Action::WithdrawHeld,
+            15 => Action::Clawback,
+            _ => Action::Warp {
+                seconds: rng.pick(&[60, DAY / 2, DAY, 2 * DAY, 4 * DAY]),
+            },
+        }
+    }
+
+    /// Signers that may succeed with this action; `None` for permissionless cranks
+    fn allowed(self) -> Option<&'static [Actor]> {
+        use Actor::*;
+        match self {
+            Action::Release | Action::ProposeRelease | Action::CancelReleaseProposal => {
+                Some(&[Hirer, Admin])
+            }
+            Action::RequestRelease
+            | Action::ClaimAutoRelease
+            | Action::ApproveRefund
+            | Action::WithdrawHeld => Some(&[Freelancer]),
+            Action::InitiateDispute | Action::Escalate => Some(&[Hirer, Freelancer]),
+            Action::Resolve { .. } | Action::Freeze { .. } | Action::Unfreeze => {
+                Some(&[Moderator, Admin])
+            }
+            Action::Fund { .. } | Action::RequestRefund => Some(&[Hirer]),
+            Action::EmergencyRefund | Action::Clawback => Some(&[Admin]),
+            Action::SettleBonds | Action::Warp { .. } => None,
+        }
+    }
+}
+
+/// Replace the signing account `from` with `to`, for builders that hardcode their signer
+fn signed_by(mut ix: Instruction, from: Pubkey, to: Pubkey) -> Instruction {
+    for meta in ix
+        .accounts
+        .iter_mut()
+        .filter(|m| m.is_signer && m.pubkey == from)
+    {
+        meta.pubkey = to;
+    }
+    ix
+}
+
+/// One escrow under test and what has been observed about it so far
+struct Tracked {
+    fixture: EscrowFixture,
+    released: bool,
+    freelancer_balance: u64,
+}
+
+struct Case {
+    seed: u64,
+    env: TestEnv,
+    platform: Platform,
+    stranger: Pubkey,
+    escrows: Vec<Tracked>,
+    trace: Vec<String>,
+}
+
+impl Case {
+    fn new(seed: u64, rng: &mut Rng) -> Self {
+        let mut env = TestEnv::new();
+        let two_step = rng.pick(&[0, 0, 1]);
+        let platform = Platform::setup_with(&mut env, |params| {
+            params.two_step_release_threshold = two_step;
+        });
+        let stranger = env.create_wallet();
+        let escrows = (0..ESCROWS_PER_CASE)
+            .map(|i| {
+                let mut builder = EscrowBuilder::new(&format!("fuzz-{i}"))
+                    .amount(1 + rng.below(10 * DEFAULT_AMOUNT))
+                    .deadline_in(rng.pick(&[DAY, 7 * DAY]))
+                    .clawback_window(rng.pick(&[0, 0, 2 * DAY]));
+                // Some escrows are created empty and funded in tranches by `Action::Fund`
+                if let Some(bps) = rng.pick(&[None, None, Some(5_000), Some(BPS_DENOMINATOR)]) {
+                    builder = builder.funding_threshold(bps);
+                }
+                let fixture = builder.create(&mut env);
+                Tracked {
+                    fixture,
+                    released: false,
+                    freelancer_balance: 0,
+                }
+            })
+            .collect();
+        Self {
+            seed,
+            env,
+            platform,
+            stranger,
+            escrows,
+            trace: Vec::new(),
+        }
+    }
+
+    fn key(&self, index: usize, actor: Actor) -> Pubkey {
+        let escrow = &self.escrows[index].fixture;
+        match actor {
+            Actor::Hirer => escrow.hirer,
+            Actor::Freelancer => escrow.freelancer,
+            Actor::Admin => self.platform.admin,
+            Actor::Moderator => self.platform.moderator,
+            Actor::Stranger => self.stranger,
+        }
+    }
+
+    fn instruction(&self, index: usize, action: Action, signer: Pubkey) -> Option<Instruction> {
+        let platform = &self.platform;
+        let escrow = &self.escrows[index].fixture;
+        let ix = match action {
+            Action::Fund { amount } => signed_by(escrow.fund_ix(amount), escrow.funder, signer),
+            Action::Release => escrow.release_ix(platform, &signer),
+            Action::RequestRelease => escrow.request_release_ix(platform, &signer, None),
+            Action::ClaimAutoRelease => escrow.claim_auto_release_ix(platform, &signer, None),
+            Action::ProposeRelease => escrow.propose_release_ix(platform, &signer),
+            Action::CancelReleaseProposal => escrow.cancel_release_proposal_ix(platform, &signer),
+            Action::InitiateDispute => escrow.initiate_dispute_ix(&self.env, platform, &signer),
+            Action::Escalate => escrow.escalate_ix(platform, &signer),
+            Action::Resolve {
+                freelancer_amount,
+                skew,
+            } => escrow.resolve_ix(
+                platform,
+                &signer,
+                escrow.amount - freelancer_amount + skew,
+                freelancer_amount,
+            ),
+            Action::SettleBonds => escrow.settle_bonds_ix(),
+            Action::RequestRefund => signed_by(escrow.request_refund_ix(), escrow.hirer, signer),
+            Action::ApproveRefund => {
+                signed_by(escrow.approve_refund_ix(), escrow.freelancer, signer)
+            }
+            Action::EmergencyRefund => {
+                escrow.emergency_refund_ix(platform, &signer, EmergencyRefundReason::StalledDispute)
+            }
+            Action::Freeze { duration } => escrow.freeze_ix(platform, &signer, duration),
+            Action::Unfreeze => escrow.unfreeze_ix(platform, &signer),
+            Action::WithdrawHeld => signed_by(escrow.withdraw_held_ix(), escrow.freelancer, signer),
+            Action::Clawback => signed_by(escrow.clawback_ix(platform), platform.admin, signer),
+            Action::Warp { .. } => return None,
+        };
+        Some(ix)
+    }
+
+    fn step(&mut self, rng: &mut Rng) {
+        let index = rng.below(ESCROWS_PER_CASE as u64) as usize;
+        let action = Action::random(rng, self.escrows[index].fixture.amount);
+        let actor = rng.pick(&ACTORS);
+
+        if let Action::Warp { seconds } = action {
+            self.env.advance(seconds);
+            self.trace.push(format!("warp {seconds}s"));
+            return;
+        }
+
+        let signer = self.key(index, actor);
+        let ix = self.instruction(index, action, signer).unwrap();
+        let signers: &[Pubkey] = if action.allowed().is_some() {
+            &[signer]
+        } else {
+            &[]
+        };
+        let result = self.env.process(ix, signers);
+        self.trace.push(format!(
+            "escrow {index}: {action:?} by {actor:?} -> {:?}",
+            result.is_ok()
+        ));
+
+        if result.is_ok() {
+            if let Some(allowed) = action.allowed() {
+                if !allowed.contains(&actor) {
+                    self.fail(format!("{action:?} succeeded for unauthorized {actor:?}"));
+                }
+            }
+        }
+    }
+
+    /// Token conservation, vault backing, bond backing and monotonic settlement per escrow,
+    /// plus the program's own `assert_invariants` check
+    fn check_invariants(&mut self) {
+        for index in 0..self.escrows.len() {
+            if let Err(violation) = self.check_escrow(index) {
+                self.fail(format!("escrow {index}: {violation}"));
+            }
+            let escrow = &self.escrows[index].fixture;
+            if self.env.account(&escrow.escrow).is_some() {
+                let ix = escrow.assert_invariants_ix();
+                if let Err(error) = self.env.process(ix, &[self.stranger]) {
+                    self.fail(format!(
+                        "escrow {index}: assert_invariants failed: {error:?}"
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_escrow(&mut self, index: usize) -> Result<(), String> {
+        let env = &self.env;
+        let tracked = &mut self.escrows[index];
+        let escrow = &tracked.fixture;
+
+        let vault = env
+            .account(&escrow.vault)
+            .map(|_| env.token_balance(&escrow.vault));
+        let hirer = env.token_balance(&escrow.hirer_token_account);
+        let freelancer = env.token_balance(&escrow.freelancer_token_account);
+        let total = hirer + freelancer + vault.unwrap_or(0);
+        if total != escrow.amount {
+            return Err(format!(
+                "tokens not conserved: {total} != {}",
+                escrow.amount
+            ));
+        }
+        if freelancer < tracked.freelancer_balance {
+            return Err("freelancer payout was reversed".to_string());
+        }
+        tracked.freelancer_balance = freelancer;
+
+        match env.try_get::<Escrow>(&escrow.escrow) {
+            Some(state) => {
+                // A tranche-funded escrow only owes what has been funded so far
+                let outstanding = if !state.is_released || state.held_until.is_some() {
+                    state.amount.min(state.funded_amount)
+                } else {
+                    0
+                };
+                // Settlement closes the vault once nothing is left for it to hold
+                let expected = (!state.is_released || outstanding > 0).then_some(outstanding);
+                if vault != expected {
+                    return Err(format!(
+                        "vault holds {vault:?}, obligations are {outstanding}"
+                    ));
+                }
+                if tracked.released && !state.is_released {
+                    return Err("released escrow became unreleased".to_string());
+                }
+                tracked.released = state.is_released;
+
+                let data_len = env.account(&escrow.escrow).unwrap().data.len();
+                let required =
+                    env.rent().minimum_balance(data_len) + state.hirer_bond + state.freelancer_bond;
+                if env.lamports(&escrow.escrow) < required {
+                    return Err("escalation bonds are not backed by lamports".to_string());
+                }
+            }
+            None => {
+                if vault.is_some() {
+                    return Err("escrow closed with its vault still open".to_string());
+                }
+                if hirer + freelancer != escrow.amount {
+                    return Err("escrow closed before paying out".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fail(&self, reason: String) -> ! {
+        panic!(
+            "fuzz case {} failed: {reason}\nreplay with TASKFI_FUZZ_SEED={}\n{}",
+            self.seed,
+            self.seed,
+            self.trace.join("\n")
+        );
+    }
+}
+
+fn run_case(seed: u64) {
+    let mut rng = Rng(seed);
+    let mut case = Case::new(seed, &mut rng);
+    case.check_invariants();
+    for _ in 0..STEPS_PER_CASE {
+        case.step(&mut rng);
+        case.check_invariants();
+    }
+}
+
+#[test]
+fn random_instruction_sequences_preserve_invariants() {
+    if let Ok(seed) = env::var("TASKFI_FUZZ_SEED") {
+        run_case(seed.parse().expect("TASKFI_FUZZ_SEED must be a u64"));
+        return;
+    }
+    let cases = env::var("TASKFI_FUZZ_CASES")
+        .ok()
+        .and_then(|cases| cases.parse().ok())
+        .unwrap_or(DEFAULT_CASES);
+    for seed in 0..cases {
+        run_case(seed);
+    }
+}
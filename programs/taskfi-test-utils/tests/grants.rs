@@ -0,0 +1,170 @@
+use taskfi_escrow::{Escrow, EscrowError, GrantCancelled, GrantClaimed, GrantRevoked};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+#[test]
+fn grants_vest_linearly_to_the_recipient() {
+    let (mut env, _platform) = setup();
+    let grant = EscrowBuilder::new("grant-vest")
+        .grant(10 * DAY, 0)
+        .create(&mut env);
+
+    let result = env.process(grant.claim_vested_ix(), &[grant.freelancer]);
+    assert_error(result, EscrowError::NothingVested);
+
+    env.advance(4 * DAY);
+    env.process(grant.claim_vested_ix(), &[grant.freelancer])
+        .unwrap();
+    assert_eq!(env.token_balance(&grant.freelancer_token_account), 400_000);
+    assert_eq!(env.get::<Escrow>(&grant.escrow).amount, 600_000);
+
+    env.advance(10 * DAY);
+    env.process(grant.claim_vested_ix(), &[grant.freelancer])
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&grant.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert_eq!(
+        env.events::<GrantClaimed>()[0].total_claimed,
+        DEFAULT_AMOUNT
+    );
+    let state: Escrow = env.get(&grant.escrow);
+    assert!(state.is_released);
+    assert_eq!(env.token_balance(&grant.vault), 0);
+}
+
+#[test]
+fn grants_skip_the_deliverable_flows() {
+    let (mut env, platform) = setup();
+    let grant = EscrowBuilder::new("grant-flows")
+        .grant(10 * DAY, 0)
+        .create(&mut env);
+
+    let result = env.process(grant.release_ix(&platform, &grant.hirer), &[grant.hirer]);
+    assert_error(result, EscrowError::GrantEscrow);
+    let result = env.process(
+        grant.initiate_dispute_ix(&env, &platform, &grant.freelancer),
+        &[grant.freelancer],
+    );
+    assert_error(result, EscrowError::GrantEscrow);
+
+    let job = EscrowBuilder::new("job-not-grant").create(&mut env);
+    let result = env.process(job.claim_vested_ix(), &[job.freelancer]);
+    assert_error(result, EscrowError::NotAGrant);
+}
+
+#[test]
+fn grantor_revokes_the_unvested_part_within_the_window() {
+    let (mut env, _platform) = setup();
+    let grant = EscrowBuilder::new("grant-revoke")
+        .grant(10 * DAY, 5 * DAY)
+        .create(&mut env);
+
+    env.advance(2 * DAY);
+    env.process(grant.revoke_grant_ix(), &[grant.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&grant.hirer_token_account), 800_000);
+    let revoked = env.events::<GrantRevoked>();
+    assert_eq!((revoked[0].refunded, revoked[0].vested), (800_000, 200_000));
+
+    // The vested part stays claimable, and nothing more vests after the revocation
+    env.advance(8 * DAY);
+    env.process(grant.claim_vested_ix(), &[grant.freelancer])
+        .unwrap();
+    assert_eq!(env.token_balance(&grant.freelancer_token_account), 200_000);
+    assert!(env.get::<Escrow>(&grant.escrow).is_released);
+    assert!(env.account(&grant.vault).is_none());
+
+    let result = env.process(grant.revoke_grant_ix(), &[grant.hirer]);
+    assert_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn revoking_everything_closes_the_vault_on_its_rent_payer() {
+    let (mut env, _platform) = setup();
+    let sponsor = env.create_wallet();
+    let grant = EscrowBuilder::new("grant-revoke-all")
+        .grant(10 * DAY, 5 * DAY)
+        .rent_payer(sponsor)
+        .create(&mut env);
+    let vault_rent = env.lamports(&grant.vault);
+    let sponsor_lamports = env.lamports(&sponsor);
+
+    env.process(grant.revoke_grant_ix(), &[grant.hirer])
+        .unwrap();
+    assert_eq!(env.events::<GrantRevoked>()[0].refunded, DEFAULT_AMOUNT);
+    assert!(env.get::<Escrow>(&grant.escrow).is_released);
+    assert!(env.account(&grant.vault).is_none());
+    assert_eq!(env.lamports(&sponsor), sponsor_lamports + vault_rent);
+}
+
+#[test]
+fn revocation_window_closes() {
+    let (mut env, _platform) = setup();
+    let grant = EscrowBuilder::new("grant-window")
+        .grant(10 * DAY, DAY)
+        .create(&mut env);
+
+    env.advance(DAY);
+    let result = env.process(grant.revoke_grant_ix(), &[grant.hirer]);
+    assert_error(result, EscrowError::RevocationWindowElapsed);
+}
+
+#[test]
+fn cancelling_settles_the_vested_and_unvested_parts_at_once() {
+    let (mut env, _platform) = setup();
+    let grant = EscrowBuilder::new("grant-cancel")
+        .grant(10 * DAY, 5 * DAY)
+        .create(&mut env);
+
+    env.advance(2 * DAY);
+    env.process(grant.claim_vested_ix(), &[grant.freelancer])
+        .unwrap();
+    env.advance(DAY);
+    let stranger = env.create_wallet();
+    let result = env.process(grant.cancel_grant_ix(&stranger), &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedParty);
+
+    env.process(grant.cancel_grant_ix(&grant.hirer), &[grant.hirer])
+        .unwrap();
+    assert_eq!(env.token_balance(&grant.freelancer_token_account), 300_000);
+    assert_eq!(env.token_balance(&grant.hirer_token_account), 700_000);
+    assert!(env.account(&grant.vault).is_none());
+    let cancelled = env.events::<GrantCancelled>();
+    assert_eq!(cancelled[0].cancelled_by, grant.hirer);
+    assert_eq!(
+        (cancelled[0].paid, cancelled[0].refunded),
+        (100_000, 700_000)
+    );
+    assert!(env.get::<Escrow>(&grant.escrow).is_released);
+
+    // Nothing is left to claim out of the closed vault
+    let result = env.process(grant.claim_vested_ix(), &[grant.freelancer]);
+    assert_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn only_the_recipient_cancels_an_irrevocable_grant() {
+    let (mut env, _platform) = setup();
+    let grant = EscrowBuilder::new("grant-walk-away")
+        .grant(10 * DAY, 0)
+        .create(&mut env);
+
+    env.advance(5 * DAY);
+    let result = env.process(grant.cancel_grant_ix(&grant.hirer), &[grant.hirer]);
+    assert_error(result, EscrowError::RevocationWindowElapsed);
+
+    env.process(
+        grant.cancel_grant_ix(&grant.freelancer),
+        &[grant.freelancer],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&grant.freelancer_token_account), 500_000);
+    assert_eq!(env.token_balance(&grant.hirer_token_account), 500_000);
+}
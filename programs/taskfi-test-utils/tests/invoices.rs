@@ -0,0 +1,77 @@
+use taskfi_escrow::{EscrowError, Invoice, InvoiceIssued};
+use taskfi_test_utils::*;
+
+const LINE_ITEMS: [u8; 32] = [42; 32];
+
+#[test]
+fn invoices_record_the_release_breakdown() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| params.platform_fee_bps = 200);
+    let escrow = EscrowBuilder::new("job-invoice").create(&mut env);
+
+    let result = env.process(escrow.issue_invoice_ix(0, LINE_ITEMS), &[escrow.freelancer]);
+    assert_error(result, EscrowError::NothingToInvoice);
+
+    let released_at = env.now();
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    env.advance(DAY);
+    let result = env.process(escrow.issue_invoice_ix(0, LINE_ITEMS), &[escrow.hirer]);
+    assert!(result.is_err());
+    env.process(escrow.issue_invoice_ix(0, LINE_ITEMS), &[escrow.freelancer])
+        .unwrap();
+
+    let invoice: Invoice = env.get(&pda::invoice(&escrow.escrow, 0));
+    assert_eq!(
+        (invoice.issuer, invoice.billed_to),
+        (escrow.freelancer, escrow.hirer)
+    );
+    assert_eq!(invoice.mint, escrow.mint);
+    assert_eq!(invoice.line_items_hash, LINE_ITEMS);
+    assert_eq!(
+        (invoice.gross, invoice.fee, invoice.net),
+        (1_000_000, 20_000, 980_000)
+    );
+    assert_eq!(
+        (invoice.first_paid_at, invoice.last_paid_at),
+        (released_at, released_at)
+    );
+    assert_eq!(invoice.issued_at, released_at + DAY);
+    assert_eq!(env.events::<InvoiceIssued>()[0].net, 980_000);
+
+    // Payouts are only invoiced once
+    let result = env.process(escrow.issue_invoice_ix(1, LINE_ITEMS), &[escrow.freelancer]);
+    assert_error(result, EscrowError::NothingToInvoice);
+}
+
+#[test]
+fn invoices_cover_every_payout_since_the_last_one() {
+    let mut env = TestEnv::new();
+    Platform::setup(&mut env);
+    let grant = EscrowBuilder::new("grant-invoice")
+        .grant(10 * DAY, 0)
+        .create(&mut env);
+    let start = env.now();
+
+    for _ in 0..2 {
+        env.advance(2 * DAY);
+        env.process(grant.claim_vested_ix(), &[grant.freelancer])
+            .unwrap();
+    }
+    env.process(grant.issue_invoice_ix(0, LINE_ITEMS), &[grant.freelancer])
+        .unwrap();
+    let first: Invoice = env.get(&pda::invoice(&grant.escrow, 0));
+    assert_eq!(first.gross, 400_000);
+    assert_eq!(
+        (first.first_paid_at, first.last_paid_at),
+        (start + 2 * DAY, start + 4 * DAY)
+    );
+
+    env.advance(DAY);
+    env.process(grant.claim_vested_ix(), &[grant.freelancer])
+        .unwrap();
+    env.process(grant.issue_invoice_ix(1, LINE_ITEMS), &[grant.freelancer])
+        .unwrap();
+    let second: Invoice = env.get(&pda::invoice(&grant.escrow, 1));
+    assert_eq!((second.invoice_no, second.gross), (1, 100_000));
+}
@@ -0,0 +1,447 @@
+use taskfi_escrow::{
+    vote_commitment, ArbitratorPaid, EscrowError, JurorAssignment, JurorPanel, JurorPool,
+    JurorRegistered, JurorSlashed, JurorsDrawn, PanelVotesTallied,
+};
+use taskfi_test_utils::*;
+
+const STAKE: u64 = 1_000;
+
+/// A platform with `count` registered jurors and an escrow escalated to the panel tier
+fn setup_panel_dispute(
+    count: usize,
+    job_id: &str,
+) -> (TestEnv, Platform, Vec<Juror>, EscrowFixture) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let jurors = platform.register_jurors(&mut env, count, STAKE);
+
+    let escrow = EscrowBuilder::new(job_id).create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    (env, platform, jurors, escrow)
+}
+
+/// Draw the panel as the moderator and return it
+fn draw(env: &mut TestEnv, platform: &Platform, escrow: &EscrowFixture) -> JurorPanel {
+    env.process(
+        escrow.draw_jurors_ix(platform, &platform.moderator, 3),
+        &[platform.moderator],
+    )
+    .unwrap();
+    env.get(&escrow.panel())
+}
+
+fn salt(index: u8) -> [u8; 32] {
+    [index + 1; 32]
+}
+
+/// Accept, commit and reveal `votes[i]` for every panel seat
+fn vote(env: &mut TestEnv, escrow: &EscrowFixture, panel: &JurorPanel, votes: &[u16]) {
+    for (index, juror) in panel.jurors.iter().enumerate() {
+        let index = index as u8;
+        env.process(escrow.accept_assignment_ix(index, juror), &[*juror])
+            .unwrap();
+        let commitment = vote_commitment(votes[index as usize], &salt(index), juror);
+        env.process(escrow.commit_vote_ix(index, juror, commitment), &[*juror])
+            .unwrap();
+    }
+    env.warp_to(panel.commit_deadline + 1);
+    for (index, juror) in panel.jurors.iter().enumerate() {
+        let index = index as u8;
+        env.process(
+            escrow.reveal_vote_ix(index, juror, votes[index as usize], salt(index)),
+            &[*juror],
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn registration_moves_stake_into_the_pool() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+
+    let juror = platform.create_juror(&mut env, STAKE);
+    let result = env.process(
+        platform.register_juror_ix(&juror, STAKE - 1),
+        &[juror.wallet],
+    );
+    assert_error(result, EscrowError::StakeBelowMinimum);
+
+    env.process(platform.register_juror_ix(&juror, STAKE), &[juror.wallet])
+        .unwrap();
+    assert_eq!(env.token_balance(&platform.pool_vault), STAKE);
+    assert_eq!(env.token_balance(&juror.token_account), 0);
+    assert_eq!(env.events::<JurorRegistered>()[0].total_stake, STAKE);
+
+    let pool: JurorPool = env.get(&platform.juror_pool);
+    assert_eq!(pool.total_stake, STAKE);
+    assert_eq!(pool.jurors[0].juror, juror.wallet);
+
+    env.process(platform.deregister_juror_ix(&juror), &[juror.wallet])
+        .unwrap();
+    assert_eq!(env.token_balance(&juror.token_account), STAKE);
+    assert!(env.get::<JurorPool>(&platform.juror_pool).jurors.is_empty());
+
+    let result = env.process(platform.deregister_juror_ix(&juror), &[juror.wallet]);
+    assert_error(result, EscrowError::JurorNotRegistered);
+}
+
+#[test]
+fn panels_are_drawn_only_at_the_panel_tier() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    platform.register_jurors(&mut env, 3, STAKE);
+    let escrow = EscrowBuilder::new("job-early-draw").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    let result = env.process(
+        escrow.draw_jurors_ix(&platform, &platform.moderator, 3),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::WrongDisputeTier);
+}
+
+#[test]
+fn drawn_jurors_get_assignments() {
+    let (mut env, platform, jurors, escrow) = setup_panel_dispute(4, "job-draw");
+    let stranger = env.create_wallet();
+
+    let result = env.process(escrow.draw_jurors_ix(&platform, &stranger, 3), &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedJurorDraw);
+    // The parties could time the draw to a slot hash they like
+    for party in [escrow.hirer, escrow.freelancer] {
+        let result = env.process(escrow.draw_jurors_ix(&platform, &party, 3), &[party]);
+        assert_error(result, EscrowError::UnauthorizedJurorDraw);
+    }
+    let result = env.process(
+        escrow.draw_jurors_ix(&platform, &platform.moderator, 2),
+        &[platform.moderator],
+    );
+    assert_error(result, EscrowError::InvalidAssignmentAccount);
+
+    let panel = draw(&mut env, &platform, &escrow);
+    assert_eq!(panel.jurors.len(), 3);
+    assert_eq!(env.events::<JurorsDrawn>()[0].jurors, panel.jurors);
+    for (index, juror) in panel.jurors.iter().enumerate() {
+        assert!(jurors.iter().any(|j| j.wallet == *juror));
+        let assignment: JurorAssignment = env.get(&escrow.assignment(index as u8));
+        assert_eq!(assignment.juror, *juror);
+        assert!(!assignment.responded);
+    }
+
+    let drawn = jurors.iter().find(|j| j.wallet == panel.jurors[0]).unwrap();
+    let result = env.process(platform.deregister_juror_ix(drawn), &[drawn.wallet]);
+    assert_error(result, EscrowError::JurorHasOpenAssignments);
+}
+
+#[test]
+fn stake_topped_up_after_the_draw_does_not_weigh_the_vote() {
+    let (mut env, platform, jurors, escrow) = setup_panel_dispute(3, "job-top-up");
+    let panel = draw(&mut env, &platform, &escrow);
+    vote(&mut env, &escrow, &panel, &[2_000, 6_000, 9_000]);
+    let assignment: JurorAssignment = env.get(&escrow.assignment(2));
+    assert_eq!(assignment.stake, STAKE);
+
+    // Seeing the other votes, the highest voter buys most of the pool's stake
+    let late = jurors.iter().find(|j| j.wallet == panel.jurors[2]).unwrap();
+    env.mint_tokens(&late.token_account, 10 * STAKE);
+    env.process(platform.register_juror_ix(late, 10 * STAKE), &[late.wallet])
+        .unwrap();
+
+    env.process(escrow.tally_ix(&panel), &[]).unwrap();
+    assert_eq!(env.events::<PanelVotesTallied>()[0].freelancer_bps, 6_000);
+}
+
+#[test]
+fn votes_follow_the_commit_reveal_schedule() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-schedule");
+    let panel = draw(&mut env, &platform, &escrow);
+
+    let juror = panel.jurors[0];
+    let commitment = vote_commitment(5_000, &salt(0), &juror);
+    let result = env.process(escrow.commit_vote_ix(0, &juror, commitment), &[juror]);
+    assert_error(result, EscrowError::AssignmentNotAccepted);
+
+    env.process(escrow.accept_assignment_ix(0, &juror), &[juror])
+        .unwrap();
+    let result = env.process(escrow.accept_assignment_ix(0, &juror), &[juror]);
+    assert_error(result, EscrowError::AssignmentAlreadyAnswered);
+
+    env.process(escrow.commit_vote_ix(0, &juror, commitment), &[juror])
+        .unwrap();
+    let result = env.process(escrow.reveal_vote_ix(0, &juror, 5_000, salt(0)), &[juror]);
+    assert_error(result, EscrowError::CommitWindowOpen);
+
+    env.warp_to(panel.commit_deadline + 1);
+    let result = env.process(escrow.commit_vote_ix(0, &juror, commitment), &[juror]);
+    assert_error(result, EscrowError::CommitWindowClosed);
+
+    env.warp_to(panel.reveal_deadline + 1);
+    let result = env.process(escrow.reveal_vote_ix(0, &juror, 5_000, salt(0)), &[juror]);
+    assert_error(result, EscrowError::RevealWindowClosed);
+}
+
+#[test]
+fn revealed_votes_are_tallied_into_a_split() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-tally");
+    let panel = draw(&mut env, &platform, &escrow);
+    vote(&mut env, &escrow, &panel, &[2_000, 6_000, 9_000]);
+
+    let juror = panel.jurors[0];
+    let result = env.process(escrow.reveal_vote_ix(0, &juror, 2_000, salt(0)), &[juror]);
+    assert_error(result, EscrowError::VoteAlreadyRevealed);
+
+    env.process(escrow.tally_ix(&panel), &[]).unwrap();
+    let tallied = env.events::<PanelVotesTallied>();
+    assert_eq!(tallied[0].freelancer_bps, 6_000);
+    assert_eq!(tallied[0].votes_counted, 3);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 600_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 400_000);
+    assert_eq!(
+        env.get::<JurorPanel>(&escrow.panel()).ruling_bps,
+        Some(6_000)
+    );
+
+    env.process(
+        escrow.close_assignment_ix(&platform, 1, &platform.moderator),
+        &[],
+    )
+    .unwrap();
+    let pool: JurorPool = env.get(&platform.juror_pool);
+    let entry = pool
+        .jurors
+        .iter()
+        .find(|j| j.juror == panel.jurors[1])
+        .unwrap();
+    assert_eq!(entry.open_assignments, 0);
+}
+
+#[test]
+fn tallied_escrows_close_once_bonds_and_assignments_are_settled() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-panel-close");
+    let panel = draw(&mut env, &platform, &escrow);
+    vote(&mut env, &escrow, &panel, &[5_000, 5_000, 5_000]);
+    env.process(escrow.tally_ix(&panel), &[]).unwrap();
+
+    let close = escrow.close_settled_after_panel_ix(&escrow.hirer, &panel);
+    let result = env.process(close.clone(), &[escrow.hirer]);
+    assert_error(result, EscrowError::EscrowNotSettled);
+    env.process(escrow.settle_bonds_ix(), &[]).unwrap();
+
+    let result = env.process(close.clone(), &[escrow.hirer]);
+    assert_error(result, EscrowError::AssignmentStillOpen);
+    let result = env.process(escrow.close_settled_ix(&escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidAssignmentAccount);
+
+    for index in 0..panel.jurors.len() as u8 {
+        env.process(
+            escrow.close_assignment_ix(&platform, index, &platform.moderator),
+            &[],
+        )
+        .unwrap();
+    }
+    env.process(close, &[escrow.hirer]).unwrap();
+    assert!(env.account(&escrow.escrow).is_none());
+}
+
+#[test]
+fn revealing_jurors_split_the_panel_fee() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| params.juror_fee_bps = 300);
+    platform.register_jurors(&mut env, 3, STAKE);
+    let escrow = EscrowBuilder::new("job-juror-fee").create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    for party in [escrow.hirer, escrow.freelancer] {
+        env.process(escrow.escalate_ix(&platform, &party), &[party])
+            .unwrap();
+    }
+    let panel = draw(&mut env, &platform, &escrow);
+    vote(&mut env, &escrow, &panel, &[2_000, 6_000, 9_000]);
+    for juror in &panel.jurors {
+        env.create_token_account(juror, &escrow.mint, 0);
+    }
+
+    // Without the jurors' token accounts the tally cannot pay them
+    let result = env.process(escrow.tally_ix(&panel), &[]);
+    assert_error(result, EscrowError::InvalidAssignmentAccount);
+
+    env.process(escrow.tally_paying_ix(&panel), &[]).unwrap();
+    for juror in &panel.jurors {
+        assert_eq!(env.token_balance(&pda::ata(juror, &escrow.mint)), 10_000);
+    }
+    let paid = env.events::<ArbitratorPaid>();
+    assert_eq!(paid.len(), 3);
+    assert!(paid.iter().all(|p| panel.jurors.contains(&p.arbitrator)));
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 582_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 388_000);
+}
+
+#[test]
+fn mismatched_reveal_is_rejected() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-mismatch");
+    let panel = draw(&mut env, &platform, &escrow);
+
+    let juror = panel.jurors[0];
+    env.process(escrow.accept_assignment_ix(0, &juror), &[juror])
+        .unwrap();
+    let commitment = vote_commitment(10_000, &salt(0), &juror);
+    env.process(escrow.commit_vote_ix(0, &juror, commitment), &[juror])
+        .unwrap();
+    env.warp_to(panel.commit_deadline + 1);
+
+    let result = env.process(escrow.reveal_vote_ix(0, &juror, 0, salt(0)), &[juror]);
+    assert_error(result, EscrowError::CommitmentMismatch);
+    let result = env.process(escrow.tally_ix(&panel), &[]);
+    assert_error(result, EscrowError::RevealWindowOpen);
+}
+
+#[test]
+fn absent_jurors_are_slashed_and_assignments_closed() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-slash");
+    let panel = draw(&mut env, &platform, &escrow);
+
+    let result = env.process(escrow.slash_juror_ix(&platform, 0), &[]);
+    assert_error(result, EscrowError::ResponseWindowOpen);
+    let result = env.process(
+        escrow.close_assignment_ix(&platform, 0, &platform.moderator),
+        &[],
+    );
+    assert_error(result, EscrowError::AssignmentStillOpen);
+
+    env.warp_to(panel.respond_by + 1);
+    let result = env.process(
+        escrow.accept_assignment_ix(0, &panel.jurors[0]),
+        &[panel.jurors[0]],
+    );
+    assert_error(result, EscrowError::ResponseWindowClosed);
+
+    env.process(escrow.slash_juror_ix(&platform, 0), &[])
+        .unwrap();
+    assert_eq!(env.events::<JurorSlashed>()[0].amount, 100);
+    assert_eq!(env.token_balance(&platform.treasury_stake_account), 100);
+    let pool: JurorPool = env.get(&platform.juror_pool);
+    let entry = pool
+        .jurors
+        .iter()
+        .find(|j| j.juror == panel.jurors[0])
+        .unwrap();
+    assert_eq!(entry.stake, STAKE - 100);
+    assert_eq!(entry.open_assignments, 0);
+    assert_eq!(pool.total_stake, 3 * STAKE - 100);
+
+    let result = env.process(escrow.slash_juror_ix(&platform, 0), &[]);
+    assert_error(result, EscrowError::AssignmentAlreadyAnswered);
+
+    let payer_lamports = env.lamports(&platform.moderator);
+    let rent = env.lamports(&escrow.assignment(0));
+    env.process(
+        escrow.close_assignment_ix(&platform, 0, &platform.moderator),
+        &[],
+    )
+    .unwrap();
+    assert!(env.account(&escrow.assignment(0)).is_none());
+    assert_eq!(env.lamports(&platform.moderator), payer_lamports + rent);
+}
+
+#[test]
+fn jurors_who_commit_but_never_reveal_are_slashed_after_the_tally() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-silent");
+    let panel = draw(&mut env, &platform, &escrow);
+
+    for (index, juror) in panel.jurors.iter().enumerate() {
+        let index = index as u8;
+        env.process(escrow.accept_assignment_ix(index, juror), &[*juror])
+            .unwrap();
+        let commitment = vote_commitment(5_000, &salt(index), juror);
+        env.process(escrow.commit_vote_ix(index, juror, commitment), &[*juror])
+            .unwrap();
+    }
+    env.warp_to(panel.commit_deadline + 1);
+    for (index, juror) in panel.jurors.iter().enumerate().take(2) {
+        let index = index as u8;
+        env.process(
+            escrow.reveal_vote_ix(index, juror, 5_000, salt(index)),
+            &[*juror],
+        )
+        .unwrap();
+    }
+    env.warp_to(panel.reveal_deadline + 1);
+    env.process(escrow.tally_ix(&panel), &[]).unwrap();
+    assert_eq!(env.events::<PanelVotesTallied>()[0].votes_counted, 2);
+
+    // The settled escrow does not let the silent juror walk away
+    let result = env.process(
+        escrow.close_assignment_ix(&platform, 2, &platform.moderator),
+        &[],
+    );
+    assert_error(result, EscrowError::AssignmentStillOpen);
+    let result = env.process(escrow.slash_juror_ix(&platform, 0), &[]);
+    assert_error(result, EscrowError::AssignmentAlreadyAnswered);
+
+    env.process(escrow.slash_juror_ix(&platform, 2), &[])
+        .unwrap();
+    let slashed = env.events::<JurorSlashed>();
+    assert_eq!(slashed[0].juror, panel.jurors[2]);
+    assert_eq!(slashed[0].amount, 100);
+    assert_eq!(env.token_balance(&platform.treasury_stake_account), 100);
+
+    env.process(
+        escrow.close_assignment_ix(&platform, 2, &platform.moderator),
+        &[],
+    )
+    .unwrap();
+    let pool: JurorPool = env.get(&platform.juror_pool);
+    let entry = pool
+        .jurors
+        .iter()
+        .find(|j| j.juror == panel.jurors[2])
+        .unwrap();
+    assert_eq!(entry.stake, STAKE - 100);
+    assert_eq!(entry.open_assignments, 0);
+}
+
+#[test]
+fn unanimous_panel_awards_the_freelancer_everything() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-unanimous");
+    let panel = draw(&mut env, &platform, &escrow);
+    vote(&mut env, &escrow, &panel, &[10_000, 10_000, 10_000]);
+
+    env.process(escrow.tally_ix(&panel), &[]).unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 0);
+}
+
+#[test]
+fn panel_without_reveals_cannot_be_tallied() {
+    let (mut env, platform, _jurors, escrow) = setup_panel_dispute(3, "job-quiet");
+    let panel = draw(&mut env, &platform, &escrow);
+
+    env.warp_to(panel.reveal_deadline + 1);
+    let result = env.process(escrow.tally_ix(&panel), &[]);
+    assert_error(result, EscrowError::NoRevealedVotes);
+}
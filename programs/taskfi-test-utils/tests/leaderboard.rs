@@ -0,0 +1,120 @@
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{EscrowError, Leaderboard, LeaderboardEntry};
+use taskfi_test_utils::*;
+
+/// Open and release an escrow of `amount` in `mint` paying `freelancer`
+fn settle(
+    env: &mut TestEnv,
+    platform: &Platform,
+    job_id: &str,
+    mint: Pubkey,
+    freelancer: Pubkey,
+    amount: u64,
+) {
+    let escrow = EscrowBuilder::new(job_id)
+        .mint(mint)
+        .freelancer(freelancer)
+        .amount(amount)
+        .create(env);
+    env.process(escrow.release_ix(platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+}
+
+#[test]
+fn payouts_keep_freelancers_ranked_by_volume() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let mint = env.create_mint(MINT_DECIMALS);
+    let leaderboard = platform.initialize_leaderboard(&mut env, &mint);
+
+    let freelancers: Vec<_> = (0..3).map(|_| env.create_wallet()).collect();
+    for (i, (freelancer, amount)) in freelancers.iter().zip([300, 500, 100]).enumerate() {
+        settle(
+            &mut env,
+            &platform,
+            &format!("job-rank-{i}"),
+            mint,
+            *freelancer,
+            amount,
+        );
+    }
+    let board: Leaderboard = env.get(&leaderboard);
+    let ranked: Vec<_> = board
+        .entries
+        .iter()
+        .map(|e| (e.freelancer, e.volume))
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![
+            (freelancers[1], 500),
+            (freelancers[0], 300),
+            (freelancers[2], 100)
+        ]
+    );
+
+    // Further payouts add to the freelancer's volume and move them up
+    settle(
+        &mut env,
+        &platform,
+        "job-rank-again",
+        mint,
+        freelancers[2],
+        450,
+    );
+    let board: Leaderboard = env.get(&leaderboard);
+    assert_eq!(
+        board.entries[0],
+        LeaderboardEntry {
+            freelancer: freelancers[2],
+            volume: 550
+        }
+    );
+}
+
+#[test]
+fn full_leaderboards_drop_their_lowest_entry() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let mint = env.create_mint(MINT_DECIMALS);
+    let leaderboard = platform.initialize_leaderboard(&mut env, &mint);
+
+    let lowest = env.create_wallet();
+    settle(&mut env, &platform, "job-low", mint, lowest, 50);
+    for i in 0..Leaderboard::MAX_ENTRIES {
+        let freelancer = env.create_wallet();
+        settle(
+            &mut env,
+            &platform,
+            &format!("job-top-{i}"),
+            mint,
+            freelancer,
+            100 + i as u64,
+        );
+    }
+    let board: Leaderboard = env.get(&leaderboard);
+    assert_eq!(board.entries.len(), Leaderboard::MAX_ENTRIES);
+    assert!(board.entries.iter().all(|e| e.freelancer != lowest));
+    assert_eq!(
+        board.entries[0].volume,
+        100 + Leaderboard::MAX_ENTRIES as u64 - 1
+    );
+
+    // A payout too small to make the board leaves it unchanged
+    let newcomer = env.create_wallet();
+    settle(&mut env, &platform, "job-small", mint, newcomer, 10);
+    assert_eq!(env.get::<Leaderboard>(&leaderboard).entries, board.entries);
+}
+
+#[test]
+fn leaderboards_only_rank_payouts_in_their_mint() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    let mint = env.create_mint(MINT_DECIMALS);
+    let leaderboard = platform.initialize_leaderboard(&mut env, &mint);
+
+    let mut escrow = EscrowBuilder::new("job-other-mint").create(&mut env);
+    escrow.leaderboard = Some(leaderboard);
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidLeaderboard);
+}
@@ -0,0 +1,513 @@
+use taskfi_escrow::{
+    ArbitratorPaid, DailyStats, EmergencyRefundReason, Escrow, EscrowError,
+    MilestoneDisputeResolved, MilestoneRefunded, MilestoneReleased, MilestoneResolutionApproved,
+    MilestoneStatus, MilestoneSubmitted, MilestoneTerms, Reputation, BPS_DENOMINATOR,
+};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+/// Milestones of `amounts`, each due `days` from now without a late penalty
+fn due_in(env: &TestEnv, days: i64, amounts: &[u64]) -> Vec<MilestoneTerms> {
+    amounts
+        .iter()
+        .map(|&amount| MilestoneTerms {
+            amount,
+            deadline: env.now() + days * DAY,
+            late_penalty_bps: 0,
+        })
+        .collect()
+}
+
+#[test]
+fn milestones_split_the_amount_and_release_one_at_a_time() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-milestones").create(&mut env);
+    let both = [escrow.hirer, escrow.freelancer];
+
+    for amounts in [&[][..], &[300_000, 600_000], &[0, DEFAULT_AMOUNT]] {
+        let result = env.process(escrow.set_milestones_ix(&due_in(&env, 7, amounts)), &both);
+        assert_error(result, EscrowError::InvalidMilestones);
+    }
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 7, &[300_000, 700_000])),
+        &both,
+    )
+    .unwrap();
+    let result = env.process(
+        escrow.set_milestones_ix(&due_in(&env, 7, &[DEFAULT_AMOUNT])),
+        &both,
+    );
+    assert_error(result, EscrowError::MilestonesAlreadySet);
+
+    // The escrow-wide request and dispute give way to their milestone counterparts
+    let ix = escrow.request_release_ix(&platform, &escrow.freelancer, None);
+    let result = env.process(ix, &[escrow.freelancer]);
+    assert_error(result, EscrowError::MilestoneEscrow);
+    let ix = escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer);
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, EscrowError::MilestoneEscrow);
+    let ix = escrow.propose_change_order_ix(&env, &escrow.freelancer, 0, DAY);
+    let result = env.process(ix, &[escrow.freelancer]);
+    assert_error(result, EscrowError::MilestoneEscrow);
+
+    let ix = escrow.release_milestone_ix(&platform, &escrow.freelancer, 0);
+    let result = env.process(ix, &[escrow.freelancer]);
+    assert_error(result, EscrowError::UnauthorizedRelease);
+    let release = escrow.release_milestone_ix(&platform, &escrow.hirer, 0);
+    env.process(release.clone(), &[escrow.hirer]).unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 300_000);
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.amount, 700_000);
+    assert!(!state.is_released);
+    let result = env.process(release, &[escrow.hirer]);
+    assert_error(result, EscrowError::MilestoneNotOpen);
+
+    let ix = escrow.release_milestone_ix(&platform, &escrow.hirer, 1);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    assert_eq!(
+        env.token_balance(&escrow.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+}
+
+#[test]
+fn settling_the_last_milestone_closes_the_vault_on_its_rent_payer() {
+    let (mut env, platform) = setup();
+    let sponsor = env.create_wallet();
+    let escrow = EscrowBuilder::new("job-milestone-rent")
+        .rent_payer(sponsor)
+        .create(&mut env);
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 1, &[300_000, 700_000])),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+    let vault_rent = env.lamports(&escrow.vault);
+    let sponsor_lamports = env.lamports(&sponsor);
+
+    let ix = escrow.release_milestone_ix(&platform, &escrow.hirer, 0);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    assert!(env.account(&escrow.vault).is_some());
+
+    // Refunding the overdue remainder empties the vault and settles the escrow
+    env.advance(DAY + 1);
+    env.process(escrow.refund_overdue_milestone_ix(1), &[])
+        .unwrap();
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+    assert!(env.account(&escrow.vault).is_none());
+    assert_eq!(env.lamports(&sponsor), sponsor_lamports + vault_rent);
+}
+
+#[test]
+fn disputed_milestones_leave_the_others_to_release() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-milestone-dispute").create(&mut env);
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 7, &[200_000, 300_000, 500_000])),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+
+    let stranger = env.create_wallet();
+    let result = env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &stranger, 1),
+        &[stranger],
+    );
+    assert_error(result, EscrowError::UnauthorizedDispute);
+    env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.freelancer, 1),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.milestones[1].status, MilestoneStatus::Disputed);
+    assert!(!state.is_disputed);
+
+    let ix = escrow.release_milestone_ix(&platform, &escrow.hirer, 1);
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, EscrowError::MilestoneNotOpen);
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::MilestoneDisputed);
+    // A mutual refund would close the escrow without a ruling on the milestone
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+    let result = env.process(escrow.approve_refund_ix(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::MilestoneDisputed);
+    for index in [0, 2] {
+        let ix = escrow.release_milestone_ix(&platform, &escrow.hirer, index);
+        env.process(ix, &[escrow.hirer]).unwrap();
+    }
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 700_000);
+    assert_eq!(env.token_balance(&escrow.vault), 300_000);
+
+    let ix = escrow.resolve_milestone_dispute_ix(&platform, &stranger, 1, 100_000);
+    let result = env.process(ix, &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedResolution);
+    let ix = escrow.resolve_milestone_dispute_ix(&platform, &platform.moderator, 1, 300_001);
+    let result = env.process(ix, &[platform.moderator]);
+    assert_error(result, EscrowError::InvalidSplitAmount);
+    // A ruling cannot land in the slot the dispute was opened in
+    let ix = escrow.resolve_milestone_dispute_ix(&platform, &platform.moderator, 1, 100_000);
+    let result = env.process(ix.clone(), &[platform.moderator]);
+    assert_error(result, EscrowError::SameSlotAction);
+    env.next_slot();
+    env.process(ix, &[platform.moderator]).unwrap();
+
+    let resolved = env.events::<MilestoneDisputeResolved>();
+    assert_eq!(resolved[0].index, 1);
+    assert_eq!(resolved[0].hirer_amount, 200_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 800_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 200_000);
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+}
+
+#[test]
+fn emergency_refunds_close_open_milestone_disputes() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-milestone-emergency").create(&mut env);
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 1, &[400_000, 600_000])),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.freelancer, 1),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let reputation = pda::reputation(&escrow.freelancer);
+    assert_eq!(env.get::<Reputation>(&reputation).open_disputes, 1);
+
+    env.warp_to(env.get::<Escrow>(&escrow.escrow).deadline + 1);
+    env.process(
+        escrow.emergency_refund_ix(
+            &platform,
+            &platform.admin,
+            EmergencyRefundReason::StalledDispute,
+        ),
+        &[platform.admin],
+    )
+    .unwrap();
+    assert_eq!(env.get::<Reputation>(&reputation).open_disputes, 0);
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+}
+
+#[test]
+fn amendments_resplit_unstarted_milestones_within_the_funded_amount() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-milestone-amend").create(&mut env);
+    let both = [escrow.hirer, escrow.freelancer];
+    let result = env.process(
+        escrow.amend_milestones_ix(&due_in(&env, 7, &[DEFAULT_AMOUNT])),
+        &both,
+    );
+    assert_error(result, EscrowError::NoMilestones);
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 7, &[200_000, 300_000, 500_000])),
+        &both,
+    )
+    .unwrap();
+    let ix = escrow.release_milestone_ix(&platform, &escrow.hirer, 0);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.hirer, 1),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    // Only the last milestone is unstarted, and the disputed one still counts
+    for amounts in [&[250_000, 250_001][..], &[700_000], &[500_000, 0]] {
+        let result = env.process(escrow.amend_milestones_ix(&due_in(&env, 7, amounts)), &both);
+        assert_error(result, EscrowError::InvalidMilestones);
+    }
+    let mut ix = escrow.amend_milestones_ix(&due_in(&env, 7, &[250_000, 250_000]));
+    ix.accounts[2].is_signer = false;
+    assert!(env.process(ix, &[escrow.hirer]).is_err());
+    env.process(
+        escrow.amend_milestones_ix(&due_in(&env, 7, &[250_000, 250_000])),
+        &both,
+    )
+    .unwrap();
+
+    let state: Escrow = env.get(&escrow.escrow);
+    let schedule: Vec<_> = state
+        .milestones
+        .iter()
+        .map(|milestone| (milestone.amount, milestone.status))
+        .collect();
+    assert_eq!(
+        schedule,
+        vec![
+            (200_000, MilestoneStatus::Settled),
+            (300_000, MilestoneStatus::Disputed),
+            (250_000, MilestoneStatus::Pending),
+            (250_000, MilestoneStatus::Pending),
+        ]
+    );
+    let ix = escrow.release_milestone_ix(&platform, &escrow.hirer, 3);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 450_000);
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).amount, 550_000);
+}
+
+#[test]
+fn milestones_keep_their_own_deadlines() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-milestone-deadlines").create(&mut env);
+    let both = [escrow.hirer, escrow.freelancer];
+    let result = env.process(
+        escrow.set_milestones_ix(&due_in(&env, 0, &[DEFAULT_AMOUNT])),
+        &both,
+    );
+    assert_error(result, EscrowError::InvalidDeadline);
+    let mut milestones = due_in(&env, 1, &[400_000, 600_000]);
+    milestones[0].late_penalty_bps = BPS_DENOMINATOR + 1;
+    let result = env.process(escrow.set_milestones_ix(&milestones), &both);
+    assert_error(result, EscrowError::InvalidMilestones);
+    milestones[0].late_penalty_bps = 500;
+    milestones[1].deadline += DAY;
+    env.process(escrow.set_milestones_ix(&milestones), &both)
+        .unwrap();
+    let hirer_balance = env.token_balance(&escrow.hirer_token_account);
+
+    let result = env.process(escrow.refund_overdue_milestone_ix(0), &[]);
+    assert_error(result, EscrowError::MilestoneNotOverdue);
+    env.advance(DAY + 1);
+    env.process(
+        escrow.submit_milestone_ix(&platform, 0),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let result = env.process(escrow.refund_overdue_milestone_ix(1), &[]);
+    assert_error(result, EscrowError::MilestoneNotOverdue);
+
+    // A submitted milestone is no longer refundable, however late it came in
+    env.advance(DAY);
+    let result = env.process(escrow.refund_overdue_milestone_ix(0), &[]);
+    assert_error(result, EscrowError::MilestoneNotOverdue);
+    env.process(escrow.refund_overdue_milestone_ix(1), &[])
+        .unwrap();
+    assert_eq!(env.events::<MilestoneRefunded>()[0].amount, 600_000);
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        hirer_balance + 600_000
+    );
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).milestones[1].status,
+        MilestoneStatus::Settled
+    );
+
+    // The first milestone came in a second past its own deadline and forfeits its penalty
+    let ix = escrow.release_milestone_ix(&platform, &escrow.hirer, 0);
+    env.process(ix, &[escrow.hirer]).unwrap();
+    assert_eq!(env.events::<MilestoneReleased>()[0].late_penalty, 20_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 380_000);
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        hirer_balance + 620_000
+    );
+    assert!(env.get::<Escrow>(&escrow.escrow).is_released);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+}
+
+#[test]
+fn submitted_milestones_become_claimable_after_the_review_window() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-milestone-claim").create(&mut env);
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 7, &[400_000, 600_000])),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+
+    let claim = escrow.claim_milestone_ix(&platform, &escrow.freelancer, 0);
+    let result = env.process(claim.clone(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::ReleaseNotRequested);
+    env.process(
+        escrow.submit_milestone_ix(&platform, 0),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let review_ends_at = env.events::<MilestoneSubmitted>()[0].review_ends_at;
+    assert_eq!(review_ends_at, env.now() + 3 * DAY);
+
+    env.warp_to(review_ends_at - 1);
+    let result = env.process(claim.clone(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::ReviewWindowOpen);
+    env.warp_to(review_ends_at);
+    let ix = escrow.claim_milestone_ix(&platform, &escrow.hirer, 0);
+    let result = env.process(ix, &[escrow.hirer]);
+    assert_error(result, EscrowError::UnauthorizedRelease);
+    env.process(claim.clone(), &[escrow.freelancer]).unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 400_000);
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.milestones[0].status, MilestoneStatus::Settled);
+    assert_eq!(state.amount, 600_000);
+    assert!(!state.is_released);
+    let result = env.process(claim, &[escrow.freelancer]);
+    assert_error(result, EscrowError::MilestoneNotOpen);
+
+    // A dispute inside the window keeps the milestone from being claimed
+    env.process(
+        escrow.submit_milestone_ix(&platform, 1),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.hirer, 1),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.advance(3 * DAY);
+    let ix = escrow.claim_milestone_ix(&platform, &escrow.freelancer, 1);
+    let result = env.process(ix, &[escrow.freelancer]);
+    assert_error(result, EscrowError::MilestoneNotOpen);
+}
+
+#[test]
+fn milestone_disputes_follow_the_dispute_opening_rules() {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.dispute_requires_trigger = true;
+        params.max_open_disputes = 1;
+        params.lost_dispute_cooldown = 7 * DAY;
+    });
+    let escrow = EscrowBuilder::new("job-milestone-rules").create(&mut env);
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 7, &[400_000, 600_000])),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+
+    // Nothing was delivered and the milestone is not yet due
+    let result = env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.hirer, 0),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::NoDisputeTrigger);
+    for index in [0, 1] {
+        env.process(
+            escrow.submit_milestone_ix(&platform, index),
+            &[escrow.freelancer],
+        )
+        .unwrap();
+    }
+    env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.hirer, 0),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let stats: DailyStats = env.get(&pda::daily_stats(env.now()));
+    assert_eq!(stats.disputes_opened, 1);
+    let result = env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.hirer, 1),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::TooManyOpenDisputes);
+
+    // The ruling frees the hirer's slot and puts the losing freelancer on cooldown
+    env.next_slot();
+    env.process(
+        escrow.resolve_milestone_dispute_ix(&platform, &platform.moderator, 0, 0),
+        &[platform.moderator],
+    )
+    .unwrap();
+    let hirer: Reputation = env.get(&pda::reputation(&escrow.hirer));
+    assert_eq!((hirer.open_disputes, hirer.disputes_initiated), (0, 1));
+    let freelancer: Reputation = env.get(&pda::reputation(&escrow.freelancer));
+    assert_eq!(freelancer.disputes_lost, 1);
+    let result = env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.freelancer, 1),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::DisputeCooldownActive);
+    env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.hirer, 1),
+        &[escrow.hirer],
+    )
+    .unwrap();
+}
+
+#[test]
+fn large_milestone_rulings_need_a_second_approver_and_pay_the_moderator() {
+    let mut env = TestEnv::new();
+    let second_moderator = env.create_wallet();
+    let platform = Platform::setup_with(&mut env, |params| {
+        params.moderators.push(second_moderator);
+        params.dual_approval_threshold = 500_000;
+        params.moderator_fee_bps = 500;
+    });
+    let escrow = EscrowBuilder::new("job-milestone-dual").create(&mut env);
+    env.process(
+        escrow.set_milestones_ix(&due_in(&env, 7, &[400_000, 600_000])),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.dispute_milestone_ix(&env, &platform, &escrow.hirer, 1),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.next_slot();
+
+    env.process(
+        escrow.resolve_milestone_dispute_ix(&platform, &platform.moderator, 1, 400_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+    let approved = env.events::<MilestoneResolutionApproved>();
+    assert_eq!(approved[0].approver, platform.moderator);
+    assert_eq!(approved[0].index, 1);
+    assert_eq!(env.token_balance(&escrow.vault), DEFAULT_AMOUNT);
+
+    let result = env.process(
+        escrow.resolve_milestone_dispute_ix(&platform, &second_moderator, 1, 300_000),
+        &[second_moderator],
+    );
+    assert_error(result, EscrowError::ResolutionSplitMismatch);
+    let result = env.process(
+        escrow.resolve_milestone_dispute_ix(&platform, &second_moderator, 1, 400_000),
+        &[second_moderator],
+    );
+    assert_error(result, EscrowError::InvalidArbitratorAccount);
+
+    // The moderator whose ruling executes earns the fee
+    let moderator_account = env.create_token_account(&platform.moderator, &escrow.mint, 0);
+    env.process(
+        escrow.resolve_milestone_paying_ix(
+            &platform,
+            &second_moderator,
+            1,
+            400_000,
+            Some(moderator_account),
+        ),
+        &[second_moderator],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&moderator_account), 30_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 380_000);
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 190_000);
+    assert_eq!(
+        env.events::<ArbitratorPaid>()[0].arbitrator,
+        platform.moderator
+    );
+    let resolved = env.events::<MilestoneDisputeResolved>().remove(0);
+    assert_eq!(
+        (resolved.hirer_amount, resolved.freelancer_amount),
+        (200_000, 400_000)
+    );
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.milestones[1].status, MilestoneStatus::Settled);
+    assert_eq!(state.milestones[1].pending_resolution, None);
+}
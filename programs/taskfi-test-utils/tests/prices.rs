@@ -0,0 +1,118 @@
+use anchor_lang::prelude::Pubkey;
+use taskfi_escrow::{Escrow, EscrowError, PriceFeed, PriceSnapshot, PriceSnapshotRecorded};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform, Pubkey) {
+    let mut env = TestEnv::new();
+    let oracle = env.create_wallet();
+    let platform = Platform::setup_with(&mut env, |params| params.price_oracle = oracle);
+    (env, platform, oracle)
+}
+
+#[test]
+fn only_the_oracle_publishes_prices() {
+    let (mut env, platform, oracle) = setup();
+    let mint = env.create_mint(MINT_DECIMALS);
+
+    let stranger = env.create_wallet();
+    let result = env.process(
+        platform.update_price_feed_ix(&stranger, &mint, 100_000_000, -8),
+        &[stranger],
+    );
+    assert_error(result, EscrowError::UnauthorizedPriceOracle);
+    let result = env.process(
+        platform.update_price_feed_ix(&oracle, &mint, 0, -8),
+        &[oracle],
+    );
+    assert_error(result, EscrowError::InvalidPrice);
+
+    env.process(
+        platform.update_price_feed_ix(&oracle, &mint, 99_980_000, -8),
+        &[oracle],
+    )
+    .unwrap();
+    let feed: PriceFeed = env.get(&pda::price_feed(&mint));
+    assert_eq!((feed.mint, feed.price, feed.expo), (mint, 99_980_000, -8));
+    assert_eq!(feed.published_at, env.now());
+}
+
+#[test]
+fn escrows_snapshot_the_price_at_creation_and_settlement() {
+    let (mut env, platform, oracle) = setup();
+    let mint = env.create_mint(MINT_DECIMALS);
+    env.process(
+        platform.update_price_feed_ix(&oracle, &mint, 100_000_000, -8),
+        &[oracle],
+    )
+    .unwrap();
+    let created_at = env.now();
+    let escrow = EscrowBuilder::new("job-priced").mint(mint).create(&mut env);
+    let created = PriceSnapshot {
+        price: 100_000_000,
+        expo: -8,
+        published_at: created_at,
+    };
+    assert_eq!(
+        env.get::<Escrow>(&escrow.escrow).created_price,
+        Some(created)
+    );
+
+    env.advance(DAY);
+    env.process(
+        platform.update_price_feed_ix(&oracle, &mint, 99_500_000, -8),
+        &[oracle],
+    )
+    .unwrap();
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    let settled = PriceSnapshot {
+        price: 99_500_000,
+        expo: -8,
+        published_at: created_at + DAY,
+    };
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(state.settled_price, Some(settled.clone()));
+    let recorded = env.events::<PriceSnapshotRecorded>();
+    assert!(recorded[0].at_settlement);
+    assert_eq!(recorded[0].snapshot, settled);
+}
+
+#[test]
+fn escrows_without_a_feed_carry_no_snapshot() {
+    let (mut env, platform, _oracle) = setup();
+    let escrow = EscrowBuilder::new("job-unpriced").create(&mut env);
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!((state.created_price, state.settled_price), (None, None));
+}
+
+#[test]
+fn stale_prices_are_not_snapshotted() {
+    let (mut env, platform, oracle) = setup();
+    let mint = env.create_mint(MINT_DECIMALS);
+    env.process(
+        platform.update_price_feed_ix(&oracle, &mint, 100_000_000, -8),
+        &[oracle],
+    )
+    .unwrap();
+
+    // A mint with a feed is always snapshotted, so a stale feed blocks creation
+    env.advance(DAY + 1);
+    let (_, result) = EscrowBuilder::new("job-stale")
+        .mint(mint)
+        .try_create(&mut env);
+    assert_error(result, EscrowError::StalePrice);
+
+    env.process(
+        platform.update_price_feed_ix(&oracle, &mint, 100_000_000, -8),
+        &[oracle],
+    )
+    .unwrap();
+    let escrow = EscrowBuilder::new("job-stale").mint(mint).create(&mut env);
+
+    env.advance(DAY + 1);
+    let result = env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer]);
+    assert_error(result, EscrowError::StalePrice);
+}
@@ -0,0 +1,127 @@
+use taskfi_escrow::{Escrow, EscrowError, RetainerPeriodPaid};
+use taskfi_test_utils::*;
+
+const PERIOD: i64 = 30 * DAY;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+#[test]
+fn auto_renewal_pulls_periods_until_the_allowance_runs_out() {
+    let (mut env, _platform) = setup();
+    let retainer = EscrowBuilder::new("retainer-renew")
+        .retainer(PERIOD, PERIOD)
+        .create(&mut env);
+    env.mint_tokens(&retainer.hirer_token_account, 5 * DEFAULT_AMOUNT);
+
+    let result = env.process(retainer.renew_retainer_ix(), &[]);
+    assert_error(result, EscrowError::RetainerPeriodActive);
+
+    env.process(retainer.enable_auto_renew_ix(2), &[retainer.hirer])
+        .unwrap();
+    for period in 1..=2 {
+        env.advance(PERIOD);
+        env.process(retainer.renew_retainer_ix(), &[]).unwrap();
+        assert_eq!(
+            env.token_balance(&retainer.freelancer_token_account),
+            period * DEFAULT_AMOUNT
+        );
+        assert_eq!(env.token_balance(&retainer.vault), DEFAULT_AMOUNT);
+    }
+    assert_eq!(
+        env.token_balance(&retainer.hirer_token_account),
+        3 * DEFAULT_AMOUNT
+    );
+
+    // The allowance covered two renewals, so the third payout settles the retainer
+    env.advance(PERIOD);
+    env.process(retainer.renew_retainer_ix(), &[]).unwrap();
+    let paid = env.events::<RetainerPeriodPaid>();
+    assert_eq!((paid[0].period, paid[0].renewed), (3, false));
+    assert_eq!(
+        env.token_balance(&retainer.freelancer_token_account),
+        3 * DEFAULT_AMOUNT
+    );
+    assert!(env.get::<Escrow>(&retainer.escrow).is_released);
+}
+
+#[test]
+fn retainers_without_auto_renewal_settle_after_one_period() {
+    let (mut env, _platform) = setup();
+    let retainer = EscrowBuilder::new("retainer-once")
+        .retainer(PERIOD, 0)
+        .create(&mut env);
+
+    env.advance(PERIOD);
+    env.process(retainer.renew_retainer_ix(), &[]).unwrap();
+    assert_eq!(
+        env.token_balance(&retainer.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert!(env.get::<Escrow>(&retainer.escrow).is_released);
+    assert!(env.account(&retainer.vault).is_none());
+
+    let result = env.process(retainer.renew_retainer_ix(), &[]);
+    assert_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn cancellation_waits_out_the_notice_period() {
+    let (mut env, _platform) = setup();
+    let retainer = EscrowBuilder::new("retainer-notice")
+        .retainer(PERIOD, 45 * DAY)
+        .create(&mut env);
+    env.mint_tokens(&retainer.hirer_token_account, 12 * DEFAULT_AMOUNT);
+    env.process(retainer.enable_auto_renew_ix(12), &[retainer.hirer])
+        .unwrap();
+
+    env.advance(10 * DAY);
+    let stranger = env.create_wallet();
+    let result = env.process(retainer.cancel_retainer_ix(&stranger), &[stranger]);
+    assert_error(result, EscrowError::UnauthorizedParty);
+    env.process(
+        retainer.cancel_retainer_ix(&retainer.hirer),
+        &[retainer.hirer],
+    )
+    .unwrap();
+    let result = env.process(
+        retainer.cancel_retainer_ix(&retainer.freelancer),
+        &[retainer.freelancer],
+    );
+    assert_error(result, EscrowError::RetainerAlreadyCancelled);
+
+    // Notice runs to day 55, so the day-30 payout still renews and the day-60 one settles
+    env.advance(20 * DAY);
+    env.process(retainer.renew_retainer_ix(), &[]).unwrap();
+    assert!(env.events::<RetainerPeriodPaid>()[0].renewed);
+    env.advance(PERIOD);
+    env.process(retainer.renew_retainer_ix(), &[]).unwrap();
+    assert!(!env.events::<RetainerPeriodPaid>()[0].renewed);
+    assert_eq!(
+        env.token_balance(&retainer.freelancer_token_account),
+        2 * DEFAULT_AMOUNT
+    );
+    assert!(env.get::<Escrow>(&retainer.escrow).is_released);
+}
+
+#[test]
+fn an_unfunded_renewal_still_pays_the_period() {
+    let (mut env, _platform) = setup();
+    let retainer = EscrowBuilder::new("retainer-drained")
+        .retainer(PERIOD, PERIOD)
+        .create(&mut env);
+    env.process(retainer.enable_auto_renew_ix(6), &[retainer.hirer])
+        .unwrap();
+
+    env.advance(PERIOD);
+    env.process(retainer.renew_retainer_ix(), &[]).unwrap();
+    assert_eq!(
+        env.token_balance(&retainer.freelancer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert!(!env.events::<RetainerPeriodPaid>()[0].renewed);
+    assert!(env.get::<Escrow>(&retainer.escrow).is_released);
+}
@@ -0,0 +1,232 @@
+use taskfi_escrow::{
+    Escrow, EscrowError, Reputation, SettlementExecuted, SettlementProposal, SettlementProposed,
+};
+use taskfi_test_utils::*;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+/// Create an escrow and open a dispute on it as the hirer, in an earlier slot
+fn disputed(env: &mut TestEnv, platform: &Platform, job_id: &str) -> EscrowFixture {
+    let escrow = EscrowBuilder::new(job_id).create(env);
+    env.process(
+        escrow.initiate_dispute_ix(env, platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.next_slot();
+    escrow
+}
+
+#[test]
+fn both_parties_accepting_executes_the_split() {
+    let (mut env, platform) = setup();
+    let escrow = disputed(&mut env, &platform, "job-settle");
+    let settlement = pda::settlement(&escrow.escrow);
+
+    let stranger = env.create_wallet();
+    let result = env.process(
+        escrow.propose_settlement_ix(&stranger, 300_000, 700_000),
+        &[stranger],
+    );
+    assert_error(result, EscrowError::UnauthorizedSettlement);
+    let result = env.process(
+        escrow.propose_settlement_ix(&escrow.freelancer, 300_000, 600_000),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::InvalidSplitAmount);
+
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    env.process(
+        escrow.propose_settlement_ix(&escrow.freelancer, 300_000, 700_000),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let proposed = env.events::<SettlementProposed>().remove(0);
+    assert_eq!(proposed.proposer, escrow.freelancer);
+    let proposal: SettlementProposal = env.get(&settlement);
+    assert!(proposal.freelancer_accepted && !proposal.hirer_accepted);
+
+    // The proposing party has already accepted, so the counterparty's acceptance executes it
+    let result = env.process(
+        escrow.accept_settlement_ix(&env, &platform, &stranger),
+        &[stranger],
+    );
+    assert_error(result, EscrowError::UnauthorizedSettlement);
+    env.process(
+        escrow.accept_settlement_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    assert_eq!(env.token_balance(&escrow.hirer_token_account), 300_000);
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 700_000);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+    let executed = env.events::<SettlementExecuted>().remove(0);
+    assert_eq!(
+        (executed.hirer_amount, executed.freelancer_amount),
+        (300_000, 700_000)
+    );
+
+    let state: Escrow = env.get(&escrow.escrow);
+    assert!(state.is_released);
+    assert_eq!(state.ruling_freelancer_amount, Some(700_000));
+    // The proposal closes and its rent goes back to whoever posted it
+    assert!(env.account(&settlement).is_none());
+    assert_eq!(env.lamports(&escrow.freelancer), freelancer_lamports);
+
+    // Settling frees the dispute slot without a cooldown for either side
+    let hirer: Reputation = env.get(&pda::reputation(&escrow.hirer));
+    assert_eq!(hirer.open_disputes, 0);
+    assert_eq!(hirer.disputes_lost, 0);
+    assert_eq!(hirer.disputes_resolved, 1);
+}
+
+#[test]
+fn mediator_proposals_need_both_parties() {
+    let mut env = TestEnv::new();
+    let mediator = env.create_wallet();
+    let platform = Platform::setup_with(&mut env, |params| params.mediators = vec![mediator]);
+    let escrow = disputed(&mut env, &platform, "job-settle-mediated");
+
+    // A registered mediator proposes only once attached to the dispute
+    let result = env.process(
+        escrow.propose_settlement_ix(&mediator, 500_000, 500_000),
+        &[mediator],
+    );
+    assert_error(result, EscrowError::UnauthorizedSettlement);
+    env.process(
+        escrow.assign_mediator_ix(&platform, &platform.moderator, &mediator),
+        &[platform.moderator],
+    )
+    .unwrap();
+    env.process(
+        escrow.propose_settlement_ix(&mediator, 500_000, 500_000),
+        &[mediator],
+    )
+    .unwrap();
+
+    env.process(
+        escrow.accept_settlement_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let proposal: SettlementProposal = env.get(&pda::settlement(&escrow.escrow));
+    assert!(proposal.hirer_accepted && !proposal.freelancer_accepted);
+    assert!(!env.get::<Escrow>(&escrow.escrow).is_released);
+
+    // A counter-proposal replaces the terms and drops the earlier acceptance
+    env.process(
+        escrow.propose_settlement_ix(&escrow.freelancer, 400_000, 600_000),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let proposal: SettlementProposal = env.get(&pda::settlement(&escrow.escrow));
+    assert!(!proposal.hirer_accepted && proposal.freelancer_accepted);
+    assert_eq!(proposal.rent_payer, mediator);
+
+    let mediator_lamports = env.lamports(&mediator);
+    env.process(
+        escrow.accept_settlement_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    assert_eq!(env.token_balance(&escrow.freelancer_token_account), 600_000);
+    assert!(env.lamports(&mediator) > mediator_lamports);
+}
+
+#[test]
+fn withdrawn_offers_stay_on_record() {
+    let (mut env, platform) = setup();
+    let escrow = disputed(&mut env, &platform, "job-settle-withdraw");
+    let settlement = pda::settlement(&escrow.escrow);
+
+    env.process(
+        escrow.propose_settlement_ix(&escrow.hirer, 800_000, 200_000),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    let result = env.process(
+        escrow.withdraw_settlement_ix(&escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::UnauthorizedSettlement);
+    env.process(
+        escrow.withdraw_settlement_ix(&escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    // Nothing is left to accept or withdraw
+    let result = env.process(
+        escrow.accept_settlement_ix(&env, &platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    );
+    assert_error(result, EscrowError::NoOpenSettlement);
+    let result = env.process(
+        escrow.withdraw_settlement_ix(&escrow.hirer),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::NoOpenSettlement);
+
+    env.process(
+        escrow.propose_settlement_ix(&escrow.freelancer, 500_000, 500_000),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let proposal: SettlementProposal = env.get(&settlement);
+    assert!(proposal.is_open);
+    let history: Vec<_> = proposal
+        .history
+        .iter()
+        .map(|offer| (offer.proposer, offer.hirer_amount, offer.withdrawn))
+        .collect();
+    assert_eq!(
+        history,
+        [
+            (escrow.hirer, 800_000, true),
+            (escrow.freelancer, 500_000, false)
+        ]
+    );
+
+    // A failed negotiation leaves the whole history readable once the dispute moves on
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    env.next_slot();
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 600_000, 400_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+    assert_eq!(env.get::<SettlementProposal>(&settlement).history.len(), 2);
+}
+
+#[test]
+fn negotiation_is_capped() {
+    let (mut env, platform) = setup();
+    let escrow = disputed(&mut env, &platform, "job-settle-cap");
+
+    for round in 0..SettlementProposal::MAX_OFFERS as u64 {
+        let proposer = if round % 2 == 0 {
+            escrow.hirer
+        } else {
+            escrow.freelancer
+        };
+        env.process(
+            escrow.propose_settlement_ix(&proposer, 1_000 * round, DEFAULT_AMOUNT - 1_000 * round),
+            &[proposer],
+        )
+        .unwrap();
+    }
+    let result = env.process(
+        escrow.propose_settlement_ix(&escrow.hirer, 0, DEFAULT_AMOUNT),
+        &[escrow.hirer],
+    );
+    assert_error(result, EscrowError::TooManySettlementOffers);
+}
@@ -0,0 +1,118 @@
+use taskfi_escrow::{EscrowError, PaymentReleased, SlaTerms};
+use taskfi_test_utils::*;
+
+const TERMS: SlaTerms = SlaTerms {
+    response_time: DAY,
+    late_period: DAY,
+    breach_bps: 500,
+    max_penalty_bps: 2_000,
+};
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+#[test]
+fn terms_need_both_parties_and_precede_delivery() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-sla-terms").create(&mut env);
+
+    let mut ix = escrow.set_sla_terms_ix(TERMS);
+    ix.accounts[2].is_signer = false;
+    assert!(env.process(ix, &[escrow.hirer]).is_err());
+
+    for terms in [
+        SlaTerms {
+            breach_bps: 2_001,
+            ..TERMS
+        },
+        SlaTerms {
+            late_period: 0,
+            ..TERMS
+        },
+    ] {
+        let result = env.process(
+            escrow.set_sla_terms_ix(terms),
+            &[escrow.hirer, escrow.freelancer],
+        );
+        assert_error(result, EscrowError::InvalidSlaTerms);
+    }
+
+    env.process(
+        escrow.request_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    let result = env.process(
+        escrow.set_sla_terms_ix(TERMS),
+        &[escrow.hirer, escrow.freelancer],
+    );
+    assert_error(result, EscrowError::WorkDelivered);
+}
+
+#[test]
+fn late_delivery_and_slow_revisions_are_withheld_at_release() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-sla")
+        .deadline_in(2 * DAY)
+        .create(&mut env);
+    env.process(
+        escrow.set_sla_terms_ix(TERMS),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+
+    // Two started days late, then a revision two days after the rejection
+    let request = escrow.request_release_ix(&platform, &escrow.freelancer, None);
+    env.advance(3 * DAY + DAY / 2);
+    env.process(request.clone(), &[escrow.freelancer]).unwrap();
+    env.process(escrow.reject_submission_ix([1; 32]), &[escrow.hirer])
+        .unwrap();
+    env.advance(2 * DAY);
+    env.process(request, &[escrow.freelancer]).unwrap();
+
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+    let released = env.events::<PaymentReleased>().remove(0);
+    assert_eq!(
+        (released.delivery_breaches, released.response_breaches),
+        (2, 1)
+    );
+    let penalty = DEFAULT_AMOUNT * 1_500 / 10_000;
+    assert_eq!(released.sla_penalty, penalty);
+    assert_eq!(released.amount, DEFAULT_AMOUNT - penalty);
+    assert_eq!(env.token_balance(&escrow.funder_token_account), penalty);
+    assert_eq!(env.token_balance(&escrow.vault), 0);
+}
+
+#[test]
+fn penalties_are_capped() {
+    let (mut env, platform) = setup();
+    let escrow = EscrowBuilder::new("job-sla-cap")
+        .deadline_in(DAY)
+        .create(&mut env);
+    env.process(
+        escrow.set_sla_terms_ix(TERMS),
+        &[escrow.hirer, escrow.freelancer],
+    )
+    .unwrap();
+
+    env.advance(30 * DAY);
+    env.process(
+        escrow.request_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.advance(3 * DAY);
+    env.process(
+        escrow.claim_auto_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+
+    let released = env.events::<PaymentReleased>().remove(0);
+    assert_eq!(released.delivery_breaches, 29);
+    assert_eq!(released.sla_penalty, DEFAULT_AMOUNT / 5);
+}
@@ -0,0 +1,188 @@
+use taskfi_escrow::{Escrow, EscrowError, FreelancerDefaulted, StakesSettled};
+use taskfi_test_utils::*;
+
+const STAKE: u64 = 50_000_000;
+
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup(&mut env);
+    (env, platform)
+}
+
+/// An escrow where both parties have posted their stake
+fn staked(env: &mut TestEnv, job_id: &str) -> EscrowFixture {
+    let escrow = EscrowBuilder::new(job_id).create(env);
+    env.process(escrow.enable_dual_deposit_ix(STAKE), &[escrow.hirer])
+        .unwrap();
+    env.process(escrow.post_freelancer_stake_ix(), &[escrow.freelancer])
+        .unwrap();
+    escrow
+}
+
+#[test]
+fn both_parties_post_matching_stakes() {
+    let (mut env, _platform) = setup();
+    let escrow = EscrowBuilder::new("job-stakes").create(&mut env);
+
+    let result = env.process(escrow.post_freelancer_stake_ix(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::NotDualDeposit);
+    let result = env.process(escrow.enable_dual_deposit_ix(0), &[escrow.hirer]);
+    assert_error(result, EscrowError::InvalidStake);
+
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    let escrow_lamports = env.lamports(&escrow.escrow);
+    env.process(escrow.enable_dual_deposit_ix(STAKE), &[escrow.hirer])
+        .unwrap();
+    env.process(escrow.post_freelancer_stake_ix(), &[escrow.freelancer])
+        .unwrap();
+
+    assert_eq!(env.lamports(&escrow.hirer), hirer_lamports - STAKE);
+    assert_eq!(
+        env.lamports(&escrow.freelancer),
+        freelancer_lamports - STAKE
+    );
+    assert_eq!(env.lamports(&escrow.escrow), escrow_lamports + 2 * STAKE);
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!(
+        (
+            state.stake_amount,
+            state.hirer_stake,
+            state.freelancer_stake
+        ),
+        (STAKE, STAKE, STAKE)
+    );
+
+    env.advance(1);
+    let result = env.process(escrow.enable_dual_deposit_ix(STAKE), &[escrow.hirer]);
+    assert_error(result, EscrowError::DualDepositAlreadyEnabled);
+    let result = env.process(escrow.post_freelancer_stake_ix(), &[escrow.freelancer]);
+    assert_error(result, EscrowError::StakeAlreadyPosted);
+}
+
+#[test]
+fn losing_a_dispute_forfeits_the_stake() {
+    let (mut env, platform) = setup();
+    let escrow = staked(&mut env, "job-stake-dispute");
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.freelancer),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+    env.process(
+        escrow.escalate_ix(&platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+
+    let result = env.process(escrow.settle_stakes_ix(), &[]);
+    assert_error(result, EscrowError::NotReleased);
+
+    env.next_slot();
+    env.process(
+        escrow.resolve_ix(&platform, &platform.moderator, 100_000, 900_000),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    env.process(escrow.settle_stakes_ix(), &[]).unwrap();
+
+    let settled = env.events::<StakesSettled>();
+    assert_eq!(settled[0].to_hirer, 0);
+    assert_eq!(settled[0].to_freelancer, 2 * STAKE);
+    assert_eq!(env.lamports(&escrow.hirer), hirer_lamports);
+    assert_eq!(
+        env.lamports(&escrow.freelancer),
+        freelancer_lamports + 2 * STAKE
+    );
+
+    let result = env.process(escrow.settle_stakes_ix(), &[]);
+    assert_error(result, EscrowError::NoStakes);
+}
+
+#[test]
+fn undisputed_release_returns_both_stakes() {
+    let (mut env, platform) = setup();
+    let escrow = staked(&mut env, "job-stake-release");
+    env.process(escrow.release_ix(&platform, &escrow.hirer), &[escrow.hirer])
+        .unwrap();
+
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    env.process(escrow.settle_stakes_ix(), &[]).unwrap();
+    assert_eq!(env.lamports(&escrow.hirer), hirer_lamports + STAKE);
+    assert_eq!(
+        env.lamports(&escrow.freelancer),
+        freelancer_lamports + STAKE
+    );
+}
+
+#[test]
+fn mutual_refund_returns_the_freelancer_stake() {
+    let (mut env, _platform) = setup();
+    let escrow = staked(&mut env, "job-stake-refund");
+    env.process(escrow.request_refund_ix(), &[escrow.hirer])
+        .unwrap();
+
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    env.process(escrow.approve_refund_ix(), &[escrow.freelancer])
+        .unwrap();
+    assert_eq!(
+        env.lamports(&escrow.freelancer),
+        freelancer_lamports + STAKE
+    );
+    assert_eq!(env.lamports(&escrow.escrow), 0);
+}
+
+#[test]
+fn non_delivery_slashes_the_freelancer_stake() {
+    let (mut env, platform) = setup();
+    let escrow = staked(&mut env, "job-stake-default");
+
+    let result = env.process(escrow.trigger_default_ix(&platform), &[escrow.hirer]);
+    assert_error(result, EscrowError::DeadlineNotReached);
+
+    env.advance(7 * DAY + 1);
+    let hirer_lamports = env.lamports(&escrow.hirer);
+    let vault_rent = env.lamports(&escrow.vault);
+    env.process(escrow.trigger_default_ix(&platform), &[escrow.hirer])
+        .unwrap();
+
+    // The default config slashes half the stake, and the emptied vault's rent goes back too
+    let defaulted = env.events::<FreelancerDefaulted>();
+    assert_eq!(defaulted[0].refunded, DEFAULT_AMOUNT);
+    assert_eq!(defaulted[0].slashed, STAKE / 2);
+    assert_eq!(
+        env.lamports(&escrow.hirer),
+        hirer_lamports + STAKE / 2 + vault_rent
+    );
+    assert_eq!(
+        env.token_balance(&escrow.hirer_token_account),
+        DEFAULT_AMOUNT
+    );
+    assert!(env.account(&escrow.vault).is_none());
+
+    let freelancer_lamports = env.lamports(&escrow.freelancer);
+    env.process(escrow.settle_stakes_ix(), &[]).unwrap();
+    assert_eq!(
+        env.lamports(&escrow.freelancer),
+        freelancer_lamports + STAKE / 2
+    );
+}
+
+#[test]
+fn delivered_work_cannot_be_defaulted() {
+    let (mut env, platform) = setup();
+    let escrow = staked(&mut env, "job-stake-delivered");
+    env.process(
+        escrow.request_release_ix(&platform, &escrow.freelancer, None),
+        &[escrow.freelancer],
+    )
+    .unwrap();
+
+    env.advance(7 * DAY + 1);
+    let result = env.process(escrow.trigger_default_ix(&platform), &[escrow.hirer]);
+    assert_error(result, EscrowError::WorkDelivered);
+}
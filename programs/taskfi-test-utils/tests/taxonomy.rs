@@ -0,0 +1,199 @@
+use taskfi_escrow::{
+    CategoryIndex, Escrow, EscrowError, EscrowPage, EscrowSkillsSet, JurorPanel, JurorPool,
+    TaxonomyEntry, TaxonomyEntrySet, TaxonomyKind,
+};
+use taskfi_test_utils::*;
+
+const DESIGN: u16 = 3;
+const LOGOS: u16 = 10;
+const ICONS: u16 = 11;
+
+/// A platform charging a 2% fee with a design category at 0.5% and two skills
+fn setup() -> (TestEnv, Platform) {
+    let mut env = TestEnv::new();
+    let platform = Platform::setup_with(&mut env, |params| params.platform_fee_bps = 200);
+    for ix in [
+        platform.set_taxonomy_entry_ix(TaxonomyKind::Category, DESIGN, "Design", Some(50), true),
+        platform.set_taxonomy_entry_ix(TaxonomyKind::Skill, LOGOS, "Logos", None, true),
+        platform.set_taxonomy_entry_ix(TaxonomyKind::Skill, ICONS, "Icons", None, true),
+    ] {
+        env.process(ix, &[platform.admin]).unwrap();
+    }
+    (env, platform)
+}
+
+#[test]
+fn admins_manage_the_taxonomy() {
+    let (mut env, platform) = setup();
+    let design = pda::taxonomy(TaxonomyKind::Category, DESIGN);
+    let entry: TaxonomyEntry = env.get(&design);
+    assert_eq!((entry.kind, entry.index), (TaxonomyKind::Category, DESIGN));
+    assert_eq!((entry.name.as_str(), entry.fee_bps), ("Design", Some(50)));
+
+    // Entries are updated in place
+    let ix = platform.set_taxonomy_entry_ix(
+        TaxonomyKind::Category,
+        DESIGN,
+        "Design & branding",
+        Some(80),
+        true,
+    );
+    env.process(ix, &[platform.admin]).unwrap();
+    let set = env.events::<TaxonomyEntrySet>().remove(0);
+    assert_eq!((set.entry, set.fee_bps), (design, Some(80)));
+    assert_eq!(env.get::<TaxonomyEntry>(&design).name, "Design & branding");
+
+    let ix = platform.set_taxonomy_entry_ix(TaxonomyKind::Skill, LOGOS, "Logos", Some(50), true);
+    let result = env.process(ix, &[platform.admin]);
+    assert_error(result, EscrowError::InvalidFeeConfig);
+
+    let long_name = "x".repeat(TaxonomyEntry::MAX_NAME_LEN + 1);
+    let ix = platform.set_taxonomy_entry_ix(TaxonomyKind::Skill, LOGOS, &long_name, None, true);
+    let result = env.process(ix, &[platform.admin]);
+    assert_error(result, EscrowError::TaxonomyNameTooLong);
+
+    let stranger = env.create_wallet();
+    let mut ix = platform.set_taxonomy_entry_ix(TaxonomyKind::Skill, 12, "Fonts", None, true);
+    ix.accounts[3].pubkey = stranger;
+    let result = env.process(ix, &[stranger]);
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintHasOne);
+}
+
+#[test]
+fn category_fee_rates_replace_the_platform_fee() {
+    let (mut env, platform) = setup();
+    let design = pda::taxonomy(TaxonomyKind::Category, DESIGN);
+
+    let escrow = EscrowBuilder::new("job-design")
+        .category(design)
+        .create(&mut env);
+    let state: Escrow = env.get(&escrow.escrow);
+    assert_eq!((state.taxonomy_category, state.fee_bps), (Some(DESIGN), 50));
+
+    let plain = EscrowBuilder::new("job-plain").create(&mut env);
+    let state: Escrow = env.get(&plain.escrow);
+    assert_eq!((state.taxonomy_category, state.fee_bps), (None, 200));
+
+    // Retired categories, and skills, cannot be opened in
+    let ix = platform.set_taxonomy_entry_ix(TaxonomyKind::Category, DESIGN, "Design", None, false);
+    env.process(ix, &[platform.admin]).unwrap();
+    for category in [design, pda::taxonomy(TaxonomyKind::Skill, LOGOS)] {
+        let (_, result) = EscrowBuilder::new("job-retired")
+            .category(category)
+            .try_create(&mut env);
+        assert_error(result, EscrowError::TaxonomyEntryUnavailable);
+    }
+}
+
+#[test]
+fn escrows_opened_in_a_category_are_listed_under_it() {
+    let (mut env, _platform) = setup();
+    let design = pda::taxonomy(TaxonomyKind::Category, DESIGN);
+    let first = EscrowBuilder::new("job-board-1")
+        .category(design)
+        .create(&mut env);
+    let second = EscrowBuilder::new("job-board-2")
+        .category(design)
+        .create(&mut env);
+    EscrowBuilder::new("job-board-plain").create(&mut env);
+
+    let index: CategoryIndex = env.get(&pda::category_index(&design));
+    assert_eq!((index.category, index.escrow_count), (design, 2));
+    let page = pda::category_page(&design, 0);
+    assert_eq!(first.category_page, Some(page));
+    assert_eq!(
+        env.get::<EscrowPage>(&page).escrows,
+        vec![first.escrow, second.escrow]
+    );
+
+    // Closing the escrow takes it off the category page, which has to be passed
+    env.process(first.request_refund_ix(), &[first.hirer])
+        .unwrap();
+    let mut ix = first.approve_refund_ix();
+    let meta = ix.accounts.iter_mut().find(|meta| meta.pubkey == page);
+    // Omitted optional accounts are passed as the program id
+    meta.unwrap().pubkey = taskfi_escrow::ID;
+    let result = env.process(ix, &[first.freelancer]);
+    assert_error(result, EscrowError::CategoryAccountsMissing);
+    env.process(first.approve_refund_ix(), &[first.freelancer])
+        .unwrap();
+    assert_eq!(env.get::<EscrowPage>(&page).escrows, vec![second.escrow]);
+}
+
+#[test]
+fn escrows_are_tagged_with_active_skills() {
+    let (mut env, platform) = setup();
+    let logos = pda::taxonomy(TaxonomyKind::Skill, LOGOS);
+    let icons = pda::taxonomy(TaxonomyKind::Skill, ICONS);
+    let escrow = EscrowBuilder::new("job-skills").create(&mut env);
+
+    env.process(
+        escrow.set_skills_ix(&[logos, icons, logos]),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    assert_eq!(env.get::<Escrow>(&escrow.escrow).skills, vec![LOGOS, ICONS]);
+    assert_eq!(
+        env.events::<EscrowSkillsSet>()[0].skills,
+        vec![LOGOS, ICONS]
+    );
+
+    let design = pda::taxonomy(TaxonomyKind::Category, DESIGN);
+    let result = env.process(escrow.set_skills_ix(&[design]), &[escrow.hirer]);
+    assert_error(result, EscrowError::TaxonomyEntryUnavailable);
+
+    let result = env.process(escrow.set_skills_ix(&[logos; 5]), &[escrow.hirer]);
+    assert_error(result, EscrowError::TooManySkills);
+
+    let ix = platform.set_taxonomy_entry_ix(TaxonomyKind::Skill, ICONS, "Icons", None, false);
+    env.process(ix, &[platform.admin]).unwrap();
+    let result = env.process(escrow.set_skills_ix(&[icons]), &[escrow.hirer]);
+    assert_error(result, EscrowError::TaxonomyEntryUnavailable);
+}
+
+#[test]
+fn panels_are_drawn_from_category_specialists() {
+    let (mut env, platform) = setup();
+    let design = pda::taxonomy(TaxonomyKind::Category, DESIGN);
+    let jurors = platform.register_jurors(&mut env, 6, 1_000);
+    for juror in &jurors[3..] {
+        env.process(
+            platform.set_juror_specialty_ix(juror, Some(design)),
+            &[juror.wallet],
+        )
+        .unwrap();
+    }
+    let pool: JurorPool = env.get(&platform.juror_pool);
+    assert_eq!(pool.jurors[3].specialty, Some(DESIGN));
+    assert_eq!(pool.jurors[0].specialty, None);
+
+    let escrow = EscrowBuilder::new("job-panel")
+        .category(design)
+        .create(&mut env);
+    env.process(
+        escrow.initiate_dispute_ix(&env, &platform, &escrow.hirer),
+        &[escrow.hirer],
+    )
+    .unwrap();
+    for party in [escrow.hirer, escrow.freelancer] {
+        env.process(escrow.escalate_ix(&platform, &party), &[party])
+            .unwrap();
+    }
+    env.process(
+        escrow.draw_jurors_ix(&platform, &platform.moderator, 3),
+        &[platform.moderator],
+    )
+    .unwrap();
+
+    let panel: JurorPanel = env.get(&escrow.panel());
+    let specialists: Vec<_> = jurors[3..].iter().map(|j| j.wallet).collect();
+    assert!(panel.jurors.iter().all(|j| specialists.contains(j)));
+
+    // Jurors outside the pool cannot declare a specialty
+    let outsider = platform.create_juror(&mut env, 1_000);
+    let result = env.process(
+        platform.set_juror_specialty_ix(&outsider, Some(design)),
+        &[outsider.wallet],
+    );
+    assert_error(result, EscrowError::JurorNotRegistered);
+}